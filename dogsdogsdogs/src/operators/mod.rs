@@ -1,12 +1,29 @@
+//! Operators for delta queries and worst-case optimal joins.
+//!
+//! The operators live in differential dataflow, in `operators::half_join` and `operators::extend`,
+//! and are re-exported here under their original paths.
+
 pub mod half_join;
-pub mod lookup_map;
 
-pub mod count;
-pub mod propose;
-pub mod validate;
+pub mod lookup_map {
+    //! Looks up prefixes in an arrangement.
+    pub use differential_dataflow::operators::extend::lookup_map;
+}
+pub mod count {
+    //! Counts the extensions of prefixes.
+    pub use differential_dataflow::operators::extend::count;
+}
+pub mod propose {
+    //! Proposes extensions of prefixes.
+    pub use differential_dataflow::operators::extend::{propose, propose_distinct};
+}
+pub mod validate {
+    //! Validates extensions of prefixes.
+    pub use differential_dataflow::operators::extend::validate;
+}
 
 pub use self::half_join::half_join;
 pub use self::lookup_map::lookup_map;
 pub use self::count::count;
 pub use self::propose::{propose, propose_distinct};
-pub use self::validate::validate;
\ No newline at end of file
+pub use self::validate::validate;
@@ -1,6 +1,7 @@
 use rand::{Rng, SeedableRng, StdRng};
 
 use differential_dataflow::input::Input;
+use differential_dataflow::operators::arrange::{ArrangeBySelf, IdleMaintenance};
 use differential_dataflow::operators::count::CountTotal;
 
 fn main() {
@@ -19,15 +20,20 @@ fn main() {
         let index = worker.index();
         let peers = worker.peers();
 
+        // merge batches of the arranged sources between rounds of input.
+        let mut maintenance = IdleMaintenance::new(1 << 20);
+
         // create a degree counting differential dataflow
         let (mut input, probe) = worker.dataflow(|scope| {
 
             // create edge input, count a few ways.
             let (input, edges) = scope.new_collection();
 
-            let degrs = edges.map(|(src, _dst)| src)
-                             .count_total()
-                             ;
+            let sources = edges.map(|(src, _dst)| src)
+                               .arrange_by_self();
+            maintenance.register(&sources.trace);
+
+            let degrs = sources.count_total();
 
             // // pull of count, and count.
             // let distr = degrs.map(|(_src, cnt)| cnt as usize)
@@ -81,7 +87,7 @@ fn main() {
                     input.flush();
                     let elapsed1 = timer.elapsed();
                     let elapsed1_ns = elapsed1.as_secs() * 1_000_000_000 + (elapsed1.subsec_nanos() as u64);
-                    worker.step_while(|| probe.less_than(input.time()));
+                    maintenance.step_while(worker, || probe.less_than(input.time()));
                     let elapsed2 = timer.elapsed();
                     let elapsed2_ns = elapsed2.as_secs() * 1_000_000_000 + (elapsed2.subsec_nanos() as u64);
                     let count_index = (elapsed2_ns - elapsed1_ns).next_power_of_two().trailing_zeros() as usize;
@@ -0,0 +1,127 @@
+//! Per-key values achieving a minimum or maximum.
+
+use std::rc::Rc;
+
+use timely::dataflow::Scope;
+
+use crate::{Collection, ExchangeData};
+use crate::lattice::Lattice;
+use crate::operators::*;
+use crate::difference::Semigroup;
+use crate::hashable::Hashable;
+
+/// Extension trait for the `argmin` and `argmax` methods.
+///
+/// Each method reports, for each key, the one value with a non-zero accumulated count that
+/// achieves the extremum. Values that compare equally under the supplied ordering are broken
+/// by their own `Ord` implementation, in favor of the smaller value, so that the result does
+/// not depend on the order in which updates arrive.
+///
+/// The implementation is hierarchical: values are placed in buckets by their hash, and extrema
+/// are determined for progressively coarser buckets. A change to a value only requires the
+/// re-evaluation of one bucket at each level, rather than the whole group of the key.
+pub trait Extrema<G: Scope, K: ExchangeData, V: ExchangeData> {
+    /// The least value for each key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::algorithms::extrema::Extrema;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     data.map(|x| (x % 3, x))
+    ///         .argmin()
+    ///         .assert_eq(&data.filter(|x| x < &4).map(|x| (x % 3, x)));
+    /// });
+    /// ```
+    fn argmin(&self) -> Collection<G, (K, V)> {
+        self.argmin_by(|value| value.clone())
+    }
+    /// The greatest value for each key.
+    fn argmax(&self) -> Collection<G, (K, V)> {
+        self.argmax_by(|value| value.clone())
+    }
+    /// The value for each key with the least `order(value)`, with ties broken by the least value.
+    ///
+    /// Further tie-breaking preferences can be expressed by extending the result of `order`,
+    /// for example by returning a tuple of the primary and secondary criteria.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::algorithms::extrema::Extrema;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     // the shortest name, ties broken by name.
+    ///     scope.new_collection_from(vec!["ant", "bee", "wasp"]).1
+    ///          .map(|name| ((), name.to_string()))
+    ///          .argmin_by(|name| name.len())
+    ///          .assert_eq(&scope.new_collection_from(vec![((), "ant".to_string())]).1);
+    /// });
+    /// ```
+    fn argmin_by<O, F>(&self, order: F) -> Collection<G, (K, V)>
+    where
+        O: Ord,
+        F: Fn(&V)->O+'static;
+    /// The value for each key with the greatest `order(value)`, with ties broken by the least value.
+    fn argmax_by<O, F>(&self, order: F) -> Collection<G, (K, V)>
+    where
+        O: Ord,
+        F: Fn(&V)->O+'static,
+    {
+        self.argmin_by(move |value| ::std::cmp::Reverse(order(value)))
+    }
+}
+
+impl<G, K, V, R> Extrema<G, K, V> for Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+::std::hash::Hash,
+    V: ExchangeData+::std::hash::Hash,
+    R: ExchangeData+Semigroup,
+{
+    fn argmin_by<O, F>(&self, order: F) -> Collection<G, (K, V)>
+    where
+        O: Ord,
+        F: Fn(&V)->O+'static,
+    {
+        let order = Rc::new(order);
+
+        // The first level buckets values by all but the lowest eight bits of their hash.
+        let order1 = order.clone();
+        let mut stage: Collection<G, ((K, u64), V)> =
+        self.map(|(key, val)| ((key, val.hashed() >> 8), val))
+            .reduce_named("ArgMin", move |_key, input, output| output.push((least(&*order1, input), 1)));
+
+        // Each subsequent level coarsens the buckets by a further eight bits.
+        for _level in 0 .. 6 {
+            let order2 = order.clone();
+            stage =
+            stage
+                .map(|((key, hash), val)| ((key, hash >> 8), val))
+                .reduce_named("ArgMin", move |_key, input, output| output.push((least(&*order2, input), 1)));
+        }
+
+        // The final level groups all values of each key.
+        stage
+            .map(|((key, _hash), val)| (key, val))
+            .reduce_named("ArgMin", move |_key, input, output| output.push((least(&*order, input), 1)))
+    }
+}
+
+/// The least value in `input` by `order`, breaking ties by the value itself.
+fn least<V: Ord+Clone, O: Ord, R, F: Fn(&V)->O>(order: &F, input: &[(&V, R)]) -> V {
+    input
+        .iter()
+        .map(|(val, _)| *val)
+        .min_by(|x, y| (order(x), x).cmp(&(order(y), y)))
+        .expect("reduce presents non-empty input")
+        .clone()
+}
@@ -1,5 +1,6 @@
 //! Common algorithms constructed from differential dataflow operators.
 
 pub mod identifiers;
+pub mod extrema;
 pub mod prefix_sum;
 pub mod graphs;
\ No newline at end of file
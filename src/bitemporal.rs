@@ -0,0 +1,264 @@
+//! Timestamps with a system time and a valid time.
+//!
+//! Temporal data often carry two notions of time: the *system time* at which an update became known,
+//! and the *valid time* at which it takes effect in the modeled world. A late-arriving correction to
+//! yesterday's balance is known today and valid yesterday. The `Bitemporal` type pairs the two, ordered
+//! by the product order, so that the accumulation of a collection at `Bitemporal::new(system, valid)`
+//! is its contents as of valid time `valid`, as known at system time `system`.
+//!
+//! Dataflows with bitemporal timestamps usually advance their inputs in system time, and would compact
+//! their arrangements in both coordinates. The `arrange_bitemporal` method instead compacts only along
+//! system time, retaining the history of valid times, so that `read_as_of` can answer queries as of any
+//! valid time. The `as_of_valid` method maintains the contents as of a fixed valid time as they evolve
+//! in system time.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::InputSession;
+//! use differential_dataflow::bitemporal::{Bitemporal, read_as_of};
+//!
+//! ::timely::execute_directly(|worker| {
+//!
+//!     let mut input = InputSession::<Bitemporal<u64, u64>, (String, i64), isize>::new();
+//!     let mut trace = worker.dataflow(|scope| {
+//!         input.to_collection(scope)
+//!              .arrange_bitemporal()
+//!              .trace
+//!     });
+//!
+//!     // at system time 0, the balance is 100 from valid time 0 on.
+//!     input.update_at(("alice".to_string(), 100), Bitemporal::new(0, 0), 1);
+//!     // at system time 1, a correction: the balance was 90 from valid time 0 on.
+//!     input.update_at(("alice".to_string(), 100), Bitemporal::new(1, 0), -1);
+//!     input.update_at(("alice".to_string(), 90), Bitemporal::new(1, 0), 1);
+//!     input.close();
+//!     while worker.step() { }
+//!
+//!     let before = read_as_of(&mut trace, 0, 5).unwrap();
+//!     let after = read_as_of(&mut trace, 1, 5).unwrap();
+//!     assert_eq!(before, vec![(("alice".to_string(), 100), 1)]);
+//!     assert_eq!(after, vec![(("alice".to_string(), 90), 1)]);
+//! });
+//! ```
+
+use abomonation_derive::Abomonation;
+use serde::{Deserialize, Serialize};
+use timely::container::columnation::{Columnation, CopyRegion};
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Filter, Operator};
+use timely::order::PartialOrder;
+use timely::progress::{Antichain, PathSummary, Timestamp};
+use timely::progress::frontier::AntichainRef;
+use timely::progress::timestamp::Refines;
+
+use crate::{Collection, Data, ExchangeData, Hashable};
+use crate::collection::AsCollection;
+use crate::difference::Semigroup;
+use crate::lattice::{Lattice, Maximum};
+use crate::operators::arrange::{Arranged, ArrangeByKey, TraceAgent};
+use crate::operators::arrange::compaction::CompactionPolicy;
+use crate::trace::{Cursor, TraceReader};
+use crate::trace::clock::Clock;
+use crate::trace::implementations::ValSpine;
+
+/// A system time and a valid time, ordered by the product order.
+///
+/// As a path summary, the type describes a delay in each of the two coordinates.
+#[derive(Copy, Clone, Default, Hash, Eq, PartialEq, Ord, PartialOrd, Debug, Serialize, Deserialize, Abomonation)]
+pub struct Bitemporal<S, V> {
+    /// The time at which an update became known.
+    pub system: S,
+    /// The time at which an update takes effect.
+    pub valid: V,
+}
+
+impl<S, V> Bitemporal<S, V> {
+    /// The time `valid`, as known at `system`.
+    pub fn new(system: S, valid: V) -> Self {
+        Bitemporal { system, valid }
+    }
+}
+
+impl<S: PartialOrder, V: PartialOrder> PartialOrder for Bitemporal<S, V> {
+    #[inline]
+    fn less_equal(&self, other: &Self) -> bool {
+        self.system.less_equal(&other.system) && self.valid.less_equal(&other.valid)
+    }
+}
+
+impl<S: Timestamp, V: Timestamp> PathSummary<Bitemporal<S, V>> for Bitemporal<S::Summary, V::Summary> {
+    #[inline]
+    fn results_in(&self, src: &Bitemporal<S, V>) -> Option<Bitemporal<S, V>> {
+        let system = self.system.results_in(&src.system)?;
+        let valid = self.valid.results_in(&src.valid)?;
+        Some(Bitemporal { system, valid })
+    }
+    #[inline]
+    fn followed_by(&self, other: &Self) -> Option<Self> {
+        let system = self.system.followed_by(&other.system)?;
+        let valid = self.valid.followed_by(&other.valid)?;
+        Some(Bitemporal { system, valid })
+    }
+}
+
+impl<S: Timestamp, V: Timestamp> Timestamp for Bitemporal<S, V> {
+    type Summary = Bitemporal<S::Summary, V::Summary>;
+    fn minimum() -> Self { Bitemporal::new(S::minimum(), V::minimum()) }
+}
+
+impl<S: Timestamp, V: Timestamp> Refines<()> for Bitemporal<S, V> {
+    fn to_inner(_outer: ()) -> Self { Self::minimum() }
+    fn to_outer(self) -> () { }
+    fn summarize(_summary: Self::Summary) -> () { }
+}
+
+impl<S: Lattice, V: Lattice> Lattice for Bitemporal<S, V> {
+    #[inline]
+    fn join(&self, other: &Self) -> Self {
+        Bitemporal::new(self.system.join(&other.system), self.valid.join(&other.valid))
+    }
+    #[inline]
+    fn meet(&self, other: &Self) -> Self {
+        Bitemporal::new(self.system.meet(&other.system), self.valid.meet(&other.valid))
+    }
+}
+
+impl<S: Maximum, V: Maximum> Maximum for Bitemporal<S, V> {
+    fn maximum() -> Self { Bitemporal::new(S::maximum(), V::maximum()) }
+}
+
+impl<S: Copy+'static, V: Copy+'static> Columnation for Bitemporal<S, V> {
+    type InnerRegion = CopyRegion<Bitemporal<S, V>>;
+}
+
+/// Methods for collections with bitemporal timestamps.
+impl<G, D, R, S, V> Collection<G, D, R>
+where
+    G: Scope<Timestamp=Bitemporal<S, V>>,
+    D: Data,
+    R: Semigroup,
+    S: Timestamp+Lattice,
+    V: Timestamp+Lattice,
+{
+    /// The contents as of valid time `valid`, as they evolve in system time.
+    ///
+    /// Updates at valid times not less or equal to `valid` are discarded, and the others are delayed
+    /// to valid time `valid`. The accumulation of the result at `Bitemporal::new(system, valid)` is
+    /// that of the collection, and the result only changes at that valid time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::InputSession;
+    /// use differential_dataflow::bitemporal::Bitemporal;
+    ///
+    /// ::timely::execute_directly(|worker| {
+    ///     let mut input = InputSession::<Bitemporal<u64, u64>, String, isize>::new();
+    ///     worker.dataflow(|scope| {
+    ///         input.to_collection(scope)
+    ///              .as_of_valid(10)
+    ///              .inspect(|(_, time, _)| assert_eq!(time.valid, 10));
+    ///     });
+    ///     input.update_at("known".to_string(), Bitemporal::new(0, 5), 1);
+    ///     input.update_at("future".to_string(), Bitemporal::new(0, 20), 1);
+    /// });
+    /// ```
+    pub fn as_of_valid(&self, valid: V) -> Collection<G, D, R> {
+        let bound = valid.clone();
+        self.inner
+            .filter(move |(_, time, _)| time.valid.less_equal(&bound))
+            .as_collection()
+            .delay(move |time| Bitemporal::new(time.system.clone(), valid.clone()))
+    }
+}
+
+/// Methods for collections of key-value pairs with bitemporal timestamps.
+impl<G, K, Val, R, S, V> Collection<G, (K, Val), R>
+where
+    G: Scope<Timestamp=Bitemporal<S, V>>,
+    K: ExchangeData+Hashable,
+    Val: ExchangeData,
+    R: ExchangeData+Semigroup,
+    S: Timestamp+Lattice,
+    V: Timestamp+Lattice,
+{
+    /// Arranges the collection by key, compacting only along system time.
+    ///
+    /// The returned handle has the `SystemCompaction` policy: asked to compact to a frontier, it compacts
+    /// only to the system times of the frontier, at the least valid time. Its holder advances it as any
+    /// other handle, for example to the frontier of the input, and the trace then distinguishes all valid
+    /// times, and its accumulation at any valid time is correct as of any system time not earlier than
+    /// those of the frontier. The arrangement also holds a handle for as long as the dataflow runs, whose
+    /// compaction follows the system time of the input frontier, and so the trace retains the history of
+    /// valid times even once the returned handle is dropped.
+    pub fn arrange_bitemporal(&self) -> Arranged<G, TraceAgent<ValSpine<K, Val, Bitemporal<S, V>, R>>> {
+
+        let mut arranged = self.arrange_by_key_named("Arrange: Bitemporal");
+        arranged.trace.set_compaction_policy(SystemCompaction);
+        let mut trace = arranged.trace.clone();
+        trace.set_compaction_policy(SystemCompaction);
+
+        arranged.stream.sink(Pipeline, "BitemporalCompaction", move |input| {
+            input.for_each(|_, _| { });
+            trace.set_logical_compaction(input.frontier().frontier());
+            trace.set_physical_compaction(input.frontier().frontier());
+        });
+
+        arranged
+    }
+}
+
+/// Compacts a trace of bitemporal updates only along system time.
+///
+/// Asked to compact to a frontier, the policy compacts to the meet of its system times, at the least
+/// valid time, and so retains the history of valid times.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemCompaction;
+
+impl<S: Timestamp+Lattice, V: Timestamp+Lattice> CompactionPolicy<Bitemporal<S, V>> for SystemCompaction {
+    fn logical_compaction(&mut self, _clock: &dyn Clock, requested: AntichainRef<Bitemporal<S, V>>, permitted: &mut Antichain<Bitemporal<S, V>>) {
+        let system = requested.iter().map(|time| time.system.clone()).reduce(|x, y| x.meet(&y));
+        permitted.extend(system.map(|system| Bitemporal::new(system, V::minimum())));
+    }
+}
+
+/// Reads the contents of `trace` as of valid time `valid`, as known at system time `system`.
+///
+/// The contents are the key-value pairs whose accumulated differences through `Bitemporal::new(system, valid)`
+/// are non-zero, in sorted order. The method returns `None` if the trace has been compacted past this
+/// time, and reads only the batches the trace has received, which may not yet be complete through it.
+pub fn read_as_of<Tr, K, Val, S, V>(trace: &mut Tr, system: S, valid: V) -> Option<Vec<((K, Val), Tr::Diff)>>
+where
+    Tr: for<'a> TraceReader<Key<'a>=&'a K, Val<'a>=&'a Val, Time=Bitemporal<S, V>>,
+    K: Clone,
+    Val: Clone,
+    S: Timestamp+Lattice,
+    V: Timestamp+Lattice,
+{
+    let time = Bitemporal::new(system, valid);
+    if !trace.get_logical_compaction().less_equal(&time) { return None; }
+
+    let mut contents = Vec::new();
+    let (mut cursor, storage) = trace.cursor();
+    while let Some(key) = cursor.get_key(&storage) {
+        while let Some(val) = cursor.get_val(&storage) {
+            let mut accum: Option<Tr::Diff> = None;
+            cursor.map_times(&storage, |t, diff| {
+                if t.less_equal(&time) {
+                    match &mut accum {
+                        Some(accum) => accum.plus_equals(diff),
+                        None => accum = Some(diff.clone()),
+                    }
+                }
+            });
+            if let Some(accum) = accum.filter(|accum| !accum.is_zero()) {
+                contents.push(((key.clone(), val.clone()), accum));
+            }
+            cursor.step_val(&storage);
+        }
+        cursor.step_key(&storage);
+    }
+    Some(contents)
+}
@@ -7,7 +7,8 @@
 //!
 //! The methods are parameterized by implementors of byte sources and byte sinks. For
 //! example implementations of these traits, consult the commented text at the end of
-//! this file.
+//! this file. With the `parquet-sink` feature, the `columnar` module writes collections
+//! to Parquet files instead. The `remote` module shares arrangements between processes.
 
 use std::time::Duration;
 use abomonation_derive::Abomonation;
@@ -66,8 +67,11 @@ pub mod iterator {
     /// A direct implementation of a deduplicating, re-ordering iterator.
     ///
     /// The iterator draws from a source that may have arbitrary duplication, be arbitrarily out of order,
-    /// and yet produces each update once, with in-order batches. The iterator maintains a bounded memory
-    /// footprint, proportional to the mismatch between the received updates and progress messages.
+    /// and yet produces each update once, with in-order batches. The iterator maintains a memory footprint
+    /// proportional to the mismatch between the received updates and progress messages. A source whose
+    /// progress messages lag far behind its updates, or never arrive, can make this footprint unbounded;
+    /// with the `capture-spill` feature, `Iter::with_spill` bounds the updates held in memory, and spills
+    /// the others to disk.
     pub struct Iter<I, D, T, R>
     where
         I: Iterator<Item = Message<D, T, R>>,
@@ -110,6 +114,20 @@ pub mod iterator {
         /// incorporated, and that we will not leave a gap by incorporating the counts
         /// and reflecting the progress statement's upper frontier.
         progress_queue: Vec<Progress<T>>,
+        /// Storage for updates beyond `updates`, once it holds more than a number of updates.
+        overflow: Option<(usize, Box<dyn Overflow<D, T, R>>)>,
+        /// The first error reported by `overflow`, after which the iterator produces no more batches.
+        error: Option<std::io::Error>,
+    }
+
+    /// Storage for updates that an `Iter` holds outside of memory.
+    pub(crate) trait Overflow<D, T, R> {
+        /// Indicates whether `update` is held.
+        fn contains(&mut self, update: &(D, T, R)) -> std::io::Result<bool>;
+        /// Holds each of `updates`.
+        fn extend(&mut self, updates: Vec<(D, T, R)>) -> std::io::Result<()>;
+        /// Removes and returns the held updates at times not beyond `frontier`.
+        fn extract(&mut self, frontier: &Antichain<T>) -> std::io::Result<Vec<(D, T, R)>>;
     }
 
     impl<D, T, R, I> Iterator for Iter<I, D, T, R>
@@ -126,18 +144,41 @@ pub mod iterator {
             //
             // An interval can be completed once our frontier of received progress
             // information and our frontier of unresolved counts have advanced.
+            if self.error.is_some() {
+                return None;
+            }
             while let Some(message) = self.iterator.next() {
                 match message {
                     Message::Updates(mut updates) => {
                         // Discard updates at reported times, or duplicates at unreported times.
-                        updates.retain(|dtr| {
-                            self.reported_frontier.less_equal(&dtr.1) && !self.updates.contains(dtr)
-                        });
+                        let reported_frontier = &self.reported_frontier;
+                        let held = &self.updates;
+                        updates.retain(|dtr| reported_frontier.less_equal(&dtr.1) && !held.contains(dtr));
+                        if let Some((_, overflow)) = &mut self.overflow {
+                            let mut retained = Vec::with_capacity(updates.len());
+                            for update in updates {
+                                match overflow.contains(&update) {
+                                    Ok(true) => { },
+                                    Ok(false) => retained.push(update),
+                                    Err(error) => { self.error = Some(error); return None; },
+                                }
+                            }
+                            updates = retained;
+                        }
                         // Decrement our counts of accounted-for messages.
                         self.messages_frontier
                             .update_iter(updates.iter().map(|(_, t, _)| (t.clone(), -1)));
                         // Record the messages in our de-duplication collection.
                         self.updates.extend(updates.into_iter());
+                        // Move updates out of memory once there are too many of them.
+                        if let Some((limit, overflow)) = &mut self.overflow {
+                            if self.updates.len() > *limit {
+                                if let Err(error) = overflow.extend(self.updates.drain().collect()) {
+                                    self.error = Some(error);
+                                    return None;
+                                }
+                            }
+                        }
                     }
                     Message::Progress(progress) => {
                         // A progress statement may not be immediately actionable.
@@ -182,13 +223,19 @@ pub mod iterator {
                 let mut lower_bound = self.progress_frontier.clone();
                 lower_bound.extend(self.messages_frontier.frontier().iter().cloned());
                 if lower_bound != self.reported_frontier {
-                    let to_publish = self
+                    let mut to_publish = self
                         .updates
                         .iter()
                         .filter(|(_, t, _)| !lower_bound.less_equal(t))
                         .cloned()
                         .collect::<Vec<_>>();
                     self.updates.retain(|(_, t, _)| lower_bound.less_equal(t));
+                    if let Some((_, overflow)) = &mut self.overflow {
+                        match overflow.extract(&lower_bound) {
+                            Ok(extracted) => to_publish.extend(extracted),
+                            Err(error) => { self.error = Some(error); return None; },
+                        }
+                    }
                     self.reported_frontier = lower_bound.clone();
                     return Some((to_publish, lower_bound));
                 }
@@ -213,11 +260,45 @@ pub mod iterator {
                 progress_frontier: Antichain::from_elem(T::minimum()),
                 messages_frontier: MutableAntichain::new(),
                 progress_queue: Vec::new(),
+                overflow: None,
+                error: None,
             }
         }
+
+        /// Takes the error that ended the iterator, if an error did.
+        ///
+        /// Only iterators created `with_spill` report errors, when they fail to read or write spilled
+        /// updates. The iterator produces no batches after an error, and should be discarded.
+        pub fn take_error(&mut self) -> Option<std::io::Error> {
+            self.error.take()
+        }
+
+        /// Construct a new re-ordering, deduplicating iterator that holds at most `limit` updates in memory.
+        ///
+        /// Once more than `limit` updates are held, they are spilled to a new file in `directory`, sorted by
+        /// time. Memory then holds, for each spilled update, only its hash and its position in the file.
+        /// Each batch the iterator produces reads back only the spilled updates at its times, and a file is
+        /// removed once all its updates have been produced, or when the iterator is dropped.
+        ///
+        /// If reading or writing spilled updates fails, the iterator ends, and `take_error` reports why.
+        #[cfg(feature = "capture-spill")]
+        pub fn with_spill(iterator: I, limit: usize, directory: &std::path::Path) -> std::io::Result<Self>
+        where
+            D: serde::Serialize + serde::de::DeserializeOwned + 'static,
+            T: serde::Serialize + serde::de::DeserializeOwned + 'static,
+            R: serde::Serialize + serde::de::DeserializeOwned + 'static,
+        {
+            let spill = super::spill::Spill::<D, T, R>::new(directory)?;
+            let mut iter = Self::new(iterator);
+            iter.overflow = Some((limit, Box::new(spill)));
+            Ok(iter)
+        }
     }
 }
 
+#[cfg(feature = "capture-spill")]
+mod spill;
+
 /// Methods for recovering update streams from binary bundles.
 pub mod source {
 
@@ -719,6 +800,229 @@ pub mod sink {
     }
 }
 
+#[cfg(feature = "parquet-sink")]
+pub mod columnar;
+
+pub mod remote;
+
+/// Record and replay collections, to reproduce and test for nondeterminism.
+///
+/// A collection is recorded with `record`, which produces the CDC V2 messages that describe it.
+/// The messages can be retained, for example as a test fixture, and later replayed into another
+/// dataflow with `replay`. Replay can optionally shuffle the order in which messages arrive, and
+/// the moments at which each worker introduces them, as directed by a seed. The `assert_replay`
+/// method uses this to check that a computation produces the same output however its input
+/// arrives.
+pub mod harness {
+
+    use std::cell::RefCell;
+    use std::hash::Hash;
+    use std::rc::Rc;
+
+    use serde::{Deserialize, Serialize};
+
+    use timely::dataflow::Scope;
+    use timely::progress::Timestamp;
+    use timely::scheduling::SyncActivator;
+
+    use crate::{Collection, ExchangeData, Hashable};
+    use crate::collection::AsCollection;
+    use crate::difference::{Abelian, Semigroup};
+    use crate::lattice::Lattice;
+    use super::{Message, Writer};
+
+    /// An in-memory recording of CDC V2 messages.
+    #[derive(Clone, Debug)]
+    pub struct Recording<D, T, R> {
+        messages: Vec<Message<D, T, R>>,
+    }
+
+    impl<D, T, R> Recording<D, T, R> {
+        /// Creates an empty recording.
+        pub fn new() -> Self {
+            Recording { messages: Vec::new() }
+        }
+        /// The messages recorded so far.
+        pub fn messages(&self) -> &[Message<D, T, R>] {
+            &self.messages
+        }
+        /// Extracts the recorded messages.
+        pub fn into_messages(self) -> Vec<Message<D, T, R>> {
+            self.messages
+        }
+    }
+
+    impl<D, T, R> Default for Recording<D, T, R> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<D: Clone, T: Clone, R: Clone> Writer<Message<D, T, R>> for Recording<D, T, R> {
+        fn poll(&mut self, item: &Message<D, T, R>) -> Option<std::time::Duration> {
+            self.messages.push(item.clone());
+            None
+        }
+        fn done(&self) -> bool { true }
+    }
+
+    /// Records the updates and frontier advances of `collection`.
+    ///
+    /// Each worker records the updates it holds, and worker zero also records the progress of the
+    /// collection. The recording describes the whole collection once the recordings of all workers
+    /// are combined, in any order; it is complete once the collection's frontier is empty.
+    pub fn record<G, D, T, R>(collection: &Collection<G, D, R>) -> Rc<RefCell<Recording<D, T, R>>>
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+        T: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a> + Timestamp + Lattice,
+        R: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a> + Semigroup,
+    {
+        let recording = Rc::new(RefCell::new(Recording::new()));
+        super::sink::build(
+            &collection.consolidate().inner,
+            0,
+            Rc::downgrade(&recording),
+            Rc::downgrade(&recording),
+        );
+        recording
+    }
+
+    /// Replays recorded messages as a collection.
+    ///
+    /// Each worker should be supplied with all messages, or with any subset of them provided each
+    /// message is supplied to at least one worker. If `seed` is supplied, each worker introduces its
+    /// messages in a shuffled order and over several activations, both determined by the seed and the
+    /// worker index. The collection is unaffected, but the operators that consume it may observe its
+    /// updates in different batches and at different moments.
+    ///
+    /// The replay continues until the returned token is dropped, or all messages have been replayed.
+    pub fn replay<G, D, T, R>(
+        scope: &G,
+        messages: Vec<Message<D, T, R>>,
+        seed: Option<u64>,
+    ) -> (Box<dyn std::any::Any + Send + Sync>, Collection<G, D, R>)
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash,
+        T: ExchangeData + Hash + Timestamp + Lattice,
+        R: ExchangeData + Hash + Semigroup,
+    {
+        let index = scope.index() as u64;
+        let (token, stream) = super::source::build(scope.clone(), move |activator| {
+            Shuffled::new(messages, seed.map(|seed| seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15)), activator)
+        });
+        (token, stream.as_collection())
+    }
+
+    /// Asserts that `logic` produces the same output from recorded messages, however they arrive.
+    ///
+    /// The messages are replayed twice, once in order and once shuffled by `seed`, and the results
+    /// of applying `logic` to each are asserted to be equal. Trying several seeds exercises several
+    /// arrival orders; a seed that provokes a failure reproduces it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::capture::harness::{record, assert_replay};
+    /// use differential_dataflow::operators::Count;
+    ///
+    /// // record a collection, by running a computation to completion.
+    /// let messages = ::timely::execute_directly(|worker| {
+    ///     let recording = worker.dataflow::<u32,_,_>(|scope| {
+    ///         record(&scope.new_collection_from(0 .. 100u32).1)
+    ///     });
+    ///     while worker.step() { }
+    ///     let messages = recording.borrow().messages().to_vec();
+    ///     messages
+    /// });
+    ///
+    /// // replay the recording, checking that counting its contents is deterministic.
+    /// ::timely::execute_directly(move |worker| {
+    ///     let _tokens = worker.dataflow::<u32,_,_>(|scope| {
+    ///         (0 .. 10)
+    ///             .map(|seed| assert_replay(&*scope, &messages, seed, |data| data.map(|x| x % 7).count()))
+    ///             .collect::<Vec<_>>()
+    ///     });
+    ///     while worker.step() { }
+    /// });
+    /// ```
+    pub fn assert_replay<G, D, T, R, D2, R2, L>(
+        scope: &G,
+        messages: &[Message<D, T, R>],
+        seed: u64,
+        logic: L,
+    ) -> Box<dyn std::any::Any + Send + Sync>
+    where
+        G: Scope<Timestamp = T>,
+        D: ExchangeData + Hash,
+        T: ExchangeData + Hash + Timestamp + Lattice,
+        R: ExchangeData + Hash + Semigroup,
+        D2: ExchangeData + Hashable,
+        R2: ExchangeData + Hashable + Abelian,
+        L: Fn(&Collection<G, D, R>) -> Collection<G, D2, R2>,
+    {
+        let (token1, ordered) = replay(scope, messages.to_vec(), None);
+        let (token2, shuffled) = replay(scope, messages.to_vec(), Some(seed));
+        logic(&ordered).assert_eq(&logic(&shuffled));
+        Box::new((token1, token2))
+    }
+
+    /// An iterator over messages that optionally shuffles them and yields between them.
+    struct Shuffled<D, T, R> {
+        messages: Vec<Message<D, T, R>>,
+        /// State of a xorshift generator, if shuffling.
+        state: Option<u64>,
+        /// Messages to produce before yielding.
+        budget: usize,
+        activator: SyncActivator,
+    }
+
+    impl<D, T, R> Shuffled<D, T, R> {
+        fn new(mut messages: Vec<Message<D, T, R>>, seed: Option<u64>, activator: SyncActivator) -> Self {
+            // Present messages in order when popped from the back.
+            messages.reverse();
+            Shuffled {
+                messages,
+                // xorshift must not be seeded with zero.
+                state: seed.map(|seed| seed | 1),
+                budget: 0,
+                activator,
+            }
+        }
+    }
+
+    impl<D, T, R> Iterator for Shuffled<D, T, R> {
+        type Item = Message<D, T, R>;
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.messages.is_empty() {
+                return None;
+            }
+            match &mut self.state {
+                None => self.messages.pop(),
+                Some(state) => {
+                    *state ^= *state << 13;
+                    *state ^= *state >> 7;
+                    *state ^= *state << 17;
+                    let random = *state as usize;
+                    if self.budget == 0 {
+                        // Yield, and ask to be re-activated for a few further messages.
+                        self.budget = 1 + random % 4;
+                        let _ = self.activator.activate();
+                        None
+                    }
+                    else {
+                        self.budget -= 1;
+                        let position = random % self.messages.len();
+                        Some(self.messages.swap_remove(position))
+                    }
+                }
+            }
+        }
+    }
+}
+
 // pub mod kafka {
 
 //     use serde::{Serialize, Deserialize};
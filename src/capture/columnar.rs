@@ -0,0 +1,262 @@
+//! A capture sink that writes collections to Parquet files, partitioned by time interval.
+//!
+//! Each worker consolidates the updates it receives, and once its input is complete through a time
+//! interval, writes the updates of that interval to the file `part-{interval}-{worker}.parquet`, with
+//! the interval zero-padded to twenty digits. The file has the columns of the data, then of the time,
+//! and then of the difference, as described by their `Columns` implementations. Intervals without
+//! updates have no file.
+//!
+//! Each worker also maintains a progress file `_progress-{worker}`, which holds the first interval the
+//! worker may not yet have written, or `complete` once its input is complete. Data files of intervals
+//! before that of the progress file are complete, and will not change. Both kinds of files are written
+//! to a temporary file that is then renamed, so that readers never observe a partial file. A dataflow
+//! that restarts and replays its input rewrites the same files with the same contents, and so readers
+//! that respect the progress files observe each update exactly once.
+//!
+//! This module is available with the `parquet-sink` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::capture::columnar;
+//!
+//! let directory = std::env::temp_dir().join(format!("columnar-sink-{}", std::process::id()));
+//! let target = directory.clone();
+//!
+//! ::timely::example(move |scope| {
+//!     let data = scope.new_collection_from(vec![(1u64, "one".to_string()), (2, "two".to_string())]).1;
+//!     // write intervals of ten times each.
+//!     columnar::write(&data, target, |time| *time as u64 / 10).unwrap();
+//! });
+//!
+//! assert!(directory.join(format!("part-{:020}-0.parquet", 0)).exists());
+//! assert_eq!(std::fs::read_to_string(directory.join("_progress-0")).unwrap(), "complete");
+//! # std::fs::remove_dir_all(&directory).unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrow::array::{ArrayRef, BooleanArray, Int32Array, Int64Array, StringArray, UInt32Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::generic::{FrontieredInputHandle, builder_rc::OperatorBuilder};
+
+use crate::{Collection, Data};
+use crate::consolidation::consolidate_updates;
+use crate::difference::Semigroup;
+
+/// The delay after which the sink retries writes that failed.
+pub const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// A type whose values are written as one or more columns.
+pub trait Columns: Sized {
+    /// Appends the fields of the columns, named after `name`.
+    fn fields(name: &str, fields: &mut Vec<Field>);
+    /// Appends the columns that hold `values`.
+    fn columns<'a, I>(values: I, columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a;
+}
+
+macro_rules! primitive_columns {
+    ($($type:ty => $array:ty, $native:ty, $data_type:expr;)*) => { $(
+        impl Columns for $type {
+            fn fields(name: &str, fields: &mut Vec<Field>) {
+                fields.push(Field::new(name, $data_type, false));
+            }
+            #[allow(clippy::unnecessary_cast)]
+            fn columns<'a, I>(values: I, columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a {
+                columns.push(Arc::new(<$array>::from_iter_values(values.map(|value| *value as $native))));
+            }
+        }
+    )* }
+}
+
+primitive_columns! {
+    i32 => Int32Array, i32, DataType::Int32;
+    i64 => Int64Array, i64, DataType::Int64;
+    isize => Int64Array, i64, DataType::Int64;
+    u32 => UInt32Array, u32, DataType::UInt32;
+    u64 => UInt64Array, u64, DataType::UInt64;
+    usize => UInt64Array, u64, DataType::UInt64;
+}
+
+impl Columns for bool {
+    fn fields(name: &str, fields: &mut Vec<Field>) {
+        fields.push(Field::new(name, DataType::Boolean, false));
+    }
+    fn columns<'a, I>(values: I, columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a {
+        columns.push(Arc::new(BooleanArray::from(values.cloned().collect::<Vec<_>>())));
+    }
+}
+
+impl Columns for String {
+    fn fields(name: &str, fields: &mut Vec<Field>) {
+        fields.push(Field::new(name, DataType::Utf8, false));
+    }
+    fn columns<'a, I>(values: I, columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a {
+        columns.push(Arc::new(StringArray::from_iter_values(values)));
+    }
+}
+
+/// The unit type has no columns.
+impl Columns for () {
+    fn fields(_name: &str, _fields: &mut Vec<Field>) { }
+    fn columns<'a, I>(_values: I, _columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a { }
+}
+
+/// Tuples have the columns of their elements, named with the element index as a suffix.
+impl<A: Columns, B: Columns> Columns for (A, B) {
+    fn fields(name: &str, fields: &mut Vec<Field>) {
+        A::fields(&format!("{}_0", name), fields);
+        B::fields(&format!("{}_1", name), fields);
+    }
+    fn columns<'a, I>(values: I, columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a {
+        A::columns(values.clone().map(|(a, _)| a), columns);
+        B::columns(values.map(|(_, b)| b), columns);
+    }
+}
+
+impl<A: Columns, B: Columns, C: Columns> Columns for (A, B, C) {
+    fn fields(name: &str, fields: &mut Vec<Field>) {
+        A::fields(&format!("{}_0", name), fields);
+        B::fields(&format!("{}_1", name), fields);
+        C::fields(&format!("{}_2", name), fields);
+    }
+    fn columns<'a, I>(values: I, columns: &mut Vec<ArrayRef>) where I: Iterator<Item=&'a Self>+Clone, Self: 'a {
+        A::columns(values.clone().map(|(a, _, _)| a), columns);
+        B::columns(values.clone().map(|(_, b, _)| b), columns);
+        C::columns(values.map(|(_, _, c)| c), columns);
+    }
+}
+
+/// Writes the updates of `collection` to Parquet files in `directory`, partitioned by time interval.
+///
+/// The `interval` function maps each time to the interval that contains it. It must be monotone: a time
+/// greater than another must map to the same or a later interval. The method returns an error if it
+/// cannot create `directory`. If the operator fails to write a file, it reports the error to standard
+/// error, holds the interval and all later intervals pending, and retries after `RETRY_DELAY`; the
+/// dataflow does not complete while intervals remain unwritten.
+pub fn write<G, D, R, F>(collection: &Collection<G, D, R>, directory: impl Into<PathBuf>, interval: F) -> io::Result<()>
+where
+    G: Scope,
+    G::Timestamp: Data+Columns,
+    D: Data+Columns,
+    R: Semigroup+Columns,
+    F: Fn(&G::Timestamp)->u64+'static,
+{
+    let directory = directory.into();
+    fs::create_dir_all(&directory)?;
+    let worker = collection.scope().index();
+
+    let mut builder = OperatorBuilder::new("ColumnarSink".to_owned(), collection.scope());
+    let reactivator = collection.scope().activator_for(&builder.operator_info().address);
+    let mut input = builder.new_input(&collection.inner, Pipeline);
+
+    // Updates by interval, for intervals not yet written.
+    let mut pending: BTreeMap<u64, Vec<(D, G::Timestamp, R)>> = BTreeMap::new();
+    // The first interval the progress file does not report complete, or `None` once all are.
+    let mut progress = Some(0);
+    let mut buffer = Vec::new();
+
+    builder.build_reschedule(|_capabilities| {
+        move |frontiers| {
+            let mut input = FrontieredInputHandle::new(&mut input, &frontiers[0]);
+
+            input.for_each(|_capability, data| {
+                data.swap(&mut buffer);
+                for update in buffer.drain(..) {
+                    pending.entry(interval(&update.1)).or_insert_with(Vec::new).push(update);
+                }
+            });
+
+            // The first interval that may receive further updates, if any.
+            let upper = input.frontier.frontier().iter().map(&interval).min();
+
+            let mut failed = None;
+            while let Some(&next) = pending.keys().next() {
+                if upper.map_or(false, |upper| upper <= next) { break; }
+                let mut updates = pending.remove(&next).unwrap();
+                consolidate_updates(&mut updates);
+                if !updates.is_empty() {
+                    let path = directory.join(format!("part-{:020}-{}.parquet", next, worker));
+                    if let Err(error) = write_updates(&path, &updates) {
+                        eprintln!("columnar sink failed to write {:?}: {}", path, error);
+                        pending.insert(next, updates);
+                        failed = Some(next);
+                        break;
+                    }
+                }
+            }
+
+            // Intervals are complete up to the first that failed to write, if any.
+            let complete = failed.or(upper);
+            if complete != progress {
+                let contents = match complete {
+                    Some(upper) => upper.to_string(),
+                    None => "complete".to_string(),
+                };
+                let path = directory.join(format!("_progress-{}", worker));
+                match write_atomic(&path, |file| io::Write::write_all(file, contents.as_bytes())) {
+                    Ok(()) => progress = complete,
+                    Err(error) => eprintln!("columnar sink failed to write {:?}: {}", path, error),
+                }
+            }
+
+            // Retry failed writes after a delay, and signal incompleteness until they succeed.
+            let incomplete = failed.is_some() || complete != progress;
+            if incomplete {
+                reactivator.activate_after(RETRY_DELAY);
+            }
+            incomplete
+        }
+    });
+
+    Ok(())
+}
+
+/// Writes `updates` to a Parquet file at `path`.
+fn write_updates<D: Columns, T: Columns, R: Columns>(path: &Path, updates: &[(D, T, R)]) -> io::Result<()> {
+    let mut fields = Vec::new();
+    D::fields("data", &mut fields);
+    T::fields("time", &mut fields);
+    R::fields("diff", &mut fields);
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut columns = Vec::new();
+    D::columns(updates.iter().map(|(data, _, _)| data), &mut columns);
+    T::columns(updates.iter().map(|(_, time, _)| time), &mut columns);
+    R::columns(updates.iter().map(|(_, _, diff)| diff), &mut columns);
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(to_io)?;
+
+    write_atomic(path, |file| {
+        let mut writer = ArrowWriter::try_new(file, schema, None).map_err(to_io)?;
+        writer.write(&batch).map_err(to_io)?;
+        writer.close().map_err(to_io)?;
+        Ok(())
+    })
+}
+
+/// Writes a file at `path` by writing a temporary file and renaming it.
+fn write_atomic<W>(path: &Path, write: W) -> io::Result<()>
+where
+    W: FnOnce(&mut fs::File)->io::Result<()>,
+{
+    let temporary = path.with_extension("tmp");
+    let mut file = fs::File::create(&temporary)?;
+    write(&mut file)?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+/// Reports an Arrow or Parquet error as an IO error.
+fn to_io<E: std::error::Error+Send+Sync+'static>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
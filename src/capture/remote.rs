@@ -0,0 +1,131 @@
+//! Sharing arrangements between processes.
+//!
+//! An arrangement can be shared with dataflows of the same worker by importing its trace, but not
+//! with dataflows of other processes. This module moves an arrangement across processes with the CDC V2
+//! protocol: `serve` writes the batches of an arrangement as messages, both their updates and the
+//! advances of their frontiers, and `import` reads the messages in another process and arranges them
+//! again, into a trace that can be read and imported there as any other.
+//!
+//! The messages may be sent over any transport that delivers each of them at least once, in any
+//! order. To share an arrangement maintained by another dataflow, serve an import of its trace: the
+//! imported arrangement begins with the contents of the trace as of its compaction frontier, and the
+//! messages then describe the arrangement from that frontier on.
+//!
+//! # Examples
+//!
+//! ```
+//! use timely::progress::Antichain;
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::capture::harness::Recording;
+//! use differential_dataflow::capture::remote::{serve, import};
+//! use differential_dataflow::operators::arrange::ArrangeByKey;
+//! use differential_dataflow::trace::TraceReader;
+//!
+//! // the serving process writes the messages of an arrangement.
+//! let messages = ::timely::execute_directly(|worker| {
+//!     let recording = std::rc::Rc::new(std::cell::RefCell::new(Recording::new()));
+//!     worker.dataflow::<u32,_,_>(|scope| {
+//!         let arranged = scope.new_collection_from(0 .. 10u32).1
+//!                             .map(|x| (x % 3, x))
+//!                             .arrange_by_key();
+//!         serve(&arranged, 0, std::rc::Rc::downgrade(&recording), std::rc::Rc::downgrade(&recording));
+//!     });
+//!     while worker.step() { }
+//!     let messages = recording.borrow().messages().to_vec();
+//!     messages
+//! });
+//!
+//! // the importing process rebuilds the arrangement from the messages.
+//! ::timely::execute_directly(move |worker| {
+//!     let (_token, mut trace) = worker.dataflow::<u32,_,_>(|scope| {
+//!         let (token, arranged) = import::<_,_,_,u32,u32,u32,isize>(scope, Antichain::from_elem(0), move |_| messages.into_iter());
+//!         (token, arranged.trace)
+//!     });
+//!     while worker.step() { }
+//!     let mut upper = Antichain::new();
+//!     trace.read_upper(&mut upper);
+//!     assert!(upper.is_empty());
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::rc::Weak;
+
+use serde::{Deserialize, Serialize};
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Map;
+use timely::progress::{Antichain, Timestamp};
+use timely::scheduling::SyncActivator;
+
+use crate::{ExchangeData, Hashable};
+use crate::collection::AsCollection;
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::operators::arrange::{Arranged, ArrangeByKey, TraceAgent};
+use crate::trace::TraceReader;
+use crate::trace::implementations::ValSpine;
+use super::{Message, Writer};
+
+/// Writes the batches of `arranged` to `updates_sink` and `progress_sink`, as CDC V2 messages.
+///
+/// Each worker writes the updates of the batches it holds, and the worker selected by `sink_hash`
+/// writes the advances of the arrangement's frontier. The messages of all workers together describe
+/// the arrangement; they describe it through a time once its frontier has passed the time.
+///
+/// Updates of batches are consolidated, and are written without further consolidation.
+pub fn serve<G, Tr, K, V, BS>(
+    arranged: &Arranged<G, Tr>,
+    sink_hash: u64,
+    updates_sink: Weak<RefCell<BS>>,
+    progress_sink: Weak<RefCell<BS>>,
+)
+where
+    G: Scope<Timestamp = Tr::Time>,
+    Tr: for<'a> TraceReader<Key<'a> = &'a K, Val<'a> = &'a V> + Clone,
+    Tr::Time: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+    Tr::Diff: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+    K: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+    V: ExchangeData + Hash + Serialize + for<'a> Deserialize<'a>,
+    BS: Writer<Message<(K, V), Tr::Time, Tr::Diff>> + 'static,
+{
+    let updates = arranged.as_collection(|key, val| (key.clone(), val.clone()));
+    super::sink::build(&updates.inner, sink_hash, updates_sink, progress_sink);
+}
+
+/// Arranges the messages read from a source, as written by `serve`, with times advanced to `as_of`.
+///
+/// The `source_builder` argument is called with an activator for the operator that reads messages,
+/// as for `source::build`, and each worker should read all messages, or any subset of them provided
+/// each message is read by at least one worker. The returned trace handle holds back compaction to
+/// `as_of`, and so the trace can be read or imported as of any time beyond `as_of`. Times at which the
+/// served arrangement was compacted are not distinguished, and `as_of` should be beyond the compaction
+/// frontier of the served trace.
+///
+/// The arrangement is read until the returned token is dropped, or the source is exhausted.
+pub fn import<G, B, I, K, V, T, R>(
+    scope: &G,
+    as_of: Antichain<T>,
+    source_builder: B,
+) -> (Box<dyn std::any::Any + Send + Sync>, Arranged<G, TraceAgent<ValSpine<K, V, T, R>>>)
+where
+    G: Scope<Timestamp = T>,
+    B: FnOnce(SyncActivator) -> I,
+    I: Iterator<Item = Message<(K, V), T, R>> + 'static,
+    K: ExchangeData + Hash + Hashable,
+    V: ExchangeData + Hash,
+    T: ExchangeData + Hash + Timestamp + Lattice,
+    R: ExchangeData + Hash + Semigroup,
+{
+    let (token, stream) = super::source::build(scope.clone(), source_builder);
+
+    let frontier = as_of.clone();
+    let mut arranged = stream
+        .map_in_place(move |(_, time, _)| time.advance_by(frontier.borrow()))
+        .as_collection()
+        .arrange_by_key_named("ImportRemote");
+    arranged.trace.set_logical_compaction(as_of.borrow());
+
+    (token, arranged)
+}
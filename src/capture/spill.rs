@@ -0,0 +1,242 @@
+//! Storage on disk for updates held by `iterator::Iter` beyond its limit.
+//!
+//! Each spill of updates is written to a new segment file in a directory, with `bincode`, sorted by
+//! time so that the updates at each time are contiguous. Memory holds, for each segment, the position
+//! and number of the updates at each of its times, and an index from the hash of each spilled update to
+//! its segment and position, which deduplicates received updates against the spilled ones: an update
+//! whose hash is in the index is read back and compared.
+//!
+//! Extracting the updates at times a frontier has passed reads only the updates at those times, and
+//! a segment whose updates have all been extracted is removed. Each spilled update is written and read
+//! once, and the files hold only updates that have not yet been extracted.
+
+use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use timely::progress::Antichain;
+use timely::PartialOrder;
+
+use super::iterator::Overflow;
+
+/// Distinguishes the files of segments created by the same process.
+static SEGMENTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Updates at one time within a segment.
+struct Section<T> {
+    time: T,
+    /// The position of the first update in the segment file.
+    offset: u64,
+    /// The number of updates.
+    count: usize,
+}
+
+/// A file of spilled updates, sorted by time.
+struct Segment<T> {
+    path: PathBuf,
+    file: File,
+    /// The updates of the segment not yet extracted, by time.
+    sections: Vec<Section<T>>,
+}
+
+impl<T> Drop for Segment<T> {
+    fn drop(&mut self) {
+        // Best effort: the file holds no updates that are still needed.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Updates spilled to segment files, indexed by their hashes.
+pub(crate) struct Spill<D, T, R> {
+    directory: PathBuf,
+    /// Segments with updates not yet extracted, by identifier.
+    segments: BTreeMap<usize, Segment<T>>,
+    /// The segments and positions of spilled updates, by their hashes.
+    index: HashMap<u64, Vec<(usize, u64)>>,
+    phantom: PhantomData<(D, R)>,
+}
+
+fn invalid_data(error: bincode::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+impl<D, T, R> Spill<D, T, R>
+where
+    D: Hash + Eq + Serialize + DeserializeOwned,
+    T: Hash + Ord + Clone + Serialize + DeserializeOwned,
+    R: Hash + Eq + Serialize + DeserializeOwned,
+{
+    /// Creates a spill whose segments are files in `directory`, each removed once its updates are extracted.
+    pub(crate) fn new(directory: &Path) -> io::Result<Self> {
+        if !directory.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("{:?} is not a directory", directory)));
+        }
+        Ok(Spill { directory: directory.to_owned(), segments: BTreeMap::new(), index: HashMap::new(), phantom: PhantomData })
+    }
+
+    /// The number of segment files.
+    #[cfg(test)]
+    fn segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    fn hash(update: &(D, T, R)) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        update.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn read_at(file: &mut File, offset: u64) -> io::Result<(D, T, R)> {
+        file.seek(SeekFrom::Start(offset))?;
+        bincode::deserialize_from(BufReader::new(&*file)).map_err(invalid_data)
+    }
+}
+
+impl<D, T, R> Overflow<D, T, R> for Spill<D, T, R>
+where
+    D: Hash + Eq + Serialize + DeserializeOwned,
+    T: Hash + Ord + Clone + Serialize + DeserializeOwned,
+    R: Hash + Eq + Serialize + DeserializeOwned,
+{
+    fn contains(&mut self, update: &(D, T, R)) -> io::Result<bool> {
+        let positions = match self.index.get(&Self::hash(update)) {
+            Some(positions) => positions,
+            None => return Ok(false),
+        };
+        for (segment, offset) in positions.iter() {
+            let segment = self.segments.get_mut(segment).expect("index refers to a removed segment");
+            if Self::read_at(&mut segment.file, *offset)? == *update {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn extend(&mut self, mut updates: Vec<(D, T, R)>) -> io::Result<()> {
+        if updates.is_empty() { return Ok(()); }
+        updates.sort_by(|x, y| x.1.cmp(&y.1));
+
+        let id = SEGMENTS.fetch_add(1, Ordering::Relaxed);
+        let path = self.directory.join(format!("capture-spill-{}-{}", std::process::id(), id));
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        let mut segment = Segment { path, file, sections: Vec::new() };
+
+        let mut offset = 0;
+        let mut positions = Vec::with_capacity(updates.len());
+        let mut writer = BufWriter::new(&segment.file);
+        for update in updates.iter() {
+            if segment.sections.last().map_or(true, |section| section.time != update.1) {
+                segment.sections.push(Section { time: update.1.clone(), offset, count: 0 });
+            }
+            segment.sections.last_mut().unwrap().count += 1;
+            positions.push((Self::hash(update), offset));
+            offset += bincode::serialized_size(update).map_err(invalid_data)?;
+            bincode::serialize_into(&mut writer, update).map_err(invalid_data)?;
+        }
+        writer.flush()?;
+        drop(writer);
+
+        for (hash, offset) in positions {
+            self.index.entry(hash).or_default().push((id, offset));
+        }
+        self.segments.insert(id, segment);
+        Ok(())
+    }
+
+    fn extract(&mut self, frontier: &Antichain<T>) -> io::Result<Vec<(D, T, R)>> {
+        let mut extracted = Vec::new();
+        for (id, segment) in self.segments.iter_mut() {
+            let (released, retained): (Vec<_>, Vec<_>) = segment.sections
+                .drain(..)
+                .partition(|section| !frontier.less_equal(&section.time));
+            segment.sections = retained;
+            for section in released {
+                segment.file.seek(SeekFrom::Start(section.offset))?;
+                let mut reader = BufReader::new(&segment.file);
+                let mut offset = section.offset;
+                for _ in 0 .. section.count {
+                    let update: (D, T, R) = bincode::deserialize_from(&mut reader).map_err(invalid_data)?;
+                    let hash = Self::hash(&update);
+                    if let Some(positions) = self.index.get_mut(&hash) {
+                        positions.retain(|position| position != &(*id, offset));
+                        if positions.is_empty() { self.index.remove(&hash); }
+                    }
+                    offset += bincode::serialized_size(&update).map_err(invalid_data)?;
+                    extracted.push(update);
+                }
+            }
+        }
+        // Remove the files of segments whose updates have all been extracted.
+        self.segments.retain(|_id, segment| !segment.sections.is_empty());
+        Ok(extracted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use timely::progress::Antichain;
+
+    use super::Spill;
+    use crate::capture::iterator::Overflow;
+
+    fn directory() -> std::path::PathBuf {
+        std::env::temp_dir()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut spill = Spill::<String, u64, i64>::new(&directory()).unwrap();
+        spill.extend(vec![("a".to_string(), 2, 1), ("b".to_string(), 1, 1)]).unwrap();
+        spill.extend(vec![("c".to_string(), 1, -1)]).unwrap();
+        assert_eq!(spill.segments(), 2);
+
+        assert!(spill.contains(&("a".to_string(), 2, 1)).unwrap());
+        assert!(!spill.contains(&("a".to_string(), 1, 1)).unwrap());
+
+        // the updates at time one are extracted, and the segment holding only them is removed.
+        let mut extracted = spill.extract(&Antichain::from_elem(2)).unwrap();
+        extracted.sort();
+        assert_eq!(extracted, vec![("b".to_string(), 1, 1), ("c".to_string(), 1, -1)]);
+        assert_eq!(spill.segments(), 1);
+        assert!(!spill.contains(&("b".to_string(), 1, 1)).unwrap());
+        assert!(spill.contains(&("a".to_string(), 2, 1)).unwrap());
+
+        let extracted = spill.extract(&Antichain::new()).unwrap();
+        assert_eq!(extracted, vec![("a".to_string(), 2, 1)]);
+        assert_eq!(spill.segments(), 0);
+    }
+
+    #[test]
+    fn missing_directory() {
+        let missing = directory().join("capture-spill-missing-directory");
+        assert!(Spill::<String, u64, i64>::new(&missing).is_err());
+    }
+
+    #[test]
+    fn spilling_iterator() {
+        use crate::capture::{Message, Progress};
+        use crate::capture::iterator::Iter;
+
+        let messages = vec![
+            Message::Updates(vec![(1u32, 1u64, 1i64), (2, 1, 1), (3, 2, 1)]),
+            // a duplicate of a spilled update.
+            Message::Updates(vec![(1, 1, 1)]),
+            Message::Progress(Progress { lower: vec![0], upper: vec![3], counts: vec![(1, 2), (2, 1)] }),
+        ];
+        let mut iter = Iter::with_spill(messages.into_iter(), 1, &directory()).unwrap();
+        let (mut updates, frontier) = iter.next().unwrap();
+        updates.sort();
+        assert_eq!(updates, vec![(1, 1, 1), (2, 1, 1), (3, 2, 1)]);
+        assert_eq!(frontier, Antichain::from_elem(3));
+        assert!(iter.next().is_none());
+        assert!(iter.take_error().is_none());
+    }
+}
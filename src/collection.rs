@@ -203,6 +203,77 @@ impl<G: Scope, D: Data, R: Semigroup> Collection<G, D, R> where G::Timestamp: Da
             .concatenate(sources.into_iter().map(|x| x.inner))
             .as_collection()
     }
+    /// Creates a new collection accumulating the contents of this and many other collections, consolidated.
+    ///
+    /// Like `concatenate`, this method uses one operator for all of the collections. The operator sends
+    /// the updates it receives at the same time, from any of its inputs, through one consolidating
+    /// container builder, so that records that appear in several inputs are combined, and updates that
+    /// cancel are dropped, before they reach downstream operators. This suits plans that union many
+    /// collections with overlapping contents. As with `consolidate_stream`, there is no guarantee that
+    /// the result has at most one copy of each `(data, time)` pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     let odds = data.filter(|x| x % 2 == 1);
+    ///     let evens = data.filter(|x| x % 2 == 0);
+    ///
+    ///     odds.concatenate_many(vec![evens, data.negate(), data.clone()])
+    ///         .assert_eq(&data);
+    /// });
+    /// ```
+    pub fn concatenate_many<I>(&self, sources: I) -> Collection<G, D, R>
+    where
+        D: crate::Data,
+        I: IntoIterator<Item=Collection<G, D, R>>
+    {
+        use timely::dataflow::channels::pact::Pipeline;
+        use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+        use crate::consolidation::ConsolidatingContainerBuilder;
+
+        let mut builder = OperatorBuilder::new("ConcatenateMany".to_string(), self.scope());
+        let mut inputs = std::iter::once(self.clone())
+            .chain(sources)
+            .map(|collection| builder.new_input(&collection.inner, Pipeline))
+            .collect::<Vec<_>>();
+        let (mut output, stream) = builder.new_output::<ConsolidatingContainerBuilder<_>>();
+
+        builder.build(move |_capabilities| {
+
+            // Received batches, with capabilities for their times.
+            let mut received = Vec::new();
+
+            move |_frontiers| {
+                let mut output = output.activate();
+                for input in inputs.iter_mut() {
+                    input.for_each(|capability, data| {
+                        let mut batch = Vec::new();
+                        data.swap(&mut batch);
+                        received.push((capability.retain(), batch));
+                    });
+                }
+
+                // Send the batches at each time through one session, and so one container builder.
+                received.sort_by(|x, y| x.0.time().cmp(y.0.time()));
+                let mut batches = received.drain(..).peekable();
+                while let Some((capability, mut batch)) = batches.next() {
+                    let mut session = output.session_with_builder(&capability);
+                    session.give_container(&mut batch);
+                    while let Some((_, mut batch)) = batches.next_if(|(next, _)| next.time() == capability.time()) {
+                        session.give_container(&mut batch);
+                    }
+                }
+            }
+        });
+
+        stream.as_collection()
+    }
     /// Replaces each record with another, with a new difference type.
     ///
     /// This method is most commonly used to take records containing aggregatable data (e.g. numbers to be summed)
@@ -270,6 +341,68 @@ impl<G: Scope, D: Data, R: Semigroup> Collection<G, D, R> where G::Timestamp: Da
             .as_collection()
     }
 
+    /// Multiplies the difference of each record by `factor`.
+    ///
+    /// Records whose scaled difference is zero are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     data.scale_by(2)
+    ///         .assert_eq(&data.concat(&data));
+    /// });
+    /// ```
+    pub fn scale_by(&self, factor: R) -> Collection<G, D, <R as Multiply<R>>::Output>
+    where R: Multiply<R>,
+          <R as Multiply<R>>::Output: Data+Semigroup,
+    {
+        self.inner
+            .flat_map(move |(x, t, d)| {
+                let d = d.multiply(&factor);
+                if d.is_zero() { None } else { Some((x, t, d)) }
+            })
+            .as_collection()
+    }
+
+    /// Replaces the difference of each record using `logic`, potentially changing its type.
+    ///
+    /// The results are only meaningful if `logic` respects the difference types, in that it maps zero
+    /// to zero and the sum of two differences to the sum of their images. Differential dataflow may
+    /// accumulate updates before or after applying `logic`, and other functions will produce outputs
+    /// that depend on these unspecified choices. Records whose new difference is zero are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     data.map_diffs(|d| d as i64 * 3)
+    ///         .map_diffs(|d| (d / 3) as isize)
+    ///         .assert_eq(&data);
+    /// });
+    /// ```
+    pub fn map_diffs<R2, L>(&self, mut logic: L) -> Collection<G, D, R2>
+    where R2: Data+Semigroup,
+          L: FnMut(R)->R2+'static,
+    {
+        self.inner
+            .flat_map(move |(x, t, d)| {
+                let d = logic(d);
+                if d.is_zero() { None } else { Some((x, t, d)) }
+            })
+            .as_collection()
+    }
+
     /// Brings a Collection into a nested scope.
     ///
     /// # Examples
@@ -438,6 +571,146 @@ impl<G: Scope, D: Data, R: Semigroup> Collection<G, D, R> where G::Timestamp: Da
             .as_collection()
     }
 
+    /// Reports the completion of each time at which the collection has updates.
+    ///
+    /// The result contains each such time `t` once, as the record `t` at time `t`. It is only produced
+    /// once the frontier of the collection has passed `t`, so the appearance of a record announces that
+    /// the collection will see no further updates at its time. This allows dataflow logic to react to
+    /// the completion of times, for example to emit the contents of a window, without a probe.
+    ///
+    /// Times are collected at a single worker, which is the only worker to produce output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::AsCollection;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///     vec![(1u32, 0u64, 1isize), (2, 3, 1), (3, 3, 1), (4, 5, -1)]
+    ///         .to_stream(scope)
+    ///         .as_collection()
+    ///         .frontier_changes()
+    ///         .inner
+    ///         .capture()
+    /// });
+    ///
+    /// // each time with updates is reported once, at that time.
+    /// assert_eq!(captured.extract(), vec![(0, vec![(0, 0, 1)]), (3, vec![(3, 3, 1)]), (5, vec![(5, 5, 1)])]);
+    /// ```
+    pub fn frontier_changes(&self) -> Collection<G, G::Timestamp, isize>
+    where
+        G::Timestamp: Ord,
+    {
+        use std::collections::BTreeMap;
+        use timely::dataflow::channels::pact::{Exchange, Pipeline};
+
+        let mut vector = Vec::new();
+        let mut times = Vec::new();
+
+        self.inner
+            .unary(Pipeline, "FrontierTimes", move |_,_| move |input, output| {
+                input.for_each(|capability, data| {
+                    data.swap(&mut vector);
+                    times.extend(vector.drain(..).map(|(_, time, _)| time));
+                    times.sort();
+                    times.dedup();
+                    output.session(&capability).give_iterator(times.drain(..));
+                });
+            })
+            .unary_frontier(Exchange::new(|_: &G::Timestamp| 0), "FrontierChanges", move |_,_| {
+
+                // times yet to complete, each with a capability to report its completion.
+                let mut pending = BTreeMap::new();
+                let mut buffer = Vec::new();
+
+                move |input, output| {
+
+                    input.for_each(|capability, data| {
+                        data.swap(&mut buffer);
+                        for time in buffer.drain(..) {
+                            if !pending.contains_key(&time) {
+                                let delayed = capability.delayed(&time);
+                                pending.insert(time, delayed);
+                            }
+                        }
+                    });
+
+                    let frontier = input.frontier();
+                    pending.retain(|time: &G::Timestamp, capability: &mut Capability<G::Timestamp>| {
+                        if frontier.less_equal(time) { true }
+                        else {
+                            output.session(capability).give((time.clone(), time.clone(), 1));
+                            false
+                        }
+                    });
+                }
+            })
+            .as_collection()
+    }
+
+    /// Withholds each update until the frontier of the collection passes a time derived from it.
+    ///
+    /// For each update, `release` derives a time from its data and time. The update is held until the
+    /// frontier of the collection is no longer less or equal to the derived time, and then emitted unchanged,
+    /// at its original time. This implements emission policies, for example emitting the updates of a window
+    /// only once all of the window's times are complete, without writing an operator to manage capabilities.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // hold updates until their window of ten times completes.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .hold_until(|_data, time| (time / 10 + 1) * 10 - 1)
+    ///          .assert_eq(&scope.new_collection_from(1 .. 10).1);
+    /// });
+    /// ```
+    pub fn hold_until<F>(&self, mut release: F) -> Collection<G, D, R>
+    where
+        G::Timestamp: Ord,
+        F: FnMut(&D, &G::Timestamp) -> G::Timestamp + 'static,
+    {
+        use std::collections::BTreeMap;
+        use timely::dataflow::channels::pact::Pipeline;
+
+        self.inner
+            .unary_frontier(Pipeline, "HoldUntil", move |_,_| {
+
+                // held updates by their release time and time, each with a capability for the time.
+                let mut pending = BTreeMap::new();
+                let mut buffer = Vec::new();
+
+                move |input, output| {
+
+                    input.for_each(|capability, data| {
+                        data.swap(&mut buffer);
+                        for (data, time, diff) in buffer.drain(..) {
+                            let until = release(&data, &time);
+                            pending
+                                .entry((until, time.clone()))
+                                .or_insert_with(|| (capability.delayed(&time), Vec::new()))
+                                .1
+                                .push((data, time, diff));
+                        }
+                    });
+
+                    let frontier = input.frontier();
+                    pending.retain(|(until, _time): &(G::Timestamp, G::Timestamp), (capability, updates): &mut (Capability<G::Timestamp>, Vec<(D, G::Timestamp, R)>)| {
+                        if frontier.less_equal(until) { true }
+                        else {
+                            output.session(capability).give_container(updates);
+                            false
+                        }
+                    });
+                }
+            })
+            .as_collection()
+    }
+
     /// Assert if the collection is ever non-empty.
     ///
     /// Because this is a dataflow fragment, the test is only applied as the computation is run. If the computation
@@ -522,6 +795,81 @@ impl<'a, G: Scope, D: Data, R: Semigroup> Collection<Child<'a, G, G::Timestamp>,
     }
 }
 
+/// Methods for collections of key-value pairs.
+impl<G: Scope, K: crate::Data, V: crate::Data, R: Semigroup> Collection<G, (K, V), R> where G::Timestamp: Data {
+    /// The keys of the collection, consolidated as they are produced.
+    ///
+    /// This is `map(|(key, _)| key)`, except that each batch of updates is consolidated as it is
+    /// projected, so that a key with many values, or whose value changes, produces one update for
+    /// each time rather than one for each value. As with `consolidate_stream`, the updates are not
+    /// exchanged, and there is no guarantee that the result has at most one copy of each `(key, time)` pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     data.map(|x| (x % 3, x))
+    ///         .keys()
+    ///         .assert_eq(&data.map(|x| x % 3));
+    /// });
+    /// ```
+    pub fn keys(&self) -> Collection<G, K, R> {
+        self.map_consolidated("Keys", |(key, _)| key)
+    }
+
+    /// The values of the collection, consolidated as they are produced.
+    ///
+    /// As `keys`, but projecting each record to its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     data.map(|x| (x, x % 3))
+    ///         .values()
+    ///         .assert_eq(&data.map(|x| x % 3));
+    /// });
+    /// ```
+    pub fn values(&self) -> Collection<G, V, R> {
+        self.map_consolidated("Values", |(_, val)| val)
+    }
+
+    /// Maps each record with `logic`, and consolidates each batch of the results.
+    fn map_consolidated<D2, L>(&self, name: &str, logic: L) -> Collection<G, D2, R>
+    where
+        D2: crate::Data,
+        L: Fn((K, V)) -> D2 + 'static,
+    {
+        use timely::dataflow::channels::pact::Pipeline;
+        use crate::consolidation::ConsolidatingContainerBuilder;
+
+        self.inner
+            .unary::<ConsolidatingContainerBuilder<_>, _, _, _>(Pipeline, name, move |_cap, _info| {
+                let mut vector = Vec::new();
+                move |input, output| {
+                    input.for_each(|time, data| {
+                        data.swap(&mut vector);
+                        let mut session = output.session_with_builder(&time);
+                        for (datum, time, diff) in vector.drain(..) {
+                            session.give((logic(datum), time, diff));
+                        }
+                    })
+                }
+            })
+            .as_collection()
+    }
+}
+
 /// Methods requiring an Abelian difference, to support negation.
 impl<G: Scope, D: Data, R: Abelian> Collection<G, D, R> where G::Timestamp: Data {
     /// Creates a new collection whose counts are the negation of those in the input.
@@ -632,3 +980,88 @@ where
         .concatenate(iterator.into_iter().map(|x| x.inner))
         .as_collection()
 }
+
+/// Delays the updates of several collections, so that each time is released by all of them at once.
+///
+/// Each result holds the updates of the corresponding collection, but only once the frontiers of all of
+/// the collections have passed their time. Outputs of a dataflow that are derived from the same inputs
+/// along paths of different lengths otherwise reflect a time at different moments, and downstream
+/// consumers may observe some outputs updated for a time and others not yet. After alignment, all of
+/// the results reflect a time from the same moment on.
+///
+/// # Examples
+///
+/// ```
+/// use differential_dataflow::input::Input;
+/// use differential_dataflow::operators::Count;
+///
+/// ::timely::example(|scope| {
+///
+///     let data = scope.new_collection_from(1 .. 10).1;
+///
+///     let aligned = differential_dataflow::collection::align(&[data.clone(), data.map(|x| x % 3).count().map(|(x, _)| x)]);
+///     aligned[0].assert_eq(&data);
+/// });
+/// ```
+pub fn align<G, D, R>(collections: &[Collection<G, D, R>]) -> Vec<Collection<G, D, R>>
+where
+    G: Scope,
+    G::Timestamp: Data,
+    D: Data,
+    R: Semigroup,
+{
+    use std::collections::BTreeMap;
+    use timely::dataflow::channels::pact::Pipeline;
+    use timely::dataflow::operators::Capability;
+    use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+
+    let scope = match collections.first() {
+        Some(collection) => collection.scope(),
+        None => return Vec::new(),
+    };
+
+    let mut builder = OperatorBuilder::new("Align".to_string(), scope);
+    let mut inputs = collections
+        .iter()
+        .map(|collection| builder.new_input(&collection.inner, Pipeline))
+        .collect::<Vec<_>>();
+    let (mut outputs, streams): (Vec<_>, Vec<_>) = collections
+        .iter()
+        .map(|_| builder.new_output())
+        .unzip();
+
+    builder.build(move |_capabilities| {
+
+        // The updates of each input by their times, with capabilities for the corresponding output.
+        let mut pending: Vec<BTreeMap<G::Timestamp, (Capability<G::Timestamp>, Vec<(D, G::Timestamp, R)>)>> =
+            inputs.iter().map(|_| BTreeMap::new()).collect();
+        let mut buffer = Vec::new();
+
+        move |frontiers| {
+            for (index, input) in inputs.iter_mut().enumerate() {
+                input.for_each(|capability, data| {
+                    data.swap(&mut buffer);
+                    // Updates may be at times later than the capability of their message.
+                    for update in buffer.drain(..) {
+                        let (_, updates) = pending[index]
+                            .entry(update.1.clone())
+                            .or_insert_with(|| (capability.delayed_for_output(&update.1, index), Vec::new()));
+                        updates.push(update);
+                    }
+                });
+            }
+
+            // Release the updates at times that all input frontiers have passed.
+            for (pending, output) in pending.iter_mut().zip(outputs.iter_mut()) {
+                let mut output = output.activate();
+                pending.retain(|time, (capability, updates)| {
+                    if frontiers.iter().any(|frontier| frontier.less_equal(time)) { return true; }
+                    output.session(&*capability).give_container(updates);
+                    false
+                });
+            }
+        }
+    });
+
+    streams.into_iter().map(|stream| stream.as_collection()).collect()
+}
@@ -210,6 +210,62 @@ where
     }
 }
 
+/// Consolidates updates separately for each of a number of destinations, for example the workers
+/// an exchange routes updates to.
+///
+/// Each destination has its own `ConsolidatingContainerBuilder`, and so each container the builder
+/// produces holds consolidated updates for a single destination. An exchange that routes updates by
+/// the same destinations sends each container whole to its destination. The builder is not itself a
+/// `ContainerBuilder`, as the number of destinations is not known to `Default`.
+pub struct PartitionedConsolidatingBuilder<C> {
+    parts: Vec<ConsolidatingContainerBuilder<C>>,
+}
+
+impl<D,T,R> PartitionedConsolidatingBuilder<Vec<(D, T, R)>>
+where
+    D: Data,
+    T: Data,
+    R: Semigroup,
+{
+    /// Creates a builder for `parts` destinations.
+    pub fn new(parts: usize) -> Self {
+        Self { parts: (0 .. parts).map(|_| Default::default()).collect() }
+    }
+
+    /// The number of destinations.
+    pub fn parts(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Pushes an update destined for `part`.
+    #[inline]
+    pub fn push(&mut self, part: usize, update: (D, T, R)) {
+        self.parts[part].push(update);
+    }
+
+    /// Extracts a complete container, and its destination, if any.
+    pub fn extract(&mut self) -> Option<(usize, &mut Vec<(D, T, R)>)> {
+        for (part, builder) in self.parts.iter_mut().enumerate() {
+            if let Some(container) = builder.extract() {
+                return Some((part, container));
+            }
+        }
+        None
+    }
+
+    /// Extracts the remaining containers, and their destinations, one at a time.
+    ///
+    /// Call repeatedly until it returns `None`.
+    pub fn finish(&mut self) -> Option<(usize, &mut Vec<(D, T, R)>)> {
+        for (part, builder) in self.parts.iter_mut().enumerate() {
+            if let Some(container) = builder.finish() {
+                return Some((part, container));
+            }
+        }
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -318,4 +374,32 @@ mod tests {
         }
         
     }
+
+    #[test]
+    fn test_partitioned_consolidating_builder() {
+        let mut pcb = <PartitionedConsolidatingBuilder<Vec<(usize, usize, isize)>>>::new(3);
+        for round in 0..4 {
+            for i in 0..1024 {
+                let diff = if round % 2 == 0 { 1 } else { -1 };
+                pcb.push(i % 3, (i, 0, diff));
+            }
+        }
+        for i in 0..1024 {
+            pcb.push(i % 3, (i, 0, 1));
+        }
+
+        let mut collected = Vec::default();
+        while let Some((part, container)) = pcb.extract() {
+            collected.extend(container.drain(..).map(|(i, t, r)| ((i, part), t, r)));
+        }
+        while let Some((part, container)) = pcb.finish() {
+            collected.extend(container.drain(..).map(|(i, t, r)| ((i, part), t, r)));
+        }
+        // Containers are consolidated, but a record may appear in several of them.
+        consolidate_updates(&mut collected);
+        assert_eq!(collected.len(), 1024);
+        for (i, update) in collected.into_iter().enumerate() {
+            assert_eq!(update, ((i, i % 3), 0, 1));
+        }
+    }
 }
@@ -167,6 +167,198 @@ mod present {
     }
 }
 
+pub use self::tropical::MinPlus;
+mod tropical {
+    use abomonation_derive::Abomonation;
+    use serde::{Deserialize, Serialize};
+
+    use crate::Data;
+
+    /// A difference in the min-plus, or tropical, semiring.
+    ///
+    /// Addition takes the minimum of two values, and multiplication adds them. The zero element is
+    /// an infinite value, which multiplication preserves. Collections with these differences track,
+    /// for each record, the least value with which it has been introduced, for example the length of
+    /// the shortest path to a node. Joins add the values of matched records, so that a join of the
+    /// distances to nodes with weighted edges produces the distances through those edges.
+    ///
+    /// The type has no negation, and values cannot be retracted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::difference::{MinPlus, Multiply, Semigroup};
+    ///
+    /// let mut distance = MinPlus::new(5);
+    /// distance.plus_equals(&MinPlus::new(2).multiply(&MinPlus::new(1)));
+    /// assert_eq!(distance.value(), Some(&3));
+    /// ```
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash)]
+    pub struct MinPlus<T> {
+        value: Option<T>,
+    }
+
+    impl<T> MinPlus<T> {
+        /// A finite value.
+        pub fn new(value: T) -> Self {
+            Self { value: Some(value) }
+        }
+        /// The infinite value, which is the additive identity.
+        pub fn infinity() -> Self {
+            Self { value: None }
+        }
+        /// The value, unless it is infinite.
+        pub fn value(&self) -> Option<&T> {
+            self.value.as_ref()
+        }
+    }
+
+    impl<T: Data> super::Semigroup for MinPlus<T> {
+        fn plus_equals(&mut self, rhs: &Self) {
+            if let Some(other) = &rhs.value {
+                if self.value.as_ref().map(|value| other < value).unwrap_or(true) {
+                    self.value = Some(other.clone());
+                }
+            }
+        }
+        fn is_zero(&self) -> bool {
+            self.value.is_none()
+        }
+    }
+
+    impl<T: Data> super::Monoid for MinPlus<T> {
+        fn zero() -> Self {
+            Self::infinity()
+        }
+    }
+
+    impl<T: Clone+std::ops::Add<Output=T>> super::Multiply<Self> for MinPlus<T> {
+        type Output = Self;
+        fn multiply(self, rhs: &Self) -> Self {
+            match (self.value, &rhs.value) {
+                (Some(value1), Some(value2)) => Self::new(value1 + value2.clone()),
+                _ => Self::infinity(),
+            }
+        }
+    }
+}
+
+pub use self::ordinal::{FirstValue, LastValue};
+mod ordinal {
+    use abomonation_derive::Abomonation;
+    use serde::{Deserialize, Serialize};
+
+    use crate::Data;
+
+    macro_rules! ordinal_implementation {
+        ($name:ident, $replace:tt) => {
+            impl<T> $name<T> {
+                /// A value, with the ordinal that orders it among others.
+                pub fn new(ordinal: u64, value: T) -> Self {
+                    Self { entry: Some((ordinal, value)) }
+                }
+                /// The absence of a value, which is the additive identity.
+                pub fn none() -> Self {
+                    Self { entry: None }
+                }
+                /// The ordinal of the value, if any.
+                pub fn ordinal(&self) -> Option<u64> {
+                    self.entry.as_ref().map(|(ordinal, _)| *ordinal)
+                }
+                /// The value, if any.
+                pub fn value(&self) -> Option<&T> {
+                    self.entry.as_ref().map(|(_, value)| value)
+                }
+            }
+
+            impl<T: Data> super::Semigroup for $name<T> {
+                fn plus_equals(&mut self, rhs: &Self) {
+                    if let Some(other) = &rhs.entry {
+                        if self.entry.as_ref().map(|entry| other $replace entry).unwrap_or(true) {
+                            self.entry = Some(other.clone());
+                        }
+                    }
+                }
+                fn is_zero(&self) -> bool {
+                    self.entry.is_none()
+                }
+            }
+
+            impl<T: Data> super::Monoid for $name<T> {
+                fn zero() -> Self {
+                    Self::none()
+                }
+            }
+
+            /// Scales by a multiplicity, which must not be negative as values cannot be retracted.
+            ///
+            /// A positive multiplicity leaves the value unchanged, and zero removes it. This allows
+            /// `explode` to introduce values from collections with integer differences.
+            impl<T> super::Multiply<isize> for $name<T> {
+                type Output = Self;
+                fn multiply(self, rhs: &isize) -> Self {
+                    assert!(*rhs >= 0, "{} values cannot be retracted", stringify!($name));
+                    if *rhs == 0 { Self::none() } else { self }
+                }
+            }
+        }
+    }
+
+    /// A difference that retains the value with the least ordinal.
+    ///
+    /// Addition keeps the value with the least ordinal, breaking ties by the least value, and the zero
+    /// element has no value. Collections with these differences track, for each record, the first value
+    /// written to it, where the ordinal is for example a log sequence number. The type has no negation,
+    /// and values cannot be retracted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::difference::{FirstValue, Semigroup};
+    ///
+    /// let mut first = FirstValue::new(5, "later");
+    /// first.plus_equals(&FirstValue::new(2, "earlier"));
+    /// assert_eq!(first.value(), Some(&"earlier"));
+    /// ```
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash)]
+    pub struct FirstValue<T> {
+        entry: Option<(u64, T)>,
+    }
+
+    ordinal_implementation!(FirstValue, <);
+
+    /// A difference that retains the value with the greatest ordinal.
+    ///
+    /// Addition keeps the value with the greatest ordinal, breaking ties by the greatest value, and the
+    /// zero element has no value. Collections with these differences track, for each record, the most
+    /// recent value written to it, where the ordinal is for example a log sequence number. With `explode`
+    /// and `count_total`, they materialize the latest value of each key without reducing over the history
+    /// of the key. The type has no negation, and values cannot be retracted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::difference::LastValue;
+    /// use differential_dataflow::operators::CountTotal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // writes of (key, sequence number, value).
+    ///     let writes = scope.new_collection_from(vec![("a", 1, 10), ("a", 3, 30), ("b", 2, 20)]).1;
+    ///     writes.explode(|(key, ordinal, value)| Some((key, LastValue::new(ordinal, value))))
+    ///           .count_total()
+    ///           .map(|(key, last)| (key, *last.value().unwrap()))
+    ///           .assert_eq(&scope.new_collection_from(vec![("a", 30), ("b", 20)]).1);
+    /// });
+    /// ```
+    #[derive(Abomonation, Copy, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash)]
+    pub struct LastValue<T> {
+        entry: Option<(u64, T)>,
+    }
+
+    ordinal_implementation!(LastValue, >);
+}
+
 // Pair implementations.
 mod tuples {
 
@@ -273,3 +465,160 @@ mod vector {
         }
     }
 }
+
+// Array implementations
+mod array {
+
+    use super::{Semigroup, Monoid, Abelian, Multiply};
+
+    impl<R: Semigroup, const N: usize> Semigroup for [R; N] {
+        fn plus_equals(&mut self, rhs: &Self) {
+            for (update, other) in self.iter_mut().zip(rhs.iter()) {
+                update.plus_equals(other);
+            }
+        }
+        fn is_zero(&self) -> bool {
+            self.iter().all(|x| x.is_zero())
+        }
+    }
+
+    impl<R: Monoid, const N: usize> Monoid for [R; N] {
+        fn zero() -> Self {
+            std::array::from_fn(|_| R::zero())
+        }
+    }
+
+    impl<R: Abelian, const N: usize> Abelian for [R; N] {
+        fn negate(self) -> Self {
+            self.map(|x| x.negate())
+        }
+    }
+
+    impl<T, R: Multiply<T>, const N: usize> Multiply<T> for [R; N] {
+        type Output = [<R as Multiply<T>>::Output; N];
+        fn multiply(self, rhs: &T) -> Self::Output {
+            self.map(|x| x.multiply(rhs))
+        }
+    }
+}
+
+pub use self::map::VecMap;
+mod map {
+
+    use abomonation_derive::Abomonation;
+    use serde::{Deserialize, Serialize};
+
+    use crate::Data;
+    use super::{Semigroup, Monoid, Abelian, Multiply};
+
+    /// A map from keys to differences, represented as a vector sorted by key.
+    ///
+    /// Addition merges the two maps, adding the differences of keys present in both. Keys whose
+    /// differences are zero are removed, so that the map is zero exactly when it is empty. The map
+    /// suits small numbers of keys, for example counts for each of a few categories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::difference::{Semigroup, VecMap};
+    ///
+    /// let mut counts = VecMap::from(vec![("apple", 2), ("pear", 1)]);
+    /// counts.plus_equals(&VecMap::from(vec![("pear", -1), ("plum", 3)]));
+    /// assert_eq!(counts.as_slice(), &[("apple", 2), ("plum", 3)]);
+    /// ```
+    #[derive(Abomonation, Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Hash, Default)]
+    pub struct VecMap<K, R> {
+        entries: Vec<(K, R)>,
+    }
+
+    impl<K: Ord, R: Semigroup> VecMap<K, R> {
+        /// A map containing the single entry `(key, diff)`, unless `diff` is zero.
+        pub fn singleton(key: K, diff: R) -> Self {
+            let mut entries = Vec::with_capacity(1);
+            if !diff.is_zero() {
+                entries.push((key, diff));
+            }
+            Self { entries }
+        }
+        /// The difference associated with `key`, if it is non-zero.
+        pub fn get(&self, key: &K) -> Option<&R> {
+            self.entries
+                .binary_search_by(|(k, _)| k.cmp(key))
+                .ok()
+                .map(|index| &self.entries[index].1)
+        }
+        /// The entries of the map, sorted by key.
+        pub fn as_slice(&self) -> &[(K, R)] {
+            &self.entries[..]
+        }
+    }
+
+    impl<K: Ord, R: Semigroup> From<Vec<(K, R)>> for VecMap<K, R> {
+        /// Sorts the entries by key, adding the differences of repeated keys.
+        fn from(mut entries: Vec<(K, R)>) -> Self {
+            entries.sort_by(|x, y| x.0.cmp(&y.0));
+            let mut result: Vec<(K, R)> = Vec::with_capacity(entries.len());
+            for (key, diff) in entries {
+                match result.last_mut() {
+                    Some((last, sum)) if last == &key => sum.plus_equals(&diff),
+                    _ => result.push((key, diff)),
+                }
+            }
+            result.retain(|(_, diff)| !diff.is_zero());
+            Self { entries: result }
+        }
+    }
+
+    impl<K: Data, R: Semigroup> Semigroup for VecMap<K, R> {
+        fn plus_equals(&mut self, rhs: &Self) {
+            let mut merged = Vec::with_capacity(self.entries.len() + rhs.entries.len());
+            let mut rhs_iter = rhs.entries.iter().peekable();
+            for (key, mut diff) in self.entries.drain(..) {
+                while let Some((k, d)) = rhs_iter.next_if(|(k, _)| k < &key) {
+                    merged.push((k.clone(), d.clone()));
+                }
+                if let Some((_, d)) = rhs_iter.next_if(|(k, _)| k == &key) {
+                    diff.plus_equals(d);
+                }
+                if !diff.is_zero() {
+                    merged.push((key, diff));
+                }
+            }
+            merged.extend(rhs_iter.cloned());
+            self.entries = merged;
+        }
+        fn is_zero(&self) -> bool {
+            self.entries.is_empty()
+        }
+    }
+
+    impl<K: Data, R: Semigroup> Monoid for VecMap<K, R> {
+        fn zero() -> Self {
+            Self { entries: Vec::new() }
+        }
+    }
+
+    impl<K: Data, R: Abelian> Abelian for VecMap<K, R> {
+        fn negate(mut self) -> Self {
+            for (_, diff) in self.entries.iter_mut() {
+                *diff = diff.clone().negate();
+            }
+            self
+        }
+    }
+
+    impl<T, K: Data, R: Multiply<T>> Multiply<T> for VecMap<K, R>
+    where
+        <R as Multiply<T>>::Output: Semigroup,
+    {
+        type Output = VecMap<K, <R as Multiply<T>>::Output>;
+        fn multiply(self, rhs: &T) -> Self::Output {
+            let entries = self.entries
+                .into_iter()
+                .map(|(key, diff)| (key, diff.multiply(rhs)))
+                .filter(|(_, diff)| !diff.is_zero())
+                .collect();
+            VecMap { entries }
+        }
+    }
+}
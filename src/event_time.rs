@@ -0,0 +1,116 @@
+//! A timestamp type for wall-clock event times.
+//!
+//! Event-time pipelines stamp updates with the times at which they occurred, which are most often
+//! read from a clock or from the events themselves. The `Millis` type counts milliseconds since the
+//! Unix epoch, and implements the traits that timestamps require of timely and differential dataflow.
+//! Its least element is the epoch, and times before the epoch are clamped to it.
+//!
+//! As a timely `Timestamp`, `Millis` can be the timestamp of a dataflow, and can be refined into
+//! iterative scopes, whose timestamps are `Product<Millis, T>`.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::event_time::Millis;
+//! use differential_dataflow::operators::Iterate;
+//!
+//! ::timely::execute_directly(|worker| {
+//!     let mut input = worker.dataflow::<Millis,_,_>(|scope| {
+//!         let (input, events) = scope.new_collection::<u64, isize>();
+//!         events.iterate(|values| values.map(|x| x / 2));
+//!         input
+//!     });
+//!     input.advance_to(Millis::from_system_time(UNIX_EPOCH + Duration::from_secs(60)));
+//!     input.insert(100);
+//! });
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use abomonation_derive::Abomonation;
+use serde::{Deserialize, Serialize};
+use timely::container::columnation::{Columnation, CopyRegion};
+use timely::order::{PartialOrder, TotalOrder};
+use timely::progress::{PathSummary, Timestamp};
+use timely::progress::timestamp::Refines;
+
+use crate::lattice::{Lattice, Maximum};
+
+/// Milliseconds since the Unix epoch.
+///
+/// As a path summary, the type describes a delay of some number of milliseconds.
+#[derive(Copy, Clone, Default, Hash, Eq, PartialEq, Ord, PartialOrd, Debug, Serialize, Deserialize, Abomonation)]
+pub struct Millis(pub u64);
+
+impl Millis {
+    /// The time of `time`, or the epoch if `time` is earlier.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let since = time.duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO);
+        Millis(since.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+    /// The time of the system clock.
+    pub fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+    /// The corresponding time of the system clock.
+    pub fn to_system_time(self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.0)
+    }
+    /// Rounds the time down to a multiple of `period`, for example to the start of a window.
+    ///
+    /// The time is unchanged if `period` is shorter than a millisecond.
+    pub fn round_down(self, period: Duration) -> Self {
+        let period: u64 = period.as_millis().try_into().unwrap_or(u64::MAX);
+        if period == 0 { self } else { Millis(self.0 - self.0 % period) }
+    }
+}
+
+impl From<Duration> for Millis {
+    /// The time `duration` after the epoch, or the delay `duration`.
+    fn from(duration: Duration) -> Self {
+        Millis(duration.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+}
+
+impl PartialOrder for Millis {
+    #[inline]
+    fn less_equal(&self, other: &Self) -> bool { self.0 <= other.0 }
+}
+
+impl TotalOrder for Millis { }
+
+impl PathSummary<Millis> for Millis {
+    #[inline]
+    fn results_in(&self, src: &Millis) -> Option<Millis> { self.0.checked_add(src.0).map(Millis) }
+    #[inline]
+    fn followed_by(&self, other: &Millis) -> Option<Millis> { self.0.checked_add(other.0).map(Millis) }
+}
+
+impl Timestamp for Millis {
+    type Summary = Millis;
+    fn minimum() -> Self { Millis(0) }
+}
+
+impl Refines<()> for Millis {
+    fn to_inner(_outer: ()) -> Self { Self::minimum() }
+    fn to_outer(self) -> () { }
+    fn summarize(_summary: Self::Summary) -> () { }
+}
+
+impl Lattice for Millis {
+    const TOTAL: bool = true;
+    #[inline]
+    fn join(&self, other: &Self) -> Self { std::cmp::max(*self, *other) }
+    #[inline]
+    fn meet(&self, other: &Self) -> Self { std::cmp::min(*self, *other) }
+}
+
+impl Maximum for Millis {
+    fn maximum() -> Self { Millis(u64::MAX) }
+}
+
+impl Columnation for Millis {
+    type InnerRegion = CopyRegion<Millis>;
+}
@@ -11,10 +11,19 @@ use timely::dataflow::operators::Input as TimelyInput;
 use timely::dataflow::operators::input::Handle;
 use timely::dataflow::scopes::ScopeParent;
 
+use abomonation_derive::Abomonation;
+use serde::{Deserialize, Serialize};
+
 use crate::Data;
+use crate::quota::Backpressure;
 use crate::difference::Semigroup;
 use crate::collection::{Collection, AsCollection};
 
+pub mod hydrate;
+pub mod parse;
+#[cfg(feature = "futures")]
+pub mod stream;
+
 /// Create a new collection and input handle to control the collection.
 pub trait Input : TimelyInput {
     /// Create a new collection and input handle to subsequently control the collection.
@@ -170,6 +179,27 @@ pub struct InputSession<T: Timestamp+Clone, D: Data, R: Semigroup> {
     time: T,
     buffer: Vec<(D, T, R)>,
     handle: Handle<T,(D,T,R)>,
+    /// Sent updates at times the handle has not yet advanced past, if retained for checkpoints.
+    sent: Option<Vec<(D, T, R)>>,
+    /// A signal that the dataflow fed by the session is over its memory quota.
+    backpressure: Option<Backpressure>,
+}
+
+/// The state of an `InputSession`, from which an equivalent session can be restored.
+///
+/// A checkpoint records the updates of the session at times greater or equal to its `epoch`, whether
+/// or not they have been sent to the dataflow. Updates at earlier times are not recorded; they are
+/// covered by snapshots of the arrangements of the input, once those arrangements are complete through
+/// `epoch`. A checkpoint and snapshots of traces whose upper frontiers are `epoch` together describe
+/// the state of the computation, from which it can resume after a restart.
+#[derive(Clone, Debug, Serialize, Deserialize, Abomonation)]
+pub struct InputCheckpoint<T, D, R> {
+    /// The time the session last advanced its dataflow input to.
+    pub epoch: T,
+    /// The time of the session, which may be in advance of `epoch`.
+    pub time: T,
+    /// The updates of the session at times greater or equal to `epoch`.
+    pub updates: Vec<(D, T, R)>,
 }
 
 impl<T: Timestamp+Clone, D: Data> InputSession<T, D, isize> {
@@ -212,6 +242,8 @@ impl<T: Timestamp+Clone, D: Data, R: Semigroup> InputSession<T, D, R> {
             time: handle.time().clone(),
             buffer: Vec::new(),
             handle,
+            sent: None,
+            backpressure: None,
         }
     }
 
@@ -221,6 +253,8 @@ impl<T: Timestamp+Clone, D: Data, R: Semigroup> InputSession<T, D, R> {
             time: handle.time().clone(),
             buffer: Vec::new(),
             handle,
+            sent: None,
+            backpressure: None,
         }
     }
 
@@ -228,7 +262,7 @@ impl<T: Timestamp+Clone, D: Data, R: Semigroup> InputSession<T, D, R> {
     pub fn update(&mut self, element: D, change: R) {
         if self.buffer.len() == self.buffer.capacity() {
             if !self.buffer.is_empty() {
-                self.handle.send_batch(&mut self.buffer);
+                self.send_buffer();
             }
             // TODO : This is a fairly arbitrary choice; should probably use `Context::default_size()` or such.
             self.buffer.reserve(1024);
@@ -241,7 +275,7 @@ impl<T: Timestamp+Clone, D: Data, R: Semigroup> InputSession<T, D, R> {
         assert!(self.time.less_equal(&time));
         if self.buffer.len() == self.buffer.capacity() {
             if !self.buffer.is_empty() {
-                self.handle.send_batch(&mut self.buffer);
+                self.send_buffer();
             }
             // TODO : This is a fairly arbitrary choice; should probably use `Context::default_size()` or such.
             self.buffer.reserve(1024);
@@ -255,12 +289,100 @@ impl<T: Timestamp+Clone, D: Data, R: Semigroup> InputSession<T, D, R> {
     /// called, all updates may still be in internal buffers and not exposed to timely dataflow. Once the method is
     /// called, all buffers are flushed and timely dataflow is advised that some logical times are no longer possible.
     pub fn flush(&mut self) {
-        self.handle.send_batch(&mut self.buffer);
+        self.send_buffer();
         if self.handle.epoch().less_than(&self.time) {
             self.handle.advance_to(self.time.clone());
+            if let Some(sent) = self.sent.as_mut() {
+                let epoch = self.handle.epoch();
+                sent.retain(|(_, time, _)| epoch.less_equal(time));
+            }
+        }
+    }
+
+    /// Sends buffered updates to the timely dataflow input, retaining copies if checkpointing.
+    fn send_buffer(&mut self) {
+        if let Some(sent) = self.sent.as_mut() {
+            sent.extend(self.buffer.iter().cloned());
+        }
+        self.handle.send_batch(&mut self.buffer);
+    }
+
+    /// Retains sent updates, so that the session can produce checkpoints.
+    ///
+    /// Updates sent to the dataflow are retained until the session flushes a time past theirs, which
+    /// costs memory proportional to the updates at incomplete times. This method must be called before
+    /// the session sends any updates, as earlier updates would be missing from checkpoints.
+    pub fn with_checkpoints(mut self) -> Self {
+        assert!(self.buffer.is_empty(), "checkpoints enabled after updates were introduced");
+        self.sent = Some(Vec::new());
+        self
+    }
+
+    /// Associates a backpressure signal with the session, to be reported by `backpressured`.
+    pub fn with_backpressure(mut self, signal: Backpressure) -> Self {
+        self.backpressure = Some(signal);
+        self
+    }
+
+    /// Indicates whether the dataflow fed by the session is over its memory quota.
+    ///
+    /// The session does not itself withhold updates; callers should consult this method before they
+    /// introduce further updates, and step the worker rather than ingest data while it returns true.
+    /// Sessions without a backpressure signal are never backpressured.
+    pub fn backpressured(&self) -> bool {
+        self.backpressure.as_ref().map(|signal| signal.engaged()).unwrap_or(false)
+    }
+
+    /// Records the state of the session, from which an equivalent session can be restored.
+    ///
+    /// Checkpoints are only available for sessions created `with_checkpoints`. The checkpoint is
+    /// consistent with arrangements of the input once their upper frontiers reach the checkpoint's
+    /// `epoch`, which they do not pass until the session is next flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::InputSession;
+    ///
+    /// let mut session = InputSession::<usize, String, isize>::new().with_checkpoints();
+    /// session.insert("hello".to_string());
+    /// session.advance_to(1);
+    /// session.flush();
+    /// session.insert("world".to_string());
+    ///
+    /// // updates at time 0 are complete, and left to snapshots of arrangements.
+    /// let checkpoint = session.checkpoint();
+    /// assert_eq!(checkpoint.epoch, 1);
+    /// assert_eq!(checkpoint.updates, vec![("world".to_string(), 1, 1)]);
+    ///
+    /// let mut restored = InputSession::<usize, String, isize>::new();
+    /// restored.restore(checkpoint);
+    /// assert_eq!(restored.time(), &1);
+    /// ```
+    pub fn checkpoint(&self) -> InputCheckpoint<T, D, R> {
+        let sent = self.sent.as_ref().expect("checkpoints require a session created `with_checkpoints`");
+        InputCheckpoint {
+            epoch: self.handle.epoch().clone(),
+            time: self.time.clone(),
+            updates: sent.iter().chain(self.buffer.iter()).cloned().collect(),
         }
     }
 
+    /// Restores the state recorded in `checkpoint` into a newly created session.
+    ///
+    /// The session's dataflow input is advanced to the checkpoint's `epoch`, and the recorded updates are
+    /// buffered to be sent with the next flush. The contents of the input at earlier times must be restored
+    /// from snapshots of its arrangements. The session retains sent updates, as if created `with_checkpoints`.
+    pub fn restore(&mut self, checkpoint: InputCheckpoint<T, D, R>) {
+        assert!(self.buffer.is_empty() && self.sent.as_ref().map(|sent| sent.is_empty()).unwrap_or(true), "restoring into a session with updates");
+        assert!(self.handle.epoch().less_equal(&checkpoint.epoch));
+        assert!(checkpoint.epoch.less_equal(&checkpoint.time));
+        self.handle.advance_to(checkpoint.epoch);
+        self.time = checkpoint.time;
+        self.buffer = checkpoint.updates;
+        self.sent = Some(Vec::new());
+    }
+
     /// Advances the logical time for future records.
     ///
     /// Importantly, this method does **not** immediately inform timely dataflow of the change. This happens only when
@@ -0,0 +1,86 @@
+//! Restores collections from a snapshot and a changelog.
+//!
+//! A collection kept in external storage is often stored as a snapshot of its contents as of some time,
+//! together with a changelog of the updates from then on. The `hydrate` method stitches the two into one
+//! collection: the snapshot enters at its `as_of` time, a bounded number of records per activation so
+//! that a large snapshot does not stall the worker, and the changelog contributes its updates at later
+//! times. Updates in the changelog that the snapshot already reflects are discarded.
+//!
+//! # Examples
+//!
+//! ```
+//! use timely::dataflow::operators::ToStream;
+//! use differential_dataflow::AsCollection;
+//! use differential_dataflow::input::hydrate::hydrate;
+//!
+//! ::timely::example(|scope| {
+//!
+//!     // the contents as of time 5, and a changelog that overlaps with them.
+//!     let snapshot = vec![("a", 2), ("b", 1)];
+//!     let changelog = vec![("a", 4, 1), ("c", 7, 1)].into_iter().to_stream(scope).as_collection();
+//!
+//!     let expected = vec![("a", 5, 2), ("b", 5, 1), ("c", 7, 1)].into_iter().to_stream(scope).as_collection();
+//!     hydrate(5, snapshot, &changelog).assert_eq(&expected);
+//! });
+//! ```
+
+use timely::PartialOrder;
+use timely::dataflow::Scope;
+use timely::dataflow::operators::Filter;
+use timely::dataflow::operators::generic::operator::source;
+
+use crate::{Collection, Data};
+use crate::collection::AsCollection;
+use crate::difference::Semigroup;
+
+/// The number of snapshot records the source introduces in each activation.
+const SNAPSHOT_CHUNK: usize = 1024;
+
+/// Combines the contents of a collection as of `as_of` with a changelog of its subsequent updates.
+///
+/// Each record `(data, diff)` of `snapshot` is introduced at time `as_of`. The snapshot should reflect all
+/// updates at times less or equal to `as_of`, and such updates in `changelog` are discarded. Each worker
+/// introduces the snapshot it is provided, and so the workers should each be provided a part of the
+/// snapshot, for example the part they read from storage.
+///
+/// The result is not complete through `as_of` until the whole snapshot has been introduced.
+pub fn hydrate<G, D, R, I>(as_of: G::Timestamp, snapshot: I, changelog: &Collection<G, D, R>) -> Collection<G, D, R>
+where
+    G: Scope,
+    D: Data,
+    R: Semigroup,
+    I: IntoIterator<Item=(D, R)>,
+    I::IntoIter: 'static,
+{
+    let mut snapshot = snapshot.into_iter().peekable();
+    let time = as_of.clone();
+
+    let scope = changelog.scope();
+    let activations = scope.clone();
+    let restored = source(&scope, "HydrateSnapshot", move |capability, info| {
+
+        let activator = activations.activator_for(&info.address[..]);
+        let mut capability = Some(capability.delayed(&time));
+
+        move |output| {
+            if let Some(cap) = &capability {
+                let mut session = output.session(cap);
+                for (data, diff) in (&mut snapshot).take(SNAPSHOT_CHUNK) {
+                    session.give((data, time.clone(), diff));
+                }
+            }
+            if snapshot.peek().is_some() {
+                activator.activate();
+            }
+            else {
+                capability = None;
+            }
+        }
+    });
+
+    changelog
+        .inner
+        .filter(move |(_, time, _)| !time.less_equal(&as_of))
+        .as_collection()
+        .concat(&restored.as_collection())
+}
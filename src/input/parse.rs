@@ -0,0 +1,160 @@
+//! Parses collections of text lines into typed collections.
+//!
+//! Data often arrive as lines of text, for example read from a file or a socket. The `Parse` trait turns
+//! a collection of lines into a collection of typed records and a collection of `ParseError`s, one for
+//! each line that failed to parse, so that pipelines need not write their own parsing operators. Lines
+//! of CSV are split into fields, which a user-provided schema maps to a record. Lines of JSON, with the
+//! `json` feature, are deserialized into any type that implements `serde::Deserialize`.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::input::parse::{Parse, column};
+//!
+//! ::timely::example(|scope| {
+//!
+//!     let lines = scope.new_collection_from(vec![
+//!         "alice,31".to_string(),
+//!         "\"bob, jr.\",7".to_string(),
+//!         "carol,unknown".to_string(),
+//!     ]).1;
+//!
+//!     let (people, errors) = lines.parse_csv(|fields| {
+//!         Ok((column::<String>(fields, 0)?, column::<u32>(fields, 1)?))
+//!     });
+//!
+//!     people.assert_eq(&scope.new_collection_from(vec![("alice".to_string(), 31), ("bob, jr.".to_string(), 7)]).1);
+//!     errors.map(|error| error.line).assert_eq(&scope.new_collection_from(vec!["carol,unknown".to_string()]).1);
+//! });
+//! ```
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use abomonation_derive::Abomonation;
+use serde::{Deserialize, Serialize};
+use timely::dataflow::Scope;
+
+use crate::{Collection, Data};
+use crate::difference::Semigroup;
+
+/// A line that failed to parse.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Abomonation)]
+pub struct ParseError {
+    /// The line.
+    pub line: String,
+    /// A description of the failure.
+    pub message: String,
+}
+
+/// Parses collections of lines into typed collections.
+pub trait Parse<G: Scope, R: Semigroup> {
+    /// Parses each line as comma-separated values, and maps its fields to a record with `schema`.
+    ///
+    /// Fields may be enclosed in double quotes, in which case they may contain commas, and double
+    /// quotes written twice. Trailing line breaks are ignored. The method returns the records, and the
+    /// lines that either are not valid CSV or for which `schema` returns an error.
+    fn parse_csv<D, F>(&self, schema: F) -> (Collection<G, D, R>, Collection<G, ParseError, R>)
+    where
+        D: Data,
+        F: FnMut(&[String])->Result<D, String>+'static;
+
+    /// Deserializes each line as JSON.
+    ///
+    /// The method returns the records, and the lines that could not be deserialized as a `D`.
+    /// This method is available with the `json` feature.
+    #[cfg(feature = "json")]
+    fn parse_json<D>(&self) -> (Collection<G, D, R>, Collection<G, ParseError, R>)
+    where
+        D: Data+serde::de::DeserializeOwned;
+}
+
+impl<G: Scope, R: Semigroup> Parse<G, R> for Collection<G, String, R> {
+    fn parse_csv<D, F>(&self, mut schema: F) -> (Collection<G, D, R>, Collection<G, ParseError, R>)
+    where
+        D: Data,
+        F: FnMut(&[String])->Result<D, String>+'static,
+    {
+        let mut fields = Vec::new();
+        split(&self.map(move |line| {
+            split_csv(&line, &mut fields)
+                .and_then(|()| schema(&fields))
+                .map_err(|message| ParseError { line, message })
+        }))
+    }
+
+    #[cfg(feature = "json")]
+    fn parse_json<D>(&self) -> (Collection<G, D, R>, Collection<G, ParseError, R>)
+    where
+        D: Data+serde::de::DeserializeOwned,
+    {
+        split(&self.map(|line| {
+            serde_json::from_str(&line)
+                .map_err(|error| ParseError { message: error.to_string(), line })
+        }))
+    }
+}
+
+/// Separates parsed records from parse failures.
+fn split<G: Scope, D: Data, R: Semigroup>(results: &Collection<G, Result<D, ParseError>, R>) -> (Collection<G, D, R>, Collection<G, ParseError, R>) {
+    let records = results.flat_map(|result| result.ok());
+    let errors = results.flat_map(|result| result.err());
+    (records, errors)
+}
+
+/// Splits a line of comma-separated values into `fields`.
+///
+/// Fields may be enclosed in double quotes, in which case they may contain commas, and double quotes
+/// written twice. Trailing line breaks are ignored. The method returns an error if a quoted field is not
+/// terminated, or is followed by anything other than a comma.
+pub fn split_csv(line: &str, fields: &mut Vec<String>) -> Result<(), String> {
+    fields.clear();
+    let mut chars = line.trim_end_matches(&['\r', '\n'][..]).chars().peekable();
+    let mut field = String::new();
+    loop {
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => { chars.next(); field.push('"'); },
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quoted field".to_string()),
+                }
+            }
+            match chars.next() {
+                Some(',') => fields.push(std::mem::take(&mut field)),
+                Some(c) => return Err(format!("unexpected {:?} after quoted field", c)),
+                None => {
+                    fields.push(field);
+                    return Ok(());
+                },
+            }
+        }
+        else {
+            loop {
+                match chars.next() {
+                    Some(',') => { fields.push(std::mem::take(&mut field)); break; },
+                    Some(c) => field.push(c),
+                    None => {
+                        fields.push(field);
+                        return Ok(());
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Parses the field at `index`, for use in CSV schemas.
+///
+/// The method returns an error if there is no field at `index`, or if it does not parse as a `T`.
+pub fn column<T>(fields: &[String], index: usize) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let field = fields.get(index).ok_or_else(|| format!("missing field {}", index))?;
+    field.parse().map_err(|error| format!("field {}: {}", index, error))
+}
@@ -0,0 +1,162 @@
+//! Feeds input sessions from asynchronous streams.
+//!
+//! Services that embed a dataflow often receive their updates from an asynchronous source, for
+//! example a message queue or a network connection. A `StreamInput` drives an `InputSession` from a
+//! `futures::Stream` of `InputEvent`s, which are updates and progress markers. It ingests the events
+//! the stream has ready, flushes the session, and steps the worker until the dataflow has caught up
+//! with the session, yielding to other tasks between steps. It then waits for further events.
+//!
+//! The worker is not `Send`, and so the future that drives it must run on a single-threaded
+//! executor, for example a `tokio` `LocalSet` or `futures::executor::block_on`.
+//!
+//! This module is available with the `futures` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use futures::stream;
+//! use timely::dataflow::ProbeHandle;
+//! use differential_dataflow::input::InputSession;
+//! use differential_dataflow::operators::Count;
+//! use differential_dataflow::input::stream::{InputEvent, StreamInput};
+//!
+//! ::timely::execute_directly(|worker| {
+//!
+//!     let mut probe = ProbeHandle::new();
+//!     let mut session = InputSession::<usize, String, isize>::new();
+//!     worker.dataflow(|scope| {
+//!         session.to_collection(scope)
+//!                .count()
+//!                .probe_with(&mut probe);
+//!     });
+//!
+//!     let events = stream::iter(vec![
+//!         InputEvent::Update("hello".to_string(), 0, 1),
+//!         InputEvent::Advance(1),
+//!         InputEvent::Update("world".to_string(), 1, 1),
+//!         InputEvent::Advance(2),
+//!     ]);
+//!
+//!     futures::executor::block_on(StreamInput::new(events, session).run(worker, &probe));
+//!     assert!(probe.done());
+//! });
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{FutureExt, Stream, StreamExt};
+use timely::communication::Allocate;
+use timely::dataflow::ProbeHandle;
+use timely::progress::Timestamp;
+use timely::worker::Worker;
+
+use crate::Data;
+use crate::difference::Semigroup;
+use super::InputSession;
+
+/// An event that drives an input session.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InputEvent<D, T, R> {
+    /// An update to the collection, at a time greater or equal to the time of the session.
+    Update(D, T, R),
+    /// A progress marker: there will be no further updates at times not greater or equal to the time.
+    Advance(T),
+}
+
+impl<D, T, R> From<(D, T, R)> for InputEvent<D, T, R> {
+    fn from((data, time, diff): (D, T, R)) -> Self {
+        InputEvent::Update(data, time, diff)
+    }
+}
+
+/// Feeds an input session from a stream of events.
+pub struct StreamInput<S, T: Timestamp+Clone, D: Data, R: Semigroup> {
+    /// The source of events.
+    stream: S,
+    /// The session the events are applied to.
+    session: InputSession<T, D, R>,
+}
+
+impl<S, T, D, R> StreamInput<S, T, D, R>
+where
+    S: Stream<Item = InputEvent<D, T, R>> + Unpin,
+    T: Timestamp+Clone,
+    D: Data,
+    R: Semigroup,
+{
+    /// Creates an adapter that applies the events of `stream` to `session`.
+    pub fn new(stream: S, session: InputSession<T, D, R>) -> Self {
+        StreamInput { stream, session }
+    }
+
+    /// Applies the events of the stream to the session, and steps `worker` to process them.
+    ///
+    /// Whenever the stream has no events ready, the session is flushed and the worker is stepped until
+    /// `probe` is no longer less than the time of the session. When the stream ends, the session is
+    /// closed, and the worker is stepped until `probe` is done. The probe should follow the dataflows
+    /// that consume the session, and should not wait on inputs that are driven elsewhere.
+    ///
+    /// The method panics if the stream produces an update or progress marker at a time that is not
+    /// greater or equal to the most recent progress marker.
+    pub async fn run<A: Allocate>(mut self, worker: &mut Worker<A>, probe: &ProbeHandle<T>) {
+        loop {
+            // Ingest the events the stream has ready, without waiting.
+            while let Some(event) = self.stream.next().now_or_never() {
+                match event {
+                    Some(event) => self.apply(event),
+                    None => return self.finish(worker, probe).await,
+                }
+            }
+
+            // Bring the dataflow up to date with the session, and then wait for further events.
+            self.session.flush();
+            while probe.less_than(self.session.time()) {
+                worker.step();
+                YieldNow(false).await;
+            }
+            match self.stream.next().await {
+                Some(event) => self.apply(event),
+                None => return self.finish(worker, probe).await,
+            }
+        }
+    }
+
+    /// Applies `event` to the session.
+    fn apply(&mut self, event: InputEvent<D, T, R>) {
+        match event {
+            InputEvent::Update(data, time, diff) => self.session.update_at(data, time, diff),
+            InputEvent::Advance(time) => {
+                self.session.advance_to(time);
+                self.session.flush();
+            }
+        }
+    }
+
+    /// Closes the session, and steps the worker until the probe is done.
+    async fn finish<A: Allocate>(self, worker: &mut Worker<A>, probe: &ProbeHandle<T>) {
+        self.session.close();
+        while !probe.done() {
+            worker.step();
+            YieldNow(false).await;
+        }
+    }
+}
+
+/// A future that is pending once, so that other tasks may run.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        }
+        else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
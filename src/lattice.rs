@@ -10,6 +10,14 @@ use timely::progress::{Antichain, frontier::AntichainRef};
 /// A bounded partially ordered type supporting joins and meets.
 pub trait Lattice : PartialOrder {
 
+    /// Indicates that the type is totally ordered.
+    ///
+    /// This mirrors timely's `TotalOrder` marker trait, on which generic code cannot specialize. Code
+    /// generic over lattices consults the constant instead, to take faster paths for totally ordered
+    /// times, whose antichains have at most one element. Implementations must only set it for types
+    /// that implement `TotalOrder`.
+    const TOTAL: bool = false;
+
     /// The smallest element greater than or equal to both arguments.
     ///
     /// # Examples
@@ -131,6 +139,13 @@ pub trait Lattice : PartialOrder {
     /// ```
     #[inline]
     fn advance_by(&mut self, frontier: AntichainRef<Self>) where Self: Sized {
+        if Self::TOTAL {
+            // Totally ordered frontiers have at most one element.
+            if let Some(first) = frontier.first() {
+                self.join_assign(first);
+            }
+            return;
+        }
         let mut iter = frontier.iter();
         if let Some(first) = iter.next() {
             let mut result = self.join(first);
@@ -186,6 +201,8 @@ use timely::progress::Timestamp;
 // Tuples have the annoyance that they are only a lattice for `T2` with maximal elements,
 // as the `meet` operator on `(x, _)` and `(y, _)` would be `(x meet y, maximum())`.
 impl<T1: Lattice+Clone, T2: Lattice+Clone+Maximum+Timestamp> Lattice for (T1, T2) {
+    // Tuples are ordered lexicographically.
+    const TOTAL: bool = T1::TOTAL && T2::TOTAL;
     #[inline]
     fn join(&self, other: &(T1, T2)) -> (T1, T2) {
         if self.0.eq(&other.0) {
@@ -215,6 +232,7 @@ impl<T1: Lattice+Clone, T2: Lattice+Clone+Maximum+Timestamp> Lattice for (T1, T2
 macro_rules! implement_lattice {
     ($index_type:ty, $minimum:expr) => (
         impl Lattice for $index_type {
+            const TOTAL: bool = true;
             #[inline] fn join(&self, other: &Self) -> Self { ::std::cmp::max(*self, *other) }
             #[inline] fn meet(&self, other: &Self) -> Self { ::std::cmp::min(*self, *other) }
         }
@@ -332,6 +350,62 @@ pub fn antichain_meet<T: Lattice+Clone>(one: &[T], other: &[T]) -> Antichain<T>
     upper
 }
 
+/// Returns true if each element of `other` is greater or equal to some element of `one`.
+///
+/// This is the partial order on frontiers: `one` is less or equal to `other` when every time
+/// that `other` may yet produce, `one` may also produce. The empty frontier is greater or
+/// equal to all frontiers.
+///
+/// # Examples
+///
+/// ```
+/// # use timely::order::Product;
+/// # use differential_dataflow::lattice::antichain_less_equal;
+/// # fn main() {
+///
+/// let f1 = &[Product::new(3, 7), Product::new(5, 6)];
+/// let f2 = &[Product::new(4, 7), Product::new(6, 6)];
+/// assert!(antichain_less_equal(f1, f2));
+/// assert!(!antichain_less_equal(f2, f1));
+/// assert!(antichain_less_equal(f1, &[]));
+/// # }
+/// ```
+pub fn antichain_less_equal<T: PartialOrder>(one: &[T], other: &[T]) -> bool {
+    other.iter().all(|time2| one.iter().any(|time1| time1.less_equal(time2)))
+}
+
+/// Advances each element of `antichain` by `frontier`.
+///
+/// Each element is advanced with `Lattice::advance_by`. Advanced elements may become comparable,
+/// and the result retains only the minimal elements. When provided an empty frontier `antichain`
+/// is not modified.
+///
+/// # Examples
+///
+/// ```
+/// # use timely::order::Product;
+/// # use timely::progress::Antichain;
+/// # use differential_dataflow::lattice::antichain_advance_by;
+/// # fn main() {
+///
+/// let mut antichain = Antichain::from(vec![Product::new(3, 7), Product::new(5, 6)]);
+/// let frontier = Antichain::from_elem(Product::new(4, 7));
+/// antichain_advance_by(&mut antichain, frontier.borrow());
+/// assert_eq!(&*antichain.elements(), &[Product::new(4, 7)]);
+/// # }
+/// ```
+pub fn antichain_advance_by<T: Lattice+Clone>(antichain: &mut Antichain<T>, frontier: AntichainRef<T>) {
+    if !frontier.is_empty() {
+        let mut advanced = Antichain::new();
+        for time in antichain.elements().iter() {
+            let mut time = time.clone();
+            time.advance_by(frontier);
+            advanced.insert(time);
+        }
+        *antichain = advanced;
+    }
+}
+
 impl<T: Lattice+Clone> Lattice for Antichain<T> {
     fn join(&self, other: &Self) -> Self {
         let mut upper = Antichain::new();
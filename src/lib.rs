@@ -96,6 +96,8 @@ pub mod hashable;
 pub mod operators;
 pub mod algorithms;
 pub mod lattice;
+pub mod event_time;
+pub mod bitemporal;
 pub mod trace;
 pub mod input;
 pub mod difference;
@@ -104,6 +106,7 @@ pub mod collection;
 pub mod logging;
 pub mod consolidation;
 pub mod capture;
+pub mod quota;
 
 /// Configuration options for differential dataflow.
 #[derive(Default)]
@@ -115,7 +118,65 @@ pub struct Config {
     /// cause these operators to reschedule themselves as long as their arrangemnt has not
     /// reached a compact representation, and each scheduling quantum they will perform
     /// compaction work as if `effort` records had been added to the arrangement.
-    pub idle_merge_effort: Option<isize>
+    pub idle_merge_effort: Option<isize>,
+    /// A bound on the number of keys a `reduce` operator processes each scheduling quantum.
+    ///
+    /// The default value of `None` processes all keys of a newly completed interval at once,
+    /// which can stall the worker when many keys change at the same time. Setting the value
+    /// to `Some(keys)` causes the operator to yield after `keys` keys and reschedule itself
+    /// to continue the work, at the cost of delaying its output until the work completes.
+    pub reduce_fuel: Option<usize>,
+    /// A bound on the number of output records a `join` operator produces each scheduling quantum.
+    ///
+    /// The default value of `None` uses a bound of one million records. Smaller values allow
+    /// other operators on the worker to run more often, at the expense of more frequent
+    /// rescheduling of the join operator.
+    pub join_fuel: Option<usize>,
+    /// A bound on the time a `reduce` operator spends processing keys each scheduling quantum.
+    ///
+    /// The default value of `None` places no bound. Setting the value to `Some(duration)` causes the
+    /// operator to yield once it has spent `duration` processing keys, as with `reduce_fuel`.
+    pub reduce_time_slice: Option<std::time::Duration>,
+    /// A bound on the time a `join` operator spends producing output each scheduling quantum.
+    ///
+    /// The default value of `None` places no bound. Setting the value to `Some(duration)` causes the
+    /// operator to yield once it has spent `duration` producing output. The time is checked between
+    /// units of work, each of which produces at most `join_fuel` records.
+    pub join_time_slice: Option<std::time::Duration>,
+    /// Logic reporting memory pressure to the traces of arrangements.
+    ///
+    /// The default value of `None` maintains traces without regard to memory. When set, traces
+    /// complete merges in progress while memory is tight, releasing the batches being merged, and
+    /// defer idle merge effort while memory is plentiful.
+    pub memory_pressure_logic: Option<trace::MemoryPressureLogic>,
+    /// A budget of idle merge effort shared by the arrangements of each worker, and the period over which it applies.
+    ///
+    /// The default value of `None` lets each arrangement exert its idle merge effort independently.
+    /// Setting the value to `Some((budget, period))` grants arrangements at most `budget` idle effort
+    /// in total each `period`, as described by `trace::scheduler::MergeScheduler`. Arrangements exert
+    /// idle effort only if `idle_merge_effort` is set, or else `budget` is used in its place.
+    pub merge_budget: Option<(usize, std::time::Duration)>,
+    /// A bound on the number of input records an arrange operator receives each scheduling quantum.
+    ///
+    /// The default value of `None` receives all available input at once, which can stall the worker
+    /// when a large amount of input arrives for many arrangements. Setting the value to `Some(records)`
+    /// causes the operator to yield once it has received at least `records` records, and reschedule
+    /// itself to receive the rest. Input not yet received holds back the operator's input frontier,
+    /// and so only delays the batches the operator produces.
+    pub arrange_fuel: Option<usize>,
+    /// A bound on the time an arrange operator spends each scheduling quantum.
+    ///
+    /// The default value of `None` places no bound. Setting the value to `Some(duration)` causes the
+    /// operator to yield once it has spent `duration` receiving input, as with `arrange_fuel`, and to
+    /// defer its idle merge effort to its next scheduling quantum if it has spent `duration` in all.
+    pub arrange_time_slice: Option<std::time::Duration>,
+    /// The clock that measures wall-clock time for trace maintenance.
+    ///
+    /// The default value of `None` uses the system clock. Setting the value to `Some(clock)` measures
+    /// the rounds of `merge_budget`, the time slices of arrange, reduce, and join operators, the latencies
+    /// reduce and join operators log, and the time presented to the compaction policies of trace handles
+    /// with `clock`, for example a `trace::clock::SimulatedClock` that makes their behavior deterministic.
+    pub clock: Option<std::sync::Arc<dyn trace::clock::Clock>>,
 }
 
 impl Config {
@@ -124,23 +185,99 @@ impl Config {
         self.idle_merge_effort = effort;
         self
     }
+    /// Assign a bound on the number of keys a `reduce` operator processes before yielding.
+    pub fn reduce_fuel(mut self, fuel: Option<usize>) -> Self {
+        self.reduce_fuel = fuel;
+        self
+    }
+    /// Assign a bound on the number of records a `join` operator produces before yielding.
+    pub fn join_fuel(mut self, fuel: Option<usize>) -> Self {
+        self.join_fuel = fuel;
+        self
+    }
+    /// Assign a bound on the time a `reduce` operator spends before yielding.
+    pub fn reduce_time_slice(mut self, slice: Option<std::time::Duration>) -> Self {
+        self.reduce_time_slice = slice;
+        self
+    }
+    /// Assign a bound on the time a `join` operator spends before yielding.
+    pub fn join_time_slice(mut self, slice: Option<std::time::Duration>) -> Self {
+        self.join_time_slice = slice;
+        self
+    }
+    /// Assign logic reporting memory pressure to the traces of arrangements.
+    pub fn memory_pressure_logic(mut self, logic: Option<trace::MemoryPressureLogic>) -> Self {
+        self.memory_pressure_logic = logic;
+        self
+    }
+    /// Assign a budget of idle merge effort shared by the arrangements of each worker.
+    pub fn merge_budget(mut self, budget: Option<(usize, std::time::Duration)>) -> Self {
+        self.merge_budget = budget;
+        self
+    }
+    /// Assign a bound on the number of records an arrange operator receives before yielding.
+    pub fn arrange_fuel(mut self, fuel: Option<usize>) -> Self {
+        self.arrange_fuel = fuel;
+        self
+    }
+    /// Assign a bound on the time an arrange operator spends before yielding.
+    pub fn arrange_time_slice(mut self, slice: Option<std::time::Duration>) -> Self {
+        self.arrange_time_slice = slice;
+        self
+    }
+    /// Assign the clock that measures wall-clock time for trace maintenance.
+    pub fn clock(mut self, clock: Option<std::sync::Arc<dyn trace::clock::Clock>>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
 
 /// Introduces differential options to a timely configuration.
 pub fn configure(config: &mut timely::WorkerConfig, options: &Config) {
     if let Some(effort) = options.idle_merge_effort {
         config.set("differential/idle_merge_effort".to_string(), effort);
-        config.set::<trace::ExertionLogic>(
-            "differential/default_exert_logic".to_string(),
-            std::sync::Arc::new(move |batches| {
-                let mut non_empty = 0;
-                for (_index, count, length) in batches {
-                    if *count > 1 { return Some(effort as usize); }
-                    if *length > 0 { non_empty += 1; }
-                    if non_empty > 1 { return Some(effort as usize); }
-                }
-                None
-            }),
-        );
+    }
+    let effort = options.idle_merge_effort.or(options.merge_budget.map(|(budget, _)| budget as isize));
+    if let Some(effort) = effort {
+        let mut exert_logic: trace::ExertionLogic = std::sync::Arc::new(move |batches| {
+            let mut non_empty = 0;
+            for (_index, count, length) in batches {
+                if *count > 1 { return Some(effort as usize); }
+                if *length > 0 { non_empty += 1; }
+                if non_empty > 1 { return Some(effort as usize); }
+            }
+            None
+        });
+        if let Some((budget, period)) = options.merge_budget {
+            let clock = options.clock.clone().unwrap_or_else(|| std::sync::Arc::new(trace::clock::SystemClock));
+            let scheduler = std::sync::Arc::new(trace::scheduler::MergeScheduler::with_clock(budget, period, clock));
+            config.set("differential/merge_scheduler".to_string(), scheduler.clone());
+            exert_logic = scheduler.exertion_logic(exert_logic);
+        }
+        config.set::<trace::ExertionLogic>("differential/default_exert_logic".to_string(), exert_logic);
+    }
+    if let Some(fuel) = options.reduce_fuel {
+        config.set("differential/reduce_fuel".to_string(), fuel);
+    }
+    if let Some(fuel) = options.join_fuel {
+        config.set("differential/join_fuel".to_string(), fuel);
+    }
+    if let Some(slice) = options.reduce_time_slice {
+        config.set("differential/reduce_time_slice".to_string(), slice);
+    }
+    if let Some(slice) = options.join_time_slice {
+        config.set("differential/join_time_slice".to_string(), slice);
+    }
+    if let Some(fuel) = options.arrange_fuel {
+        config.set("differential/arrange_fuel".to_string(), fuel);
+    }
+    if let Some(slice) = options.arrange_time_slice {
+        config.set("differential/arrange_time_slice".to_string(), slice);
+    }
+    if let Some(clock) = &options.clock {
+        config.set::<std::sync::Arc<dyn trace::clock::Clock>>("differential/clock".to_string(), clock.clone());
+    }
+    if let Some(logic) = &options.memory_pressure_logic {
+        config.set::<trace::MemoryPressureLogic>("differential/memory_pressure_logic".to_string(), logic.clone());
     }
 }
@@ -33,6 +33,8 @@ pub enum DifferentialEvent {
     TraceShare(TraceShare),
     /// Batcher size event
     Batcher(BatcherEvent),
+    /// Time taken to respond to input.
+    Latency(LatencyEvent),
 }
 
 /// Either the start or end of a merge event.
@@ -115,3 +117,136 @@ pub struct TraceShare {
 }
 
 impl From<TraceShare> for DifferentialEvent { fn from(e: TraceShare) -> Self { DifferentialEvent::TraceShare(e) } }
+
+/// The time an operator took to respond to a batch of input.
+///
+/// The latency is measured from the receipt of the batch to the production of the last output it
+/// prompted, and so includes time spent waiting for other work of the operator, and for the worker.
+#[derive(Debug, Clone, Abomonation, Ord, PartialOrd, Eq, PartialEq)]
+pub struct LatencyEvent {
+    /// Operator identifier.
+    pub operator: usize,
+    /// Elapsed nanoseconds, as measured by the operator's clock.
+    pub nanos: u64,
+}
+
+impl From<LatencyEvent> for DifferentialEvent { fn from(e: LatencyEvent) -> Self { DifferentialEvent::Latency(e) } }
+
+/// A histogram of durations, in buckets of powers of two nanoseconds.
+///
+/// # Examples
+///
+/// ```
+/// use differential_dataflow::logging::Histogram;
+///
+/// let mut histogram = Histogram::default();
+/// for nanos in [100, 200, 300, 5_000] {
+///     histogram.record(nanos);
+/// }
+/// assert_eq!(histogram.count(), 4);
+/// assert_eq!(histogram.quantile(0.5), Some(256));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// The count of durations in `[2^i, 2^(i+1))` nanoseconds, at position `i`.
+    buckets: Vec<u64>,
+}
+
+impl Histogram {
+    /// Records a duration of `nanos` nanoseconds.
+    pub fn record(&mut self, nanos: u64) {
+        let bucket = (64 - nanos.leading_zeros()).saturating_sub(1) as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+    /// The number of recorded durations.
+    pub fn count(&self) -> u64 { self.buckets.iter().sum() }
+    /// The counts of each bucket, where bucket `i` covers `[2^i, 2^(i+1))` nanoseconds.
+    pub fn buckets(&self) -> &[u64] { &self.buckets }
+    /// An upper bound on the `quantile` of recorded durations, in nanoseconds.
+    ///
+    /// The bound is the upper end of the bucket containing the quantile, and is within a factor of two
+    /// of the quantile itself. Returns `None` if no durations were recorded.
+    pub fn quantile(&self, quantile: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 { return None; }
+        let rank = ((quantile.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, bucket_count) in self.buckets.iter().enumerate() {
+            seen += bucket_count;
+            if seen >= rank {
+                return Some(1u64.checked_shl(bucket as u32 + 1).unwrap_or(u64::MAX));
+            }
+        }
+        None
+    }
+}
+
+/// Per-operator histograms of processing latencies and of merge durations.
+///
+/// Operators that log `LatencyEvent`s contribute to their processing histograms, and traces that log
+/// the starts and completions of merges contribute to their merge histograms. An operator whose latency
+/// rises with the durations of the merges of the traces it reads is stalled by merging, rather than by
+/// the cost of its own computation.
+///
+/// The histograms observe events through `observe`, which a logger for `"differential/arrange"` should
+/// call for each event, or are installed as that logger by `enable_histograms`.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistograms {
+    processing: std::collections::HashMap<usize, Histogram>,
+    merging: std::collections::HashMap<usize, Histogram>,
+    /// The start times of in-progress merges, by operator and scale.
+    merge_starts: std::collections::HashMap<(usize, usize), std::time::Duration>,
+}
+
+impl LatencyHistograms {
+    /// Incorporates an event logged at `time`.
+    pub fn observe(&mut self, time: std::time::Duration, event: &DifferentialEvent) {
+        match event {
+            DifferentialEvent::Latency(latency) => {
+                self.processing.entry(latency.operator).or_default().record(latency.nanos);
+            },
+            DifferentialEvent::Merge(merge) => {
+                match merge.complete {
+                    None => { self.merge_starts.insert((merge.operator, merge.scale), time); },
+                    Some(_) => {
+                        if let Some(start) = self.merge_starts.remove(&(merge.operator, merge.scale)) {
+                            let nanos = time.saturating_sub(start).as_nanos() as u64;
+                            self.merging.entry(merge.operator).or_default().record(nanos);
+                        }
+                    },
+                }
+            },
+            _ => { },
+        }
+    }
+    /// The histogram of processing latencies of `operator`, if it has logged any.
+    pub fn processing(&self, operator: usize) -> Option<&Histogram> { self.processing.get(&operator) }
+    /// The histogram of the durations of merges in the trace of `operator`, if any have completed.
+    pub fn merging(&self, operator: usize) -> Option<&Histogram> { self.merging.get(&operator) }
+    /// The operators with processing latency histograms.
+    pub fn operators(&self) -> impl Iterator<Item=usize>+'_ { self.processing.keys().cloned() }
+}
+
+/// Installs latency histograms as the logger of differential dataflow events.
+///
+/// This replaces any logger registered as `"differential/arrange"`, and should be called before the
+/// dataflows whose latencies are of interest are built.
+pub fn enable_histograms<A>(worker: &mut timely::worker::Worker<A>) -> std::rc::Rc<std::cell::RefCell<LatencyHistograms>>
+where
+    A: timely::communication::Allocate,
+{
+    let histograms = std::rc::Rc::new(std::cell::RefCell::new(LatencyHistograms::default()));
+    let shared = histograms.clone();
+    worker
+        .log_register()
+        .insert::<DifferentialEvent,_>("differential/arrange", move |_time, data| {
+            let mut histograms = shared.borrow_mut();
+            for (time, _worker, event) in data.iter() {
+                histograms.observe(*time, event);
+            }
+        });
+    histograms
+}
@@ -0,0 +1,188 @@
+//! Aggregate the values of each key into the difference of the key.
+//!
+//! A `reduce` maintains an arrangement of its output, from which it learns the prior output of a key
+//! when the key's input changes. When the aggregate of each key can instead live in the difference
+//! component, as in `(key, time, aggregate)`, a change to the aggregate is reported as the difference
+//! between the new and the prior aggregate, and the prior aggregate can be recomputed from the input
+//! arrangement. For totally ordered times, the `aggregate_total` operator does this, and saves the
+//! output arrangement entirely when downstream operators only need the aggregated collection.
+
+use std::collections::BTreeMap;
+
+use timely::order::TotalOrder;
+use timely::dataflow::*;
+use timely::dataflow::operators::Operator;
+use timely::dataflow::channels::pact::Pipeline;
+
+use crate::lattice::Lattice;
+use crate::{ExchangeData, Collection};
+use crate::difference::{Semigroup, Abelian};
+use crate::hashable::Hashable;
+use crate::collection::AsCollection;
+use crate::operators::arrange::{Arranged, ArrangeByKey};
+use crate::trace::{BatchReader, Cursor, TraceReader};
+
+/// Extension trait for the `aggregate_total` differential dataflow method.
+pub trait AggregateTotal<G: Scope, K: ExchangeData, V: ExchangeData, R: ExchangeData+Semigroup> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Aggregates the values of each key into a difference, whose accumulation is the aggregate.
+    ///
+    /// The `logic` closure is invoked with the key and its values with their non-zero accumulations,
+    /// in sorted order, and returns the aggregate. It is only invoked for keys with values, and keys
+    /// without values have a zero aggregate. The output collection contains each key with accumulated
+    /// difference equal to its aggregate, and without the operator maintaining an arrangement of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::AggregateTotal;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///     // the sum of the distinct values of each key.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x))
+    ///          .aggregate_total(|_key, vals| vals.iter().map(|(val, _)| **val as isize).sum::<isize>())
+    ///          .inner
+    ///          .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(0, 0, 3), (1, 0, 12), (2, 0, 21), (3, 0, 9)])]);
+    /// ```
+    fn aggregate_total<R2, L>(&self, logic: L) -> Collection<G, K, R2>
+    where
+        R2: Abelian,
+        L: FnMut(&K, &[(&V, R)])->R2+'static,
+        ;
+}
+
+impl<G: Scope, K: ExchangeData+Hashable, V: ExchangeData, R: ExchangeData+Semigroup> AggregateTotal<G, K, V, R> for Collection<G, (K, V), R>
+where G::Timestamp: TotalOrder+Lattice+Ord {
+    fn aggregate_total<R2, L>(&self, logic: L) -> Collection<G, K, R2>
+    where
+        R2: Abelian,
+        L: FnMut(&K, &[(&V, R)])->R2+'static,
+    {
+        self.arrange_by_key_named("Arrange: AggregateTotal")
+            .aggregate_total(logic)
+    }
+}
+
+impl<G, K, V, T1> AggregateTotal<G, K, V, T1::Diff> for Arranged<G, T1>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: for<'a> TraceReader<Key<'a>=&'a K, Val<'a>=&'a V>+Clone+'static,
+    K: ExchangeData,
+    V: ExchangeData,
+    T1::Time: TotalOrder,
+    T1::Diff: ExchangeData,
+{
+    fn aggregate_total<R2, L>(&self, mut logic: L) -> Collection<G, K, R2>
+    where
+        R2: Abelian,
+        L: FnMut(&K, &[(&V, T1::Diff)])->R2+'static,
+    {
+
+        let mut trace = self.trace.clone();
+        let mut buffer = Vec::new();
+
+        self.stream.unary_frontier(Pipeline, "AggregateTotal", move |_,_| {
+
+            // tracks the upper limit of known-complete timestamps.
+            let mut upper_limit = timely::progress::frontier::Antichain::from_elem(<G::Timestamp as timely::progress::Timestamp>::minimum());
+
+            // the accumulated values of the current key, and the changes to them in the current batch.
+            let mut values: BTreeMap<V, T1::Diff> = BTreeMap::new();
+            let mut changes: Vec<(G::Timestamp, V, T1::Diff)> = Vec::new();
+
+            move |input, output| {
+
+                input.for_each(|capability, batches| {
+                    batches.swap(&mut buffer);
+                    let mut session = output.session(&capability);
+                    for batch in buffer.drain(..) {
+
+                        let mut batch_cursor = batch.cursor();
+                        let (mut trace_cursor, trace_storage) = trace.cursor_through(batch.lower().borrow()).unwrap();
+
+                        upper_limit.clone_from(batch.upper());
+
+                        while let Some(key) = batch_cursor.get_key(&batch) {
+
+                            // Accumulate the values of this key before the current batch.
+                            values.clear();
+                            trace_cursor.seek_key(&trace_storage, key);
+                            if trace_cursor.get_key(&trace_storage) == Some(key) {
+                                while let Some(val) = trace_cursor.get_val(&trace_storage) {
+                                    trace_cursor.map_times(&trace_storage, |_, diff| {
+                                        accumulate(&mut values, val, diff);
+                                    });
+                                    trace_cursor.step_val(&trace_storage);
+                                }
+                            }
+
+                            // Collect the changes of the current batch, in order of time.
+                            while let Some(val) = batch_cursor.get_val(&batch) {
+                                batch_cursor.map_times(&batch, |time, diff| {
+                                    changes.push((time.clone(), val.clone(), diff.clone()));
+                                });
+                                batch_cursor.step_val(&batch);
+                            }
+                            changes.sort_by(|x, y| x.0.cmp(&y.0));
+
+                            // Report the change in the aggregate at each time.
+                            let mut current = aggregate(&mut logic, key, &values);
+                            let mut updates = changes.drain(..).peekable();
+                            while let Some((time, val, diff)) = updates.next() {
+                                accumulate(&mut values, &val, &diff);
+                                if updates.peek().map_or(true, |(next, _, _)| next != &time) {
+                                    let next = aggregate(&mut logic, key, &values);
+                                    let mut difference = next.clone();
+                                    difference.plus_equals(&current.negate());
+                                    if !difference.is_zero() {
+                                        session.give((key.clone(), time, difference));
+                                    }
+                                    current = next;
+                                }
+                            }
+
+                            batch_cursor.step_key(&batch);
+                        }
+                    }
+                });
+
+                // tidy up the shared input trace.
+                trace.advance_upper(&mut upper_limit);
+                trace.set_logical_compaction(upper_limit.borrow());
+                trace.set_physical_compaction(upper_limit.borrow());
+            }
+        })
+        .as_collection()
+    }
+}
+
+/// Adds `diff` to the accumulation of `val`, removing it if it becomes zero.
+fn accumulate<V: Ord+Clone, R: Semigroup>(values: &mut BTreeMap<V, R>, val: &V, diff: &R) {
+    match values.get_mut(val) {
+        Some(accum) => {
+            accum.plus_equals(diff);
+            if accum.is_zero() { values.remove(val); }
+        }
+        None => {
+            if !diff.is_zero() { values.insert(val.clone(), diff.clone()); }
+        }
+    }
+}
+
+/// The aggregate of `key` with accumulated `values`, which is zero if there are no values.
+fn aggregate<K, V, R, R2, L>(logic: &mut L, key: &K, values: &BTreeMap<V, R>) -> R2
+where
+    R: Clone,
+    R2: Abelian,
+    L: FnMut(&K, &[(&V, R)])->R2,
+{
+    if values.is_empty() { return R2::zero(); }
+    let input = values.iter().map(|(val, diff)| (val, diff.clone())).collect::<Vec<_>>();
+    logic(key, &input[..])
+}
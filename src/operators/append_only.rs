@@ -0,0 +1,92 @@
+//! Adapt collections for append-only consumers.
+//!
+//! Many sinks, for example logs and notification systems, can record the arrival of data but have
+//! no way to take it back. The `append_only` operator passes along the updates of a collection with
+//! positive differences, and handles the updates with negative differences according to a policy:
+//! it either drops them, or panics, asserting that the collection is monotone.
+//!
+//! When dropping retractions, updates are consolidated within each batch the operator receives before
+//! they are inspected, but not across batches. A collection whose updates only cancel once all updates
+//! at a time are known should first be consolidated, for example with `suppress_updates_until_closed`.
+//! When panicking on retractions, the operator itself withholds the updates at each time until the time
+//! is complete, and inspects their accumulation, so that updates which cancel across batches or workers
+//! are not mistaken for retractions.
+
+use timely::dataflow::*;
+use timely::dataflow::operators::Operator;
+use timely::dataflow::channels::pact::Pipeline;
+
+use crate::{Data, ExchangeData, Collection, Hashable};
+use crate::difference::Monoid;
+use crate::lattice::Lattice;
+use crate::collection::AsCollection;
+use crate::consolidation::consolidate_updates;
+
+/// What to do with an update that has a negative difference.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OnRetraction {
+    /// Discard the update.
+    Drop,
+    /// Panic, reporting the update.
+    Panic,
+}
+
+/// Extension trait for the `append_only` differential dataflow method.
+pub trait AppendOnly<G: Scope, D: Data, R: Monoid+Ord> where G::Timestamp: Data {
+    /// Passes along the updates with positive differences, handling the others according to `policy`.
+    ///
+    /// An update has a negative difference if its difference is less than zero. The output contains
+    /// only updates with positive differences, and is suitable for sinks that cannot retract data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::AppendOnly;
+    /// use differential_dataflow::operators::append_only::OnRetraction;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(1 .. 10u32).1;
+    ///
+    ///     // the retractions are dropped, leaving only the insertions.
+    ///     x.negate()
+    ///      .concat(&x.map(|x| x + 100))
+    ///      .append_only(OnRetraction::Drop)
+    ///      .assert_eq(&x.map(|x| x + 100));
+    /// });
+    /// ```
+    fn append_only(&self, policy: OnRetraction) -> Collection<G, D, R>;
+}
+
+impl<G: Scope, D: ExchangeData+Hashable, R: ExchangeData+Monoid+Ord> AppendOnly<G, D, R> for Collection<G, D, R> where G::Timestamp: Data+Lattice {
+    fn append_only(&self, policy: OnRetraction) -> Collection<G, D, R> {
+
+        let zero = R::zero();
+        let mut buffer = Vec::new();
+
+        // An update is only known to be a retraction once all updates at its time have accumulated.
+        let updates = match policy {
+            OnRetraction::Drop => self.clone(),
+            OnRetraction::Panic => self.suppress_updates_until_closed(),
+        };
+
+        updates.inner
+            .unary(Pipeline, "AppendOnly", move |_,_| move |input, output| {
+                input.for_each(|capability, data| {
+                    data.swap(&mut buffer);
+                    consolidate_updates(&mut buffer);
+                    if policy == OnRetraction::Panic {
+                        if let Some((data, time, diff)) = buffer.iter().find(|(_, _, diff)| diff < &zero) {
+                            panic!("append_only: retraction of {:?} at {:?} with difference {:?}", data, time, diff);
+                        }
+                    }
+                    buffer.retain(|(_, _, diff)| diff > &zero);
+                    if !buffer.is_empty() {
+                        output.session(&capability).give_container(&mut buffer);
+                    }
+                });
+            })
+            .as_collection()
+    }
+}
@@ -3,6 +3,7 @@
 use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::sync::Arc;
 
 use timely::dataflow::Scope;
 use timely::dataflow::operators::generic::{OperatorInfo, source};
@@ -12,11 +13,13 @@ use timely::dataflow::operators::CapabilitySet;
 
 use crate::trace::{Trace, TraceReader, Batch, BatchReader};
 use crate::trace::wrappers::rc::TraceBox;
+use crate::trace::clock::{Clock, SystemClock};
 
 use timely::scheduling::Activator;
 
 use super::{TraceWriter, TraceAgentQueueWriter, TraceAgentQueueReader, Arranged};
 use super::TraceReplayInstruction;
+use super::compaction::{CompactionPolicy, Immediate};
 
 use crate::trace::wrappers::frontier::{TraceFrontier, BatchFrontier};
 
@@ -34,6 +37,12 @@ where
     logical_compaction: Antichain<Tr::Time>,
     physical_compaction: Antichain<Tr::Time>,
     temp_antichain: Antichain<Tr::Time>,
+    /// Determines the logical compaction frontier from the frontiers requested of the handle.
+    compaction_policy: Box<dyn CompactionPolicy<Tr::Time>>,
+    /// The frontier the policy permits, reused across requests.
+    permitted: Antichain<Tr::Time>,
+    /// The clock that reports the time presented to the compaction policy.
+    clock: Arc<dyn Clock>,
 
     operator: OperatorInfo,
     logging: Option<crate::logging::Logger>,
@@ -56,7 +65,9 @@ where
     fn set_logical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) {
         // This method does not enforce that `frontier` is greater or equal to `self.logical_compaction`.
         // Instead, it determines the joint consequences of both guarantees and moves forward with that.
-        crate::lattice::antichain_join_into(&self.logical_compaction.borrow()[..], &frontier[..], &mut self.temp_antichain);
+        self.permitted.clear();
+        self.compaction_policy.logical_compaction(&*self.clock, frontier, &mut self.permitted);
+        crate::lattice::antichain_join_into(&self.logical_compaction.borrow()[..], &self.permitted.borrow()[..], &mut self.temp_antichain);
         self.trace.borrow_mut().adjust_logical_compaction(self.logical_compaction.borrow(), self.temp_antichain.borrow());
         ::std::mem::swap(&mut self.logical_compaction, &mut self.temp_antichain);
         self.temp_antichain.clear();
@@ -76,9 +87,20 @@ where
         self.physical_compaction.borrow()
     }
     fn cursor_through(&mut self, frontier: AntichainRef<Tr::Time>) -> Option<(Self::Cursor, Self::Storage)> {
-        self.trace.borrow_mut().trace.cursor_through(frontier)
+        // A released trace no longer holds the updates of the arrangement.
+        let mut trace_box = self.trace.borrow_mut();
+        if trace_box.released { None }
+        else { trace_box.trace.cursor_through(frontier) }
+    }
+    fn cursor_for_keys(&mut self, lower: Tr::Key<'_>, upper: Tr::Key<'_>) -> (Self::Cursor, Self::Storage) {
+        let mut trace_box = self.trace.borrow_mut();
+        if trace_box.released { panic!("unable to acquire complete cursor for trace; is it closed?"); }
+        trace_box.trace.cursor_for_keys(lower, upper)
     }
     fn map_batches<F: FnMut(&Self::Batch)>(&self, f: F) { self.trace.borrow().trace.map_batches(f) }
+    fn read_merged_upper(&mut self, target: &mut Antichain<Tr::Time>) {
+        self.trace.borrow_mut().trace.read_merged_upper(target)
+    }
 }
 
 impl<Tr: TraceReader> TraceAgent<Tr> {
@@ -103,6 +125,9 @@ impl<Tr: TraceReader> TraceAgent<Tr> {
             logical_compaction: trace.borrow().logical_compaction.frontier().to_owned(),
             physical_compaction: trace.borrow().physical_compaction.frontier().to_owned(),
             temp_antichain: Antichain::new(),
+            compaction_policy: Box::new(Immediate),
+            permitted: Antichain::new(),
+            clock: Arc::new(SystemClock),
             operator,
             logging,
         };
@@ -150,6 +175,88 @@ impl<Tr: TraceReader> TraceAgent<Tr> {
         reference
     }
 
+    /// The logical compaction frontier of this handle.
+    pub fn logical_compaction(&self) -> Antichain<Tr::Time> {
+        self.logical_compaction.clone()
+    }
+
+    /// The physical compaction frontier of this handle.
+    pub fn physical_compaction(&self) -> Antichain<Tr::Time> {
+        self.physical_compaction.clone()
+    }
+
+    /// Sets the policy that determines the logical compaction frontier of this handle.
+    ///
+    /// The policy is consulted whenever the handle is asked to compact, and determines the frontier the
+    /// handle compacts to instead. The policy applies only to this handle, and clones of the handle use
+    /// the default `Immediate` policy, which compacts to each requested frontier.
+    pub fn set_compaction_policy<P: CompactionPolicy<Tr::Time>+'static>(&mut self, policy: P) {
+        self.compaction_policy = Box::new(policy);
+    }
+
+    /// Sets the clock that reports the time presented to the compaction policy of this handle.
+    ///
+    /// Handles use the system clock unless set otherwise, and clones of the handle use its clock.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Advances the logical compaction frontier of this handle, if `frontier` is a valid advance.
+    ///
+    /// Unlike `set_logical_compaction`, which joins `frontier` with the current frontier, this method
+    /// returns an error and leaves the frontier unchanged if `frontier` is not an antichain, or does
+    /// not equal or advance the current frontier.
+    pub fn try_set_logical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) -> Result<(), String> {
+        check_advance(self.logical_compaction.borrow(), frontier)?;
+        self.set_logical_compaction(frontier);
+        Ok(())
+    }
+
+    /// Advances the physical compaction frontier of this handle, if `frontier` is a valid advance.
+    ///
+    /// Unlike `set_physical_compaction`, which joins `frontier` with the current frontier, this method
+    /// returns an error and leaves the frontier unchanged if `frontier` is not an antichain, or does
+    /// not equal or advance the current frontier.
+    pub fn try_set_physical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) -> Result<(), String> {
+        check_advance(self.physical_compaction.borrow(), frontier)?;
+        self.set_physical_compaction(frontier);
+        Ok(())
+    }
+
+    /// Drops the batches of the shared trace, and closes it.
+    ///
+    /// All handles to the trace, and all listeners that import it, observe an empty trace whose upper
+    /// frontier is empty. The operator that maintains the trace continues to run until its dataflow
+    /// shuts down, but no longer records batches. This releases the memory held by the trace at once,
+    /// rather than once every handle to it has been dropped.
+    pub fn release(&mut self)
+    where
+        Tr: Trace,
+        Tr::Batch: Batch,
+    {
+        let mut trace = Tr::new(self.operator.clone(), self.logging.clone(), None);
+        trace.close();
+
+        let mut trace_box = self.trace.borrow_mut();
+        trace.set_logical_compaction(trace_box.logical_compaction.frontier());
+        trace.set_physical_compaction(trace_box.physical_compaction.frontier());
+        trace_box.trace = trace;
+        trace_box.released = true;
+        drop(trace_box);
+
+        // discard batches queued for listeners, and inform them that the trace is complete.
+        if let Some(queues) = self.queues.upgrade() {
+            for queue in queues.borrow().iter() {
+                if let Some(pair) = queue.upgrade() {
+                    let mut queue = pair.1.borrow_mut();
+                    queue.clear();
+                    queue.push_back(TraceReplayInstruction::Frontier(Antichain::new()));
+                    pair.0.activate();
+                }
+            }
+        }
+    }
+
     /// The [OperatorInfo] of the underlying Timely operator
     pub fn operator(&self) -> &OperatorInfo {
         &self.operator
@@ -164,6 +271,91 @@ impl<Tr: TraceReader> TraceAgent<Tr> {
     pub fn trace_box_unstable(&self) -> Rc<RefCell<TraceBox<Tr>>> {
         Rc::clone(&self.trace)
     }
+
+    /// A handle reporting the frontier of times whose batches are fully merged.
+    ///
+    /// The handle reads `read_merged_upper` of the shared trace, and unlike a clone of the agent does not
+    /// hold back the compaction of the trace. Once the trace is dropped, the handle reports an empty
+    /// frontier. Merges in progress advance as further batches are inserted, and otherwise only with idle
+    /// merge effort, which should be configured to wait for merging to quiesce once input stops.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeBySelf;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// let options = differential_dataflow::Config::default().idle_merge_effort(Some(1000));
+    /// differential_dataflow::configure(&mut config.worker, &options);
+    ///
+    /// timely::execute(config, |worker| {
+    ///
+    ///     let (mut input, trace) = worker.dataflow::<u32,_,_>(|scope| {
+    ///         let (input, data) = scope.new_collection();
+    ///         (input, data.arrange_by_self().trace)
+    ///     });
+    ///     let probe = trace.merge_probe();
+    ///
+    ///     // backfill, then wait for merging to quiesce.
+    ///     for round in 0 .. 10u32 {
+    ///         input.insert(round);
+    ///         input.advance_to(round + 1);
+    ///     }
+    ///     input.flush();
+    ///     while probe.less_than(input.time()) {
+    ///         worker.step();
+    ///     }
+    /// }).unwrap();
+    /// ```
+    pub fn merge_probe(&self) -> MergeProbe<Tr> {
+        MergeProbe {
+            trace: Rc::downgrade(&self.trace),
+            frontier: RefCell::new(Antichain::new()),
+        }
+    }
+}
+
+/// A handle reporting the frontier of times whose batches are fully merged, as `read_merged_upper`.
+///
+/// Systems that read from arrangements can use the handle to wait, as with a timely probe, until the
+/// batches of a backfill have been merged, in addition to inserted into the trace.
+pub struct MergeProbe<Tr: TraceReader> {
+    trace: Weak<RefCell<TraceBox<Tr>>>,
+    frontier: RefCell<Antichain<Tr::Time>>,
+}
+
+impl<Tr: TraceReader> MergeProbe<Tr> {
+    /// Returns `true` iff the merged frontier is strictly less than `time`.
+    pub fn less_than(&self, time: &Tr::Time) -> bool {
+        self.with_frontier(|frontier| frontier.iter().any(|t| timely::PartialOrder::less_than(t, time)))
+    }
+    /// Returns `true` iff the merged frontier is less than or equal to `time`.
+    pub fn less_equal(&self, time: &Tr::Time) -> bool {
+        self.with_frontier(|frontier| frontier.iter().any(|t| timely::PartialOrder::less_equal(t, time)))
+    }
+    /// Allows inspection of the merged frontier.
+    pub fn with_frontier<R, F: FnOnce(AntichainRef<Tr::Time>)->R>(&self, function: F) -> R {
+        let mut frontier = self.frontier.borrow_mut();
+        frontier.clear();
+        if let Some(trace) = self.trace.upgrade() {
+            trace.borrow_mut().trace.read_merged_upper(&mut frontier);
+        }
+        function(frontier.borrow())
+    }
+}
+
+/// Checks that `proposed` is an antichain that equals or advances `current`.
+fn check_advance<T: Timestamp>(current: AntichainRef<T>, proposed: AntichainRef<T>) -> Result<(), String> {
+    for (index, time) in proposed.iter().enumerate() {
+        if proposed.iter().enumerate().any(|(other, prior)| other != index && prior.less_equal(time)) {
+            return Err(format!("frontier {:?} is not an antichain", &proposed[..]));
+        }
+        if !current.less_equal(time) {
+            return Err(format!("frontier {:?} does not advance {:?}", &proposed[..], &current[..]));
+        }
+    }
+    Ok(())
 }
 
 impl<Tr> TraceAgent<Tr>
@@ -483,6 +675,50 @@ where
 
         (Arranged { stream, trace }, shutdown_button.unwrap())
     }
+
+    /// Imports the arrangement with its updates advanced to `as_of`, if the trace can distinguish times there.
+    ///
+    /// The imported arrangement presents the contents of the trace as of `as_of`, followed by changes at
+    /// later times, and its trace handle holds back compaction to `as_of`. The method returns an error
+    /// if `as_of` is not an antichain, if the trace may already be compacted beyond `as_of`, or if the
+    /// trace has been released, rather than import an arrangement whose contents are not as of `as_of`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::progress::frontier::AntichainRef;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeBySelf;
+    /// use differential_dataflow::trace::TraceReader;
+    ///
+    /// ::timely::execute_directly(|worker| {
+    ///
+    ///     let mut trace = worker.dataflow::<u32,_,_>(|scope| {
+    ///         scope.new_collection_from(0 .. 10).1
+    ///              .arrange_by_self()
+    ///              .trace
+    ///     });
+    ///     trace.set_logical_compaction(AntichainRef::new(&[5]));
+    ///
+    ///     worker.dataflow(|scope| {
+    ///         assert!(trace.import_as_of(scope, "Import", AntichainRef::new(&[3])).is_err());
+    ///         assert!(trace.import_as_of(scope, "Import", AntichainRef::new(&[7])).is_ok());
+    ///     });
+    /// });
+    /// ```
+    pub fn import_as_of<G>(&mut self, scope: &G, name: &str, as_of: AntichainRef<Tr::Time>) -> Result<(Arranged<G, TraceFrontier<TraceAgent<Tr>>>, ShutdownButton<CapabilitySet<Tr::Time>>), String>
+    where
+        G: Scope<Timestamp=Tr::Time>,
+    {
+        if self.trace.borrow().released {
+            return Err("the trace has been released".to_string());
+        }
+        check_advance(self.logical_compaction.borrow(), as_of)
+            .map_err(|error| format!("cannot import as of {:?}: {}", &as_of[..], error))?;
+        let mut trace = self.clone();
+        trace.set_logical_compaction(as_of);
+        Ok(trace.import_frontier_core(scope, name, as_of.to_owned(), Antichain::new()))
+    }
 }
 
 
@@ -549,6 +785,9 @@ where
             operator: self.operator.clone(),
             logging: self.logging.clone(),
             temp_antichain: Antichain::new(),
+            compaction_policy: Box::new(Immediate),
+            permitted: Antichain::new(),
+            clock: self.clock.clone(),
         }
     }
 }
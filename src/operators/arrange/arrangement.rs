@@ -17,25 +17,34 @@
 //! see ill-defined data at times for which the trace is not complete. (All current implementations
 //! commit only completed data to the trace).
 
+use std::hash::Hash;
+
 use timely::dataflow::operators::{Enter, Map};
-use timely::order::PartialOrder;
+use timely::order::{PartialOrder, Product};
 use timely::dataflow::{Scope, Stream, StreamCore};
 use timely::dataflow::operators::generic::Operator;
 use timely::dataflow::channels::pact::{ParallelizationContract, Pipeline, Exchange};
 use timely::progress::Timestamp;
 use timely::progress::Antichain;
 use timely::dataflow::operators::Capability;
+use timely::container::{ContainerBuilder, PushContainer, PushInto};
+
+use abomonation_derive::Abomonation;
+use serde::{Deserialize, Serialize};
 
 use crate::{Data, ExchangeData, Collection, AsCollection, Hashable};
 use crate::difference::Semigroup;
 use crate::lattice::Lattice;
 use crate::trace::{self, Trace, TraceReader, Batch, BatchReader, Batcher, Builder, Cursor};
 use crate::trace::implementations::{KeySpine, ValSpine};
+use crate::trace::implementations::ord_neu::PresortedValSpine;
 
 use trace::wrappers::enter::{TraceEnter, BatchEnter,};
 use trace::wrappers::enter_at::TraceEnter as TraceEnterAt;
 use trace::wrappers::enter_at::BatchEnter as BatchEnterAt;
 use trace::wrappers::filter::{TraceFilter, BatchFilter};
+use trace::wrappers::key_map::{TraceKeyMap, BatchKeyMap};
+use trace::wrappers::project::{TraceProject, BatchProject};
 
 use super::TraceAgent;
 
@@ -129,6 +138,55 @@ where
         }
     }
 
+    /// Brings an arranged collection into an iterative scope, entering each record at an iteration it determines.
+    ///
+    /// Each record is presented at its outer time, and at the iteration that `iteration` determines from its
+    /// key and value. This supports prioritized iteration, for example delta stepping, where a record should
+    /// only take part once the iteration reaches its priority. As records may enter at any iteration, the
+    /// compaction frontiers of the iterative scope are communicated to the shared arrangement by their outer
+    /// times only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::order::Product;
+    /// use timely::dataflow::Scope;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let arranged = scope.new_collection_from(0 .. 10u64).1
+    ///                         .map(|x| (x, x))
+    ///                         .arrange_by_key();
+    ///
+    ///     scope.iterative::<u64,_,_>(|child| {
+    ///         // each record enters at the iteration of its value.
+    ///         arranged.enter_at_iteration(child, |_key, val| *val)
+    ///                 .as_collection(|k, v| (*k, *v))
+    ///                 .inspect(|((_, val), time, _)| assert_eq!(time.inner, *val))
+    ///                 .leave()
+    ///     });
+    /// });
+    /// ```
+    pub fn enter_at_iteration<'a, TInner, F>(&self, child: &Child<'a, G, Product<G::Timestamp, TInner>>, mut iteration: F)
+        -> Arranged<Child<'a, G, Product<G::Timestamp, TInner>>, TraceEnterAt<
+            Tr,
+            Product<G::Timestamp, TInner>,
+            impl FnMut(Tr::Key<'_>, Tr::Val<'_>, &G::Timestamp)->Product<G::Timestamp, TInner>+Clone+'static,
+            impl FnMut(&Product<G::Timestamp, TInner>)->G::Timestamp+Clone+'static,
+        >>
+        where
+            TInner: Lattice+Timestamp+Clone+'static,
+            F: FnMut(Tr::Key<'_>, Tr::Val<'_>)->TInner+Clone+'static,
+        {
+        self.enter_at(
+            child,
+            move |key, val, time| Product::new(time.clone(), iteration(key, val)),
+            |time: &Product<G::Timestamp, TInner>| time.outer.clone(),
+        )
+    }
+
     /// Filters an arranged collection.
     ///
     /// This method produces a new arrangement backed by the same shared
@@ -136,6 +194,11 @@ where
     /// filter by key and value. The resulting collection is restricted
     /// to the keys and values that return true under the user predicate.
     ///
+    /// The predicate is applied as cursors navigate the arrangement, which
+    /// skip values that fail it and keys with no values that pass it. The
+    /// filtered data are not copied; to use them in another dataflow, import
+    /// the shared arrangement there and filter the imported arrangement.
+    ///
     /// # Examples
     ///
     /// ```
@@ -167,6 +230,171 @@ where
             stream: self.stream.map(move |bw| BatchFilter::make_from(bw, logic2.clone())),
         }
     }
+
+    /// Presents an arranged collection under a different key type.
+    ///
+    /// This method produces a new arrangement backed by the same shared
+    /// arrangement as `self`, whose keys are presented as keys of type `K`.
+    /// The `forward` and `backward` functions convert between the keys of
+    /// `self` and owned keys of type `K`; they must be inverses of each other
+    /// and preserve the order of keys. This allows a shared arrangement to be
+    /// used by operators that expect an equivalent key type, for example a
+    /// newtype of the key, without re-arranging it. Keys are only converted
+    /// when they are compared with or converted to owned keys of type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    /// use differential_dataflow::trace::cursor::MyTrait;
+    ///
+    /// #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// struct UserId(u32);
+    ///
+    /// fn forward(key: &u32) -> UserId { UserId(*key) }
+    /// fn backward(key: &UserId) -> u32 { key.0 }
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let arranged =
+    ///     scope.new_collection_from(0 .. 10u32).1
+    ///          .map(|x| (x, x+1))
+    ///          .arrange_by_key();
+    ///
+    ///     arranged
+    ///         .remap_keys(forward, backward)
+    ///         .as_collection(|k, v| (k.into_owned().0, *v))
+    ///         .assert_eq(&arranged.as_collection(|k,v| (*k,*v)));
+    /// });
+    /// ```
+    pub fn remap_keys<K, F, B>(&self, forward: F, backward: B)
+        -> Arranged<G, TraceKeyMap<Tr, F, B>>
+        where
+            K: Ord+Clone+'static,
+            F: for<'a> Fn(Tr::Key<'a>)->K+Clone+'static,
+            B: Fn(&K)->Tr::KeyOwned+Clone+'static,
+    {
+        let forward2 = forward.clone();
+        let backward2 = backward.clone();
+        Arranged {
+            trace: TraceKeyMap::make_from(self.trace.clone(), forward, backward),
+            stream: self.stream.map(move |bw| BatchKeyMap::make_from(bw, forward2.clone(), backward2.clone())),
+        }
+    }
+
+    /// Presents a projection of the values of an arranged collection.
+    ///
+    /// This method produces a new arrangement backed by the same shared
+    /// arrangement as `self`, whose values are presented as references to
+    /// the result of `logic`. The projection must preserve the order of
+    /// values, as projecting to the first field of a tuple does, but may map
+    /// several values of a key to the same projected value, which is then
+    /// presented once with the updates of all of them. Consumers that need
+    /// different narrow views of a wide value can share one arrangement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let arranged =
+    ///     scope.new_collection_from(0 .. 10).1
+    ///          .map(|x| (x % 2, (x % 3, x)))
+    ///          .arrange_by_key();
+    ///
+    ///     arranged
+    ///         .project_vals(|v| &v.0)
+    ///         .as_collection(|k, v| (*k, *v))
+    ///         .assert_eq(&arranged.as_collection(|k, v| (*k, v.0)));
+    /// });
+    /// ```
+    pub fn project_vals<P, F>(&self, logic: F)
+        -> Arranged<G, TraceProject<Tr, F>>
+        where
+            P: Ord+Clone+'static,
+            F: for<'a> Fn(Tr::Val<'a>)->&'a P+Clone+'static,
+    {
+        let logic2 = logic.clone();
+        Arranged {
+            trace: TraceProject::make_from(self.trace.clone(), logic),
+            stream: self.stream.map(move |bw| BatchProject::make_from(bw, logic2.clone())),
+        }
+    }
+
+    /// Responds to a stream of keyed queries with the accumulated values of their keys.
+    ///
+    /// Each query `(key, query)` is answered once the arrangement is complete through the time of the
+    /// query, by calling `logic` with the query, the key, and each value of the key with its accumulated
+    /// difference through that time. The results are produced at the time of the query. See the
+    /// [`query`](super::query) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let arranged =
+    ///     scope.new_collection_from(0 .. 10u32).1
+    ///          .map(|x| (x % 3, x))
+    ///          .arrange_by_key();
+    ///
+    ///     // ask for the values of keys `0` and `2`, tagging each query with a request number.
+    ///     let queries = vec![(0u32, 100u64), (2, 101)].to_stream(scope);
+    ///     arranged
+    ///         .query(&queries, |request, key, val, diff| (*request, *key, *val, *diff))
+    ///         .inspect(|response| assert_eq!(response.1, response.2 % 3));
+    /// });
+    /// ```
+    pub fn query<Q, D, L>(&self, queries: &Stream<G, (Tr::KeyOwned, Q)>, logic: L) -> Stream<G, D>
+        where
+            Tr: 'static,
+            Tr::KeyOwned: ExchangeData+Hashable,
+            Q: ExchangeData,
+            D: Data,
+            L: FnMut(&Q, Tr::Key<'_>, Tr::Val<'_>, &Tr::Diff)->D+'static,
+    {
+        super::query::query(self, queries, logic)
+    }
+
+    /// Calls `logic` with the description and the number of updates of each batch of the arrangement.
+    ///
+    /// The arrangement is unchanged, and `logic` is called as each batch is produced, without holding
+    /// a handle to the trace. This can report the progress and size of an arrangement, or drive work
+    /// that follows its batches, like persisting them elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::ArrangeBySelf;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     scope.new_collection_from(0 .. 10u32).1
+    ///          .arrange_by_self()
+    ///          .inspect_batches(|description, len| {
+    ///              println!("batch [{:?}, {:?}) with {} updates", description.lower(), description.upper(), len);
+    ///          });
+    /// });
+    /// ```
+    pub fn inspect_batches<F>(&self, mut logic: F) -> Self
+        where
+            F: FnMut(&trace::Description<Tr::Time>, usize)+'static,
+    {
+        use timely::dataflow::operators::Inspect;
+        Arranged {
+            stream: self.stream.inspect(move |batch| logic(batch.description(), batch.len())),
+            trace: self.trace.clone(),
+        }
+    }
     /// Flattens the stream into a `Collection`.
     ///
     /// The underlying `Stream<G, BatchWrapper<T::Batch>>` is a much more efficient way to access the data,
@@ -192,6 +420,46 @@ where
         Self::flat_map_batches(&self.stream, logic)
     }
 
+    /// Extracts updates from an arrangement into containers built by a container builder.
+    ///
+    /// The supplied logic is called once for each key, value, time, and difference, with all of them
+    /// borrowed from the batch, and produces the updates to push into the container builder. Unlike
+    /// `flat_map_ref`, which clones each produced record for each of its times, the logic can form its
+    /// output directly from the borrowed parts, and a builder such as `ConsolidatingContainerBuilder`
+    /// can consolidate the output as it is formed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::AsCollection;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::consolidation::ConsolidatingContainerBuilder;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(0 .. 10u32).1;
+    ///
+    ///     data.map(|x| (x % 3, x))
+    ///         .arrange_by_key()
+    ///         .as_collection_core::<ConsolidatingContainerBuilder<_>, _, _>(|key, _val, time, diff| {
+    ///             Some((*key, time.clone(), diff.clone()))
+    ///         })
+    ///         .as_collection()
+    ///         .assert_eq(&data.map(|x| x % 3));
+    /// });
+    /// ```
+    pub fn as_collection_core<CB, I, L>(&self, logic: L) -> StreamCore<G, CB::Container>
+        where
+            CB: ContainerBuilder,
+            CB::Container: PushContainer,
+            I: IntoIterator,
+            I::Item: PushInto<CB::Container>,
+            L: FnMut(Tr::Key<'_>, Tr::Val<'_>, &G::Timestamp, &Tr::Diff) -> I+'static,
+    {
+        Self::flat_map_batches_core::<CB, I, L>(&self.stream, logic)
+    }
+
     /// Extracts elements from a stream of batches as a collection.
     ///
     /// The supplied logic may produce an iterator over output values, allowing either
@@ -227,6 +495,40 @@ where
         })
         .as_collection()
     }
+
+    /// Extracts updates from a stream of batches into containers built by a container builder.
+    ///
+    /// This method exists for streams of batches without the corresponding arrangement.
+    /// If you have the arrangement, its `as_collection_core` method is equivalent to this.
+    pub fn flat_map_batches_core<CB, I, L>(stream: &Stream<G, Tr::Batch>, mut logic: L) -> StreamCore<G, CB::Container>
+    where
+        CB: ContainerBuilder,
+        CB::Container: PushContainer,
+        I: IntoIterator,
+        I::Item: PushInto<CB::Container>,
+        L: FnMut(Tr::Key<'_>, Tr::Val<'_>, &G::Timestamp, &Tr::Diff) -> I+'static,
+    {
+        stream.unary::<CB, _, _, _>(Pipeline, "AsCollection", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                let mut session = output.session_with_builder(&time);
+                for wrapper in data.iter() {
+                    let batch = &wrapper;
+                    let mut cursor = batch.cursor();
+                    while let Some(key) = cursor.get_key(batch) {
+                        while let Some(val) = cursor.get_val(batch) {
+                            cursor.map_times(batch, |time, diff| {
+                                for datum in logic(key, val, time, diff) {
+                                    session.give(datum);
+                                }
+                            });
+                            cursor.step_val(batch);
+                        }
+                        cursor.step_key(batch);
+                    }
+                }
+            });
+        })
+    }
 }
 
 
@@ -319,6 +621,21 @@ where
     }
 }
 
+impl<G, Tr> Arranged<G, TraceAgent<Tr>>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    Tr: Trace,
+    Tr::Batch: Batch,
+{
+    /// Drops the batches of the arrangement's trace, and closes it.
+    ///
+    /// Operators that read the trace, in this and other dataflows, observe an empty trace from then on.
+    /// This is meant for tearing down a dataflow among many, whose batches would otherwise remain until
+    /// every handle to the trace has been dropped. See `TraceAgent::release`.
+    pub fn release(&mut self) {
+        self.trace.release();
+    }
+}
 
 impl<'a, G, Tr> Arranged<Child<'a, G, G::Timestamp>, Tr>
 where
@@ -362,6 +679,18 @@ where
         Tr::Batcher: Batcher<Input=C>,
     ;
 
+    /// Arranges updates into a shared trace, with a supplied name, bounding the size of emitted batches.
+    ///
+    /// The updates of each completed interval of times are split into several batches with chained
+    /// descriptions, each holding at most `limit` updates where the batcher is able. Updates at the same
+    /// time are not split, and so a single time with more than `limit` updates produces a larger batch.
+    fn arrange_named_limited<Tr>(&self, name: &str, limit: usize) -> Arranged<G, TraceAgent<Tr>>
+    where
+        Tr: Trace<Time=G::Timestamp> + 'static,
+        Tr::Batch: Batch,
+        Tr::Batcher: Batcher<Input=C>,
+    ;
+
     /// Arranges updates into a shared trace, using a supplied parallelization contract, with a supplied name.
     fn arrange_core<P, Tr>(&self, pact: P, name: &str) -> Arranged<G, TraceAgent<Tr>>
     where
@@ -390,6 +719,16 @@ where
         self.arrange_core(exchange, name)
     }
 
+    fn arrange_named_limited<Tr>(&self, name: &str, limit: usize) -> Arranged<G, TraceAgent<Tr>>
+    where
+        Tr: Trace<Time=G::Timestamp> + 'static,
+        Tr::Batch: Batch,
+        Tr::Batcher: Batcher<Input=Vec<((K, V), G::Timestamp, R)>>,
+    {
+        let exchange = Exchange::new(move |update: &((K,V),G::Timestamp,R)| (update.0).0.hashed().into());
+        arrange_core_limited(&self.inner, exchange, name, Some(limit))
+    }
+
     fn arrange_core<P, Tr>(&self, pact: P, name: &str) -> Arranged<G, TraceAgent<Tr>>
     where
         P: ParallelizationContract<G::Timestamp, Vec<((K,V),G::Timestamp,R)>>,
@@ -407,6 +746,23 @@ where
 /// It uses the supplied parallelization contract to distribute the data, which does not need to
 /// be consistently by key (though this is the most common).
 pub fn arrange_core<G, P, Tr>(stream: &StreamCore<G, <Tr::Batcher as Batcher>::Input>, pact: P, name: &str) -> Arranged<G, TraceAgent<Tr>>
+where
+    G: Scope,
+    G::Timestamp: Lattice,
+    P: ParallelizationContract<G::Timestamp, <Tr::Batcher as Batcher>::Input>,
+    Tr: Trace<Time=G::Timestamp>+'static,
+    Tr::Batch: Batch,
+    <Tr::Batcher as Batcher>::Input: timely::Container,
+{
+    arrange_core_limited(stream, pact, name, None)
+}
+
+/// Arranges a stream of updates by a key, optionally bounding the number of updates in each emitted batch.
+///
+/// This operator behaves as `arrange_core`, except that when `limit` is set the updates sealed for each
+/// capability are presented to the trace and the output as a sequence of batches with chained descriptions,
+/// each of which the batcher attempts to keep to at most `limit` updates.
+pub fn arrange_core_limited<G, P, Tr>(stream: &StreamCore<G, <Tr::Batcher as Batcher>::Input>, pact: P, name: &str, limit: Option<usize>) -> Arranged<G, TraceAgent<Tr>>
 where
     G: Scope,
     G::Timestamp: Lattice,
@@ -456,8 +812,20 @@ where
         if let Some(exert_logic) = scope.config().get::<trace::ExertionLogic>("differential/default_exert_logic").cloned() {
             empty_trace.set_exert_logic(exert_logic);
         }
+        if let Some(logic) = scope.config().get::<trace::MemoryPressureLogic>("differential/memory_pressure_logic").cloned() {
+            empty_trace.set_memory_pressure_logic(logic);
+        }
+
+        // Bounds on the work of each activation, after which the operator yields and reschedules itself.
+        let arrange_fuel = scope.config().get::<usize>("differential/arrange_fuel").cloned();
+        let arrange_time_slice = scope.config().get::<std::time::Duration>("differential/arrange_time_slice").cloned();
+        let yield_activator = scope.activator_for(&info.address[..]);
+        let merge_scheduler = scope.config().get::<std::sync::Arc<trace::scheduler::MergeScheduler>>("differential/merge_scheduler").cloned();
+        let clock = scope.config().get::<std::sync::Arc<dyn trace::clock::Clock>>("differential/clock").cloned();
+        let clock = clock.unwrap_or_else(|| std::sync::Arc::new(trace::clock::SystemClock));
 
-        let (reader_local, mut writer) = TraceAgent::new(empty_trace, info, logger);
+        let (mut reader_local, mut writer) = TraceAgent::new(empty_trace, info, logger);
+        reader_local.set_clock(clock.clone());
 
         *reader_ref = Some(reader_local);
 
@@ -470,10 +838,22 @@ where
             // We don't have to keep all capabilities, but we need to be able to form output messages
             // when we realize that time intervals are complete.
 
-            input.for_each(|cap, data| {
+            //
+            // With `differential/arrange_fuel` or `differential/arrange_time_slice` configured, we stop
+            // receiving once the bound is reached. Unreceived input holds back the input frontier, and
+            // so the batches we seal below remain correct; we are rescheduled to receive the rest.
+            // The clock is only read with a time slice configured.
+            let start = arrange_time_slice.map(|_| clock.now());
+            let mut fuel = arrange_fuel.unwrap_or(usize::MAX);
+            while let Some((cap, data)) = input.next() {
                 capabilities.insert(cap.retain());
+                fuel = fuel.saturating_sub(timely::Container::len(&*data));
                 batcher.push_container(data);
-            });
+                if fuel == 0 || arrange_time_slice.zip(start).map(|(slice, start)| clock.now().saturating_sub(start) >= slice).unwrap_or(false) {
+                    yield_activator.activate();
+                    break;
+                }
+            }
 
             // The frontier may have advanced by multiple elements, which is an issue because
             // timely dataflow currently only allows one capability per message. This means we
@@ -521,12 +901,26 @@ where
                             }
 
                             // Extract updates not in advance of `upper`.
-                            let batch = batcher.seal::<Tr::Builder>(upper.clone());
-
-                            writer.insert(batch.clone(), Some(capability.time().clone()));
-
-                            // send the batch to downstream consumers, empty or not.
-                            output.session(&capabilities.elements()[index]).give(batch);
+                            let batches = match limit {
+                                None => vec![batcher.seal::<Tr::Builder>(upper.clone())],
+                                Some(limit) => batcher.seal_limited::<Tr::Builder>(upper.clone(), limit),
+                            };
+
+                            for (position, batch) in batches.into_iter().enumerate() {
+
+                                // Batches after the first start at a single time, which we offer as the hint
+                                // so that importers of the trace can downgrade their capabilities as they go.
+                                let hint = if position > 0 && batch.lower().elements().len() == 1 {
+                                    batch.lower().elements()[0].clone()
+                                }
+                                else {
+                                    capability.time().clone()
+                                };
+                                writer.insert(batch.clone(), Some(hint));
+
+                                // send the batch to downstream consumers, empty or not.
+                                output.session(&capabilities.elements()[index]).give(batch);
+                            }
                         }
                     }
 
@@ -557,7 +951,17 @@ where
                 prev_frontier.extend(input.frontier().frontier().iter().cloned());
             }
 
-            writer.exert();
+            // Idle merge effort can wait for the next activation, if this one has used its time.
+            if arrange_time_slice.zip(start).map(|(slice, start)| clock.now().saturating_sub(start) >= slice).unwrap_or(false) {
+                yield_activator.activate();
+            }
+            else {
+                writer.exert();
+                // Traces denied effort by a shared merge budget retry once the budget is renewed.
+                if let Some(delay) = merge_scheduler.as_ref().and_then(|scheduler| scheduler.retry_after()) {
+                    yield_activator.activate_after(delay);
+                }
+            }
         }
     });
 
@@ -578,6 +982,16 @@ where
         self.arrange_core(exchange, name)
     }
 
+    fn arrange_named_limited<Tr>(&self, name: &str, limit: usize) -> Arranged<G, TraceAgent<Tr>>
+    where
+        Tr: Trace<Time=G::Timestamp> + 'static,
+        Tr::Batch: Batch,
+        Tr::Batcher: Batcher<Input=Vec<((K, ()), G::Timestamp, R)>>,
+    {
+        self.map(|k| (k, ()))
+            .arrange_named_limited(name, limit)
+    }
+
     fn arrange_core<P, Tr>(&self, pact: P, name: &str) -> Arranged<G, TraceAgent<Tr>>
     where
         P: ParallelizationContract<G::Timestamp, Vec<((K,()),G::Timestamp,R)>>,
@@ -590,6 +1004,47 @@ where
     }
 }
 
+/// A key of an arrangement made by `arrange_by_key_hashed`.
+///
+/// Keys are ordered first by their hash and then by the key itself, which avoids comparing large keys
+/// (for example, strings with long common prefixes) during sorting, merging, and seeking. The hash is
+/// computed once, when the arrangement receives the key, and is the hash `arrange_by_key` would use.
+///
+/// The key dereferences to `K`, so logic applied to the keys of the arrangement can generally use them
+/// as if they were `K`; `key` and `into_key` recover the key itself. Keys are not presented in the order
+/// of `K`, and so the arrangement cannot be presented as one keyed by `K`. To seek a key, or to query the
+/// arrangement, wrap the key with `HashedKey::new`. To join with another collection, arrange it with
+/// `arrange_by_key_hashed` as well, or wrap its keys with `HashedKey::new` before arranging it.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Serialize, Deserialize, Abomonation)]
+pub struct HashedKey<K> {
+    hash: u64,
+    key: K,
+}
+
+impl<K: Hash> HashedKey<K> {
+    /// The key `key`, with its hash.
+    pub fn new(key: K) -> Self {
+        HashedKey { hash: key.hashed(), key }
+    }
+}
+
+impl<K> HashedKey<K> {
+    /// A reference to the key.
+    pub fn key(&self) -> &K { &self.key }
+    /// Unwraps the key.
+    pub fn into_key(self) -> K { self.key }
+}
+
+impl<K> std::ops::Deref for HashedKey<K> {
+    type Target = K;
+    fn deref(&self) -> &K { &self.key }
+}
+
+impl<K> Hashable for HashedKey<K> {
+    type Output = u64;
+    fn hashed(&self) -> u64 { self.hash }
+}
+
 /// Arranges something as `(Key,Val)` pairs according to a type `T` of trace.
 ///
 /// This arrangement requires `Key: Hashable`, and uses the `hashed()` method to place keys in a hashed
@@ -606,6 +1061,55 @@ where G::Timestamp: Lattice+Ord {
 
     /// As `arrange_by_key` but with the ability to name the arrangement.
     fn arrange_by_key_named(&self, name: &str) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>;
+
+    /// Arranges a collection of `(Key, Val)` records by `Key`, from input that arrives sorted.
+    ///
+    /// The arrangement uses a `PresortedBatcher`, which does not sort containers of updates that
+    /// arrive sorted by `((key, val), time)` and consolidated, as they do when each worker reads its
+    /// input from sorted storage. Containers that are not sorted are detected and sorted, so that
+    /// the result is correct for any input, though without the benefit for unsorted input.
+    fn arrange_presorted(&self) -> Arranged<G, TraceAgent<PresortedValSpine<K, V, G::Timestamp, R>>>;
+
+    /// Arranges a collection of `(Key, Val)` records by `Key`, with each key prefixed by its hash.
+    ///
+    /// The arrangement orders keys by their hash before the keys themselves, which spares most key
+    /// comparisons when merging batches and navigating cursors, and can substantially speed up both
+    /// for keys that are expensive to compare, like strings. Keys are presented to user logic as
+    /// `HashedKey<K>`, which dereferences to `K`; `HashedKey::key` recovers the key.
+    ///
+    /// Data are distributed among workers exactly as `arrange_by_key` would distribute them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::arrange::{ArrangeByKey, HashedKey};
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![("apple".to_string(), 1), ("pear".to_string(), 2)]).1;
+    ///     let y = scope.new_collection_from(vec![("apple".to_string(), 'a')]).1;
+    ///
+    ///     let arranged = x.arrange_by_key_hashed();
+    ///     arranged
+    ///         .as_collection(|k, v| (k.key().clone(), *v))
+    ///         .assert_eq(&x);
+    ///
+    ///     // joins against the arrangement use hashed keys on both sides.
+    ///     y.map(|(key, val)| (HashedKey::new(key), val))
+    ///      .arrange_by_key()
+    ///      .join_core(&arranged, |k, c, v| Some((k.key().clone(), *c, *v)))
+    ///      .assert_eq(&scope.new_collection_from(vec![("apple".to_string(), 'a', 1)]).1);
+    /// });
+    /// ```
+    fn arrange_by_key_hashed(&self) -> Arranged<G, TraceAgent<ValSpine<HashedKey<K>, V, G::Timestamp, R>>>
+    where
+        K: Hash;
+
+    /// As `arrange_by_key_hashed` but with the ability to name the arrangement.
+    fn arrange_by_key_hashed_named(&self, name: &str) -> Arranged<G, TraceAgent<ValSpine<HashedKey<K>, V, G::Timestamp, R>>>
+    where
+        K: Hash;
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, V: ExchangeData, R: ExchangeData+Semigroup> ArrangeByKey<G, K, V, R> for Collection<G, (K,V), R>
@@ -619,6 +1123,25 @@ where
     fn arrange_by_key_named(&self, name: &str) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>> {
         self.arrange_named(name)
     }
+
+    fn arrange_presorted(&self) -> Arranged<G, TraceAgent<PresortedValSpine<K, V, G::Timestamp, R>>> {
+        self.arrange_named("ArrangePresorted")
+    }
+
+    fn arrange_by_key_hashed(&self) -> Arranged<G, TraceAgent<ValSpine<HashedKey<K>, V, G::Timestamp, R>>>
+    where
+        K: Hash,
+    {
+        self.arrange_by_key_hashed_named("ArrangeByKeyHashed")
+    }
+
+    fn arrange_by_key_hashed_named(&self, name: &str) -> Arranged<G, TraceAgent<ValSpine<HashedKey<K>, V, G::Timestamp, R>>>
+    where
+        K: Hash,
+    {
+        self.map(|(key, val)| (HashedKey::new(key), val))
+            .arrange_named(name)
+    }
 }
 
 /// Arranges something as `(Key, ())` pairs according to a type `T` of trace.
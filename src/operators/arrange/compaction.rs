@@ -0,0 +1,193 @@
+//! Policies that determine how far trace handles compact.
+//!
+//! A `TraceAgent` advances its logical compaction frontier whenever its holder requests it, most
+//! often to the frontier of the operator that reads it. This collapses all history before the
+//! frontier, which is appropriate for operators but not for handles held to answer historical
+//! queries. A `CompactionPolicy` consulted by the handle may hold compaction back, for example to
+//! retain the full history of the recent past and to advance through older history in steps.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::time::Duration;
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::arrange::ArrangeBySelf;
+//! use differential_dataflow::operators::arrange::compaction::Bucketed;
+//!
+//! ::timely::example(|scope| {
+//!     let mut trace = scope.new_collection_from(0 .. 10u32).1
+//!                          .arrange_by_self()
+//!                          .trace;
+//!     // retain a minute of full history, and then compact in steps of an hour.
+//!     trace.set_compaction_policy(Bucketed::new(Duration::from_secs(60), Duration::from_secs(3600)));
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::generic::source;
+use timely::progress::{Antichain, Timestamp};
+use timely::progress::frontier::AntichainRef;
+
+use crate::trace::TraceReader;
+use crate::trace::clock::Clock;
+use super::TraceAgent;
+
+/// Determines the logical compaction frontier of a trace handle from the frontiers it is asked to compact to.
+pub trait CompactionPolicy<T> {
+    /// Populates `permitted` with the frontier to compact to, given the `requested` frontier.
+    ///
+    /// The frontier `permitted` is joined with the current compaction frontier of the handle, and so
+    /// a policy that does not wish to advance compaction may report the minimal frontier. It is
+    /// cleared before the call. Policies that depend on wall-clock time read it from `clock`, the
+    /// clock of the handle; handles are asked to compact often, and so policies that do not depend
+    /// on the time should not read it.
+    fn logical_compaction(&mut self, clock: &dyn Clock, requested: AntichainRef<T>, permitted: &mut Antichain<T>);
+}
+
+/// Compacts to each requested frontier immediately, the default policy of trace handles.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Immediate;
+
+impl<T: Timestamp> CompactionPolicy<T> for Immediate {
+    fn logical_compaction(&mut self, _clock: &dyn Clock, requested: AntichainRef<T>, permitted: &mut Antichain<T>) {
+        permitted.extend(requested.iter().cloned());
+    }
+}
+
+/// Retains the full history of a recent interval, and compacts older history in steps of a period.
+///
+/// The policy records each requested frontier with the wall-clock time of the request. At most once
+/// each `period`, it advances compaction to the most recent frontier requested at least `history`
+/// ago. Updates are therefore distinguishable at all times requested within `history`, and at times
+/// between `history` and `history + period` ago, depending on the time of the last step.
+///
+/// The policy only acts when the handle is asked to compact, and so a handle should be asked to compact
+/// regularly, even to the same frontier, for older history to be compacted. A `CompactionDriver` can
+/// do this for handles that are not otherwise maintained.
+#[derive(Debug)]
+pub struct Bucketed<T> {
+    history: Duration,
+    period: Duration,
+    /// Requested frontiers not yet compacted to, with the times they were requested.
+    requests: VecDeque<(Duration, Antichain<T>)>,
+    /// The frontier most recently compacted to, and when.
+    current: (Option<Duration>, Antichain<T>),
+}
+
+impl<T: Timestamp> Bucketed<T> {
+    /// A policy that retains `history` of full history, and then compacts in steps of `period`.
+    pub fn new(history: Duration, period: Duration) -> Self {
+        Bucketed {
+            history,
+            period,
+            requests: VecDeque::new(),
+            current: (None, Antichain::from_elem(T::minimum())),
+        }
+    }
+}
+
+impl<T: Timestamp> CompactionPolicy<T> for Bucketed<T> {
+    fn logical_compaction(&mut self, clock: &dyn Clock, requested: AntichainRef<T>, permitted: &mut Antichain<T>) {
+        let now = clock.now();
+        if self.requests.back().map_or(true, |(_, frontier)| frontier.elements() != &requested[..]) {
+            self.requests.push_back((now, requested.to_owned()));
+        }
+        let due = self.current.0.map_or(true, |stepped| now.saturating_sub(stepped) >= self.period);
+        if due {
+            let mut advanced = false;
+            while self.requests.front().map_or(false, |(time, _)| now.saturating_sub(*time) >= self.history) {
+                self.current.1 = self.requests.pop_front().unwrap().1;
+                advanced = true;
+            }
+            if advanced { self.current.0 = Some(now); }
+        }
+        permitted.extend(self.current.1.iter().cloned());
+    }
+}
+
+/// Periodically compacts registered trace handles to the upper frontiers of their traces.
+///
+/// Trace handles held outside of dataflows, for example to serve queries, must be asked to compact or
+/// they retain the full history of their traces. The driver owns registered handles, and at each
+/// `interval` asks them to compact, both logically and physically, as far as the written updates and
+/// their compaction policies allow. It is driven by an operator in a dataflow of the worker, which
+/// reschedules itself after each `interval`. Handles whose traces are complete, as their upper frontiers
+/// are empty, are compacted a last time and released, and the operator completes once the driver is
+/// dropped or the traces of all registered handles are complete.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use differential_dataflow::input::Input;
+/// use differential_dataflow::operators::arrange::ArrangeBySelf;
+/// use differential_dataflow::operators::arrange::compaction::{Bucketed, CompactionDriver};
+///
+/// ::timely::example(|scope| {
+///     let trace = scope.new_collection_from(0 .. 10u32).1
+///                      .arrange_by_self()
+///                      .trace;
+///
+///     let driver = CompactionDriver::new(scope, Duration::from_secs(1));
+///     driver.register(trace, Bucketed::new(Duration::from_secs(60), Duration::from_secs(3600)));
+/// });
+/// ```
+pub struct CompactionDriver<Tr: TraceReader> {
+    handles: Rc<RefCell<Vec<TraceAgent<Tr>>>>,
+}
+
+impl<Tr: TraceReader+'static> CompactionDriver<Tr> {
+    /// Creates a driver that compacts its handles each `interval`, from an operator in `scope`.
+    pub fn new<G: Scope>(scope: &G, interval: Duration) -> Self {
+
+        let handles = Rc::new(RefCell::new(Vec::<TraceAgent<Tr>>::new()));
+        let weak = Rc::downgrade(&handles);
+
+        let activations = scope.clone();
+        let _: timely::dataflow::Stream<G, ()> = source(scope, "CompactionDriver", move |capability, info| {
+
+            let activator = activations.activator_for(&info.address[..]);
+            let mut capability = Some(capability);
+            let mut upper = Antichain::new();
+            // Whether the traces of some registered handles have completed.
+            let mut completed = false;
+
+            move |_output| {
+                match weak.upgrade() {
+                    Some(handles) => {
+                        let mut handles = handles.borrow_mut();
+                        let registered = handles.len();
+                        handles.retain_mut(|handle| {
+                            handle.read_upper(&mut upper);
+                            handle.set_logical_compaction(upper.borrow());
+                            let logical = handle.logical_compaction();
+                            handle.set_physical_compaction(logical.borrow());
+                            !upper.is_empty()
+                        });
+                        completed |= handles.len() < registered;
+                        if handles.is_empty() && completed {
+                            capability.take();
+                        }
+                        else {
+                            activator.activate_after(interval);
+                        }
+                    },
+                    None => { capability.take(); },
+                }
+            }
+        });
+
+        CompactionDriver { handles }
+    }
+
+    /// Registers `handle` to be compacted according to `policy`.
+    pub fn register<P: CompactionPolicy<Tr::Time>+'static>(&self, mut handle: TraceAgent<Tr>, policy: P) {
+        handle.set_compaction_policy(policy);
+        self.handles.borrow_mut().push(handle);
+    }
+}
@@ -0,0 +1,106 @@
+//! Maintenance of arrangements while a worker is idle.
+//!
+//! Traces merge their batches as new batches arrive, and otherwise only with the effort granted by
+//! their exertion logic, which an arrange operator applies whenever it is scheduled, whether or not
+//! fresh data awaits it. An `IdleMaintenance` instead holds the traces registered with it, and applies
+//! a budget of effort to them once the worker has caught up with its input, as reported by frontiers.
+//! Merges then proceed in the time between rounds of input, rather than delaying the next round.
+//!
+//! The maintenance holds its traces weakly, and does not prevent their compaction or release.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::arrange::{ArrangeBySelf, IdleMaintenance};
+//! use differential_dataflow::operators::count::CountTotal;
+//!
+//! ::timely::execute_directly(|worker| {
+//!
+//!     let mut maintenance = IdleMaintenance::new(1_000_000);
+//!
+//!     let (mut input, probe) = worker.dataflow(|scope| {
+//!         let (input, edges) = scope.new_collection::<(u32, u32), isize>();
+//!         let sources = edges.map(|(src, _dst)| src).arrange_by_self();
+//!         maintenance.register(&sources.trace);
+//!         (input, sources.count_total().probe())
+//!     });
+//!
+//!     for round in 0 .. 10u32 {
+//!         input.insert((round % 3, round));
+//!         input.advance_to(round + 1);
+//!         input.flush();
+//!         // steps the worker until it has caught up, and then merges batches.
+//!         maintenance.step_while(worker, || probe.less_than(input.time()));
+//!     }
+//! });
+//! ```
+
+use std::rc::Weak;
+use std::cell::RefCell;
+
+use timely::communication::Allocate;
+use timely::worker::Worker;
+
+use crate::trace::{Trace, Batch};
+use crate::trace::wrappers::rc::TraceBox;
+
+use super::TraceAgent;
+
+/// Applies merge effort to registered traces while the worker is idle.
+pub struct IdleMaintenance {
+    /// The effort applied to each trace in each idle period.
+    budget: usize,
+    /// For each registered trace, applies effort and reports whether the trace still exists.
+    traces: Vec<Box<dyn FnMut(usize) -> bool>>,
+}
+
+impl IdleMaintenance {
+    /// Creates a maintenance that applies `budget` effort to each registered trace when idle.
+    ///
+    /// Effort is measured in updates, as for the exertion logic of traces.
+    pub fn new(budget: usize) -> Self {
+        IdleMaintenance { budget, traces: Vec::new() }
+    }
+
+    /// Registers the trace of `agent` for maintenance.
+    pub fn register<Tr>(&mut self, agent: &TraceAgent<Tr>)
+    where
+        Tr: Trace+'static,
+        Tr::Batch: Batch,
+    {
+        let trace: Weak<RefCell<TraceBox<Tr>>> = std::rc::Rc::downgrade(&agent.trace_box_unstable());
+        self.traces.push(Box::new(move |effort| {
+            if let Some(trace) = trace.upgrade() {
+                trace.borrow_mut().trace.exert_with(effort);
+                true
+            }
+            else { false }
+        }));
+    }
+
+    /// The number of registered traces, less those found dropped by `exert`.
+    pub fn len(&self) -> usize {
+        self.traces.len()
+    }
+
+    /// Returns `true` iff there are no registered traces.
+    pub fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    /// Applies the budget of effort to each registered trace, and forgets traces that were dropped.
+    pub fn exert(&mut self) {
+        let budget = self.budget;
+        self.traces.retain_mut(|exert| exert(budget));
+    }
+
+    /// Steps `worker` while `pending` holds, and then applies effort to the registered traces.
+    ///
+    /// The `pending` argument should report whether input remains to be processed, typically as
+    /// `|| probe.less_than(input.time())`, so that effort is only applied once the worker is idle.
+    pub fn step_while<A: Allocate, F: FnMut() -> bool>(&mut self, worker: &mut Worker<A>, pending: F) {
+        worker.step_while(pending);
+        self.exert();
+    }
+}
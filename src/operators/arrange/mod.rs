@@ -68,8 +68,15 @@ pub mod agent;
 pub mod arrangement;
 
 pub mod upsert;
+pub mod query;
+pub mod reshard;
+pub mod registry;
+pub mod compaction;
+pub mod maintenance;
 
 pub use self::writer::TraceWriter;
-pub use self::agent::{TraceAgent, ShutdownButton};
+pub use self::agent::{TraceAgent, ShutdownButton, MergeProbe};
+pub use self::registry::ArrangementRegistry;
+pub use self::maintenance::IdleMaintenance;
 
-pub use self::arrangement::{Arranged, Arrange, ArrangeByKey, ArrangeBySelf};
\ No newline at end of file
+pub use self::arrangement::{Arranged, Arrange, ArrangeByKey, ArrangeBySelf, HashedKey};
\ No newline at end of file
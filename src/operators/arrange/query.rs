@@ -0,0 +1,129 @@
+//! Point lookups against arrangements.
+//!
+//! The `query` operator responds to a stream of queries, each naming a key, with the accumulated
+//! values of the key in an arrangement. A query that arrives at a time `t` is answered once the
+//! arrangement is complete through `t`, with the accumulation of the updates at times less or equal
+//! to `t`. The response is produced at time `t`, so that downstream operators can correlate queries
+//! and responses by time, as well as by the query data they carry.
+//!
+//! The operator holds back the logical compaction of the arrangement's trace only as far as its
+//! unanswered queries require, and releases the trace when no further queries can arrive.
+
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::{Capability, Operator};
+use timely::order::PartialOrder;
+use timely::progress::{Antichain, Timestamp};
+
+use crate::{Data, ExchangeData, Hashable};
+use crate::difference::Semigroup;
+use crate::trace::{BatchReader, Cursor, TraceReader};
+use crate::trace::cursor::MyTrait;
+
+use super::Arranged;
+
+/// Responds to `queries` with the accumulated values of their keys in `arranged`.
+///
+/// Each query `(key, query)` is routed to the worker that holds `key`, and answered once `arranged`
+/// is complete through the time of the query. For each value of the key whose updates through that
+/// time accumulate to a non-zero difference, `logic` is called with the query, the key, the value,
+/// and the accumulated difference, and its result is produced at the time of the query. Queries for
+/// keys without such values produce no output.
+pub fn query<G, Tr, Q, D, L>(arranged: &Arranged<G, Tr>, queries: &Stream<G, (Tr::KeyOwned, Q)>, mut logic: L) -> Stream<G, D>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    Tr: TraceReader+Clone+'static,
+    Tr::KeyOwned: ExchangeData+Hashable,
+    Q: ExchangeData,
+    D: Data,
+    L: FnMut(&Q, Tr::Key<'_>, Tr::Val<'_>, &Tr::Diff)->D+'static,
+{
+    let mut trace = Some(arranged.trace.clone());
+    let exchange = Exchange::new(|query: &(Tr::KeyOwned, Q)| query.0.hashed().into());
+
+    queries.binary_frontier(&arranged.stream, exchange, Pipeline, "Query", move |_,_| {
+
+        // unanswered queries, with capabilities for their times.
+        let mut pending: Vec<(Capability<G::Timestamp>, Vec<(Tr::KeyOwned, Q)>)> = Vec::new();
+        // the upper bound of batches received from the arrangement.
+        let mut acknowledged = Antichain::from_elem(<G::Timestamp>::minimum());
+        let mut buffer = Vec::new();
+        let mut batch_buffer = Vec::new();
+
+        move |input1, input2, output| {
+
+            input1.for_each(|capability, data| {
+                data.swap(&mut buffer);
+                let time = capability.time().clone();
+                match pending.iter_mut().find(|(cap, _)| cap.time() == &time) {
+                    Some((_, queries)) => queries.extend(buffer.drain(..)),
+                    None => pending.push((capability.retain(), buffer.drain(..).collect())),
+                }
+            });
+
+            input2.for_each(|_, batches| {
+                batches.swap(&mut batch_buffer);
+                for batch in batch_buffer.drain(..) {
+                    acknowledged.clone_from(batch.upper());
+                }
+            });
+
+            // answer queries whose times the arrangement has passed.
+            let frontier = input2.frontier();
+            let mut index = 0;
+            while index < pending.len() {
+                if frontier.less_equal(pending[index].0.time()) {
+                    index += 1;
+                    continue;
+                }
+                let (capability, mut queries) = pending.swap_remove(index);
+                let time = capability.time();
+                let mut session = output.session(&capability);
+                queries.sort_by(|x, y| x.0.cmp(&y.0));
+                let trace = trace.as_mut().expect("trace released with pending queries");
+                let (mut cursor, storage) = match (queries.first(), queries.last()) {
+                    (Some((lower, _)), Some((upper, _))) => trace.cursor_for_keys(MyTrait::borrow_as(lower), MyTrait::borrow_as(upper)),
+                    _ => trace.cursor(),
+                };
+                for (key, query) in queries.iter() {
+                    cursor.seek_key(&storage, MyTrait::borrow_as(key));
+                    if cursor.get_key(&storage).map(|k| k.equals(key)).unwrap_or(false) {
+                        while let Some(val) = cursor.get_val(&storage) {
+                            let mut sum: Option<Tr::Diff> = None;
+                            cursor.map_times(&storage, |t, diff| {
+                                if t.less_equal(time) {
+                                    match sum.as_mut() {
+                                        Some(sum) => sum.plus_equals(diff),
+                                        None => sum = Some(diff.clone()),
+                                    }
+                                }
+                            });
+                            if let Some(sum) = sum.filter(|sum| !sum.is_zero()) {
+                                session.give(logic(query, cursor.key(&storage), val, &sum));
+                            }
+                            cursor.step_val(&storage);
+                        }
+                        // Rewind, in case the next query is for the same key.
+                        cursor.rewind_vals(&storage);
+                    }
+                }
+            }
+
+            // Allow the trace to compact up to the times of queries that may yet need answers.
+            if input1.frontier().is_empty() && pending.is_empty() {
+                trace = None;
+            }
+            if let Some(trace) = trace.as_mut() {
+                let mut needed = Antichain::new();
+                for time in input1.frontier().frontier().iter() {
+                    needed.insert(time.clone());
+                }
+                for (capability, _) in pending.iter() {
+                    needed.insert(capability.time().clone());
+                }
+                trace.set_logical_compaction(needed.borrow());
+                trace.set_physical_compaction(acknowledged.borrow());
+            }
+        }
+    })
+}
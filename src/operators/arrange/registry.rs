@@ -0,0 +1,156 @@
+//! A registry that shares arrangements of the same collection.
+//!
+//! Each call to `arrange_by_key` builds a new trace, even for a collection that is already arranged
+//! the same way elsewhere in the dataflow. Dataflows assembled from independently written parts often
+//! arrange the same collection several times, and hold as many copies of its updates. An
+//! `ArrangementRegistry` records the arrangements of one trace type made through it, by the stream of
+//! the arranged collection, and responds to repeated requests with a clone of the recorded arrangement,
+//! which shares its operator, its trace, and its stream of batches.
+//!
+//! The registry is opt-in: only arrangements made through the registry are recorded or shared.
+//! It holds a handle to each trace it records, which prevents the traces from compacting, and so
+//! it should be dropped once the dataflow is built.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::arrange::ArrangementRegistry;
+//!
+//! ::timely::example(|scope| {
+//!
+//!     let mut registry = ArrangementRegistry::new();
+//!
+//!     let edges = scope.new_collection_from(vec![(0u32, 1u32), (1, 2)]).1;
+//!
+//!     // both arrangements share one trace.
+//!     let forward1 = registry.arrange_by_key(&edges);
+//!     let forward2 = registry.arrange_by_key(&edges);
+//!     forward1.join_core(&forward2, |_src, dst1, dst2| Some((*dst1, *dst2)));
+//!     assert_eq!(registry.len(), 1);
+//!
+//!     // release the held traces once the dataflow is built.
+//!     drop(registry);
+//! });
+//! ```
+
+use std::collections::HashMap;
+
+use timely::dataflow::Scope;
+
+use crate::{ExchangeData, Collection, Hashable};
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::{Trace, TraceReader, Batch, Batcher};
+use crate::trace::implementations::{KeySpine, ValSpine};
+
+use super::{Arranged, Arrange, TraceAgent};
+
+/// Identifies an arrangement: the scope address, and the operator and port producing the collection.
+type ArrangementId = (Vec<usize>, usize, usize);
+
+/// Records arrangements into traces of type `Tr` by the collection they arrange, and shares them among
+/// repeated requests.
+pub struct ArrangementRegistry<G, Tr>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    Tr: TraceReader+'static,
+{
+    /// The recorded arrangements.
+    arrangements: HashMap<ArrangementId, Arranged<G, TraceAgent<Tr>>>,
+}
+
+impl<G, Tr> Default for ArrangementRegistry<G, Tr>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    Tr: TraceReader+'static,
+{
+    fn default() -> Self {
+        Self { arrangements: HashMap::new() }
+    }
+}
+
+impl<G, Tr> ArrangementRegistry<G, Tr>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    Tr: TraceReader+'static,
+{
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of distinct arrangements the registry has recorded.
+    pub fn len(&self) -> usize {
+        self.arrangements.len()
+    }
+
+    /// Indicates whether the registry has recorded no arrangements.
+    pub fn is_empty(&self) -> bool {
+        self.arrangements.is_empty()
+    }
+
+    /// Clones the arrangement recorded for `collection`, or records the arrangement made by `arrange`.
+    fn arrange_with<D, R, F>(&mut self, collection: &Collection<G, D, R>, arrange: F) -> Arranged<G, TraceAgent<Tr>>
+    where
+        D: ExchangeData,
+        R: Semigroup,
+        F: FnOnce() -> Arranged<G, TraceAgent<Tr>>,
+    {
+        let source = collection.inner.name();
+        let id = (collection.scope().addr().to_vec(), source.node, source.port);
+        self.arrangements
+            .entry(id)
+            .or_insert_with(arrange)
+            .clone()
+    }
+}
+
+impl<G, Tr> ArrangementRegistry<G, Tr>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    G::Timestamp: Lattice+Ord,
+    Tr: Trace+'static,
+    Tr::Batch: Batch,
+{
+    /// Arranges `collection` into a trace of type `Tr`, or shares the arrangement recorded for it.
+    ///
+    /// The first request for a collection arranges it with `arrange_named` and records the arrangement.
+    /// Further requests return clones of the recorded arrangement, and add no operators to the dataflow.
+    pub fn arrange_named<K, V, R>(&mut self, collection: &Collection<G, (K, V), R>, name: &str) -> Arranged<G, TraceAgent<Tr>>
+    where
+        K: ExchangeData+Hashable,
+        V: ExchangeData,
+        R: ExchangeData+Semigroup,
+        Tr::Batcher: Batcher<Input=Vec<((K, V), G::Timestamp, R)>>,
+    {
+        self.arrange_with(collection, || collection.arrange_named(name))
+    }
+}
+
+impl<G, K, V, R> ArrangementRegistry<G, ValSpine<K, V, G::Timestamp, R>>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    /// Arranges a collection of `(Key, Val)` records by `Key`, or shares a recorded arrangement.
+    pub fn arrange_by_key(&mut self, collection: &Collection<G, (K, V), R>) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>> {
+        self.arrange_named(collection, "ArrangeByKey")
+    }
+}
+
+impl<G, K, R> ArrangementRegistry<G, KeySpine<K, G::Timestamp, R>>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    R: ExchangeData+Semigroup,
+{
+    /// Arranges a collection of `Key` records by `Key`, or shares a recorded arrangement.
+    pub fn arrange_by_self(&mut self, collection: &Collection<G, K, R>) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R>>> {
+        self.arrange_with(collection, || collection.map(|k| (k, ())).arrange_named("ArrangeBySelf"))
+    }
+}
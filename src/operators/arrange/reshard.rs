@@ -0,0 +1,152 @@
+//! Handing off the contents of arrangements to a differently sized set of workers.
+//!
+//! Arrangements are partitioned among workers by key, and a change in the number of workers changes
+//! the partitioning. Rather than recompute the arrangement from its sources, the current workers can
+//! hand off its contents: `split_at` divides the batches of each worker's trace into contiguous key
+//! ranges, `handoff` collects the ranges of a trace into shards, and the combined handoffs of all
+//! workers are rendered as messages in the CDC format of the `capture` module. Each shard can be
+//! stored or transmitted independently, and replayed by one of the new workers, for example with
+//! `capture::harness::replay` or `capture::source::build`.
+//!
+//! The replayed collection is complete through the upper frontier of the handed off traces, and a new
+//! dataflow continues from there by concatenating it with the updates of its live sources at times
+//! not before that frontier, and arranging the result. As replay routes updates by the arrangement's
+//! key, the number of shards need not match the number of new workers.
+//!
+//! All shards must be replayed, because the progress statements in each shard describe the whole
+//! collection. Progress statements are repeated in each shard, and replaying them more than once is
+//! harmless.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use timely::progress::{Antichain, Timestamp};
+
+use crate::capture::{Message, Progress};
+use crate::consolidation::consolidate_updates;
+use crate::difference::Semigroup;
+use crate::trace::{BatchReader, Cursor, TraceReader};
+use crate::trace::cursor::MyTrait;
+
+/// Splits the updates of `batch` into ranges of keys delimited by `bounds`.
+///
+/// The `bounds` must be sorted. The result contains `bounds.len() + 1` ranges, where the range at
+/// position `i` contains the updates whose keys are at least `bounds[i-1]`, if it exists, and less
+/// than `bounds[i]`, if it exists. The `val` function converts the batch's values to owned values.
+pub fn split_at<B, V, F>(batch: &B, bounds: &[B::KeyOwned], val: F) -> Vec<Vec<((B::KeyOwned, V), B::Time, B::Diff)>>
+where
+    B: BatchReader,
+    F: Fn(B::Val<'_>) -> V,
+    V: Clone,
+{
+    let mut ranges = Vec::with_capacity(bounds.len() + 1);
+    let mut cursor = batch.cursor();
+    for index in 0 ..= bounds.len() {
+        let mut updates = Vec::new();
+        while let Some(key) = cursor.get_key(batch) {
+            if bounds.get(index).map(|bound| !key.less_than(bound)).unwrap_or(false) { break; }
+            while let Some(v) = cursor.get_val(batch) {
+                let v = val(v);
+                cursor.map_times(batch, |time, diff| {
+                    updates.push(((key.into_owned(), v.clone()), time.clone(), diff.clone()));
+                });
+                cursor.step_val(batch);
+            }
+            cursor.step_key(batch);
+        }
+        ranges.push(updates);
+    }
+    ranges
+}
+
+/// The contents of one or more traces, divided into shards by ranges of keys.
+///
+/// Each worker produces a handoff of its trace with `handoff`, and the handoffs of all workers are
+/// combined with `Handoff::merge` before they are rendered as messages with `Handoff::into_messages`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Handoff<D, T, R> {
+    /// The consolidated updates of each range of keys.
+    pub shards: Vec<Vec<(D, T, R)>>,
+    /// The frontier through which the shards are complete.
+    pub upper: Vec<T>,
+}
+
+/// Divides the contents of `trace` into shards, one for each range of keys delimited by `bounds`.
+///
+/// The shards are those of `split_at` applied to each batch of the trace, with their updates
+/// consolidated. Updates are presented at the times the trace holds them, which the logical
+/// compaction of the trace may have advanced.
+pub fn handoff<Tr, V, F>(trace: &mut Tr, bounds: &[Tr::KeyOwned], val: F) -> Handoff<(Tr::KeyOwned, V), Tr::Time, Tr::Diff>
+where
+    Tr: TraceReader,
+    F: Fn(Tr::Val<'_>) -> V,
+    V: Ord+Clone,
+{
+    let mut shards = vec![Vec::new(); bounds.len() + 1];
+    let mut upper = Antichain::from_elem(<Tr::Time as Timestamp>::minimum());
+    trace.map_batches(|batch| {
+        for (shard, updates) in shards.iter_mut().zip(split_at(batch, bounds, |v| val(v))) {
+            shard.extend(updates);
+        }
+        upper.clone_from(batch.upper());
+    });
+    for updates in shards.iter_mut() {
+        consolidate_updates(updates);
+    }
+    Handoff { shards, upper: upper.elements().to_vec() }
+}
+
+impl<D: Ord, T: Timestamp, R: Semigroup> Handoff<D, T, R> {
+    /// Combines the handoffs of several traces, which must use the same key ranges.
+    ///
+    /// The result is complete through the earliest of the frontiers of the handoffs, and updates at
+    /// later times are discarded. The sources that continue the replayed collection must provide the
+    /// updates at those times.
+    pub fn merge<I: IntoIterator<Item=Self>>(handoffs: I) -> Self {
+        let mut shards: Vec<Vec<(D, T, R)>> = Vec::new();
+        // Each handoff is complete through its own frontier, and the result through their meet.
+        let mut upper = Antichain::new();
+        for handoff in handoffs {
+            if shards.is_empty() {
+                shards.resize_with(handoff.shards.len(), Vec::new);
+            }
+            assert_eq!(shards.len(), handoff.shards.len(), "handoffs with different numbers of shards");
+            for (shard, updates) in shards.iter_mut().zip(handoff.shards) {
+                shard.extend(updates);
+            }
+            upper.extend(handoff.upper);
+        }
+        for updates in shards.iter_mut() {
+            updates.retain(|(_, time, _)| !upper.less_equal(time));
+            consolidate_updates(updates);
+        }
+        Handoff { shards, upper: upper.elements().to_vec() }
+    }
+
+    /// Renders the shards as CDC messages, to be replayed by the new workers.
+    ///
+    /// Each shard is rendered as one `Updates` message, if it has updates, followed by a `Progress`
+    /// message describing the updates of all shards through the frontier of the handoff.
+    pub fn into_messages(self) -> Vec<Vec<Message<D, T, R>>> {
+        let mut counts = BTreeMap::new();
+        for (_, time, _) in self.shards.iter().flatten() {
+            *counts.entry(time.clone()).or_insert(0) += 1;
+        }
+        let progress = Progress {
+            lower: vec![T::minimum()],
+            upper: self.upper,
+            counts: counts.into_iter().collect(),
+        };
+        self.shards
+            .into_iter()
+            .map(|updates| {
+                let mut messages = Vec::with_capacity(2);
+                if !updates.is_empty() {
+                    messages.push(Message::Updates(updates));
+                }
+                messages.push(Message::Progress(progress.clone()));
+                messages
+            })
+            .collect()
+    }
+}
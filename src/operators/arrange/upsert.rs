@@ -168,8 +168,17 @@ where
             if let Some(exert_logic) = stream.scope().config().get::<trace::ExertionLogic>("differential/default_exert_logic").cloned() {
                 empty_trace.set_exert_logic(exert_logic);
             }
+            if let Some(logic) = stream.scope().config().get::<trace::MemoryPressureLogic>("differential/memory_pressure_logic").cloned() {
+                empty_trace.set_memory_pressure_logic(logic);
+            }
+
+            let merge_scheduler = stream.scope().config().get::<std::sync::Arc<trace::scheduler::MergeScheduler>>("differential/merge_scheduler").cloned();
+            let retry_activator = stream.scope().activator_for(&info.address[..]);
 
             let (mut reader_local, mut writer) = TraceAgent::new(empty_trace, info, logger);
+            if let Some(clock) = stream.scope().config().get::<std::sync::Arc<dyn trace::clock::Clock>>("differential/clock").cloned() {
+                reader_local.set_clock(clock);
+            }
             // Capture the reader outside the builder scope.
             *reader = Some(reader_local.clone());
 
@@ -327,6 +336,10 @@ where
                 }
 
                 writer.exert();
+                // Traces denied effort by a shared merge budget retry once the budget is renewed.
+                if let Some(delay) = merge_scheduler.as_ref().and_then(|scheduler| scheduler.retry_after()) {
+                    retry_activator.activate_after(delay);
+                }
             }
         })
     };
@@ -72,6 +72,11 @@ where
 
         self.upper.clone_from(batch.upper());
 
+        // a released trace and its listeners accept no further batches.
+        if self.trace.upgrade().map(|trace| trace.borrow().released).unwrap_or(false) {
+            return;
+        }
+
         // push information to each listener that still exists.
         let mut borrow = self.queues.borrow_mut();
         for queue in borrow.iter_mut() {
@@ -7,6 +7,7 @@
 //! drop out of, e.g. iterative computations.
 
 use timely::dataflow::Scope;
+use timely::dataflow::channels::pact::{Exchange, ParallelizationContract};
 
 use crate::{Collection, ExchangeData, Hashable};
 use crate::consolidation::ConsolidatingContainerBuilder;
@@ -105,4 +106,186 @@ where
             })
             .as_collection()
     }
+
+    /// Aggregates the weights of equal records, and partitions them among workers by hash range.
+    ///
+    /// Before they are exchanged, updates are consolidated separately for each destination worker,
+    /// across all input batches at the same time, and are sent once a full container accumulates
+    /// for a destination or once the input is complete through their time. They are consolidated
+    /// again as they are received, so that each worker sends fewer and denser messages than a
+    /// record-at-a-time exchange would. Each worker receives the updates whose `hashed()` value
+    /// falls in its range of the hash space, as described by `hash_range_pact`.
+    ///
+    /// Like `consolidate_stream`, this method does not ensure that at most one copy of each
+    /// `(data, time)` pair exists in the results.
+    ///
+    /// Note that the assignment of records to workers differs from the one used by arrangements,
+    /// which will exchange the data again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(1 .. 10u32).1;
+    ///
+    ///     x.concat(&x)
+    ///      .exchange_consolidated()
+    ///      .assert_eq(&x.concat(&x));
+    /// });
+    /// ```
+    pub fn exchange_consolidated(&self) -> Self {
+
+        use std::collections::BTreeMap;
+        use timely::dataflow::channels::pact::Pipeline;
+        use timely::dataflow::operators::{Capability, Operator};
+        use crate::collection::AsCollection;
+        use crate::consolidation::PartitionedConsolidatingBuilder;
+
+        let peers = self.inner.scope().peers();
+
+        self.inner
+            .unary_frontier(Pipeline, "ConsolidatePerWorker", move |_cap, _info| {
+
+                // Updates at times the input may not be complete through, consolidated per destination.
+                let mut pending: BTreeMap<G::Timestamp, (Capability<G::Timestamp>, PartitionedConsolidatingBuilder<Vec<(D, G::Timestamp, R)>>)> = BTreeMap::new();
+                let mut vector = Vec::new();
+                move |input, output| {
+                    input.for_each(|time, data| {
+                        data.swap(&mut vector);
+                        let (capability, builder) = pending
+                            .entry(time.time().clone())
+                            .or_insert_with(|| (time.retain(), PartitionedConsolidatingBuilder::new(peers)));
+                        for update in vector.drain(..) {
+                            let part = hash_range(update.0.hashed().into(), peers) as usize;
+                            builder.push(part, update);
+                        }
+                        let mut session = output.session(&*capability);
+                        while let Some((_part, container)) = builder.extract() {
+                            session.give_container(container);
+                        }
+                    });
+
+                    let frontier = input.frontier();
+                    pending.retain(|time, (capability, builder)| {
+                        if frontier.less_equal(time) { return true; }
+                        let mut session = output.session(&*capability);
+                        while let Some((_part, container)) = builder.finish() {
+                            session.give_container(container);
+                        }
+                        false
+                    });
+                }
+            })
+            .unary::<ConsolidatingContainerBuilder<_>, _, _, _>(hash_range_pact(peers), "ExchangeConsolidated", |_cap, _info| {
+
+                let mut vector = Vec::new();
+                move |input, output| {
+                    input.for_each(|time, data| {
+                        data.swap(&mut vector);
+                        output.session_with_builder(&time).give_container(&mut vector);
+                    })
+                }
+            })
+            .as_collection()
+    }
+
+    /// Withholds the updates at each time until the time is complete, and then emits them consolidated.
+    ///
+    /// The updates are partitioned among workers by the `hashed()` value of their data, and each worker
+    /// emits the accumulated changes at a time only once its input frontier has passed the time. The
+    /// result holds at most one copy of each `(data, time)` pair, and no intermediate updates that cancel
+    /// before the time completes. This suits sinks that should observe only the final changes at each
+    /// time, at the cost of the latency of waiting for times to complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(1 .. 10u32).1;
+    ///
+    ///     x.negate()
+    ///      .concat(&x)
+    ///      .suppress_updates_until_closed()
+    ///      .inspect(|_| panic!("no updates should be emitted"));
+    /// });
+    /// ```
+    pub fn suppress_updates_until_closed(&self) -> Self {
+        let exchange = Exchange::new(|update: &(D, G::Timestamp, R)| update.0.hashed().into());
+        consolidate_until_closed(self, exchange, "SuppressUntilClosed")
+    }
+}
+
+/// Withholds the updates at each time until the time is complete, and then emits them consolidated.
+///
+/// Updates are consolidated among those each worker receives through `pact`.
+pub(crate) fn consolidate_until_closed<G, D, R, P>(collection: &Collection<G, D, R>, pact: P, name: &str) -> Collection<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: Data,
+    D: Data,
+    R: Semigroup,
+    P: ParallelizationContract<G::Timestamp, Vec<(D, G::Timestamp, R)>>,
+{
+    use std::collections::BTreeMap;
+    use timely::dataflow::operators::{Capability, Operator};
+    use crate::collection::AsCollection;
+    use crate::consolidation::consolidate_updates;
+
+    collection
+        .inner
+        .unary_frontier(pact, name, |_cap, _info| {
+
+            // Updates by their times, which the input may not be complete through.
+            let mut pending: BTreeMap<G::Timestamp, (Capability<G::Timestamp>, Vec<(D, G::Timestamp, R)>)> = BTreeMap::new();
+            let mut vector = Vec::new();
+            move |input, output| {
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    // Updates may be at times later than the capability of their message.
+                    for update in vector.drain(..) {
+                        let (_capability, updates) = pending
+                            .entry(update.1.clone())
+                            .or_insert_with(|| (time.delayed(&update.1), Vec::new()));
+                        updates.push(update);
+                    }
+                });
+
+                let frontier = input.frontier();
+                pending.retain(|time, (capability, updates)| {
+                    if frontier.less_equal(time) { return true; }
+                    consolidate_updates(updates);
+                    if !updates.is_empty() {
+                        output.session(&*capability).give_container(updates);
+                    }
+                    false
+                });
+            }
+        })
+        .as_collection()
+}
+
+/// An exchange pact that routes updates to workers by contiguous ranges of their hash.
+///
+/// The hash space is divided into `peers` ranges of equal size, and updates whose data hash into
+/// the `i`th range are sent to worker `i`. Records with nearby hashes are sent to the same worker,
+/// which keeps the data destined for each worker in few contiguous runs once sorted by hash.
+pub fn hash_range_pact<D, T, R>(peers: usize) -> Exchange<(D, T, R), impl FnMut(&(D, T, R))->u64+'static>
+where
+    D: Hashable+'static,
+    T: 'static,
+    R: 'static,
+{
+    Exchange::new(move |update: &(D, T, R)| hash_range(update.0.hashed().into(), peers))
+}
+
+/// The index of the range among `peers` equal ranges of the hash space that contains `hash`.
+#[inline]
+fn hash_range(hash: u64, peers: usize) -> u64 {
+    ((hash as u128 * peers as u128) >> 64) as u64
 }
@@ -4,15 +4,71 @@ use timely::order::TotalOrder;
 use timely::dataflow::*;
 use timely::dataflow::operators::Operator;
 use timely::dataflow::channels::pact::Pipeline;
+use timely::progress::{PathSummary, Timestamp};
 
 use crate::lattice::Lattice;
-use crate::{ExchangeData, Collection};
+use crate::{Data, ExchangeData, Collection};
 use crate::difference::Semigroup;
 use crate::hashable::Hashable;
 use crate::collection::AsCollection;
 use crate::operators::arrange::{Arranged, ArrangeBySelf};
 use crate::trace::{BatchReader, Cursor, TraceReader};
 
+/// Diagnostic methods for keyed collections.
+impl<G, K, V, R> Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: TotalOrder+Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: Data,
+    R: Semigroup,
+{
+    /// Reports the number of updates to each key in the most recent `window` of time.
+    ///
+    /// At each time `t`, the output contains `(key, count)` for each key with `count` updates at
+    /// times greater than `t - window` and less or equal to `t`. Each update counts once, whatever
+    /// its difference. Keys that receive many updates, and may overload their worker, can be
+    /// found by filtering the output for large counts.
+    ///
+    /// Each update is counted at its time, and uncounted `window` later. Updates whose time `window`
+    /// cannot advance (overflowing the timestamp) are never uncounted. The counts are maintained in an
+    /// arrangement of keys only, which is compacted as its input completes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::AsCollection;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///     // report keys with more than two updates in ten units of time.
+    ///     vec![((0u32, 'a'), 0u64, 1isize), ((0, 'b'), 2, 1), ((0, 'a'), 4, -1), ((1, 'a'), 4, 1)]
+    ///         .to_stream(scope)
+    ///         .as_collection()
+    ///         .change_stats(10)
+    ///         .filter(|(_key, count)| *count > 2)
+    ///         .inner
+    ///         .capture()
+    /// });
+    ///
+    /// // key zero has three updates from time four, until the first of them leaves the window at time ten.
+    /// let mut output = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    /// output.sort();
+    /// assert_eq!(output, vec![((0, 3), 4, 1), ((0, 3), 10, -1)]);
+    /// ```
+    pub fn change_stats(&self, window: <G::Timestamp as Timestamp>::Summary) -> Collection<G, (K, usize), isize> {
+        use timely::dataflow::operators::Map;
+        self.inner
+            .flat_map(move |((key, _val), time, _diff)| {
+                let expiry = window.results_in(&time);
+                Some((key.clone(), time, 1isize)).into_iter().chain(expiry.map(|expiry| (key, expiry, -1isize)))
+            })
+            .as_collection()
+            .count_total_into(|count: &isize| *count as usize)
+    }
+}
+
 /// Extension trait for the `count` differential dataflow method.
 pub trait CountTotal<G: Scope, K: ExchangeData, R: Semigroup> where G::Timestamp: TotalOrder+Lattice+Ord {
     /// Counts the number of occurrences of each element.
@@ -39,14 +95,48 @@ pub trait CountTotal<G: Scope, K: ExchangeData, R: Semigroup> where G::Timestamp
     /// This method allows `count_total` to produce collections whose difference
     /// type is something other than an `isize` integer, for example perhaps an
     /// `i32`.
-    fn count_total_core<R2: Semigroup + From<i8>>(&self) -> Collection<G, (K, R), R2>;
+    fn count_total_core<R2: Semigroup + From<i8>>(&self) -> Collection<G, (K, R), R2> {
+        self.count_total_into(|count| count.clone())
+    }
+
+    /// Count whose reported counts and output differences are chosen independently of `R`.
+    ///
+    /// The `count` function converts the accumulated difference of each element to the reported count,
+    /// for example converting a rich difference type to an `isize`. Changes to the accumulation that do
+    /// not change the reported count produce no output. The output difference `R2` need only express
+    /// insertion and retraction, by way of `From<i8>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::CountTotal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report whether each key occurs more than twice, with `i32` differences.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| x / 3)
+    ///          .count_total_into::<_, i32, _>(|count| *count > 2);
+    /// });
+    /// ```
+    fn count_total_into<C, R2, F>(&self, count: F) -> Collection<G, (K, C), R2>
+    where
+        C: Data,
+        R2: Semigroup + From<i8>,
+        F: FnMut(&R) -> C + 'static,
+        ;
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, R: ExchangeData+Semigroup> CountTotal<G, K, R> for Collection<G, K, R>
 where G::Timestamp: TotalOrder+Lattice+Ord {
-    fn count_total_core<R2: Semigroup + From<i8>>(&self) -> Collection<G, (K, R), R2> {
+    fn count_total_into<C, R2, F>(&self, count: F) -> Collection<G, (K, C), R2>
+    where
+        C: Data,
+        R2: Semigroup + From<i8>,
+        F: FnMut(&R) -> C + 'static,
+    {
         self.arrange_by_self_named("Arrange: CountTotal")
-            .count_total_core()
+            .count_total_into(count)
     }
 }
 
@@ -58,7 +148,12 @@ where
     T1::Time: TotalOrder,
     T1::Diff: ExchangeData,
 {
-    fn count_total_core<R2: Semigroup + From<i8>>(&self) -> Collection<G, (T1::KeyOwned, T1::Diff), R2> {
+    fn count_total_into<C, R2, F>(&self, mut reported: F) -> Collection<G, (T1::KeyOwned, C), R2>
+    where
+        C: Data,
+        R2: Semigroup + From<i8>,
+        F: FnMut(&T1::Diff) -> C + 'static,
+    {
 
         let mut trace = self.trace.clone();
         let mut buffer = Vec::new();
@@ -90,19 +185,23 @@ where
                                 });
                             }
 
+                            // The reported count for the accumulation so far, absent if it is zero.
+                            let mut current = count.as_ref().filter(|c| !c.is_zero()).map(&mut reported);
+
                             batch_cursor.map_times(&batch, |time, diff| {
 
-                                if let Some(count) = count.as_ref() {
-                                    if !count.is_zero() {
-                                        session.give(((key.into_owned(), count.clone()), time.clone(), R2::from(-1i8)));
-                                    }
-                                }
                                 count.as_mut().map(|c| c.plus_equals(diff));
                                 if count.is_none() { count = Some(diff.clone()); }
-                                if let Some(count) = count.as_ref() {
-                                    if !count.is_zero() {
-                                        session.give(((key.into_owned(), count.clone()), time.clone(), R2::from(1i8)));
+                                let next = count.as_ref().filter(|c| !c.is_zero()).map(&mut reported);
+
+                                if next != current {
+                                    if let Some(old) = current.take() {
+                                        session.give(((key.into_owned(), old), time.clone(), R2::from(-1i8)));
+                                    }
+                                    if let Some(new) = next.as_ref() {
+                                        session.give(((key.into_owned(), new.clone()), time.clone(), R2::from(1i8)));
                                     }
+                                    current = next;
                                 }
                             });
 
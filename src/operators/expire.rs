@@ -0,0 +1,221 @@
+//! Retract the values of keys that have not been updated for some time.
+//!
+//! Long-running streaming computations often accumulate state that is never explicitly retracted,
+//! for example the most recent state of sessions that have since been abandoned. The `expire_after`
+//! operator retracts the values of each key once a time-to-live has passed since the most recent update
+//! to the key. Arranging its output, rather than its input, allows the arrangement to reclaim the state
+//! of stale keys as compaction consolidates them away. The `arrange_expiring` operator does this, and
+//! also reports the keys as they expire.
+//!
+//! Updates are applied in the order of their times, once the input frontier has passed them, and so
+//! the time-to-live of a key is measured from the time of its most recent update, however early or
+//! late the update is received. The operator is only implemented for totally ordered times, for which
+//! "most recent" is well-defined.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use timely::order::TotalOrder;
+use timely::dataflow::*;
+use timely::dataflow::operators::{Capability, Operator, Map};
+use timely::dataflow::channels::pact::Exchange;
+use timely::progress::{PathSummary, Timestamp};
+
+use crate::lattice::Lattice;
+use crate::{ExchangeData, Collection};
+use crate::difference::Abelian;
+use crate::hashable::Hashable;
+use crate::collection::AsCollection;
+use crate::consolidation::consolidate;
+use crate::operators::arrange::{Arranged, ArrangeByKey, TraceAgent};
+use crate::trace::implementations::ValSpine;
+
+/// An arrangement by key from which keys are retracted as they expire, and the keys that expire.
+pub struct ExpiringArrangement<G: Scope, K: ExchangeData, V: ExchangeData, R: ExchangeData+Abelian>
+where
+    G::Timestamp: Lattice+Ord,
+{
+    /// The arrangement of the collection, less the values of expired keys.
+    pub arranged: Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>,
+    /// Each expired key, once at the time it expires.
+    pub expired: Collection<G, K, isize>,
+}
+
+/// Extension trait for the `expire_after` differential dataflow method.
+pub trait ExpireAfter<G: Scope, K: ExchangeData, V: ExchangeData, R: ExchangeData+Abelian> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Retracts the values of each key once `ttl` has elapsed since the most recent update to the key.
+    ///
+    /// Updates are passed through unchanged. At the time that `ttl` advances the most recent update
+    /// to a key, the accumulated values of the key are retracted, and the operator releases its state
+    /// for the key. Keys whose most recent update has a time `ttl` cannot advance (overflowing the
+    /// timestamp) never expire.
+    ///
+    /// An expired key is forgotten: later updates to it start again from an empty accumulation.
+    /// In particular, a later retraction of a value of an expired key is passed through, and results
+    /// in a negative accumulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::ExpireAfter;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // sessions expire ten ticks after their last activity.
+    ///     scope.new_collection_from(vec![(0, "alice".to_string()), (1, "bob".to_string())]).1
+    ///          .expire_after(10)
+    ///          .arrange_by_key();
+    /// });
+    /// ```
+    fn expire_after(&self, ttl: <G::Timestamp as Timestamp>::Summary) -> Collection<G, (K, V), R>;
+
+    /// As `expire_after`, but arranges the result by key, and reports the keys as they expire.
+    ///
+    /// The arrangement holds the values of live keys, as for `expire_after(ttl).arrange_by_key()`. The
+    /// collection of expired keys contains each key once, at the time it expires, and lets applications
+    /// react to expiration, for example by reporting the end of a session, rather than lose the expired
+    /// state silently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::AsCollection;
+    /// use differential_dataflow::operators::ExpireAfter;
+    ///
+    /// let ended = ::timely::example(|scope| {
+    ///     // sessions of two users, active at times zero and five.
+    ///     vec![((0u32, 1u32), 0u64, 1isize), ((1, 1), 5, 1)]
+    ///         .to_stream(scope)
+    ///         .as_collection()
+    ///         .arrange_expiring(10)
+    ///         .expired
+    ///         .inner
+    ///         .capture()
+    /// });
+    ///
+    /// // each session ends ten ticks after its last activity.
+    /// assert_eq!(ended.extract(), vec![(10, vec![(0, 10, 1)]), (15, vec![(1, 15, 1)])]);
+    /// ```
+    fn arrange_expiring(&self, ttl: <G::Timestamp as Timestamp>::Summary) -> ExpiringArrangement<G, K, V, R>;
+}
+
+impl<G: Scope, K: ExchangeData+Hashable, V: ExchangeData, R: ExchangeData+Abelian> ExpireAfter<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G::Timestamp: TotalOrder+Lattice+Ord,
+{
+    fn expire_after(&self, ttl: <G::Timestamp as Timestamp>::Summary) -> Collection<G, (K, V), R> {
+        retract_expired(self, &expirations(self, ttl))
+    }
+
+    fn arrange_expiring(&self, ttl: <G::Timestamp as Timestamp>::Summary) -> ExpiringArrangement<G, K, V, R> {
+        let expirations = expirations(self, ttl);
+        ExpiringArrangement {
+            arranged: retract_expired(self, &expirations).arrange_by_key_named("ArrangeExpiring"),
+            expired: expirations.map(|(key, time, _values)| (key, time, 1)).as_collection(),
+        }
+    }
+}
+
+/// Retracts from `collection` the values of keys as `expirations` reports them.
+fn retract_expired<G, K, V, R>(collection: &Collection<G, (K, V), R>, expirations: &Stream<G, (K, G::Timestamp, Vec<(V, R)>)>) -> Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData,
+    V: ExchangeData,
+    R: ExchangeData+Abelian,
+{
+    let retractions = expirations
+        .flat_map(|(key, time, values)| values.into_iter().map(move |(val, diff)| ((key.clone(), val), time.clone(), diff.negate())))
+        .as_collection();
+    collection.concat(&retractions)
+}
+
+/// Reports each key of `collection` as it expires, with the time it expires and its accumulated values.
+fn expirations<G, K, V, R>(collection: &Collection<G, (K, V), R>, ttl: <G::Timestamp as Timestamp>::Summary) -> Stream<G, (K, G::Timestamp, Vec<(V, R)>)>
+where
+    G: Scope,
+    G::Timestamp: TotalOrder+Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Abelian,
+{
+    let exchange = Exchange::new(|update: &((K, V), G::Timestamp, R)| (update.0).0.hashed().into());
+
+    collection.inner
+        .unary_frontier(exchange, "ExpireAfter", move |_,_| {
+
+            // received updates not yet applied, by their times.
+            let mut pending: BTreeMap<G::Timestamp, Vec<(K, V, R)>> = BTreeMap::new();
+            // the deadline and accumulated values of each live key.
+            let mut keys: BTreeMap<K, (Option<G::Timestamp>, Vec<(V, R)>)> = BTreeMap::new();
+            // the keys that expire at each time; each live key appears at most once.
+            let mut deadlines: BTreeMap<G::Timestamp, BTreeSet<K>> = BTreeMap::new();
+            // a capability for the earliest pending update or deadline, if any.
+            let mut capability: Option<Capability<G::Timestamp>> = None;
+            let mut buffer = Vec::new();
+
+            move |input, output| {
+
+                input.for_each(|cap, data| {
+                    data.swap(&mut buffer);
+                    for ((key, val), time, diff) in buffer.drain(..) {
+                        if capability.as_ref().map(|c| c.time() > &time).unwrap_or(true) {
+                            capability = Some(cap.delayed(&time));
+                        }
+                        pending.entry(time).or_insert_with(Vec::new).push((key, val, diff));
+                    }
+                });
+
+                // apply updates and expire keys in the order of their times, through the input frontier.
+                let frontier = input.frontier();
+                loop {
+                    let update = pending.keys().next().filter(|time| !frontier.less_equal(time)).cloned();
+                    let deadline = deadlines.keys().next().filter(|time| !frontier.less_equal(time)).cloned();
+                    match (update, deadline) {
+                        // keys expire before updates at the same time are applied.
+                        (update, Some(deadline)) if update.as_ref().map(|time| deadline <= *time).unwrap_or(true) => {
+                            let capability = capability.as_ref().expect("pending deadlines without a capability").delayed(&deadline);
+                            let mut session = output.session(&capability);
+                            for key in deadlines.remove(&deadline).unwrap() {
+                                let (_, values) = keys.remove(&key).expect("deadline for a key without state");
+                                session.give((key, deadline.clone(), values));
+                            }
+                        },
+                        (Some(time), _) => {
+                            let mut touched = BTreeSet::new();
+                            for (key, val, diff) in pending.remove(&time).unwrap() {
+                                keys.entry(key.clone()).or_insert_with(|| (None, Vec::new())).1.push((val, diff));
+                                touched.insert(key);
+                            }
+                            for key in touched {
+                                let (deadline, values) = keys.get_mut(&key).unwrap();
+                                if let Some(deadline) = deadline.take() {
+                                    let expiring = deadlines.get_mut(&deadline).unwrap();
+                                    expiring.remove(&key);
+                                    if expiring.is_empty() { deadlines.remove(&deadline); }
+                                }
+                                consolidate(values);
+                                if values.is_empty() {
+                                    keys.remove(&key);
+                                }
+                                else if let Some(expiry) = ttl.results_in(&time) {
+                                    *deadline = Some(expiry.clone());
+                                    deadlines.entry(expiry).or_insert_with(BTreeSet::new).insert(key);
+                                }
+                            }
+                        },
+                        (None, None) => break,
+                    }
+                }
+
+                // hold a capability only for the earliest remaining update or deadline.
+                match pending.keys().next().into_iter().chain(deadlines.keys().next()).min() {
+                    Some(time) => { capability.as_mut().unwrap().downgrade(time); },
+                    None => { capability = None; },
+                }
+            }
+        })
+}
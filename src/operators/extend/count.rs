@@ -1,9 +1,9 @@
 use timely::dataflow::Scope;
 
-use differential_dataflow::{ExchangeData, Collection, Hashable};
-use differential_dataflow::difference::{Monoid, Multiply};
-use differential_dataflow::operators::arrange::Arranged;
-use differential_dataflow::trace::TraceReader;
+use crate::{ExchangeData, Collection, Hashable};
+use crate::difference::{Monoid, Multiply};
+use crate::operators::arrange::Arranged;
+use crate::trace::TraceReader;
 
 /// Reports a number of extensions to a stream of prefixes.
 ///
@@ -25,7 +25,7 @@ where
     F: Fn(&P)->Tr::KeyOwned+Clone+'static,
     P: ExchangeData,
 {
-    crate::operators::lookup_map(
+    super::lookup_map(
         prefixes,
         arrangement,
         move |p: &(P,usize,usize), k: &mut Tr::KeyOwned| { *k = key_selector(&p.0); },
@@ -0,0 +1,22 @@
+//! Operators that extend prefixes, the building blocks of worst-case optimal joins.
+//!
+//! A worst-case optimal join extends a collection of prefixes, for example partial matches of a
+//! query, with one attribute at a time. Each relation that constrains the new attribute offers an
+//! arrangement, and for each prefix
+//!
+//! 1. `count` reports the number of extensions each relation would propose, retaining the least,
+//! 2. `propose` produces the extensions of the relation that proposes the fewest, and
+//! 3. `validate` retains the extensions that each other relation also contains.
+//!
+//! Each operator looks up the prefix in an arrangement at the time of the prefix, by way of the
+//! more general `lookup_map`. Together with `half_join`, the operators support delta query plans.
+
+mod lookup_map;
+mod count;
+mod propose;
+mod validate;
+
+pub use self::lookup_map::lookup_map;
+pub use self::count::count;
+pub use self::propose::{propose, propose_distinct};
+pub use self::validate::validate;
@@ -1,9 +1,9 @@
 use timely::dataflow::Scope;
 
-use differential_dataflow::{ExchangeData, Collection, Hashable};
-use differential_dataflow::difference::{Monoid, Multiply};
-use differential_dataflow::operators::arrange::Arranged;
-use differential_dataflow::trace::TraceReader;
+use crate::{ExchangeData, Collection, Hashable};
+use crate::difference::{Monoid, Multiply};
+use crate::operators::arrange::Arranged;
+use crate::trace::TraceReader;
 
 /// Proposes extensions to a prefix stream.
 ///
@@ -29,7 +29,7 @@ where
     V: Clone + 'static,
     VF: Fn(Tr::Val<'_>) -> V + 'static,
 {
-    crate::operators::lookup_map(
+    super::lookup_map(
         prefixes,
         arrangement,
         move |p: &P, k: &mut Tr::KeyOwned | { *k = key_selector(p); },
@@ -61,7 +61,7 @@ where
     V: Clone + 'static,
     VF: Fn(Tr::Val<'_>) -> V + 'static,
 {
-    crate::operators::lookup_map(
+    super::lookup_map(
         prefixes,
         arrangement,
         move |p: &P, k: &mut Tr::KeyOwned| { *k = key_selector(p); },
@@ -2,16 +2,16 @@ use std::hash::Hash;
 
 use timely::dataflow::Scope;
 
-use differential_dataflow::{ExchangeData, Collection};
-use differential_dataflow::difference::{Monoid, Multiply};
-use differential_dataflow::operators::arrange::Arranged;
-use differential_dataflow::trace::TraceReader;
+use crate::{ExchangeData, Collection};
+use crate::difference::{Monoid, Multiply};
+use crate::operators::arrange::Arranged;
+use crate::trace::TraceReader;
 
-/// Proposes extensions to a stream of prefixes.
+/// Validates extensions to a stream of prefixes.
 ///
-/// This method takes a stream of prefixes and for each determines a
-/// key with `key_selector` and then proposes all pair af the prefix
-/// and values associated with the key in `arrangement`.
+/// This method takes a stream of `(prefix, value)` extensions and for each
+/// determines a key with `key_selector`, and retains the extension if the
+/// pair of the key and the value is present in `arrangement`.
 pub fn validate<G, K, V, Tr, F, P>(
     extensions: &Collection<G, (P, V), Tr::Diff>,
     arrangement: Arranged<G, Tr>,
@@ -26,7 +26,7 @@ where
     F: Fn(&P)->K+Clone+'static,
     P: ExchangeData,
 {
-    crate::operators::lookup_map(
+    super::lookup_map(
         extensions,
         arrangement,
         move |(pre,val),key| { *key = (key_selector(pre), val.clone()); },
@@ -0,0 +1,190 @@
+//! Conversions between collections and streams of their full contents.
+//!
+//! A collection is a stream of changes, from which each time's contents can be recovered by
+//! accumulation. Systems that consume or produce whole states, for example a sink that overwrites a
+//! table, instead want the contents themselves. The `integrate` operator produces, for each time at
+//! which a collection changes, a snapshot of its contents at that time. The `differentiate` operator
+//! recovers a collection from a stream of snapshots, by subtracting each snapshot from the next.
+//!
+//! Snapshots are records `(time, contents)` at time `time`, where `contents` are `(data, diff)` pairs.
+//! The operators are only implemented for totally ordered times, for which snapshots form a sequence.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::{Integrate, Differentiate};
+//!
+//! ::timely::example(|scope| {
+//!     let data = scope.new_collection_from(1 .. 10u32).1;
+//!     data.integrate()
+//!         .differentiate()
+//!         .assert_eq(&data);
+//! });
+//! ```
+
+use std::collections::BTreeMap;
+
+use timely::order::TotalOrder;
+use timely::dataflow::*;
+use timely::dataflow::operators::{Capability, Operator};
+use timely::dataflow::channels::pact::Pipeline;
+
+use crate::lattice::Lattice;
+use crate::{Data, ExchangeData, Collection};
+use crate::difference::{Semigroup, Abelian};
+use crate::hashable::Hashable;
+use crate::collection::AsCollection;
+use crate::consolidation::consolidate;
+use crate::operators::arrange::ArrangeBySelf;
+use crate::trace::{BatchReader, Cursor, TraceReader};
+
+/// Extension trait for the `integrate` differential dataflow method.
+pub trait Integrate<G: Scope, D: Data, R: Semigroup> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Produces a snapshot of the contents of the collection at each time it changes.
+    ///
+    /// When the collection is complete through a time at which it has updates, the output contains
+    /// the record `(time, contents)` at that time, where `contents` holds each record of the collection
+    /// with its non-zero accumulated difference. The contents are partitioned among workers by record,
+    /// and each worker produces a snapshot of its part, which may be empty.
+    fn integrate(&self) -> Stream<G, (G::Timestamp, Vec<(D, R)>)>;
+}
+
+impl<G: Scope, D: ExchangeData+Hashable, R: ExchangeData+Semigroup> Integrate<G, D, R> for Collection<G, D, R>
+where
+    G::Timestamp: TotalOrder+Lattice+Ord,
+{
+    fn integrate(&self) -> Stream<G, (G::Timestamp, Vec<(D, R)>)> {
+
+        let arranged = self.arrange_by_self_named("Arrange: Integrate");
+        let mut trace = arranged.trace.clone();
+
+        arranged.stream.unary_frontier(Pipeline, "Integrate", move |_,_| {
+
+            // times with updates, each with a capability to report its snapshot.
+            let mut pending = BTreeMap::new();
+            let mut buffer = Vec::new();
+            let mut history = Vec::new();
+
+            move |input, output| {
+
+                input.for_each(|capability, batches| {
+                    batches.swap(&mut buffer);
+                    for batch in buffer.drain(..) {
+                        let mut cursor = batch.cursor();
+                        while cursor.key_valid(&batch) {
+                            cursor.map_times(&batch, |time, _diff| {
+                                if !pending.contains_key(time) {
+                                    pending.insert(time.clone(), capability.delayed(time));
+                                }
+                            });
+                            cursor.step_key(&batch);
+                        }
+                    }
+                });
+
+                // snapshot times the input frontier has passed, in order.
+                let frontier = input.frontier().frontier();
+                let complete = pending.keys().take_while(|time| !frontier.less_equal(*time)).count();
+                if complete > 0 {
+
+                    let mut snapshots: Vec<(G::Timestamp, Capability<G::Timestamp>, Vec<(D, R)>)> = Vec::with_capacity(complete);
+                    for _ in 0 .. complete {
+                        let time = pending.keys().next().cloned().unwrap();
+                        let capability = pending.remove(&time).unwrap();
+                        snapshots.push((time, capability, Vec::new()));
+                    }
+
+                    // accumulate the updates of each record through each snapshot time.
+                    let (mut cursor, storage) = trace.cursor();
+                    while cursor.key_valid(&storage) {
+                        cursor.map_times(&storage, |time, diff| history.push((time.clone(), diff.clone())));
+                        consolidate(&mut history);
+                        let mut accum: Option<R> = None;
+                        let mut updates = history.drain(..).peekable();
+                        for (time, _, contents) in snapshots.iter_mut() {
+                            while let Some((_, diff)) = updates.next_if(|(t, _)| t.less_equal(time)) {
+                                match &mut accum {
+                                    Some(accum) => accum.plus_equals(&diff),
+                                    None => accum = Some(diff),
+                                }
+                            }
+                            if let Some(accum) = &accum {
+                                if !accum.is_zero() {
+                                    contents.push((cursor.key(&storage).clone(), accum.clone()));
+                                }
+                            }
+                        }
+                        cursor.step_key(&storage);
+                    }
+
+                    for (time, capability, contents) in snapshots {
+                        output.session(&capability).give((time, contents));
+                    }
+                }
+
+                // later snapshots only distinguish times the frontier has not passed.
+                trace.set_logical_compaction(frontier);
+                trace.set_physical_compaction(frontier);
+            }
+        })
+    }
+}
+
+/// Extension trait for the `differentiate` differential dataflow method.
+pub trait Differentiate<G: Scope, D: Data, R: Abelian> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Produces the collection whose contents at each time are the most recent snapshot.
+    ///
+    /// The input contains records `(time, contents)` at time `time`, as produced by `integrate`. When the
+    /// input is complete through a time with snapshots, their contents are combined, and the output
+    /// contains the changes from the prior snapshot to this one. A time without snapshots leaves the
+    /// contents unchanged, whereas an empty snapshot empties them.
+    ///
+    /// Snapshots are differentiated independently at each worker, which should hold the snapshots of
+    /// a fixed part of the collection.
+    fn differentiate(&self) -> Collection<G, D, R>;
+}
+
+impl<G: Scope, D: Data, R: Data+Abelian> Differentiate<G, D, R> for Stream<G, (G::Timestamp, Vec<(D, R)>)>
+where
+    G::Timestamp: TotalOrder+Lattice+Ord,
+{
+    fn differentiate(&self) -> Collection<G, D, R> {
+
+        self.unary_frontier(Pipeline, "Differentiate", move |_,_| {
+
+            // snapshots at times the input may not yet be complete through.
+            let mut pending: BTreeMap<G::Timestamp, (Capability<G::Timestamp>, Vec<(D, R)>)> = BTreeMap::new();
+            // the consolidated contents of the most recent snapshot.
+            let mut previous: Vec<(D, R)> = Vec::new();
+            let mut buffer = Vec::new();
+
+            move |input, output| {
+
+                input.for_each(|capability, data| {
+                    data.swap(&mut buffer);
+                    for (time, contents) in buffer.drain(..) {
+                        pending
+                            .entry(time.clone())
+                            .or_insert_with(|| (capability.delayed(&time), Vec::new()))
+                            .1
+                            .extend(contents);
+                    }
+                });
+
+                let frontier = input.frontier();
+                while let Some(time) = pending.keys().next().cloned() {
+                    if frontier.less_equal(&time) { break; }
+                    let (capability, mut contents) = pending.remove(&time).unwrap();
+                    consolidate(&mut contents);
+                    let mut changes = contents.clone();
+                    changes.extend(previous.drain(..).map(|(data, diff)| (data, diff.negate())));
+                    consolidate(&mut changes);
+                    previous = contents;
+                    output.session(&capability).give_iterator(changes.into_iter().map(|(data, diff)| (data, time.clone(), diff)));
+                }
+            }
+        })
+        .as_collection()
+    }
+}
@@ -21,12 +21,87 @@ use crate::hashable::Hashable;
 use crate::{Data, ExchangeData, Collection};
 use crate::difference::{Semigroup, Abelian, Multiply};
 use crate::lattice::Lattice;
-use crate::operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf};
+use crate::operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf, TraceAgent};
+use crate::trace::implementations::{KeySpine, ValSpine};
 use crate::trace::{BatchReader, Cursor};
 use crate::operators::ValueHistory;
 
 use crate::trace::TraceReader;
 
+/// An input to the `Join` methods, which is arranged by key if it is not already.
+///
+/// Collections of `(key, val)` pairs are arranged by key, and collections of keys are arranged by
+/// themselves, with `()` values. Arrangements are used as they are, so that a join with an arranged
+/// collection reuses its arrangement, without the need to call `join_core` with arranged inputs.
+///
+/// # Examples
+///
+/// ```
+/// use differential_dataflow::input::Input;
+/// use differential_dataflow::operators::Join;
+/// use differential_dataflow::operators::arrange::ArrangeByKey;
+///
+/// ::timely::example(|scope| {
+///
+///     let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+///     let y = scope.new_collection_from(vec![(0, 'a'), (1, 'b')]).1
+///                  .arrange_by_key();
+///     let z = scope.new_collection_from(vec![(0, (1, 'a')), (1, (3, 'b'))]).1;
+///
+///     // only `x` is arranged by the join, which reuses the arrangement of `y`.
+///     x.join(&y)
+///      .assert_eq(&z);
+/// });
+/// ```
+pub trait JoinInput<G: Scope, K, V> {
+    /// The trace of the arranged input.
+    type Trace: for<'a> TraceReader<Key<'a>=&'a K, Val<'a>=&'a V, Time=G::Timestamp>+Clone+'static;
+    /// The input, arranged by key.
+    fn arrange_for_join(&self) -> Arranged<G, Self::Trace>;
+}
+
+/// The difference type of a `JoinInput`.
+pub type JoinDiff<O, G, K, V> = <<O as JoinInput<G, K, V>>::Trace as TraceReader>::Diff;
+
+impl<G, K, V, R> JoinInput<G, K, V> for Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    type Trace = TraceAgent<ValSpine<K, V, G::Timestamp, R>>;
+    fn arrange_for_join(&self) -> Arranged<G, Self::Trace> {
+        self.arrange_by_key()
+    }
+}
+
+impl<G, K, R> JoinInput<G, K, ()> for Collection<G, K, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    R: ExchangeData+Semigroup,
+{
+    type Trace = TraceAgent<KeySpine<K, G::Timestamp, R>>;
+    fn arrange_for_join(&self) -> Arranged<G, Self::Trace> {
+        self.arrange_by_self()
+    }
+}
+
+impl<G, K, V, Tr> JoinInput<G, K, V> for Arranged<G, Tr>
+where
+    G: Scope<Timestamp=Tr::Time>,
+    G::Timestamp: Lattice+Ord,
+    Tr: for<'a> TraceReader<Key<'a>=&'a K, Val<'a>=&'a V>+Clone+'static,
+{
+    type Trace = Tr;
+    fn arrange_for_join(&self) -> Arranged<G, Tr> {
+        self.clone()
+    }
+}
+
 /// Join implementations for `(key,val)` data.
 pub trait Join<G: Scope, K: Data, V: Data, R: Semigroup> {
 
@@ -50,13 +125,13 @@ pub trait Join<G: Scope, K: Data, V: Data, R: Semigroup> {
     ///      .assert_eq(&z);
     /// });
     /// ```
-    fn join<V2, R2>(&self, other: &Collection<G, (K,V2), R2>) -> Collection<G, (K,(V,V2)), <R as Multiply<R2>>::Output>
+    fn join<V2, O>(&self, other: &O) -> Collection<G, (K,(V,V2)), <R as Multiply<JoinDiff<O, G, K, V2>>>::Output>
     where
         K: ExchangeData,
-        V2: ExchangeData,
-        R2: ExchangeData+Semigroup,
-        R: Multiply<R2>,
-        <R as Multiply<R2>>::Output: Semigroup
+        V2: Data,
+        O: JoinInput<G, K, V2>,
+        R: Multiply<JoinDiff<O, G, K, V2>>,
+        <R as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup
     {
         self.join_map(other, |k,v,v2| (k.clone(),(v.clone(),v2.clone())))
     }
@@ -79,8 +154,62 @@ pub trait Join<G: Scope, K: Data, V: Data, R: Semigroup> {
     ///      .assert_eq(&z);
     /// });
     /// ```
-    fn join_map<V2, R2, D, L>(&self, other: &Collection<G, (K,V2), R2>, logic: L) -> Collection<G, D, <R as Multiply<R2>>::Output>
-    where K: ExchangeData, V2: ExchangeData, R2: ExchangeData+Semigroup, R: Multiply<R2>, <R as Multiply<R2>>::Output: Semigroup, D: Data, L: FnMut(&K, &V, &V2)->D+'static;
+    fn join_map<V2, O, D, L>(&self, other: &O, logic: L) -> Collection<G, D, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output>
+    where K: ExchangeData, V2: Data, O: JoinInput<G, K, V2>, R: Multiply<JoinDiff<O, G, K, V2>>, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup, D: Data, L: FnMut(&K, &V, &V2)->D+'static;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` based on `key` and then applies a function that may discard the match.
+    ///
+    /// The function receives references to the matched key and values, and nothing is cloned on its behalf;
+    /// it should clone (or for arrangements, call `into_owned` on) only what it retains, and return `None`
+    /// for matches it discards. This avoids the cost of cloning data for joins that discard most matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Join;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(vec![(0, 1), (1, 3)]).1;
+    ///     let y = scope.new_collection_from(vec![(0, "a".to_string()), (1, "b".to_string())]).1;
+    ///
+    ///     x.join_map_ref(&y, |_key, &a, b| if a > 1 { Some((a, b.clone())) } else { None })
+    ///      .inner
+    ///      .capture()
+    /// });
+    ///
+    /// // only the match with a value greater than one is retained.
+    /// assert_eq!(captured.extract(), vec![(0, vec![((3, "b".to_string()), 0, 1)])]);
+    /// ```
+    fn join_map_ref<V2, O, D, L>(&self, other: &O, logic: L) -> Collection<G, D, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output>
+    where K: ExchangeData, V2: Data, O: JoinInput<G, K, V2>, R: Multiply<JoinDiff<O, G, K, V2>>, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup, D: Data, L: FnMut(&K, &V, &V2)->Option<D>+'static;
+
+    /// Matches pairs `(key,val1)` and `(key,val2)` of the collection with itself, and then applies a function.
+    ///
+    /// The result is that of `join_map` with the collection as both inputs, but the collection is arranged
+    /// only once, and both inputs of the join read from the same trace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Join;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     // pairs of edges with a common source.
+    ///     let edges = scope.new_collection_from(vec![(0, 1), (0, 2)]).1;
+    ///     let z = scope.new_collection_from(vec![(1, 1), (1, 2), (2, 1), (2, 2)]).1;
+    ///
+    ///     edges.self_join(|_src, &a, &b| (a, b))
+    ///          .assert_eq(&z);
+    /// });
+    /// ```
+    fn self_join<D, L>(&self, logic: L) -> Collection<G, D, <R as Multiply<R>>::Output>
+    where K: ExchangeData, R: Multiply<R>, <R as Multiply<R>>::Output: Semigroup, D: Data, L: FnMut(&K, &V, &V)->D+'static;
 
     /// Matches pairs `(key, val)` and `key` based on `key`, producing the former with frequencies multiplied.
     ///
@@ -104,8 +233,8 @@ pub trait Join<G: Scope, K: Data, V: Data, R: Semigroup> {
     ///      .assert_eq(&z);
     /// });
     /// ```
-    fn semijoin<R2>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), <R as Multiply<R2>>::Output>
-    where K: ExchangeData, R2: ExchangeData+Semigroup, R: Multiply<R2>, <R as Multiply<R2>>::Output: Semigroup;
+    fn semijoin<O>(&self, other: &O) -> Collection<G, (K, V), <R as Multiply<JoinDiff<O, G, K, ()>>>::Output>
+    where K: ExchangeData, O: JoinInput<G, K, ()>, R: Multiply<JoinDiff<O, G, K, ()>>, <R as Multiply<JoinDiff<O, G, K, ()>>>::Output: Semigroup;
 
     /// Subtracts the semijoin with `other` from `self`.
     ///
@@ -133,8 +262,8 @@ pub trait Join<G: Scope, K: Data, V: Data, R: Semigroup> {
     ///      .assert_eq(&z);
     /// });
     /// ```
-    fn antijoin<R2>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), R>
-    where K: ExchangeData, R2: ExchangeData+Semigroup, R: Multiply<R2, Output = R>, R: Abelian;
+    fn antijoin<O>(&self, other: &O) -> Collection<G, (K, V), R>
+    where K: ExchangeData, O: JoinInput<G, K, ()>, R: Multiply<JoinDiff<O, G, K, ()>, Output = R>, R: Abelian;
 }
 
 impl<G, K, V, R> Join<G, K, V, R> for Collection<G, (K, V), R>
@@ -145,26 +274,130 @@ where
     R: ExchangeData+Semigroup,
     G::Timestamp: Lattice+Ord,
 {
-    fn join_map<V2: ExchangeData, R2: ExchangeData+Semigroup, D: Data, L>(&self, other: &Collection<G, (K, V2), R2>, mut logic: L) -> Collection<G, D, <R as Multiply<R2>>::Output>
-    where R: Multiply<R2>, <R as Multiply<R2>>::Output: Semigroup, L: FnMut(&K, &V, &V2)->D+'static {
+    fn join_map<V2: Data, O: JoinInput<G, K, V2>, D: Data, L>(&self, other: &O, mut logic: L) -> Collection<G, D, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output>
+    where R: Multiply<JoinDiff<O, G, K, V2>>, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup, L: FnMut(&K, &V, &V2)->D+'static {
         let arranged1 = self.arrange_by_key();
-        let arranged2 = other.arrange_by_key();
+        let arranged2 = other.arrange_for_join();
         arranged1.join_core(&arranged2, move |k,v1,v2| Some(logic(k,v1,v2)))
     }
 
-    fn semijoin<R2: ExchangeData+Semigroup>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), <R as Multiply<R2>>::Output>
-    where R: Multiply<R2>, <R as Multiply<R2>>::Output: Semigroup {
+    fn join_map_ref<V2: Data, O: JoinInput<G, K, V2>, D: Data, L>(&self, other: &O, logic: L) -> Collection<G, D, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output>
+    where R: Multiply<JoinDiff<O, G, K, V2>>, <R as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup, L: FnMut(&K, &V, &V2)->Option<D>+'static {
         let arranged1 = self.arrange_by_key();
-        let arranged2 = other.arrange_by_self();
+        let arranged2 = other.arrange_for_join();
+        arranged1.join_core(&arranged2, logic)
+    }
+
+    fn self_join<D: Data, L>(&self, mut logic: L) -> Collection<G, D, <R as Multiply<R>>::Output>
+    where R: Multiply<R>, <R as Multiply<R>>::Output: Semigroup, L: FnMut(&K, &V, &V)->D+'static {
+        let arranged = self.arrange_by_key();
+        arranged.join_core(&arranged, move |k,v1,v2| Some(logic(k,v1,v2)))
+    }
+
+    fn semijoin<O: JoinInput<G, K, ()>>(&self, other: &O) -> Collection<G, (K, V), <R as Multiply<JoinDiff<O, G, K, ()>>>::Output>
+    where R: Multiply<JoinDiff<O, G, K, ()>>, <R as Multiply<JoinDiff<O, G, K, ()>>>::Output: Semigroup {
+        let arranged1 = self.arrange_by_key();
+        let arranged2 = other.arrange_for_join();
         arranged1.join_core(&arranged2, |k,v,_| Some((k.clone(), v.clone())))
     }
 
-    fn antijoin<R2: ExchangeData+Semigroup>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), R>
-    where R: Multiply<R2, Output=R>, R: Abelian {
+    fn antijoin<O: JoinInput<G, K, ()>>(&self, other: &O) -> Collection<G, (K, V), R>
+    where R: Multiply<JoinDiff<O, G, K, ()>, Output=R>, R: Abelian {
         self.concat(&self.semijoin(other).negate())
     }
 }
 
+impl<G, K, V, R> Collection<G, (K, V), R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+std::hash::Hash,
+    V: ExchangeData+std::hash::Hash,
+    R: ExchangeData+Abelian,
+{
+    /// Matches pairs `(key,val1)` and `(key,val2)`, spreading the work for keys of high degree across workers.
+    ///
+    /// A `join` exchanges records by key, and so all records of a key are joined by one worker. When a few
+    /// keys have many records in `self`, as with the popular vertices of a power-law graph, the workers of
+    /// those keys do most of the work of the join. This method counts the records of each key in `self`,
+    /// and treats keys with at least `threshold` records as heavy: their records in `self` are spread among
+    /// all workers, and their records in `other` are sent to every worker, to meet them. Keys with fewer
+    /// records are joined as by `join`.
+    ///
+    /// The set of heavy keys is sent to every worker, which classifies its records of both inputs without
+    /// exchanging them, and so each worker arranges the records it holds of both inputs, in addition to
+    /// the arrangements of the joins themselves. The method suits inputs where heavy keys are few and have
+    /// few records in `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Join;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     // key `0` has many more records than the others.
+    ///     let x = scope.new_collection_from((0 .. 100).map(|x| (if x < 90 { 0 } else { x }, x))).1;
+    ///     let y = scope.new_collection_from(vec![(0, 'a'), (95, 'b'), (200, 'c')]).1;
+    ///
+    ///     x.join_skewed(&y, 10)
+    ///      .assert_eq(&x.join(&y));
+    /// });
+    /// ```
+    pub fn join_skewed<V2, R2>(&self, other: &Collection<G, (K, V2), R2>, threshold: usize) -> Collection<G, (K, (V, V2)), <R as Multiply<R2>>::Output>
+    where
+        V2: ExchangeData,
+        R2: ExchangeData+Abelian+Multiply<isize, Output=R2>,
+        R: Multiply<isize, Output=R>+Multiply<R2>,
+        <R as Multiply<R2>>::Output: Semigroup,
+    {
+        use timely::dataflow::operators::{Broadcast, Map};
+        use crate::AsCollection;
+        use crate::operators::Threshold;
+        use crate::operators::arrange::arrangement::arrange_core;
+
+        let peers = self.scope().peers() as u64;
+
+        // Keys with at least `threshold` records, whose counts are first accumulated by each worker.
+        let heavy =
+        self.distinct_core::<isize>()
+            .map(|(key, _val)| key)
+            .count_local_then_global()
+            .filter(move |(_key, count)| *count >= threshold as isize)
+            .map(|(key, _count)| (key, ()));
+
+        // The heavy keys, arranged by each worker.
+        let heavy = arrange_core::<_,_,KeySpine<K, G::Timestamp, isize>>(&heavy.inner.broadcast(), Pipeline, "HeavyKeys");
+
+        // The records of each input with heavy keys, and the remaining records, without exchanging them.
+        let local1 = arrange_core::<_,_,ValSpine<K, V, G::Timestamp, R>>(&self.inner, Pipeline, "SkewedLocal");
+        let heavy1 = local1.join_core(&heavy, |key, val, &()| Some((key.clone(), val.clone())));
+        let light1 = self.concat(&heavy1.negate());
+        let local2 = arrange_core::<_,_,ValSpine<K, V2, G::Timestamp, R2>>(&other.inner, Pipeline, "SkewedLocal");
+        let heavy2 = local2.join_core(&heavy, |key, val, &()| Some((key.clone(), val.clone())));
+        let light2 = other.concat(&heavy2.negate());
+
+        // Heavy records of `self` are spread among the workers by their values, and those of `other` sent to each.
+        let heavy1 = heavy1.map(move |(key, val)| {
+            let part = val.hashed() % peers;
+            ((key, part), val)
+        });
+        let heavy2 =
+        heavy2
+            .inner
+            .flat_map(move |((key, val), time, diff)| {
+                (0 .. peers).map(move |part| (((key.clone(), part), val.clone()), time.clone(), diff.clone()))
+            })
+            .as_collection();
+        let heavy = heavy1.join_map(&heavy2, |(key, _part), val1, val2| (key.clone(), (val1.clone(), val2.clone())));
+
+        light1
+            .join(&light2)
+            .concat(&heavy)
+    }
+}
+
 impl<G, K, V, Tr> Join<G, K, V, Tr::Diff> for Arranged<G, Tr>
 where
     G: Scope<Timestamp=Tr::Time>,
@@ -172,24 +405,43 @@ where
     K: ExchangeData+Hashable,
     V: Data + 'static,
 {
-    fn join_map<V2: ExchangeData, R2: ExchangeData+Semigroup, D: Data, L>(&self, other: &Collection<G, (K, V2), R2>, mut logic: L) -> Collection<G, D, <Tr::Diff as Multiply<R2>>::Output>
-    where 
-        Tr::Diff: Multiply<R2>,
-        <Tr::Diff as Multiply<R2>>::Output: Semigroup,
+    fn join_map<V2: Data, O: JoinInput<G, K, V2>, D: Data, L>(&self, other: &O, mut logic: L) -> Collection<G, D, <Tr::Diff as Multiply<JoinDiff<O, G, K, V2>>>::Output>
+    where
+        Tr::Diff: Multiply<JoinDiff<O, G, K, V2>>,
+        <Tr::Diff as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup,
         L: for<'a> FnMut(Tr::Key<'a>, Tr::Val<'a>, &V2)->D+'static,
     {
-        let arranged2 = other.arrange_by_key();
+        let arranged2 = other.arrange_for_join();
         self.join_core(&arranged2, move |k,v1,v2| Some(logic(k,v1,v2)))
     }
 
-    fn semijoin<R2: ExchangeData+Semigroup>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), <Tr::Diff as Multiply<R2>>::Output>
-    where Tr::Diff: Multiply<R2>, <Tr::Diff as Multiply<R2>>::Output: Semigroup {
-        let arranged2 = other.arrange_by_self();
+    fn join_map_ref<V2: Data, O: JoinInput<G, K, V2>, D: Data, L>(&self, other: &O, logic: L) -> Collection<G, D, <Tr::Diff as Multiply<JoinDiff<O, G, K, V2>>>::Output>
+    where
+        Tr::Diff: Multiply<JoinDiff<O, G, K, V2>>,
+        <Tr::Diff as Multiply<JoinDiff<O, G, K, V2>>>::Output: Semigroup,
+        L: for<'a> FnMut(Tr::Key<'a>, Tr::Val<'a>, &V2)->Option<D>+'static,
+    {
+        let arranged2 = other.arrange_for_join();
+        self.join_core(&arranged2, logic)
+    }
+
+    fn self_join<D: Data, L>(&self, mut logic: L) -> Collection<G, D, <Tr::Diff as Multiply<Tr::Diff>>::Output>
+    where
+        Tr::Diff: Multiply<Tr::Diff>,
+        <Tr::Diff as Multiply<Tr::Diff>>::Output: Semigroup,
+        L: for<'a> FnMut(Tr::Key<'a>, Tr::Val<'a>, Tr::Val<'a>)->D+'static,
+    {
+        self.join_core(self, move |k,v1,v2| Some(logic(k,v1,v2)))
+    }
+
+    fn semijoin<O: JoinInput<G, K, ()>>(&self, other: &O) -> Collection<G, (K, V), <Tr::Diff as Multiply<JoinDiff<O, G, K, ()>>>::Output>
+    where Tr::Diff: Multiply<JoinDiff<O, G, K, ()>>, <Tr::Diff as Multiply<JoinDiff<O, G, K, ()>>>::Output: Semigroup {
+        let arranged2 = other.arrange_for_join();
         self.join_core(&arranged2, |k,v,_| Some((k.clone(), v.clone())))
     }
 
-    fn antijoin<R2: ExchangeData+Semigroup>(&self, other: &Collection<G, K, R2>) -> Collection<G, (K, V), Tr::Diff>
-    where Tr::Diff: Multiply<R2, Output=Tr::Diff>, Tr::Diff: Abelian {
+    fn antijoin<O: JoinInput<G, K, ()>>(&self, other: &O) -> Collection<G, (K, V), Tr::Diff>
+    where Tr::Diff: Multiply<JoinDiff<O, G, K, ()>, Output=Tr::Diff>, Tr::Diff: Abelian {
         self.as_collection(|k,v| (k.clone(), v.clone()))
             .concat(&self.semijoin(other).negate())
     }
@@ -386,6 +638,16 @@ where
         let activations = arranged1.stream.scope().activations().clone();
         let activator = Activator::new(&info.address[..], activations);
 
+        // Bounds on the output records to produce for each input, and on the time to spend, before yielding.
+        let join_fuel = arranged1.stream.scope().config().get::<usize>("differential/join_fuel").cloned().unwrap_or(1_000_000);
+        let join_time_slice = arranged1.stream.scope().config().get::<std::time::Duration>("differential/join_time_slice").cloned();
+        let clock = arranged1.stream.scope().config().get::<std::sync::Arc<dyn crate::trace::clock::Clock>>("differential/clock").cloned();
+        let clock = clock.unwrap_or_else(|| std::sync::Arc::new(crate::trace::clock::SystemClock));
+
+        // Acquire a logger for the latencies of completed work.
+        let logger = arranged1.stream.scope().log_register().get::<crate::logging::DifferentialEvent>("differential/arrange");
+        let operator_id = info.global_id;
+
         // Our initial invariants are that for each trace, physical compaction is less or equal the trace's upper bound.
         // These invariants ensure that we can reference observed batch frontiers from `_start_upper` onward, as long as
         // we maintain our physical compaction capabilities appropriately. These assertions are tested as we load up the
@@ -432,20 +694,31 @@ where
         // TODO: in the case that this does not hold, instead "upgrade" the physical compaction frontier.
         assert!(PartialOrder::less_equal(&trace2.get_physical_compaction(), &acknowledged2.borrow()));
 
+        // Set if a trace can no longer provide cursors, for example because it was released. The operator
+        // then drops its traces and deferred work, and discards its inputs, holding no capabilities.
+        let mut shutdown = false;
+
         // Load up deferred work using trace2 cursors and batches captured just above.
         for (batch2_cursor, batch2) in batch2_cursors.into_iter() {
             // It is safe to ask for `ack1` because we have confirmed it to be in advance of `distinguish_since`.
-            let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
-            // We could downgrade the capability here, but doing so is a bit complicated mathematically.
-            // TODO: downgrade the capability by searching out the one time in `batch2.lower()` and not
-            // in `batch2.upper()`. Only necessary for non-empty batches, as empty batches may not have
-            // that property.
-            todo2.push_back(Deferred::new(trace1_cursor, trace1_storage, batch2_cursor, batch2.clone(), capability.clone()));
+            if let Some((trace1_cursor, trace1_storage)) = trace1.cursor_through(acknowledged1.borrow()) {
+                // We could downgrade the capability here, but doing so is a bit complicated mathematically.
+                // TODO: downgrade the capability by searching out the one time in `batch2.lower()` and not
+                // in `batch2.upper()`. Only necessary for non-empty batches, as empty batches may not have
+                // that property.
+                todo2.push_back(Deferred::new(trace1_cursor, trace1_storage, batch2_cursor, batch2.clone(), capability.clone(), &logger, &*clock));
+            }
+            else { shutdown = true; }
         }
 
         // Droppable handles to shared trace data structures.
         let mut trace1_option = Some(trace1);
         let mut trace2_option = Some(trace2);
+        if shutdown {
+            trace1_option = None;
+            trace2_option = None;
+            todo2.clear();
+        }
 
         // Swappable buffers for input extraction.
         let mut input1_buffer = Vec::new();
@@ -478,9 +751,11 @@ where
                             if !batch1.is_empty() {
                                 // It is safe to ask for `ack2` as we validated that it was at least `get_physical_compaction()`
                                 // at start-up, and have held back physical compaction ever since.
-                                let (trace2_cursor, trace2_storage) = trace2.cursor_through(acknowledged2.borrow()).unwrap();
-                                let batch1_cursor = batch1.cursor();
-                                todo1.push_back(Deferred::new(trace2_cursor, trace2_storage, batch1_cursor, batch1.clone(), capability.clone()));
+                                if let Some((trace2_cursor, trace2_storage)) = trace2.cursor_through(acknowledged2.borrow()) {
+                                    let batch1_cursor = batch1.cursor();
+                                    todo1.push_back(Deferred::new(trace2_cursor, trace2_storage, batch1_cursor, batch1.clone(), capability.clone(), &logger, &*clock));
+                                }
+                                else { shutdown = true; }
                             }
 
                             // To update `acknowledged1` we might presume that `batch1.lower` should equal it, but we
@@ -491,7 +766,7 @@ where
                         }
                     }
                 }
-                else { panic!("`trace2_option` dropped before `input1` emptied!"); }
+                else if !shutdown { panic!("`trace2_option` dropped before `input1` emptied!"); }
             });
 
             // Drain input 2, prepare work.
@@ -506,9 +781,11 @@ where
                             if !batch2.is_empty() {
                                 // It is safe to ask for `ack1` as we validated that it was at least `get_physical_compaction()`
                                 // at start-up, and have held back physical compaction ever since.
-                                let (trace1_cursor, trace1_storage) = trace1.cursor_through(acknowledged1.borrow()).unwrap();
-                                let batch2_cursor = batch2.cursor();
-                                todo2.push_back(Deferred::new(trace1_cursor, trace1_storage, batch2_cursor, batch2.clone(), capability.clone()));
+                                if let Some((trace1_cursor, trace1_storage)) = trace1.cursor_through(acknowledged1.borrow()) {
+                                    let batch2_cursor = batch2.cursor();
+                                    todo2.push_back(Deferred::new(trace1_cursor, trace1_storage, batch2_cursor, batch2.clone(), capability.clone(), &logger, &*clock));
+                                }
+                                else { shutdown = true; }
                             }
 
                             // To update `acknowledged2` we might presume that `batch2.lower` should equal it, but we
@@ -519,9 +796,17 @@ where
                         }
                     }
                 }
-                else { panic!("`trace1_option` dropped before `input2` emptied!"); }
+                else if !shutdown { panic!("`trace1_option` dropped before `input2` emptied!"); }
             });
 
+            // Drop the traces and any deferred work, which releases the capabilities of the deferred work.
+            if shutdown {
+                trace1_option = None;
+                trace2_option = None;
+                todo1.clear();
+                todo2.clear();
+            }
+
             // Advance acknowledged frontiers through any empty regions that we may not receive as batches.
             if let Some(trace1) = trace1_option.as_mut() {
                 trace1.advance_upper(&mut acknowledged1);
@@ -540,26 +825,36 @@ where
             // which results in unintentionally quadratic processing time (each batch of either
             // input must scan all batches from the other input).
 
+            // The clock is only read with a time slice configured.
+            let start = join_time_slice.map(|_| clock.now());
+            let out_of_time = || join_time_slice.zip(start).map(|(slice, start)| clock.now().saturating_sub(start) >= slice).unwrap_or(false);
+
             // Perform some amount of outstanding work.
-            let mut fuel = 1_000_000;
-            while !todo1.is_empty() && fuel > 0 {
+            let mut fuel = join_fuel;
+            while !todo1.is_empty() && fuel > 0 && !out_of_time() {
                 todo1.front_mut().unwrap().work(
                     output,
                     |k,v2,v1,t,r2,r1,c| result(k,v1,v2,t,r1,r2,c),
                     &mut fuel
                 );
-                if !todo1.front().unwrap().work_remains() { todo1.pop_front(); }
+                if !todo1.front().unwrap().work_remains() {
+                    let deferred = todo1.pop_front().unwrap();
+                    deferred.log_latency(&logger, operator_id, &*clock);
+                }
             }
 
             // Perform some amount of outstanding work.
-            let mut fuel = 1_000_000;
-            while !todo2.is_empty() && fuel > 0 {
+            let mut fuel = join_fuel;
+            while !todo2.is_empty() && fuel > 0 && !out_of_time() {
                 todo2.front_mut().unwrap().work(
                     output,
                     |k,v1,v2,t,r1,r2,c| result(k,v1,v2,t,r1,r2,c),
                     &mut fuel
                 );
-                if !todo2.front().unwrap().work_remains() { todo2.pop_front(); }
+                if !todo2.front().unwrap().work_remains() {
+                    let deferred = todo2.pop_front().unwrap();
+                    deferred.log_latency(&logger, operator_id, &*clock);
+                }
             }
 
             // Re-activate operator if work remains.
@@ -627,6 +922,8 @@ where
     batch_storage: C2::Storage,
     capability: Capability<T>,
     done: bool,
+    /// When the batch was received, by the operator's clock, if latencies are logged.
+    received: Option<std::time::Duration>,
 }
 
 impl<T, C1, C2> Deferred<T, C1, C2>
@@ -635,7 +932,7 @@ where
     C2: for<'a> Cursor<Key<'a>=C1::Key<'a>, Time=T>,
     T: Timestamp+Lattice+Ord,
 {
-    fn new(trace: C1, trace_storage: C1::Storage, batch: C2, batch_storage: C2::Storage, capability: Capability<T>) -> Self {
+    fn new(trace: C1, trace_storage: C1::Storage, batch: C2, batch_storage: C2::Storage, capability: Capability<T>, logger: &Option<crate::logging::Logger>, clock: &dyn crate::trace::clock::Clock) -> Self {
         Deferred {
             trace,
             trace_storage,
@@ -643,6 +940,7 @@ where
             batch_storage,
             capability,
             done: false,
+            received: logger.as_ref().map(|_| clock.now()),
         }
     }
 
@@ -650,6 +948,16 @@ where
         !self.done
     }
 
+    /// Logs the time elapsed since the batch was received.
+    fn log_latency(&self, logger: &Option<crate::logging::Logger>, operator: usize, clock: &dyn crate::trace::clock::Clock) {
+        if let (Some(logger), Some(received)) = (logger, self.received) {
+            logger.log(crate::logging::LatencyEvent {
+                operator,
+                nanos: clock.now().saturating_sub(received).as_nanos() as u64,
+            });
+        }
+    }
+
     /// Process keys until at least `fuel` output tuples produced, or the work is exhausted.
     #[inline(never)]
     fn work<L, CB: ContainerBuilder>(&mut self, output: &mut OutputHandleCore<T, EffortBuilder<CB>, Tee<T, CB::Container>>, mut logic: L, fuel: &mut usize)
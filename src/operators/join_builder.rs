@@ -0,0 +1,175 @@
+//! Chains of joins on a common key.
+//!
+//! The `JoinBuilder` type assembles a sequence of binary joins, each matching the accumulated
+//! result against a further input on the same key. Each collection is arranged at most once:
+//! a collection that appears several times in the chain, for example in a self-join, shares one
+//! arrangement. Inputs that are already arranged are used as they are, and intermediate results
+//! are only arranged when a subsequent join requires it. The result of the final join is not
+//! arranged at all.
+//!
+//! The builder holds a handle to each trace it arranges, which prevents the traces from
+//! compacting until the builder is consumed by `build`.
+
+use std::any::Any;
+use std::rc::Rc;
+
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::operators::Map;
+
+use crate::{ExchangeData, Collection};
+use crate::difference::{Semigroup, Multiply};
+use crate::hashable::Hashable;
+use crate::lattice::Lattice;
+use crate::operators::arrange::{Arranged, ArrangeByKey, TraceAgent};
+use crate::trace::TraceReader;
+use crate::trace::implementations::ValSpine;
+
+/// An arrangement made by a builder, held to be shared if its collection appears again.
+///
+/// Arrangements of collections with different value types are held together, and so the trace and
+/// the batches of the arrangement are held as `Any`, to be recovered with the types of the collection.
+struct Shared<G: Scope> {
+    /// The operator and output port producing the arranged collection.
+    source: (usize, usize),
+    /// The `TraceAgent` of the arrangement.
+    trace: Box<dyn Any>,
+    /// The batches of the arrangement, each an `Rc` of the batch type of `trace`.
+    batches: Stream<G, Rc<dyn Any>>,
+}
+
+/// The accumulated result of a chain of joins.
+enum Stage<G, K, V, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    /// A result that would need to be arranged to be joined further.
+    Collection(Collection<G, (K, V), R>),
+    /// A result that is already arranged by key.
+    Arranged(Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>),
+}
+
+/// Assembles a chain of joins on a common key.
+///
+/// # Examples
+///
+/// ```
+/// use differential_dataflow::input::Input;
+/// use differential_dataflow::operators::arrange::ArrangeByKey;
+/// use differential_dataflow::operators::join_builder::JoinBuilder;
+///
+/// ::timely::example(|scope| {
+///
+///     let a = scope.new_collection_from(vec![(0, 'a'), (1, 'b')]).1;
+///     let b = scope.new_collection_from(vec![(0, 'x'), (1, 'y')]).1;
+///     let c = scope.new_collection_from(vec![(0, 10), (2, 20)]).1.arrange_by_key();
+///
+///     // `a` is joined twice, and arranged once.
+///     JoinBuilder::new(&a)
+///         .join(&b)
+///         .join(&a)
+///         .join_arranged(&c)
+///         .build()
+///         .assert_eq(&scope.new_collection_from(vec![(0, ((('a', 'x'), 'a'), 10))]).1);
+/// });
+/// ```
+pub struct JoinBuilder<G, K, V, R = isize>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup,
+{
+    stage: Stage<G, K, V, R>,
+    shared: Vec<Shared<G>>,
+}
+
+impl<G, K, V, R> JoinBuilder<G, K, V, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    V: ExchangeData,
+    R: ExchangeData+Semigroup+Multiply<R, Output=R>,
+{
+    /// Starts a chain of joins from a collection of `(key, val)` pairs.
+    pub fn new(input: &Collection<G, (K, V), R>) -> Self {
+        JoinBuilder { stage: Stage::Collection(input.clone()), shared: Vec::new() }
+    }
+
+    /// Starts a chain of joins from an existing arrangement, which will not be re-arranged.
+    pub fn from_arranged(input: &Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>>) -> Self {
+        JoinBuilder { stage: Stage::Arranged(input.clone()), shared: Vec::new() }
+    }
+
+    /// Joins the accumulated result with a collection of `(key, val2)` pairs.
+    ///
+    /// The collection is arranged for this join, unless the builder has already arranged it.
+    /// If it is also used outside the chain, consider arranging it once and using `join_arranged`.
+    pub fn join<V2: ExchangeData>(mut self, other: &Collection<G, (K, V2), R>) -> JoinBuilder<G, K, (V, V2), R> {
+        let other = self.arrange(other);
+        self.join_arranged(&other)
+    }
+
+    /// Joins the accumulated result with an existing arrangement of `(key, val2)` pairs.
+    pub fn join_arranged<V2, Tr2>(mut self, other: &Arranged<G, Tr2>) -> JoinBuilder<G, K, (V, V2), R>
+    where
+        V2: ExchangeData,
+        Tr2: for<'a> TraceReader<Key<'a>=&'a K, Val<'a>=&'a V2, Time=G::Timestamp, Diff=R>+Clone+'static,
+    {
+        let joined =
+        self.arranged()
+            .join_core(other, |key, val1, val2| Some((key.clone(), (val1.clone(), val2.clone()))));
+
+        JoinBuilder { stage: Stage::Collection(joined), shared: self.shared }
+    }
+
+    /// The number of arrangements the builder has made.
+    pub fn arrangements(&self) -> usize {
+        self.shared.len()
+    }
+
+    /// Completes the chain of joins, returning the accumulated result.
+    pub fn build(self) -> Collection<G, (K, V), R> {
+        match self.stage {
+            Stage::Collection(collection) => collection,
+            Stage::Arranged(arranged) => arranged.as_collection(|key, val| (key.clone(), val.clone())),
+        }
+    }
+
+    /// The accumulated result, arranged by key if it is not already.
+    fn arranged(&mut self) -> Arranged<G, TraceAgent<ValSpine<K, V, G::Timestamp, R>>> {
+        match &self.stage {
+            Stage::Collection(collection) => { let collection = collection.clone(); self.arrange(&collection) },
+            Stage::Arranged(arranged) => arranged.clone(),
+        }
+    }
+
+    /// Arranges `collection` by key, or shares the arrangement the builder has made of it.
+    fn arrange<V2: ExchangeData>(&mut self, collection: &Collection<G, (K, V2), R>) -> Arranged<G, TraceAgent<ValSpine<K, V2, G::Timestamp, R>>> {
+        let source = collection.inner.name();
+        let source = (source.node, source.port);
+        for shared in self.shared.iter().filter(|shared| shared.source == source) {
+            if let Some(trace) = shared.trace.downcast_ref::<TraceAgent<ValSpine<K, V2, G::Timestamp, R>>>() {
+                return Arranged {
+                    trace: trace.clone(),
+                    stream: shared.batches.map(|batch| {
+                        batch.downcast_ref::<<ValSpine<K, V2, G::Timestamp, R> as TraceReader>::Batch>().expect("batches of another type").clone()
+                    }),
+                };
+            }
+        }
+
+        let arranged = collection.arrange_by_key_named("Arrange: JoinBuilder");
+        self.shared.push(Shared {
+            source,
+            trace: Box::new(arranged.trace.clone()),
+            batches: arranged.stream.map(|batch| Rc::new(batch) as Rc<dyn Any>),
+        });
+        arranged
+    }
+}
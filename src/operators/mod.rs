@@ -4,19 +4,33 @@
 //! operators have specialized implementations to make them work efficiently, and are in addition
 //! to several operations defined directly on the `Collection` type (e.g. `map` and `filter`).
 
-pub use self::reduce::{Reduce, Threshold, Count};
+pub use self::reduce::{Reduce, Threshold, DistinctValues, Count, Minimize};
 pub use self::iterate::Iterate;
 pub use self::join::{Join, JoinCore};
 pub use self::count::CountTotal;
-pub use self::threshold::ThresholdTotal;
+pub use self::aggregate::AggregateTotal;
+pub use self::threshold::{ThresholdTotal, DistinctValuesTotal, DistinctPresent};
+pub use self::expire::ExpireAfter;
+pub use self::monotonic::ReduceMonotonic;
+pub use self::integrate::{Integrate, Differentiate};
+pub use self::append_only::AppendOnly;
 
 pub mod arrange;
 pub mod reduce;
 pub mod consolidate;
 pub mod iterate;
 pub mod join;
+pub mod join_builder;
+pub mod half_join;
+pub mod extend;
 pub mod count;
+pub mod aggregate;
 pub mod threshold;
+pub mod expire;
+pub mod monotonic;
+pub mod integrate;
+pub mod append_only;
+pub mod unary;
 
 use crate::lattice::Lattice;
 use crate::trace::Cursor;
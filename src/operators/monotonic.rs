@@ -0,0 +1,120 @@
+//! Aggregates of append-only collections.
+//!
+//! The `reduce` operator maintains an arrangement of its input, so that it can recompute the output
+//! of a key from all of its values when any of them change. When values are only ever added, and the
+//! aggregate can be updated from its prior value and the new values alone, as is the case for minima,
+//! maxima, and sums of non-negative quantities, the input arrangement is unnecessary. The
+//! `reduce_monotonic` operator keeps only the current aggregate of each key.
+//!
+//! The operator is only implemented for totally ordered times, for which the values that precede
+//! each time are well-defined.
+
+use std::collections::BTreeMap;
+
+use timely::order::TotalOrder;
+use timely::dataflow::*;
+use timely::dataflow::operators::{Capability, Operator};
+use timely::dataflow::channels::pact::Exchange;
+
+use crate::lattice::Lattice;
+use crate::{Data, ExchangeData, Collection};
+use crate::difference::Semigroup;
+use crate::hashable::Hashable;
+use crate::collection::AsCollection;
+
+/// Extension trait for the `reduce_monotonic` differential dataflow method.
+pub trait ReduceMonotonic<G: Scope, K: ExchangeData, V: ExchangeData, R: ExchangeData+Semigroup> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Folds the values of each key into an aggregate, for collections that are only appended to.
+    ///
+    /// When the input is complete through a time, `logic` is called for each update at that time
+    /// with the key, its current aggregate if any, and the value and difference of the update, and
+    /// returns the new aggregate. The output contains each key with its aggregate, and changes only
+    /// when the aggregate changes.
+    ///
+    /// The operator does not retain the values of keys, and so cannot recompute aggregates when values
+    /// are retracted. It presents retractions to `logic` like any other update, and it is up to `logic`
+    /// to handle them, or to ignore them for inputs that never retract values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::ReduceMonotonic;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // the largest value of each key.
+    ///     scope.new_collection_from(1 .. 10u32).1
+    ///          .map(|x| (x % 3, x))
+    ///          .reduce_monotonic(|_key, max: Option<&u32>, val, _diff| {
+    ///              max.map(|max| std::cmp::max(*max, *val)).unwrap_or(*val)
+    ///          });
+    /// });
+    /// ```
+    fn reduce_monotonic<A, L>(&self, logic: L) -> Collection<G, (K, A), isize>
+    where
+        A: Data,
+        L: FnMut(&K, Option<&A>, &V, &R)->A+'static;
+}
+
+impl<G: Scope, K: ExchangeData+Hashable, V: ExchangeData, R: ExchangeData+Semigroup> ReduceMonotonic<G, K, V, R> for Collection<G, (K, V), R>
+where
+    G::Timestamp: TotalOrder+Lattice+Ord,
+{
+    fn reduce_monotonic<A, L>(&self, mut logic: L) -> Collection<G, (K, A), isize>
+    where
+        A: Data,
+        L: FnMut(&K, Option<&A>, &V, &R)->A+'static,
+    {
+        let exchange = Exchange::new(|update: &((K, V), G::Timestamp, R)| (update.0).0.hashed().into());
+
+        self.inner
+            .unary_frontier(exchange, "ReduceMonotonic", move |_,_| {
+
+                // the current aggregate of each key.
+                let mut aggregates: BTreeMap<K, A> = BTreeMap::new();
+                // updates at times the input may not yet be complete through.
+                let mut pending: BTreeMap<G::Timestamp, (Capability<G::Timestamp>, Vec<(K, V, R)>)> = BTreeMap::new();
+                // the prior aggregates of keys changed at the current time.
+                let mut changed: BTreeMap<K, Option<A>> = BTreeMap::new();
+                let mut buffer = Vec::new();
+
+                move |input, output| {
+
+                    input.for_each(|cap, data| {
+                        data.swap(&mut buffer);
+                        for ((key, val), time, diff) in buffer.drain(..) {
+                            pending
+                                .entry(time.clone())
+                                .or_insert_with(|| (cap.delayed(&time), Vec::new()))
+                                .1
+                                .push((key, val, diff));
+                        }
+                    });
+
+                    // fold updates at times the input frontier has passed, in order.
+                    let frontier = input.frontier();
+                    while let Some(time) = pending.keys().next().cloned() {
+                        if frontier.less_equal(&time) { break; }
+                        let (capability, updates) = pending.remove(&time).unwrap();
+                        for (key, val, diff) in updates {
+                            let prior = aggregates.get(&key);
+                            let aggregate = logic(&key, prior, &val, &diff);
+                            let prior = aggregates.insert(key.clone(), aggregate);
+                            changed.entry(key).or_insert(prior);
+                        }
+                        let mut session = output.session(&capability);
+                        for (key, prior) in std::mem::take(&mut changed) {
+                            let aggregate = &aggregates[&key];
+                            if prior.as_ref() != Some(aggregate) {
+                                if let Some(prior) = prior {
+                                    session.give(((key.clone(), prior), time.clone(), -1));
+                                }
+                                session.give(((key, aggregate.clone()), time.clone(), 1));
+                            }
+                        }
+                    }
+                }
+            })
+            .as_collection()
+    }
+}
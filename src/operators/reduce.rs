@@ -7,7 +7,7 @@
 
 use crate::hashable::Hashable;
 use crate::{Data, ExchangeData, Collection};
-use crate::difference::{Semigroup, Abelian};
+use crate::difference::{Semigroup, Monoid, Abelian, MinPlus};
 
 use timely::order::PartialOrder;
 use timely::progress::frontier::Antichain;
@@ -19,7 +19,7 @@ use timely::dataflow::operators::Capability;
 
 use crate::operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf, TraceAgent};
 use crate::lattice::Lattice;
-use crate::trace::{Batch, BatchReader, Cursor, Trace, Builder, ExertionLogic};
+use crate::trace::{Batch, BatchReader, Cursor, Trace, Builder, ExertionLogic, MemoryPressureLogic};
 use crate::trace::cursor::CursorList;
 use crate::trace::implementations::{KeySpine, ValSpine};
 
@@ -63,8 +63,124 @@ pub trait Reduce<G: Scope, K: Data, V: Data, R: Semigroup> where G::Timestamp: L
     /// As `reduce` with the ability to name the operator.
     fn reduce_named<L, V2: Data, R2: Abelian>(&self, name: &str, logic: L) -> Collection<G, (K, V2), R2>
     where L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)+'static;
+
+    /// Applies a reduction function that may fail, routing errors to a second collection.
+    ///
+    /// The reduction function is as for `reduce`, but returns a `Result`. For each key whose function
+    /// returns `Ok(())`, the first collection contains the output values the function produced. For each
+    /// key whose function returns an error, the second collection contains the key and the error, and
+    /// the first collection contains no output for the key. Errors are specific to keys, and other keys
+    /// are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Reduce;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report the quotient of the two values of each group, or an error.
+    ///     let (quotients, errors) =
+    ///     scope.new_collection_from(vec![(0, 6), (0, 3), (1, 4), (1, 0)]).1
+    ///          .reduce_fallible(|_key, input, output| {
+    ///              if *input[0].0 == 0 {
+    ///                  return Err("division by zero".to_string());
+    ///              }
+    ///              output.push((input[input.len()-1].0 / input[0].0, 1));
+    ///              Ok(())
+    ///          });
+    ///
+    ///     quotients.assert_eq(&scope.new_collection_from(vec![(0, 2)]).1);
+    ///     errors.assert_eq(&scope.new_collection_from(vec![(1, "division by zero".to_string())]).1);
+    /// });
+    /// ```
+    fn reduce_fallible<L, V2: Data, R2: Abelian+From<i8>, E: Data>(&self, logic: L) -> (Collection<G, (K, V2), R2>, Collection<G, (K, E), R2>)
+    where L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)->Result<(), E>+'static {
+        self.reduce_fallible_named("ReduceFallible", logic)
+    }
+
+    /// As `reduce_fallible` with the ability to name the operator.
+    fn reduce_fallible_named<L, V2: Data, R2: Abelian+From<i8>, E: Data>(&self, name: &str, logic: L) -> (Collection<G, (K, V2), R2>, Collection<G, (K, E), R2>)
+    where L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)->Result<(), E>+'static;
+
+    /// Computes several aggregates of each group in one pass, arranging the tuple of results by key.
+    ///
+    /// The `aggregates` argument is a tuple of functions, each of which takes the same arguments as the
+    /// first two arguments of `reduce` logic and returns one aggregate of the group. The output holds for
+    /// each key a single tuple of the aggregates, each in the position of its function. This maintains one
+    /// output arrangement, where applying `reduce` once for each aggregate would maintain one per aggregate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Reduce;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///     // report the smallest and largest values, and the number of distinct values, of each group.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x))
+    ///          .group_many((
+    ///              |_key: &_, input: &[(&i32, isize)]| *input[0].0,
+    ///              |_key: &_, input: &[(&i32, isize)]| *input[input.len()-1].0,
+    ///              |_key: &_, input: &[(&i32, isize)]| input.len(),
+    ///          ))
+    ///          .as_collection(|key, aggregates| (*key, *aggregates))
+    ///          .inner
+    ///          .capture()
+    /// });
+    ///
+    /// let expected = vec![(0, (1, 2, 2)), (1, (3, 5, 3)), (2, (6, 8, 3)), (3, (9, 9, 1))];
+    /// assert_eq!(captured.extract(), vec![(0, expected.into_iter().map(|x| (x, 0, 1)).collect())]);
+    /// ```
+    fn group_many<A>(&self, aggregates: A) -> Arranged<G, TraceAgent<ValSpine<K, A::Output, G::Timestamp, isize>>>
+    where A: Aggregates<K, V, R>+'static {
+        self.group_many_named("GroupMany", aggregates)
+    }
+
+    /// As `group_many` with the ability to name the operator.
+    fn group_many_named<A>(&self, name: &str, aggregates: A) -> Arranged<G, TraceAgent<ValSpine<K, A::Output, G::Timestamp, isize>>>
+    where A: Aggregates<K, V, R>+'static;
 }
 
+/// A tuple of aggregation functions, applied together to each group by `group_many`.
+///
+/// This trait is implemented for tuples of up to eight functions `FnMut(&K, &[(&V, R)]) -> A`,
+/// whose output is the tuple of their results.
+pub trait Aggregates<K, V, R> {
+    /// The tuple of aggregate values.
+    type Output: Data;
+    /// Applies each aggregation function to the group of `key`.
+    fn aggregate(&mut self, key: &K, input: &[(&V, R)]) -> Self::Output;
+}
+
+macro_rules! implement_aggregates {
+    ($($func:ident $out:ident),+) => {
+        impl<K, V, R, $($func, $out),+> Aggregates<K, V, R> for ($($func,)+)
+        where
+            $($func: FnMut(&K, &[(&V, R)])->$out, $out: Data,)+
+        {
+            type Output = ($($out,)+);
+            #[allow(non_snake_case)]
+            fn aggregate(&mut self, key: &K, input: &[(&V, R)]) -> Self::Output {
+                let ($($func,)+) = self;
+                ($($func(key, input),)+)
+            }
+        }
+    }
+}
+
+implement_aggregates!(F1 A1);
+implement_aggregates!(F1 A1, F2 A2);
+implement_aggregates!(F1 A1, F2 A2, F3 A3);
+implement_aggregates!(F1 A1, F2 A2, F3 A3, F4 A4);
+implement_aggregates!(F1 A1, F2 A2, F3 A3, F4 A4, F5 A5);
+implement_aggregates!(F1 A1, F2 A2, F3 A3, F4 A4, F5 A5, F6 A6);
+implement_aggregates!(F1 A1, F2 A2, F3 A3, F4 A4, F5 A5, F6 A6, F7 A7);
+implement_aggregates!(F1 A1, F2 A2, F3 A3, F4 A4, F5 A5, F6 A6, F7 A7, F8 A8);
+
 impl<G, K, V, R> Reduce<G, K, V, R> for Collection<G, (K, V), R>
     where
         G: Scope,
@@ -78,6 +194,18 @@ impl<G, K, V, R> Reduce<G, K, V, R> for Collection<G, (K, V), R>
         self.arrange_by_key_named(&format!("Arrange: {}", name))
             .reduce_named(name, logic)
     }
+
+    fn reduce_fallible_named<L, V2: Data, R2: Abelian+From<i8>, E: Data>(&self, name: &str, logic: L) -> (Collection<G, (K, V2), R2>, Collection<G, (K, E), R2>)
+        where L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)->Result<(), E>+'static {
+        self.arrange_by_key_named(&format!("Arrange: {}", name))
+            .reduce_fallible_named(name, logic)
+    }
+
+    fn group_many_named<A>(&self, name: &str, aggregates: A) -> Arranged<G, TraceAgent<ValSpine<K, A::Output, G::Timestamp, isize>>>
+        where A: Aggregates<K, V, R>+'static {
+        self.arrange_by_key_named(&format!("Arrange: {}", name))
+            .group_many_named(name, aggregates)
+    }
 }
 
 impl<G, K: Data, V: Data, T1, R: Semigroup> Reduce<G, K, V, R> for Arranged<G, T1>
@@ -90,6 +218,103 @@ where
         self.reduce_abelian::<_,V2,_,ValSpine<_,_,_,_>>(name, |val| val.clone(), logic)
             .as_collection(|k,v| (k.clone(), v.clone()))
     }
+
+    fn reduce_fallible_named<L, V2: Data, R2: Abelian+From<i8>, E: Data>(&self, name: &str, mut logic: L) -> (Collection<G, (K, V2), R2>, Collection<G, (K, E), R2>)
+        where L: FnMut(&K, &[(&V, R)], &mut Vec<(V2, R2)>)->Result<(), E>+'static {
+        let mut buffer = Vec::new();
+        let results = self.reduce_abelian::<_,Result<V2, E>,_,ValSpine<_,_,_,_>>(name, |val| val.clone(), move |key, input, output| {
+            match logic(key, input, &mut buffer) {
+                Ok(()) => output.extend(buffer.drain(..).map(|(val, diff)| (Ok(val), diff))),
+                Err(error) => {
+                    // a failed key produces only its error.
+                    buffer.clear();
+                    output.push((Err(error), R2::from(1)));
+                },
+            }
+        });
+        let values = results.flat_map_ref(|key, result| result.as_ref().ok().map(|val| (key.clone(), val.clone())));
+        let errors = results.flat_map_ref(|key, result| result.as_ref().err().map(|error| (key.clone(), error.clone())));
+        (values, errors)
+    }
+
+    fn group_many_named<A>(&self, name: &str, mut aggregates: A) -> Arranged<G, TraceAgent<ValSpine<K, A::Output, G::Timestamp, isize>>>
+        where A: Aggregates<K, V, R>+'static {
+        self.reduce_abelian::<_,A::Output,_,ValSpine<_,_,_,_>>(name, |val| val.clone(), move |key, input, output| {
+            output.push((aggregates.aggregate(key, input), 1));
+        })
+    }
+}
+
+/// The number of hash bits that distinguish groups at each level of `reduce_hierarchical`.
+const HIERARCHY_BITS: usize = 8;
+
+/// Methods for associative reductions of keyed collections.
+impl<G, K, V> Collection<G, (K, V), isize>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+std::hash::Hash,
+    V: ExchangeData,
+{
+    /// Applies an associative reduction in stages, to bound the number of values each group reduces.
+    ///
+    /// A key with many values is reduced on a single worker, and any change to its values revisits all of
+    /// them. When the reduction is associative, this method instead reduces the values in `levels` stages
+    /// before the final reduction. The first stage groups the values of each key by the leading
+    /// `8 * levels` bits of their `hash`, each subsequent stage groups the results by eight fewer bits,
+    /// and the final stage groups by key alone. Each group has at most 256 inputs beyond the first stage,
+    /// and the groups of a key are spread across workers.
+    ///
+    /// The `logic` closure reduces the values of a key and their multiplicities to a single value, and is
+    /// applied both to input values and to its own results, which have multiplicity one. The output has
+    /// the result for each key with values. The method panics if `levels` exceeds eight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::hashable::Hashable;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // the sum of the values of a single, heavily skewed key.
+    ///     scope.new_collection_from(1 .. 10000u64).1
+    ///          .map(|x| ((), x))
+    ///          .reduce_hierarchical(2, |x| x.hashed(), |_key, input| {
+    ///              input.iter().map(|(val, diff)| **val * (*diff as u64)).sum()
+    ///          })
+    ///          .assert_eq(&scope.new_collection_from(Some(((), 49995000))).1);
+    /// });
+    /// ```
+    pub fn reduce_hierarchical<H, L>(&self, levels: usize, hash: H, logic: L) -> Collection<G, (K, V), isize>
+    where
+        H: Fn(&V)->u64+'static,
+        L: FnMut(&K, &[(&V, isize)])->V+Clone+'static,
+    {
+        assert!(levels * HIERARCHY_BITS <= 64, "reduce_hierarchical supports at most {} levels", 64 / HIERARCHY_BITS);
+
+        // Values keyed by their key and the hash prefix of their group.
+        let shift = (64 - levels * HIERARCHY_BITS) as u32;
+        let mut stage = self.map(move |(key, val)| {
+            let prefix = hash(&val).checked_shr(shift).unwrap_or(0);
+            ((key, prefix), val)
+        });
+
+        for level in (1 ..= levels).rev() {
+            let mut logic = logic.clone();
+            stage = stage
+                .reduce_named(&format!("ReduceHierarchical{}", level), move |(key, _prefix), input, output| {
+                    output.push((logic(key, input), 1));
+                })
+                .map(|((key, prefix), val)| ((key, prefix >> HIERARCHY_BITS), val));
+        }
+
+        let mut logic = logic;
+        stage
+            .map(|((key, _prefix), val)| (key, val))
+            .reduce_named("ReduceHierarchical", move |key, input, output| {
+                output.push((logic(key, input), 1));
+            })
+    }
 }
 
 /// Extension trait for the `threshold` and `distinct` differential dataflow methods.
@@ -120,6 +345,41 @@ pub trait Threshold<G: Scope, K: Data, R1: Semigroup> where G::Timestamp: Lattic
     /// A `threshold` with the ability to name the operator.
     fn threshold_named<R2: Abelian, F: FnMut(&K, &R1)->R2+'static>(&self, name: &str, thresh: F) -> Collection<G, K, R2>;
 
+    /// A `threshold` that returns the arrangement the operator maintains of its output.
+    ///
+    /// The operator arranges its output in any case, and the arrangement can be used directly by other
+    /// operators, for example by a `join` with the result, while its `as_collection` view provides the
+    /// result as a collection. Both share the one operator, rather than arranging the result again.
+    ///
+    /// The default implementation arranges the result of `threshold_named`; the implementations in
+    /// this crate return the arrangement of the operator instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Threshold;
+    /// use differential_dataflow::operators::arrange::ArrangeBySelf;
+    ///
+    /// ::timely::example(|scope| {
+    ///     let data = scope.new_collection_from(1 .. 10).1.map(|x| x / 3);
+    ///     let odd = data.threshold_arranged("Odd", |_,c| c % 2);
+    ///     // the collection view of the arrangement is the result of `threshold`.
+    ///     odd.as_collection(|k,_| *k)
+    ///        .assert_eq(&data.threshold(|_,c| c % 2));
+    ///     // the arrangement can be joined with other arrangements, without arranging the result again.
+    ///     odd.join_core(&data.arrange_by_self(), |k,_,_| Some(*k));
+    /// });
+    /// ```
+    fn threshold_arranged<R2: Abelian, F: FnMut(&K, &R1)->R2+'static>(&self, name: &str, thresh: F) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R2>>>
+    where
+        K: ExchangeData+Hashable,
+        R2: ExchangeData,
+    {
+        self.threshold_named(name, thresh)
+            .arrange_by_self_named(&format!("Arrange: {}", name))
+    }
+
     /// Reduces the collection to one occurrence of each distinct element.
     ///
     /// # Examples
@@ -147,6 +407,16 @@ pub trait Threshold<G: Scope, K: Data, R1: Semigroup> where G::Timestamp: Lattic
     fn distinct_core<R2: Abelian+From<i8>>(&self) -> Collection<G, K, R2> {
         self.threshold_named("Distinct", |_,_| R2::from(1i8))
     }
+
+    /// A `distinct` that returns the arrangement the operator maintains of its output.
+    ///
+    /// As for `threshold_arranged`, the arrangement and its `as_collection` view share one operator.
+    fn distinct_arranged(&self) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, isize>>>
+    where
+        K: ExchangeData+Hashable,
+    {
+        self.threshold_arranged("Distinct", |_,_| 1)
+    }
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, R1: ExchangeData+Semigroup> Threshold<G, K, R1> for Collection<G, K, R1>
@@ -155,6 +425,11 @@ where G::Timestamp: Lattice+Ord {
         self.arrange_by_self_named(&format!("Arrange: {}", name))
             .threshold_named(name, thresh)
     }
+
+    fn threshold_arranged<R2: Abelian, F: FnMut(&K,&R1)->R2+'static>(&self, name: &str, mut thresh: F) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R2>>> {
+        self.arrange_by_self_named(&format!("Arrange: {}", name))
+            .reduce_abelian::<_,(),_,KeySpine<_,_,_>>(name, |&()| (), move |k,s,t| t.push(((), thresh(k, &s[0].1))))
+    }
 }
 
 impl<G, K: Data, T1, R1: Semigroup> Threshold<G, K, R1> for Arranged<G, T1>
@@ -166,6 +441,96 @@ where
         self.reduce_abelian::<_,(),_,KeySpine<_,_,_>>(name, |&()| (), move |k,s,t| t.push(((), thresh(k, &s[0].1))))
             .as_collection(|k,_| k.clone())
     }
+
+    fn threshold_arranged<R2: Abelian, F: FnMut(&K,&R1)->R2+'static>(&self, name: &str, mut thresh: F) -> Arranged<G, TraceAgent<KeySpine<K, G::Timestamp, R2>>> {
+        self.reduce_abelian::<_,(),_,KeySpine<_,_,_>>(name, |&()| (), move |k,s,t| t.push(((), thresh(k, &s[0].1))))
+    }
+}
+
+/// Extension trait for the `distinct_values` differential dataflow method.
+pub trait DistinctValues<G: Scope, K: Data, V: Data, R: Semigroup> where G::Timestamp: Lattice+Ord {
+    /// Reduces the collection to one occurrence of each distinct value within each key.
+    ///
+    /// Each `(key, val)` pair with a non-zero accumulation is retained once, regardless of its multiplicity.
+    /// The operator arranges its input by key, rather than requiring the records be re-keyed to `((key, val), ())`.
+    /// For totally ordered times, `DistinctValuesTotal::distinct_values_total` does less work.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::DistinctValues;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report at most one of each (key, val) pair.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x % 2))
+    ///          .distinct_values();
+    /// });
+    /// ```
+    fn distinct_values(&self) -> Collection<G, (K, V), isize> {
+        self.distinct_values_core()
+    }
+
+    /// Distinct values for general integer differences.
+    ///
+    /// This method allows `distinct_values` to produce collections whose difference
+    /// type is something other than an `isize` integer, for example perhaps an
+    /// `i32`.
+    fn distinct_values_core<R2: Abelian+From<i8>>(&self) -> Collection<G, (K, V), R2>;
+}
+
+impl<G: Scope, K: ExchangeData+Hashable, V: ExchangeData, R: ExchangeData+Semigroup> DistinctValues<G, K, V, R> for Collection<G, (K, V), R>
+where G::Timestamp: Lattice+Ord {
+    fn distinct_values_core<R2: Abelian+From<i8>>(&self) -> Collection<G, (K, V), R2> {
+        self.arrange_by_key_named("Arrange: DistinctValues")
+            .distinct_values_core()
+    }
+}
+
+impl<G, K: Data, V: Data, T1, R: Semigroup> DistinctValues<G, K, V, R> for Arranged<G, T1>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: for<'a> TraceReader<Key<'a>=&'a K, KeyOwned=K, Val<'a>=&'a V, Diff=R>+Clone+'static,
+{
+    fn distinct_values_core<R2: Abelian+From<i8>>(&self) -> Collection<G, (K, V), R2> {
+        self.reduce_abelian::<_,V,_,ValSpine<_,_,_,_>>("DistinctValues", |v| v.clone(), |_k,s,t| t.extend(s.iter().map(|(v,_)| ((*v).clone(), R2::from(1i8)))))
+            .as_collection(|k,v| (k.clone(), v.clone()))
+    }
+}
+
+impl<G, K: Data, V: Data, T1, R: Semigroup> Arranged<G, T1>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: for<'a> TraceReader<Key<'a>=&'a K, KeyOwned=K, Val<'a>=&'a V, Diff=R>+Clone+'static,
+{
+    /// Reduces the keys of an arrangement by key to one occurrence of each distinct key.
+    ///
+    /// A key is present if any of its values has a non-zero accumulation. Unlike `keys().distinct()`,
+    /// which arranges the keys again, this method uses the existing arrangement, which other operators
+    /// may share.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Threshold;
+    /// use differential_dataflow::operators::arrange::ArrangeByKey;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     data.map(|x| (x % 3, x))
+    ///         .arrange_by_key()
+    ///         .distinct_keys()
+    ///         .assert_eq(&data.map(|x| x % 3).distinct());
+    /// });
+    /// ```
+    pub fn distinct_keys(&self) -> Collection<G, K, isize> {
+        self.reduce_abelian::<_,(),_,KeySpine<_,_,_>>("DistinctKeys", |&()| (), |_k,_s,t| t.push(((), 1)))
+            .as_collection(|k,_| k.clone())
+    }
 }
 
 /// Extension trait for the `count` differential dataflow method.
@@ -195,6 +560,23 @@ pub trait Count<G: Scope, K: Data, R: Semigroup> where G::Timestamp: Lattice+Ord
     /// type is something other than an `isize` integer, for example perhaps an
     /// `i32`.
     fn count_core<R2: Abelian + From<i8>>(&self) -> Collection<G, (K, R), R2>;
+
+    /// A `count` that returns the arrangement the operator maintains of its output.
+    ///
+    /// The arrangement has the counted elements as keys, and their counts as values. As for
+    /// `Threshold::threshold_arranged`, the arrangement and its `as_collection` view share one operator.
+    ///
+    /// The default implementation arranges the result of `count_core`; the implementations in this
+    /// crate return the arrangement of the operator instead.
+    fn count_arranged<R2: Abelian + From<i8>>(&self) -> Arranged<G, TraceAgent<ValSpine<K, R, G::Timestamp, R2>>>
+    where
+        K: ExchangeData+Hashable,
+        R: ExchangeData,
+        R2: ExchangeData,
+    {
+        self.count_core()
+            .arrange_by_key_named("Arrange: Count")
+    }
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, R: ExchangeData+Semigroup> Count<G, K, R> for Collection<G, K, R>
@@ -205,6 +587,91 @@ where
         self.arrange_by_self_named("Arrange: Count")
             .count_core()
     }
+
+    fn count_arranged<R2: Abelian + From<i8>>(&self) -> Arranged<G, TraceAgent<ValSpine<K, R, G::Timestamp, R2>>> {
+        self.arrange_by_self_named("Arrange: Count")
+            .reduce_abelian::<_,R,_,ValSpine<_,_,_,_>>("Count", |r| r.clone(), |_k,s,t| t.push((s[0].1.clone(), R2::from(1i8))))
+    }
+}
+
+impl<G, K, R> Collection<G, K, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    R: ExchangeData+Semigroup,
+{
+    /// Counts the number of occurrences of each element, first within each worker and then across workers.
+    ///
+    /// Each worker accumulates the occurrences of the elements it holds at each time, without exchanging
+    /// them, and once the time is complete sends one partial count for each element to the `count`
+    /// operator, which sums them. When many occurrences of each element arrive at each worker, this
+    /// exchanges far fewer updates than `count`, at the cost of buffering each worker's updates until
+    /// their time is complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::Count;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(1 .. 100).1
+    ///                  .map(|x| x % 3);
+    ///
+    ///     x.count_local_then_global()
+    ///      .assert_eq(&x.count());
+    /// });
+    /// ```
+    pub fn count_local_then_global(&self) -> Collection<G, (K, R), isize> {
+        crate::operators::consolidate::consolidate_until_closed(self, Pipeline, "CountLocal")
+            .count()
+    }
+}
+
+impl<G, K, R> Collection<G, K, R>
+where
+    G: Scope,
+    G::Timestamp: Lattice+Ord,
+    K: ExchangeData+Hashable,
+    R: ExchangeData+Monoid+Ord,
+{
+    /// Reports the elements whose accumulated multiplicities are negative, with their multiplicities.
+    ///
+    /// A multiplicity is negative if it is less than zero. The result contains `(element, multiplicity)`
+    /// for each element whose multiplicity is negative once its time is complete, and retracts it when
+    /// the multiplicity changes. Retractions of records that were never inserted are usually a bug in
+    /// the program that feeds a collection, and are much easier to diagnose near their source than from
+    /// their consequences downstream.
+    ///
+    /// The operator arranges the collection by self, and maintains only the reported elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let data = scope.new_collection_from(1 .. 10).1;
+    ///
+    ///     // retracts `3` twice.
+    ///     data.concat(&data.filter(|x| *x == 3).negate())
+    ///         .concat(&data.filter(|x| *x == 3).negate())
+    ///         .assert_nonnegative()
+    ///         .assert_eq(&scope.new_collection_from(vec![(3, -1)]).1);
+    /// });
+    /// ```
+    pub fn assert_nonnegative(&self) -> Collection<G, (K, R), isize> {
+        self.arrange_by_self_named("Arrange: AssertNonnegative")
+            .reduce_abelian::<_,R,_,ValSpine<_,_,_,_>>("AssertNonnegative", |diff| diff.clone(), |_key, input, output| {
+                if input[0].1 < R::zero() {
+                    output.push((input[0].1.clone(), 1));
+                }
+            })
+            .as_collection(|key, diff| (key.clone(), diff.clone()))
+    }
 }
 
 impl<G, K: Data, T1, R: Semigroup> Count<G, K, R> for Arranged<G, T1>
@@ -216,6 +683,97 @@ where
         self.reduce_abelian::<_,R,_,ValSpine<_,_,_,_>>("Count", |r| r.clone(), |_k,s,t| t.push((s[0].1.clone(), R2::from(1i8))))
             .as_collection(|k,c| (k.clone(), c.clone()))
     }
+
+    fn count_arranged<R2: Abelian + From<i8>>(&self) -> Arranged<G, TraceAgent<ValSpine<K, R, G::Timestamp, R2>>> {
+        self.reduce_abelian::<_,R,_,ValSpine<_,_,_,_>>("Count", |r| r.clone(), |_k,s,t| t.push((s[0].1.clone(), R2::from(1i8))))
+    }
+}
+
+/// Extension trait for the `minimize` differential dataflow method.
+pub trait Minimize<G: Scope, K: Data, W: Data> where G::Timestamp: Lattice+Ord {
+    /// Retains only the updates that improve the least value of each element.
+    ///
+    /// The accumulated value of each element is the same in the input and the output, but an update
+    /// that does not improve on the value of its element at its time produces no output. Iterative
+    /// computations with min-plus differences rely on this to reach a fixed point, as otherwise they
+    /// would continue to derive values that do not improve on those already known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::difference::{MinPlus, Semigroup};
+    /// use differential_dataflow::operators::{Iterate, Join, Minimize};
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///
+    ///     // weighted edges, with the weights in their differences.
+    ///     let edges = scope.new_collection_from_raw(vec![
+    ///         ((0u32, 1u32), 0, MinPlus::new(3u64)),
+    ///         ((1, 2), 0, MinPlus::new(4)),
+    ///         ((0, 2), 0, MinPlus::new(9)),
+    ///         ((2, 0), 0, MinPlus::new(1)),
+    ///     ]).1;
+    ///     let roots = scope.new_collection_from_raw(vec![(0u32, 0, MinPlus::new(0u64))]).1;
+    ///
+    ///     // the shortest distance from the root to each node.
+    ///     scope.iterate(|dists| {
+    ///         let edges = edges.enter(&dists.scope());
+    ///         let roots = roots.enter(&dists.scope());
+    ///         dists.map(|node| (node, ()))
+    ///              .join_map(&edges, |_src, &(), &dst| dst)
+    ///              .concat(&roots)
+    ///              .minimize()
+    ///     })
+    ///     .inner
+    ///     .capture()
+    /// });
+    ///
+    /// // accumulate the least distance to each node.
+    /// let mut distances = std::collections::BTreeMap::new();
+    /// for (node, _time, diff) in captured.extract().into_iter().flat_map(|(_, data)| data) {
+    ///     distances.entry(node).and_modify(|d: &mut MinPlus<u64>| d.plus_equals(&diff)).or_insert(diff);
+    /// }
+    /// let distances = distances.iter().map(|(node, d)| (*node, *d.value().unwrap())).collect::<Vec<_>>();
+    /// assert_eq!(distances, vec![(0, 0), (1, 3), (2, 7)]);
+    /// ```
+    fn minimize(&self) -> Collection<G, K, MinPlus<W>> {
+        self.minimize_named("Minimize")
+    }
+
+    /// A `minimize` with the ability to name the operator.
+    fn minimize_named(&self, name: &str) -> Collection<G, K, MinPlus<W>>;
+}
+
+impl<G: Scope, K: ExchangeData+Hashable, W: ExchangeData> Minimize<G, K, W> for Collection<G, K, MinPlus<W>>
+where G::Timestamp: Lattice+Ord {
+    fn minimize_named(&self, name: &str) -> Collection<G, K, MinPlus<W>> {
+        self.arrange_by_self_named(&format!("Arrange: {}", name))
+            .minimize_named(name)
+    }
+}
+
+impl<G, K: Data, T1, W: Data> Minimize<G, K, W> for Arranged<G, T1>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: for<'a> TraceReader<Key<'a>=&'a K, KeyOwned=K, Val<'a>=&'a (), Diff=MinPlus<W>>+Clone+'static,
+{
+    fn minimize_named(&self, name: &str) -> Collection<G, K, MinPlus<W>> {
+        self.reduce_core::<_,(),_,KeySpine<_,_,_>>(name, |&()| (), move |_k, input, output, change| {
+            // Input accumulations only decrease, and output what improves on the current value.
+            if let Some((_, value)) = input.first() {
+                let current = output.first().map(|(_, current)| current.clone()).unwrap_or_else(MinPlus::zero);
+                let mut improved = current.clone();
+                improved.plus_equals(value);
+                if improved != current {
+                    change.push(((), value.clone()));
+                }
+            }
+        })
+        .as_collection(|k,_| k.clone())
+    }
 }
 
 /// Extension trait for the `reduce_core` differential dataflow method.
@@ -335,11 +893,20 @@ where
             if let Some(exert_logic) = trace.stream.scope().config().get::<ExertionLogic>("differential/default_exert_logic").cloned() {
                 empty.set_exert_logic(exert_logic);
             }
+            if let Some(logic) = trace.stream.scope().config().get::<MemoryPressureLogic>("differential/memory_pressure_logic").cloned() {
+                empty.set_memory_pressure_logic(logic);
+            }
 
 
             let mut source_trace = trace.trace.clone();
 
-            let (mut output_reader, mut output_writer) = TraceAgent::new(empty, operator_info, logger);
+            let operator_id = operator_info.global_id;
+            let (mut output_reader, mut output_writer) = TraceAgent::new(empty, operator_info, logger.clone());
+            let clock = trace.stream.scope().config().get::<std::sync::Arc<dyn crate::trace::clock::Clock>>("differential/clock").cloned();
+            if let Some(clock) = &clock {
+                output_reader.set_clock(clock.clone());
+            }
+            let clock = clock.unwrap_or_else(|| std::sync::Arc::new(crate::trace::clock::SystemClock));
 
             // let mut output_trace = TraceRc::make_from(agent).0;
             *result_trace = Some(output_reader.clone());
@@ -366,6 +933,37 @@ where
 
             let mut input_buffer = Vec::new();
 
+            // Batches and capabilities received from the input, but not yet folded into a round of work.
+            // Their upper bound is tracked by `queued_upper`, which may run ahead of `upper_limit` while
+            // a round of work is still in progress.
+            let mut queued_batches = Vec::new();
+            let mut queued_capabilities = Vec::<Capability<G::Timestamp>>::new();
+            let mut queued_upper = Antichain::from_elem(<G::Timestamp as timely::progress::Timestamp>::minimum());
+            // When the earliest queued batch, and the earliest batch of the current round, were received.
+            let mut queued_since: Option<std::time::Duration> = None;
+            let mut round_since: Option<std::time::Duration> = None;
+
+            // State of the round of work covering the interval `[lower_limit, upper_limit)`.
+            //
+            // A round may span several activations when `differential/reduce_fuel` or `differential/reduce_time_slice`
+            // is configured. The input batches of the round are retained, and a fresh cursor over them is positioned
+            // at `resume_key` each time we resume. We hold back compaction of the source and output traces at
+            // `lower_limit` until the round completes, so that cursors over their contents remain available.
+            let mut in_progress = false;
+            // Set if the traces can no longer provide cursors, after which the operator discards its input.
+            let mut shutdown = false;
+            let mut round_batches = Vec::<T1::Batch>::new();
+            let mut resume_key: Option<T1::KeyOwned> = None;
+            let mut exposed = Vec::<(T1::KeyOwned, G::Timestamp)>::new();
+            let mut exposed_position = 0;
+            let mut buffers = Vec::<(G::Timestamp, Vec<(V, G::Timestamp, T2::Diff)>)>::new();
+            let mut builders = Vec::<T2::Builder>::new();
+
+            // The number of keys to process, and the time to spend processing them, before yielding, if configured.
+            let reduce_fuel = trace.stream.scope().config().get::<usize>("differential/reduce_fuel").cloned().unwrap_or(usize::MAX);
+            let reduce_time_slice = trace.stream.scope().config().get::<std::time::Duration>("differential/reduce_time_slice").cloned();
+            let activator = trace.stream.scope().activator_for(&operator_info.address[..]);
+
             let id = trace.stream.scope().index();
 
             move |input, output| {
@@ -388,37 +986,48 @@ where
                 // both information from batches as well as progress information. I think this means that
                 // we keep times that are greater than or equal to a time in the other frontier, deduplicated.
 
-                let mut batch_cursors = Vec::new();
-                let mut batch_storage = Vec::new();
-
-                // Downgrate previous upper limit to be current lower limit.
-                lower_limit.clear();
-                lower_limit.extend(upper_limit.borrow().iter().cloned());
-
                 // Drain the input stream of batches, validating the contiguity of the batch descriptions and
-                // capturing a cursor for each of the batches as well as ensuring we hold a capability for the
-                // times in the batch.
+                // capturing each of the batches as well as ensuring we hold a capability for the times in the
+                // batch. These are queued until the current round of work (if any) completes.
                 input.for_each(|capability, batches| {
 
+                    if shutdown { return; }
                     batches.swap(&mut input_buffer);
+                    if logger.is_some() {
+                        queued_since.get_or_insert_with(|| clock.now());
+                    }
                     for batch in input_buffer.drain(..) {
-                        upper_limit.clone_from(batch.upper());
-                        batch_cursors.push(batch.cursor());
-                        batch_storage.push(batch);
+                        queued_upper.clone_from(batch.upper());
+                        queued_batches.push(batch);
                     }
 
-                    // Ensure that `capabilities` covers the capability of the batch.
-                    capabilities.retain(|cap| !capability.time().less_than(cap.time()));
-                    if !capabilities.iter().any(|cap| cap.time().less_equal(capability.time())) {
-                        capabilities.push(capability.retain());
+                    // Ensure that `queued_capabilities` covers the capability of the batch.
+                    queued_capabilities.retain(|cap| !capability.time().less_than(cap.time()));
+                    if !queued_capabilities.iter().any(|cap| cap.time().less_equal(capability.time())) {
+                        queued_capabilities.push(capability.retain());
                     }
                 });
 
                 // Pull in any subsequent empty batches we believe to exist.
-                source_trace.advance_upper(&mut upper_limit);
+                source_trace.advance_upper(&mut queued_upper);
+
+                // Only if our upper limit can advance, and we are not mid-round, should we start new work.
+                if !in_progress && !shutdown && queued_upper != upper_limit {
 
-                // Only if our upper limit has advanced should we do work.
-                if upper_limit != lower_limit {
+                    round_since = queued_since.take();
+
+                    // Downgrade previous upper limit to be current lower limit.
+                    lower_limit.clone_from(&upper_limit);
+                    upper_limit.clone_from(&queued_upper);
+                    round_batches.extend(queued_batches.drain(..));
+
+                    // Ensure that `capabilities` covers the capabilities of the queued batches.
+                    for capability in queued_capabilities.drain(..) {
+                        capabilities.retain(|cap| !capability.time().less_than(cap.time()));
+                        if !capabilities.iter().any(|cap| cap.time().less_equal(capability.time())) {
+                            capabilities.push(capability);
+                        }
+                    }
 
                     // If we have no capabilities, then we (i) should not produce any outputs and (ii) could not send
                     // any produced outputs even if they were (incorrectly) produced. We cannot even send empty batches
@@ -432,11 +1041,11 @@ where
                         // We first extract those times from this list that lie in the interval we will process.
                         sort_dedup(&mut interesting);
                         // `exposed` contains interesting (key, time)s now below `upper_limit`
-                        let exposed = {
-                            let (exposed, new_interesting) = interesting.drain(..).partition(|(_, time)| !upper_limit.less_equal(time));
-                            interesting = new_interesting;
-                            exposed
-                        };
+                        let (new_exposed, new_interesting): (Vec<_>, Vec<_>) = interesting.drain(..).partition(|(_, time)| !upper_limit.less_equal(time));
+                        exposed = new_exposed;
+                        interesting = new_interesting;
+                        exposed_position = 0;
+                        resume_key = None;
 
                         // Prepare an output buffer and builder for each capability.
                         //
@@ -447,20 +1056,74 @@ where
                         //
                         // TODO: It would be better if all updates went into one batch, but timely dataflow prevents
                         //       this as long as it requires that there is only one capability for each message.
-                        let mut buffers = Vec::<(G::Timestamp, Vec<(V, G::Timestamp, T2::Diff)>)>::new();
-                        let mut builders = Vec::new();
+                        buffers.clear();
+                        builders.clear();
                         for cap in capabilities.iter() {
                             buffers.push((cap.time().clone(), Vec::new()));
                             builders.push(T2::Builder::new());
                         }
 
-                        // cursors for navigating input and output traces.
-                        let (mut source_cursor, source_storage): (T1::Cursor, _) = source_trace.cursor_through(lower_limit.borrow()).expect("failed to acquire source cursor");
-                        let source_storage = &source_storage;
-                        let (mut output_cursor, output_storage): (T2::Cursor, _) = output_reader.cursor_through(lower_limit.borrow()).expect("failed to acquire output cursor");
-                        let output_storage = &output_storage;
-                        let (mut batch_cursor, batch_storage) = (CursorList::new(batch_cursors, &batch_storage), batch_storage);
-                        let batch_storage = &batch_storage;
+                        in_progress = true;
+                    }
+                    else {
+                        round_batches.clear();
+                        output_writer.seal(upper_limit.clone());
+
+                        // We only anticipate future times in advance of `upper_limit`.
+                        source_trace.set_logical_compaction(upper_limit.borrow());
+                        output_reader.set_logical_compaction(upper_limit.borrow());
+
+                        // We will only slice the data between future batches.
+                        source_trace.set_physical_compaction(upper_limit.borrow());
+                        output_reader.set_physical_compaction(upper_limit.borrow());
+                    }
+                }
+
+                // cursors for navigating input and output traces.
+                let mut cursors = None;
+                if in_progress {
+                    match (source_trace.cursor_through(lower_limit.borrow()), output_reader.cursor_through(lower_limit.borrow())) {
+                        (Some(source), Some(output)) => { cursors = Some((source, output)); },
+                        _ => {
+                            // The source trace was closed or released, and we cannot produce further output.
+                            // Shut down cleanly: complete the output, release our capabilities and traces,
+                            // and discard any further input.
+                            shutdown = true;
+                            in_progress = false;
+                            capabilities.clear();
+                            queued_capabilities.clear();
+                            queued_batches.clear();
+                            round_batches.clear();
+                            interesting.clear();
+                            exposed.clear();
+                            buffers.clear();
+                            builders.clear();
+                            output_writer.seal(Antichain::new());
+                            source_trace.set_logical_compaction(Antichain::new().borrow());
+                            source_trace.set_physical_compaction(Antichain::new().borrow());
+                            output_reader.set_logical_compaction(Antichain::new().borrow());
+                            output_reader.set_physical_compaction(Antichain::new().borrow());
+                        }
+                    }
+                }
+
+                if let Some(((mut source_cursor, source_storage), (mut output_cursor, output_storage))) = cursors {
+
+                    // Process keys of the current round, until we run out of keys or fuel.
+                    let (work_remains, batches_remain) = {
+
+                        let source_storage: &T1::Storage = &source_storage;
+                        let output_storage: &T2::Storage = &output_storage;
+                        let batch_cursors = round_batches.iter().map(|batch| batch.cursor()).collect::<Vec<_>>();
+                        let batch_storage = &round_batches;
+                        let mut batch_cursor = CursorList::new(batch_cursors, batch_storage);
+
+                        use crate::trace::cursor::MyTrait;
+
+                        // Return to where we left off, if we yielded in a previous activation.
+                        if let Some(key) = resume_key.take() {
+                            batch_cursor.seek_key(batch_storage, <_ as MyTrait>::borrow_as(&key));
+                        }
 
                         let mut thinker = history_replay::HistoryReplayer::new();
 
@@ -469,11 +1132,13 @@ where
                         // We only keep valid cursors (those with more data) in `batch_cursors`, and so its length
                         // indicates whether more data remain. We move through `exposed` using (index) `exposed_position`.
                         // There could perhaps be a less provocative variable name.
-                        let mut exposed_position = 0;
-                        while batch_cursor.key_valid(batch_storage) || exposed_position < exposed.len() {
+                        // The clock is only read with a time slice configured.
+                        let mut fuel = reduce_fuel;
+                        let start = reduce_time_slice.map(|_| clock.now());
+                        let mut out_of_time = false;
+                        while (batch_cursor.key_valid(batch_storage) || exposed_position < exposed.len()) && fuel > 0 && !out_of_time {
 
                             use std::borrow::Borrow;
-                            use crate::trace::cursor::MyTrait;
 
                             // Determine the next key we will work on; could be synthetic, could be from a batch.
                             let key1 = exposed.get(exposed_position).map(|x| <_ as MyTrait>::borrow_as(&x.0));
@@ -534,8 +1199,24 @@ where
                                     builders[index].push(((key.into_owned(), val), time, diff));
                                 }
                             }
+
+                            fuel -= 1;
+                            out_of_time = reduce_time_slice.zip(start).map(|(slice, start)| clock.now().saturating_sub(start) >= slice).unwrap_or(false);
                         }
 
+                        // Record where to resume, should we have run out of fuel or time.
+                        resume_key = batch_cursor.get_key(batch_storage).map(|key| key.into_owned());
+                        let batches_remain = batch_cursor.key_valid(batch_storage);
+                        (batches_remain || exposed_position < exposed.len(), batches_remain)
+                    };
+
+                    // Once the batches are exhausted, we no longer need to retain them.
+                    if !batches_remain {
+                        round_batches.clear();
+                    }
+
+                    if !work_remains {
+
                         // We start sealing output batches from the lower limit (previous upper limit).
                         // In principle, we could update `lower_limit` itself, and it should arrive at
                         // `upper_limit` by the end of the process.
@@ -565,6 +1246,8 @@ where
                                 output_lower.extend(output_upper.borrow().iter().cloned());
                             }
                         }
+                        buffers.clear();
+                        exposed.clear();
 
                         // This should be true, as the final iteration introduces no capabilities, and
                         // uses exactly `upper_limit` to determine the upper bound. Good to check though.
@@ -593,18 +1276,30 @@ where
 
                         // ensure that observed progres is reflected in the output.
                         output_writer.seal(upper_limit.clone());
-                    }
-                    else {
-                        output_writer.seal(upper_limit.clone());
-                    }
 
-                    // We only anticipate future times in advance of `upper_limit`.
-                    source_trace.set_logical_compaction(upper_limit.borrow());
-                    output_reader.set_logical_compaction(upper_limit.borrow());
+                        // We only anticipate future times in advance of `upper_limit`.
+                        source_trace.set_logical_compaction(upper_limit.borrow());
+                        output_reader.set_logical_compaction(upper_limit.borrow());
+
+                        // We will only slice the data between future batches.
+                        source_trace.set_physical_compaction(upper_limit.borrow());
+                        output_reader.set_physical_compaction(upper_limit.borrow());
+
+                        // Log the time from the receipt of the round's first batch to the output of its results.
+                        if let (Some(logger), Some(since)) = (&logger, round_since.take()) {
+                            logger.log(crate::logging::LatencyEvent {
+                                operator: operator_id,
+                                nanos: clock.now().saturating_sub(since).as_nanos() as u64,
+                            });
+                        }
+
+                        in_progress = false;
+                    }
+                }
 
-                    // We will only slice the data between future batches.
-                    source_trace.set_physical_compaction(upper_limit.borrow());
-                    output_reader.set_physical_compaction(upper_limit.borrow());
+                // Reschedule ourselves if we have yielded mid-round, or have queued work to start.
+                if in_progress || (!shutdown && queued_upper != upper_limit) {
+                    activator.activate();
                 }
 
                 // Exert trace maintenance if we have been so requested.
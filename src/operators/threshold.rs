@@ -2,6 +2,8 @@
 //!
 //! The `distinct_total` and `distinct_total_u` operators are optimizations of the more general
 //! `distinct` and `distinct_u` operators for the case in which time is totally ordered.
+//! Similarly, the `distinct_values_total` operator optimizes `distinct_values`, and the
+//! `distinct_present` operator specializes `distinct` to collections of `Present` records.
 
 use timely::order::TotalOrder;
 use timely::dataflow::*;
@@ -10,15 +12,44 @@ use timely::dataflow::channels::pact::Pipeline;
 
 use crate::lattice::Lattice;
 use crate::{ExchangeData, Collection};
-use crate::difference::{Semigroup, Abelian};
+use crate::difference::{Semigroup, Abelian, Present};
 use crate::hashable::Hashable;
 use crate::collection::AsCollection;
-use crate::operators::arrange::{Arranged, ArrangeBySelf};
+use crate::operators::arrange::{Arranged, ArrangeByKey, ArrangeBySelf};
 use crate::trace::{BatchReader, Cursor, TraceReader};
 
 /// Extension trait for the `distinct` differential dataflow method.
 pub trait ThresholdTotal<G: Scope, K: ExchangeData, R: ExchangeData+Semigroup> where G::Timestamp: TotalOrder+Lattice+Ord {
-    /// Reduces the collection to one occurrence of each distinct element.
+    /// Determines the change in output weight of each element from its multiplicity before and after each change.
+    ///
+    /// The `thresh` closure is invoked with the key, its multiplicity after a change, and its multiplicity
+    /// before the change (absent if the key had no prior updates), and returns the change to the output
+    /// weight of the key, or `None` for no change. Unlike `threshold_total`, the output weights need not
+    /// be negatable, and the closure can report only the transitions that matter, for example when an
+    /// accumulated sum crosses a limit. The method is also available on arrangements by self, which
+    /// avoids arranging the collection again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::ThresholdTotal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report keys while the sum of their values exceeds twenty.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .explode(|x| Some((x % 2, x as isize)))
+    ///          .threshold_semigroup(|_key, new, old| {
+    ///              let was = old.map_or(false, |old| *old > 20);
+    ///              match (was, *new > 20) {
+    ///                  (false, true) => Some(1isize),
+    ///                  (true, false) => Some(-1isize),
+    ///                  _ => None,
+    ///              }
+    ///          })
+    ///          .assert_eq(&scope.new_collection_from(vec![1]).1);
+    /// });
+    /// ```
     fn threshold_semigroup<R2, F>(&self, thresh: F) -> Collection<G, K, R2>
     where
         R2: Semigroup,
@@ -78,6 +109,49 @@ pub trait ThresholdTotal<G: Scope, K: ExchangeData, R: ExchangeData+Semigroup> w
         self.threshold_total(|_,_| R2::from(1i8))
     }
 
+    /// Determines the output weight of each element from its change in multiplicity and its current output.
+    ///
+    /// The `policy` closure is invoked with the key, its multiplicity before and after each change (the former
+    /// absent if the key had no prior updates), and its current output weight (absent if zero). It returns the
+    /// new output weight, or `None` for zero. Because the current output is presented, the policy can express
+    /// thresholds whose result depends on history and not only on the current multiplicity.
+    ///
+    /// The operator maintains the current output weight of each key with a non-zero output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::AsCollection;
+    /// use differential_dataflow::operators::ThresholdTotal;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///     // keys enter once they have at least four occurrences, and leave with fewer than two.
+    ///     vec![(0u32, 0u64, 4isize), (1, 0, 3), (0, 1, -1), (0, 2, -2)]
+    ///         .to_stream(scope)
+    ///         .as_collection()
+    ///         .threshold_stateful(|_key, _old, new, output: Option<&isize>| {
+    ///             match output {
+    ///                 None if *new >= 4 => Some(1),
+    ///                 Some(_) if *new >= 2 => Some(1),
+    ///                 _ => None,
+    ///             }
+    ///         })
+    ///         .inner
+    ///         .capture()
+    /// });
+    ///
+    /// // key zero enters with four occurrences, stays with three, and leaves with one.
+    /// let mut output = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    /// output.sort();
+    /// assert_eq!(output, vec![(0, 0, 1), (0, 2, -1)]);
+    /// ```
+    fn threshold_stateful<R2, F>(&self, policy: F) -> Collection<G, K, R2>
+    where
+        R2: Abelian,
+        F: FnMut(&K,Option<&R>,&R,Option<&R2>)->Option<R2>+'static,
+        ;
 }
 
 impl<G: Scope, K: ExchangeData+Hashable, R: ExchangeData+Semigroup> ThresholdTotal<G, K, R> for Collection<G, K, R>
@@ -90,6 +164,15 @@ where G::Timestamp: TotalOrder+Lattice+Ord {
         self.arrange_by_self_named("Arrange: ThresholdTotal")
             .threshold_semigroup(thresh)
     }
+
+    fn threshold_stateful<R2, F>(&self, policy: F) -> Collection<G, K, R2>
+    where
+        R2: Abelian,
+        F: FnMut(&K,Option<&R>,&R,Option<&R2>)->Option<R2>+'static,
+    {
+        self.arrange_by_self_named("Arrange: ThresholdStateful")
+            .threshold_stateful(policy)
+    }
 }
 
 impl<G, K, T1> ThresholdTotal<G, K, T1::Diff> for Arranged<G, T1>
@@ -180,4 +263,224 @@ where
         })
         .as_collection()
     }
+
+    fn threshold_stateful<R2, F>(&self, mut policy: F) -> Collection<G, K, R2>
+    where
+        R2: Abelian,
+        F: for<'a> FnMut(T1::Key<'a>,Option<&T1::Diff>,&T1::Diff,Option<&R2>)->Option<R2>+'static,
+    {
+        // the current non-zero output weight of each key.
+        let mut outputs = std::collections::BTreeMap::<K, R2>::new();
+
+        // Consult `policy` for each change to the multiplicity of a key, and report the change to its output weight.
+        self.threshold_semigroup(move |key: &K, new: &T1::Diff, old: Option<&T1::Diff>| {
+            let result = policy(key, old, new, outputs.get(key));
+            let mut difference = result.clone().unwrap_or_else(R2::zero);
+            let previous = match result.filter(|r| !r.is_zero()) {
+                Some(result) => outputs.insert(key.clone(), result),
+                None => outputs.remove(key),
+            };
+            if let Some(previous) = previous {
+                difference.plus_equals(&previous.negate());
+            }
+            Some(difference)
+        })
+    }
+}
+
+/// Extension trait for the `distinct_values_total` differential dataflow method.
+pub trait DistinctValuesTotal<G: Scope, K: ExchangeData, V: ExchangeData, R: ExchangeData+Semigroup> where G::Timestamp: TotalOrder+Lattice+Ord {
+    /// Reduces the collection to one occurrence of each distinct value within each key.
+    ///
+    /// Rather than reconsider all values of a key when any of them change, the operator only looks up
+    /// the prior multiplicity of each changed `(key, val)` pair, and reports the pairs whose multiplicity
+    /// changes between zero and non-zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::operators::DistinctValuesTotal;
+    ///
+    /// ::timely::example(|scope| {
+    ///     // report at most one of each (key, val) pair.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .map(|x| (x / 3, x % 2))
+    ///          .distinct_values_total();
+    /// });
+    /// ```
+    fn distinct_values_total(&self) -> Collection<G, (K, V), isize> {
+        self.distinct_values_total_core()
+    }
+
+    /// Distinct values for general integer differences.
+    ///
+    /// This method allows `distinct_values_total` to produce collections whose difference
+    /// type is something other than an `isize` integer, for example perhaps an `i32`.
+    fn distinct_values_total_core<R2: Abelian+From<i8>>(&self) -> Collection<G, (K, V), R2>;
+}
+
+impl<G: Scope, K: ExchangeData+Hashable, V: ExchangeData, R: ExchangeData+Semigroup> DistinctValuesTotal<G, K, V, R> for Collection<G, (K, V), R>
+where G::Timestamp: TotalOrder+Lattice+Ord {
+    fn distinct_values_total_core<R2: Abelian+From<i8>>(&self) -> Collection<G, (K, V), R2> {
+        self.arrange_by_key_named("Arrange: DistinctValuesTotal")
+            .distinct_values_total_core()
+    }
+}
+
+impl<G, K, V, T1> DistinctValuesTotal<G, K, V, T1::Diff> for Arranged<G, T1>
+where
+    G: Scope<Timestamp=T1::Time>,
+    T1: for<'a> TraceReader<Key<'a>=&'a K, Val<'a>=&'a V>+Clone+'static,
+    K: ExchangeData,
+    V: ExchangeData,
+    T1::Time: TotalOrder,
+    T1::Diff: ExchangeData,
+{
+    fn distinct_values_total_core<R2: Abelian+From<i8>>(&self) -> Collection<G, (K, V), R2> {
+
+        let mut trace = self.trace.clone();
+        let mut buffer = Vec::new();
+
+        self.stream.unary_frontier(Pipeline, "DistinctValuesTotal", move |_,_| {
+
+            // tracks the upper limit of known-complete timestamps.
+            let mut upper_limit = timely::progress::frontier::Antichain::from_elem(<G::Timestamp as timely::progress::Timestamp>::minimum());
+
+            move |input, output| {
+
+                input.for_each(|capability, batches| {
+                    batches.swap(&mut buffer);
+                    let mut session = output.session(&capability);
+                    for batch in buffer.drain(..) {
+
+                        let mut batch_cursor = batch.cursor();
+                        let (mut trace_cursor, trace_storage) = trace.cursor_through(batch.lower().borrow()).unwrap();
+
+                        upper_limit.clone_from(batch.upper());
+
+                        while let Some(key) = batch_cursor.get_key(&batch) {
+
+                            trace_cursor.seek_key(&trace_storage, key);
+                            let trace_key = trace_cursor.get_key(&trace_storage) == Some(key);
+
+                            while let Some(val) = batch_cursor.get_val(&batch) {
+                                let mut count: Option<T1::Diff> = None;
+
+                                // Compute the multiplicity of this pair before the current batch.
+                                if trace_key {
+                                    trace_cursor.seek_val(&trace_storage, val);
+                                    if trace_cursor.get_val(&trace_storage) == Some(val) {
+                                        trace_cursor.map_times(&trace_storage, |_, diff| {
+                                            count.as_mut().map(|c| c.plus_equals(diff));
+                                            if count.is_none() { count = Some(diff.clone()); }
+                                        });
+                                    }
+                                }
+
+                                // Report the pair whenever its multiplicity changes between zero and non-zero.
+                                batch_cursor.map_times(&batch, |time, diff| {
+                                    let before = count.as_ref().map_or(false, |c| !c.is_zero());
+                                    if let Some(count) = &mut count {
+                                        count.plus_equals(diff);
+                                    }
+                                    else {
+                                        count = Some(diff.clone());
+                                    }
+                                    let after = count.as_ref().map_or(false, |c| !c.is_zero());
+                                    if before != after {
+                                        let difference = if after { R2::from(1i8) } else { R2::from(-1i8) };
+                                        session.give(((key.clone(), val.clone()), time.clone(), difference));
+                                    }
+                                });
+
+                                batch_cursor.step_val(&batch);
+                            }
+
+                            batch_cursor.step_key(&batch);
+                        }
+                    }
+                });
+
+                // tidy up the shared input trace.
+                trace.advance_upper(&mut upper_limit);
+                trace.set_logical_compaction(upper_limit.borrow());
+                trace.set_physical_compaction(upper_limit.borrow());
+            }
+        })
+        .as_collection()
+    }
+}
+
+/// Extension trait for the `distinct_present` differential dataflow method.
+pub trait DistinctPresent<G: Scope, D: ExchangeData> {
+    /// Reduces a collection of present records to one occurrence of each record at each time.
+    ///
+    /// As `Present` differences cannot be retracted, and their accumulation is idempotent, a record is
+    /// present from the first times at which it occurs. The operator reports each record at each time at
+    /// which it occurs, unless it has already reported the record at a lesser or equal time. Unlike
+    /// `distinct`, it neither arranges its input nor produces retractions, which suits computations such
+    /// as reachability, whose facts are only ever derived. The operator retains, for each record, the
+    /// times at which it reported it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{Map, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use differential_dataflow::AsCollection;
+    /// use differential_dataflow::input::Input;
+    /// use differential_dataflow::difference::Present;
+    /// use differential_dataflow::operators::DistinctPresent;
+    ///
+    /// let captured = ::timely::example(|scope| {
+    ///     // report each residue once.
+    ///     scope.new_collection_from(1 .. 10).1
+    ///          .inner
+    ///          .map(|(x, time, _diff)| (x % 3, time, Present))
+    ///          .as_collection()
+    ///          .distinct_present()
+    ///          .inner
+    ///          .capture()
+    /// });
+    ///
+    /// let mut output = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    /// output.sort();
+    /// assert_eq!(output, vec![(0, 0, Present), (1, 0, Present), (2, 0, Present)]);
+    /// ```
+    fn distinct_present(&self) -> Collection<G, D, Present>;
+}
+
+impl<G: Scope, D: ExchangeData+Hashable> DistinctPresent<G, D> for Collection<G, D, Present>
+where G::Timestamp: Lattice+Ord {
+    fn distinct_present(&self) -> Collection<G, D, Present> {
+
+        use std::collections::BTreeMap;
+        use timely::dataflow::channels::pact::Exchange;
+        use timely::progress::Antichain;
+
+        let exchange = Exchange::new(|update: &(D, G::Timestamp, Present)| update.0.hashed().into());
+        let mut buffer = Vec::new();
+
+        self.inner.unary(exchange, "DistinctPresent", move |_,_| {
+
+            // the times at which each record has been reported.
+            let mut reported: BTreeMap<D, Antichain<G::Timestamp>> = BTreeMap::new();
+
+            move |input, output| {
+                input.for_each(|capability, data| {
+                    data.swap(&mut buffer);
+                    let mut session = output.session(&capability);
+                    for (data, time, diff) in buffer.drain(..) {
+                        let times = reported.entry(data.clone()).or_insert_with(Antichain::new);
+                        if !times.less_equal(&time) {
+                            times.insert(time.clone());
+                            session.give((data, time, diff));
+                        }
+                    }
+                });
+            }
+        })
+        .as_collection()
+    }
 }
@@ -0,0 +1,143 @@
+//! Custom unary operators on collections.
+//!
+//! Operators that differential dataflow does not provide are usually written against the timely
+//! stream inside a collection, with `inner` and `as_collection`, and must maintain the invariants of
+//! collections by hand: updates are `(data, time, diff)` triples, each time must be greater or equal
+//! to that of the capability it is sent with, and zero differences carry no information. The
+//! `unary_frontier_collection` method presents the input as updates, and provides an output that
+//! checks the times of updates against their capabilities and discards zero differences.
+
+use timely::dataflow::{Scope, Stream};
+use timely::dataflow::channels::pact::ParallelizationContract;
+use timely::dataflow::operators::{Capability, Operator};
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::order::PartialOrder;
+use timely::progress::{Antichain, Timestamp};
+use timely::progress::frontier::AntichainRef;
+
+use crate::{Collection, Data};
+use crate::collection::AsCollection;
+use crate::difference::Semigroup;
+
+/// The input of a custom collection operator.
+pub struct UpdateInput<T, D, R> {
+    /// Received batches of updates, with capabilities for their times.
+    batches: Vec<(Capability<T>, Vec<(D, T, R)>)>,
+    /// The frontier of the input.
+    frontier: Antichain<T>,
+}
+
+impl<T: Timestamp, D, R> UpdateInput<T, D, R> {
+    /// Calls `logic` on each batch of updates received since the last call, with a capability for its time.
+    ///
+    /// Updates left in a batch after `logic` returns are discarded. Batches the logic does not visit,
+    /// by not calling this method, remain for its next invocation.
+    pub fn for_each<F: FnMut(&Capability<T>, &mut Vec<(D, T, R)>)>(&mut self, mut logic: F) {
+        for (capability, mut updates) in self.batches.drain(..) {
+            logic(&capability, &mut updates);
+        }
+    }
+
+    /// The frontier of the input: the times at which further updates may arrive.
+    pub fn frontier(&self) -> AntichainRef<T> {
+        self.frontier.borrow()
+    }
+}
+
+/// The output of a custom collection operator.
+pub struct UpdateOutput<T, D, R> {
+    /// Updates to send, grouped by the capability they are sent with.
+    staged: Vec<(Capability<T>, Vec<(D, T, R)>)>,
+}
+
+impl<T: Timestamp, D, R: Semigroup> UpdateOutput<T, D, R> {
+    /// Sends `update` with `capability`, unless its difference is zero.
+    ///
+    /// The method panics if the time of `update` is not greater or equal to that of `capability`.
+    pub fn give(&mut self, capability: &Capability<T>, update: (D, T, R)) {
+        assert!(
+            capability.time().less_equal(&update.1),
+            "update at {:?} sent with capability for {:?}", update.1, capability.time(),
+        );
+        if update.2.is_zero() { return; }
+        match self.staged.last_mut() {
+            Some((staged, updates)) if staged.time() == capability.time() => updates.push(update),
+            _ => self.staged.push((capability.clone(), vec![update])),
+        }
+    }
+
+    /// Sends each of `updates` with `capability`, as `give` does.
+    pub fn give_iterator<I: IntoIterator<Item=(D, T, R)>>(&mut self, capability: &Capability<T>, updates: I) {
+        for update in updates {
+            self.give(capability, update);
+        }
+    }
+}
+
+impl<G, D, R> Collection<G, D, R>
+where
+    G: Scope,
+    G::Timestamp: Data,
+    D: Data,
+    R: Semigroup,
+{
+    /// Creates a custom operator from this collection to another, with access to the input frontier.
+    ///
+    /// The method mirrors timely's `unary_frontier`: `constructor` is called with a capability for
+    /// the least time and information about the operator, and returns the logic that is called each
+    /// time the operator is scheduled. The logic receives the input as batches of updates and its
+    /// frontier, and an output that checks the updates it is given, as described by `UpdateOutput`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use differential_dataflow::input::Input;
+    ///
+    /// ::timely::example(|scope| {
+    ///
+    ///     let x = scope.new_collection_from(1 .. 10u32).1;
+    ///
+    ///     // doubles each record, as `map` would.
+    ///     x.unary_frontier_collection(Pipeline, "Double", |_capability, _info| {
+    ///          move |input, output| {
+    ///              input.for_each(|capability, updates| {
+    ///                  output.give_iterator(capability, updates.drain(..).map(|(x, t, r)| (2 * x, t, r)));
+    ///              });
+    ///          }
+    ///      })
+    ///      .assert_eq(&x.map(|x| 2 * x));
+    /// });
+    /// ```
+    pub fn unary_frontier_collection<D2, R2, P, B, L>(&self, pact: P, name: &str, constructor: B) -> Collection<G, D2, R2>
+    where
+        D2: Data,
+        R2: Semigroup,
+        P: ParallelizationContract<G::Timestamp, Vec<(D, G::Timestamp, R)>>,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo)->L,
+        L: FnMut(&mut UpdateInput<G::Timestamp, D, R>, &mut UpdateOutput<G::Timestamp, D2, R2>)+'static,
+    {
+        let stream: Stream<G, (D2, G::Timestamp, R2)> = self.inner.unary_frontier(pact, name, move |capability, info| {
+
+            let mut logic = constructor(capability, info);
+            let mut updates = UpdateInput { batches: Vec::new(), frontier: Antichain::new() };
+            let mut staged = UpdateOutput { staged: Vec::new() };
+
+            move |input, output| {
+                input.for_each(|capability, data| {
+                    let mut batch = Vec::new();
+                    data.swap(&mut batch);
+                    updates.batches.push((capability.retain(), batch));
+                });
+                updates.frontier.clone_from(&input.frontier().frontier().to_owned());
+
+                logic(&mut updates, &mut staged);
+
+                for (capability, mut batch) in staged.staged.drain(..) {
+                    output.session(&capability).give_container(&mut batch);
+                }
+            }
+        });
+        stream.as_collection()
+    }
+}
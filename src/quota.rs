@@ -0,0 +1,165 @@
+//! Accounting of the records held by each dataflow, with quotas and backpressure.
+//!
+//! Arrangements hold their updates in batchers, until their times are complete, and then in traces.
+//! A computation that ingests data faster than it can retire it accumulates updates in both, and may
+//! exhaust its memory. `MemoryQuotas` follows the size accounting that batchers and traces log, and
+//! aggregates the number of records held by the arrangements of each dataflow. A dataflow can be given
+//! a quota, and a `Backpressure` signal reports when its records exceed that quota, so that the inputs
+//! of the dataflow can defer ingestion until merging and compaction have caught up.
+//!
+//! The accounting attributes arrangement operators to dataflows using timely's operator logging, and
+//! so must be installed before the dataflows it accounts for are built. Installing it replaces any
+//! loggers registered under the names `"timely"` and `"differential/arrange"`.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::arrange::ArrangeBySelf;
+//! use differential_dataflow::quota::MemoryQuotas;
+//!
+//! ::timely::execute_directly(|worker| {
+//!
+//!     let quotas = MemoryQuotas::install(worker);
+//!     let dataflow = worker.next_dataflow_index();
+//!     quotas.set_quota(dataflow, 1_000);
+//!
+//!     let mut input = worker.dataflow::<u32,_,_>(|scope| {
+//!         let (input, data) = scope.new_collection::<u32, isize>();
+//!         data.arrange_by_self();
+//!         input
+//!     }).with_backpressure(quotas.signal(dataflow));
+//!
+//!     for round in 0 .. 10u32 {
+//!         // wait for the dataflow to retire records, rather than ingest more.
+//!         while input.backpressured() {
+//!             worker.step();
+//!         }
+//!         for datum in 0 .. 100 {
+//!             input.insert(round * 100 + datum);
+//!         }
+//!         input.advance_to(round + 1);
+//!         input.flush();
+//!         worker.step();
+//!     }
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use timely::communication::Allocate;
+use timely::logging::TimelyEvent;
+use timely::worker::Worker;
+
+use crate::logging::DifferentialEvent;
+
+/// Per-dataflow accounting of the records held by arrangements, and their quotas.
+#[derive(Clone, Default)]
+pub struct MemoryQuotas {
+    accounts: Rc<RefCell<Accounts>>,
+}
+
+#[derive(Default)]
+struct Accounts {
+    /// The dataflow index of each operator, by its global identifier.
+    dataflows: HashMap<usize, usize>,
+    /// The records held by the arrangements of each dataflow.
+    records: HashMap<usize, isize>,
+    /// The number of records each dataflow may hold before it is backpressured.
+    quotas: HashMap<usize, usize>,
+}
+
+impl Accounts {
+    /// Charges the dataflow of `operator` with a change in records.
+    fn charge(&mut self, operator: usize, diff: isize) {
+        if let Some(dataflow) = self.dataflows.get(&operator) {
+            *self.records.entry(*dataflow).or_insert(0) += diff;
+        }
+    }
+}
+
+impl MemoryQuotas {
+    /// Installs accounting in `worker`, which applies to dataflows built afterwards.
+    pub fn install<A: Allocate>(worker: &mut Worker<A>) -> Self {
+        let quotas = MemoryQuotas::default();
+
+        let accounts = quotas.accounts.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            let mut accounts = accounts.borrow_mut();
+            for (_, _, event) in data.iter() {
+                if let TimelyEvent::Operates(operates) = event {
+                    accounts.dataflows.insert(operates.id, operates.addr[0]);
+                }
+            }
+        });
+
+        let accounts = quotas.accounts.clone();
+        worker.log_register().insert::<DifferentialEvent,_>("differential/arrange", move |_time, data| {
+            let mut accounts = accounts.borrow_mut();
+            for (_, _, event) in data.iter() {
+                match event {
+                    DifferentialEvent::Batcher(event) => accounts.charge(event.operator, event.records_diff),
+                    DifferentialEvent::Batch(event) => accounts.charge(event.operator, event.length as isize),
+                    DifferentialEvent::Drop(event) => accounts.charge(event.operator, -(event.length as isize)),
+                    DifferentialEvent::Merge(event) => {
+                        // A completed merge replaces its inputs with its output.
+                        if let Some(length) = event.complete {
+                            let diff = length as isize - (event.length1 + event.length2) as isize;
+                            accounts.charge(event.operator, diff);
+                        }
+                    },
+                    _ => { },
+                }
+            }
+        });
+
+        quotas
+    }
+
+    /// Limits the records the arrangements of `dataflow` may hold before it is backpressured.
+    pub fn set_quota(&self, dataflow: usize, records: usize) {
+        self.accounts.borrow_mut().quotas.insert(dataflow, records);
+    }
+
+    /// Removes the quota of `dataflow`, which is then never backpressured.
+    pub fn clear_quota(&self, dataflow: usize) {
+        self.accounts.borrow_mut().quotas.remove(&dataflow);
+    }
+
+    /// The number of records held by the arrangements of `dataflow`.
+    ///
+    /// The count reflects the events logged so far, which timely delivers in batches as the worker runs.
+    pub fn records(&self, dataflow: usize) -> usize {
+        self.accounts.borrow().records.get(&dataflow).map(|records| (*records).max(0) as usize).unwrap_or(0)
+    }
+
+    /// Indicates whether `dataflow` holds more records than its quota.
+    pub fn exceeded(&self, dataflow: usize) -> bool {
+        let quota = self.accounts.borrow().quotas.get(&dataflow).cloned();
+        quota.map(|quota| self.records(dataflow) > quota).unwrap_or(false)
+    }
+
+    /// A signal that reports whether `dataflow` exceeds its quota, for use by its inputs.
+    pub fn signal(&self, dataflow: usize) -> Backpressure {
+        Backpressure { quotas: self.clone(), dataflow }
+    }
+}
+
+/// A signal indicating that a dataflow holds more records than its quota.
+///
+/// Inputs and sources consult the signal before introducing further data, and yield to the worker while
+/// it is engaged, allowing the dataflow to merge and compact the data it holds.
+#[derive(Clone)]
+pub struct Backpressure {
+    quotas: MemoryQuotas,
+    dataflow: usize,
+}
+
+impl Backpressure {
+    /// Indicates whether the dataflow currently exceeds its quota.
+    pub fn engaged(&self) -> bool {
+        self.quotas.exceeded(self.dataflow)
+    }
+}
@@ -0,0 +1,91 @@
+//! Sources of wall-clock time for trace maintenance.
+//!
+//! Trace maintenance is driven by update counts and exertion logic, and reads no time of its own.
+//! Components that do need wall-clock time read it from a `Clock`, rather than from `Instant::now`,
+//! which panics on targets without a system clock such as `wasm32-unknown-unknown`. Such targets can
+//! supply a clock of their own, and the system clock is only read by code that asks a `SystemClock`.
+//!
+//! Times are reported as durations since an origin fixed by the clock, so that clocks can be
+//! implemented without access to `Instant`.
+//!
+//! Some trace maintenance depends on wall-clock time: a `MergeScheduler` grants idle merge effort in
+//! rounds of a period, compaction policies of trace handles retain history for intervals of time, and
+//! arrange operators may yield after a slice of time. With the system clock, their behavior depends on
+//! the timing of a run, which makes it hard to test and to reproduce. A `SimulatedClock` installed with
+//! `Config::clock` replaces the system clock for all of these, and its time advances only when told to.
+//! A program that reproduces a stall of compaction can advance it in step with its input, so that the
+//! effort granted and the history retained are the same in each run.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use differential_dataflow::trace::clock::SimulatedClock;
+//! use differential_dataflow::trace::scheduler::MergeScheduler;
+//!
+//! let clock = Arc::new(SimulatedClock::new());
+//! let scheduler = MergeScheduler::with_clock(100, Duration::from_secs(1), clock.clone());
+//!
+//! // the budget is spent, and is only renewed once the clock advances by the period.
+//! assert_eq!(scheduler.grant(150), 100);
+//! assert_eq!(scheduler.grant(50), 0);
+//! clock.advance(Duration::from_secs(1));
+//! assert_eq!(scheduler.grant(50), 50);
+//! ```
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A source of wall-clock time.
+pub trait Clock: std::fmt::Debug + Send + Sync + 'static {
+    /// The time elapsed since the origin of the clock.
+    ///
+    /// Successive calls must report non-decreasing times.
+    fn now(&self) -> Duration;
+}
+
+/// The system clock, as reported by `Instant::now`.
+///
+/// The origin of the clock is the first time any `SystemClock` is read, and so all system clocks of
+/// a process report comparable times.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A clock whose time advances only when told to.
+///
+/// The clock reports the total of all calls to `advance` as the time since its origin.
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    elapsed: Mutex<Duration>,
+}
+
+impl SimulatedClock {
+    /// A clock at its origin, which does not advance.
+    pub fn new() -> Self {
+        SimulatedClock {
+            elapsed: Mutex::new(Duration::from_secs(0)),
+        }
+    }
+    /// Advances the time of the clock by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().expect("simulated clock lock poisoned") += duration;
+    }
+    /// The total time the clock has been advanced by.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().expect("simulated clock lock poisoned")
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Duration {
+        self.elapsed()
+    }
+}
@@ -0,0 +1,272 @@
+//! Cursor adapters that present derived views of other cursors.
+//!
+//! These adapters take care of positioning their underlying cursors and of routing storage to them,
+//! so that operators and trace wrappers can compose them rather than implement `Cursor` themselves.
+//! Each adapter uses the storage of the cursors it wraps: `MapCursor` and `FilterCursor` use the
+//! storage of their cursor, and `ChainCursor` uses a pair of the storages of its two cursors.
+
+use timely::progress::Timestamp;
+
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+
+use super::Cursor;
+
+/// A cursor that transforms the times and differences of another cursor.
+///
+/// The keys and values are those of the wrapped cursor. For each update of the wrapped cursor,
+/// `logic` produces the time and difference to present in its place.
+#[derive(Debug)]
+pub struct MapCursor<C, F> {
+    cursor: C,
+    logic: F,
+}
+
+impl<C: Cursor, F> MapCursor<C, F> {
+    /// Creates a cursor presenting the updates of `cursor` as transformed by `logic`.
+    pub fn new(cursor: C, logic: F) -> Self {
+        MapCursor { cursor, logic }
+    }
+    /// Unwraps the underlying cursor.
+    pub fn into_inner(self) -> C { self.cursor }
+}
+
+impl<C, T, R, F> Cursor for MapCursor<C, F>
+where
+    C: Cursor,
+    T: Timestamp+Lattice+Ord+Clone,
+    R: Semigroup,
+    F: FnMut(&C::Time, &C::Diff)->(T, R),
+{
+    type Key<'a> = C::Key<'a>;
+    type KeyOwned = C::KeyOwned;
+    type Val<'a> = C::Val<'a>;
+    type Time = T;
+    type Diff = R;
+
+    type Storage = C::Storage;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(storage) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(storage) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { self.cursor.key(storage) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { self.cursor.val(storage) }
+
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        let map = &mut self.logic;
+        self.cursor.map_times(storage, |time, diff| {
+            let (time, diff) = map(time, diff);
+            logic(&time, &diff)
+        })
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
+    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) { self.cursor.seek_key(storage, key) }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage) }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) { self.cursor.seek_val(storage, val) }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(storage) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(storage) }
+}
+
+/// A cursor that presents the keys and values of another cursor that satisfy a predicate.
+///
+/// The cursor only rests on values for which `logic` returns true, and skips keys without such
+/// values entirely, so that consumers never observe the filtered data.
+#[derive(Debug)]
+pub struct FilterCursor<C, F> {
+    cursor: C,
+    logic: F,
+}
+
+impl<C, F> FilterCursor<C, F>
+where
+    C: Cursor,
+    F: FnMut(C::Key<'_>, C::Val<'_>)->bool,
+{
+    /// Creates a cursor presenting the keys and values of `cursor` that satisfy `logic`.
+    ///
+    /// The cursor is positioned at the first such key and value, which requires `storage`.
+    pub fn new(cursor: C, logic: F, storage: &C::Storage) -> Self {
+        let mut result = FilterCursor { cursor, logic };
+        result.skip_keys(storage);
+        result
+    }
+    /// Unwraps the underlying cursor.
+    pub fn into_inner(self) -> C { self.cursor }
+
+    /// Advances the cursor to the next value that satisfies the predicate, if any.
+    fn skip_vals(&mut self, storage: &C::Storage) {
+        while let Some(val) = self.cursor.get_val(storage) {
+            if (self.logic)(self.cursor.key(storage), val) { break; }
+            self.cursor.step_val(storage);
+        }
+    }
+    /// Advances the cursor to the next key with a value that satisfies the predicate, if any.
+    fn skip_keys(&mut self, storage: &C::Storage) {
+        while self.cursor.key_valid(storage) {
+            self.skip_vals(storage);
+            if self.cursor.val_valid(storage) { break; }
+            self.cursor.step_key(storage);
+        }
+    }
+}
+
+impl<C, F> Cursor for FilterCursor<C, F>
+where
+    C: Cursor,
+    F: FnMut(C::Key<'_>, C::Val<'_>)->bool,
+{
+    type Key<'a> = C::Key<'a>;
+    type KeyOwned = C::KeyOwned;
+    type Val<'a> = C::Val<'a>;
+    type Time = C::Time;
+    type Diff = C::Diff;
+
+    type Storage = C::Storage;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(storage) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(storage) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { self.cursor.key(storage) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { self.cursor.val(storage) }
+
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, logic: L) {
+        self.cursor.map_times(storage, logic)
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage); self.skip_keys(storage); }
+    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) { self.cursor.seek_key(storage, key); self.skip_keys(storage); }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage); self.skip_vals(storage); }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) { self.cursor.seek_val(storage, val); self.skip_vals(storage); }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(storage); self.skip_keys(storage); }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(storage); self.skip_vals(storage); }
+}
+
+/// A cursor that presents the updates of two cursors, as if they were one.
+///
+/// The two cursors may be of different types, but must present the same types of keys, values,
+/// times, and differences. Unlike `CursorList`, the cursors keep no state beyond their positions,
+/// and the minimum key and value are determined as needed.
+#[derive(Debug)]
+pub struct ChainCursor<C1, C2> {
+    cursor1: C1,
+    cursor2: C2,
+}
+
+impl<C1, C2> ChainCursor<C1, C2> {
+    /// Creates a cursor presenting the updates of both `cursor1` and `cursor2`.
+    pub fn new(cursor1: C1, cursor2: C2) -> Self {
+        ChainCursor { cursor1, cursor2 }
+    }
+    /// Unwraps the underlying cursors.
+    pub fn into_inner(self) -> (C1, C2) { (self.cursor1, self.cursor2) }
+}
+
+impl<C1, C2> ChainCursor<C1, C2>
+where
+    C1: Cursor,
+    C2: for<'a> Cursor<Key<'a>=C1::Key<'a>, KeyOwned=C1::KeyOwned, Val<'a>=C1::Val<'a>, Time=C1::Time, Diff=C1::Diff>,
+{
+    /// Indicates which cursors are positioned at the current key.
+    fn at_key(&self, storage: &(C1::Storage, C2::Storage)) -> (bool, bool) {
+        match (self.cursor1.get_key(&storage.0), self.cursor2.get_key(&storage.1)) {
+            (Some(key1), Some(key2)) => (key1 <= key2, key2 <= key1),
+            (key1, key2) => (key1.is_some(), key2.is_some()),
+        }
+    }
+    /// Indicates which cursors are positioned at the current key and value.
+    fn at_val(&self, storage: &(C1::Storage, C2::Storage)) -> (bool, bool) {
+        let (at1, at2) = self.at_key(storage);
+        let val1 = if at1 { self.cursor1.get_val(&storage.0) } else { None };
+        let val2 = if at2 { self.cursor2.get_val(&storage.1) } else { None };
+        match (val1, val2) {
+            (Some(val1), Some(val2)) => (val1 <= val2, val2 <= val1),
+            (val1, val2) => (val1.is_some(), val2.is_some()),
+        }
+    }
+}
+
+impl<C1, C2> Cursor for ChainCursor<C1, C2>
+where
+    C1: Cursor,
+    C2: for<'a> Cursor<Key<'a>=C1::Key<'a>, KeyOwned=C1::KeyOwned, Val<'a>=C1::Val<'a>, Time=C1::Time, Diff=C1::Diff>,
+{
+    type Key<'a> = C1::Key<'a>;
+    type KeyOwned = C1::KeyOwned;
+    type Val<'a> = C1::Val<'a>;
+    type Time = C1::Time;
+    type Diff = C1::Diff;
+
+    type Storage = (C1::Storage, C2::Storage);
+
+    #[inline]
+    fn key_valid(&self, storage: &Self::Storage) -> bool {
+        self.cursor1.key_valid(&storage.0) || self.cursor2.key_valid(&storage.1)
+    }
+    #[inline]
+    fn val_valid(&self, storage: &Self::Storage) -> bool {
+        let (at1, at2) = self.at_val(storage);
+        at1 || at2
+    }
+
+    #[inline]
+    fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> {
+        let (at1, _) = self.at_key(storage);
+        if at1 { self.cursor1.key(&storage.0) } else { self.cursor2.key(&storage.1) }
+    }
+    #[inline]
+    fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> {
+        let (at1, _) = self.at_val(storage);
+        if at1 { self.cursor1.val(&storage.0) } else { self.cursor2.val(&storage.1) }
+    }
+
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        let (at1, at2) = self.at_val(storage);
+        if at1 { self.cursor1.map_times(&storage.0, &mut logic); }
+        if at2 { self.cursor2.map_times(&storage.1, &mut logic); }
+    }
+
+    #[inline]
+    fn step_key(&mut self, storage: &Self::Storage) {
+        let (at1, at2) = self.at_key(storage);
+        if at1 { self.cursor1.step_key(&storage.0); }
+        if at2 { self.cursor2.step_key(&storage.1); }
+    }
+    #[inline]
+    fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) {
+        self.cursor1.seek_key(&storage.0, key);
+        self.cursor2.seek_key(&storage.1, key);
+    }
+
+    #[inline]
+    fn step_val(&mut self, storage: &Self::Storage) {
+        let (at1, at2) = self.at_val(storage);
+        if at1 { self.cursor1.step_val(&storage.0); }
+        if at2 { self.cursor2.step_val(&storage.1); }
+    }
+    #[inline]
+    fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) {
+        let (at1, at2) = self.at_key(storage);
+        if at1 { self.cursor1.seek_val(&storage.0, val); }
+        if at2 { self.cursor2.seek_val(&storage.1, val); }
+    }
+
+    #[inline]
+    fn rewind_keys(&mut self, storage: &Self::Storage) {
+        self.cursor1.rewind_keys(&storage.0);
+        self.cursor2.rewind_keys(&storage.1);
+    }
+    #[inline]
+    fn rewind_vals(&mut self, storage: &Self::Storage) {
+        let (at1, at2) = self.at_key(storage);
+        if at1 { self.cursor1.rewind_vals(&storage.0); }
+        if at2 { self.cursor2.rewind_vals(&storage.1); }
+    }
+}
@@ -10,8 +10,10 @@ use crate::difference::Semigroup;
 use crate::lattice::Lattice;
 
 pub mod cursor_list;
+pub mod combinators;
 
 pub use self::cursor_list::CursorList;
+pub use self::combinators::{MapCursor, FilterCursor, ChainCursor};
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
@@ -118,6 +120,13 @@ pub trait Cursor {
     /// Rewinds the cursor to the first value for current key.
     fn rewind_vals(&mut self, storage: &Self::Storage);
 
+    /// Converts the cursor into an iterator over its remaining keys.
+    ///
+    /// The iterator only steps between keys, and does not visit the values or times of each key.
+    fn into_keys(self, storage: &Self::Storage) -> Keys<'_, Self> where Self: Sized {
+        Keys { cursor: self, storage }
+    }
+
     /// Rewinds the cursor and outputs its contents to a Vec
     fn to_vec<V, F>(&mut self, from: F, storage: &Self::Storage) -> Vec<((Self::KeyOwned, V), Vec<(Self::Time, Self::Diff)>)>
     where 
@@ -140,3 +149,20 @@ pub trait Cursor {
         out
     }
 }
+
+/// An iterator over the keys of a cursor, produced by `Cursor::into_keys`.
+///
+/// Keys are reported if the cursor has updates for them, even if the updates accumulate to zero.
+pub struct Keys<'a, C: Cursor> {
+    cursor: C,
+    storage: &'a C::Storage,
+}
+
+impl<'a, C: Cursor> Iterator for Keys<'a, C> {
+    type Item = C::Key<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.cursor.get_key(self.storage)?;
+        self.cursor.step_key(self.storage);
+        Some(key)
+    }
+}
@@ -0,0 +1,225 @@
+//! A zero-copy archive format for batches, based on `rkyv`.
+//!
+//! A `BatchArchive` lays out the contents of a batch as flat sequences of keys, values, and
+//! `(time, diff)` updates, with offsets delimiting the values of each key and the updates of each
+//! value, as the ordered batches do. Its `rkyv` archive can be read in place, for example from a
+//! memory-mapped file, without deserializing it: `BatchArchive::access` validates the bytes and
+//! presents them as an `ArchivedBatchArchive`, whose contents are navigated by index.
+//!
+//! Unlike abomonation, the archive is validated before it is read, and so corrupted or malicious
+//! bytes result in an error rather than undefined behavior. The bytes begin with a versioned
+//! `encoding::Header`, so that later versions of the crate can read archives written by this one.
+//!
+//! This module is available with the `rkyv` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::trace::{BatchReader, Trace};
+//! use differential_dataflow::trace::implementations::ValSpine;
+//! use differential_dataflow::trace::implementations::archive::BatchArchive;
+//! use differential_dataflow::trace::testing;
+//!
+//! type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+//!
+//! let batch = testing::random_batches::<Builder>(0, 1, 100, 10, 10).pop().unwrap();
+//!
+//! let bytes = BatchArchive::from_batch(&batch, |v| *v).to_bytes();
+//! let archived = BatchArchive::<u64, u64, u64, i64>::access(&bytes).unwrap();
+//! assert_eq!(archived.updates.len(), batch.len());
+//!
+//! let rebuilt = BatchArchive::deserialize_from(archived).into_batch::<Builder>();
+//! assert_eq!(testing::contents(&rebuilt, |v| *v), testing::contents(&batch, |v| *v));
+//! ```
+
+use std::ops::Range;
+
+use rkyv::{Archive, Archived, Deserialize, Serialize};
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::bytecheck::CheckBytes;
+
+use timely::progress::{Antichain, Timestamp};
+
+use crate::trace::{BatchReader, Builder, Cursor};
+use crate::trace::cursor::MyTrait;
+use super::encoding::{Format, Header, HEADER_LEN, unsupported_version};
+
+/// The scratch space to reserve when serializing an archive.
+const SCRATCH: usize = 4096;
+/// The version of the layout of archives.
+const VERSION: u16 = 1;
+
+/// The contents of a batch, laid out for zero-copy archiving.
+#[derive(Archive, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[archive(check_bytes)]
+pub struct BatchArchive<K, V, T, R> {
+    /// Lower frontier of the batch's description.
+    pub lower: Vec<T>,
+    /// Upper frontier of the batch's description.
+    pub upper: Vec<T>,
+    /// Compaction frontier of the batch's description.
+    pub since: Vec<T>,
+    /// Keys, in increasing order.
+    pub keys: Vec<K>,
+    /// Offsets into `vals`: the values of `keys[i]` are `vals[keys_offs[i] .. keys_offs[i+1]]`.
+    pub keys_offs: Vec<u64>,
+    /// Values, in increasing order within each key.
+    pub vals: Vec<V>,
+    /// Offsets into `updates`: the updates of `vals[i]` are `updates[vals_offs[i] .. vals_offs[i+1]]`.
+    pub vals_offs: Vec<u64>,
+    /// Pairs of times and differences.
+    pub updates: Vec<(T, R)>,
+}
+
+impl<K, V, T, R> BatchArchive<K, V, T, R> {
+
+    /// Lays out the contents of `batch`, converting its values to owned values with `val`.
+    pub fn from_batch<B, F>(batch: &B, val: F) -> Self
+    where
+        B: BatchReader<KeyOwned=K, Time=T, Diff=R>,
+        F: Fn(B::Val<'_>) -> V,
+    {
+        let description = batch.description();
+        let mut archive = BatchArchive {
+            lower: description.lower().elements().to_vec(),
+            upper: description.upper().elements().to_vec(),
+            since: description.since().elements().to_vec(),
+            keys: Vec::new(),
+            keys_offs: vec![0],
+            vals: Vec::new(),
+            vals_offs: vec![0],
+            updates: Vec::with_capacity(batch.len()),
+        };
+
+        let mut cursor = batch.cursor();
+        while let Some(key) = cursor.get_key(batch) {
+            archive.keys.push(key.into_owned());
+            while let Some(v) = cursor.get_val(batch) {
+                archive.vals.push(val(v));
+                let updates = &mut archive.updates;
+                cursor.map_times(batch, |time, diff| updates.push((time.clone(), diff.clone())));
+                archive.vals_offs.push(archive.updates.len() as u64);
+                cursor.step_val(batch);
+            }
+            archive.keys_offs.push(archive.vals.len() as u64);
+            cursor.step_key(batch);
+        }
+        archive
+    }
+
+    /// Builds a batch with the archived contents and description.
+    pub fn into_batch<Bu>(self) -> Bu::Output
+    where
+        K: Clone,
+        V: Clone,
+        Bu: Builder<Input=((K, V), T, R), Time=T>,
+        T: Timestamp,
+    {
+        let mut builder = Bu::with_capacity(self.keys.len(), self.vals.len(), self.updates.len());
+        let mut updates = self.updates.into_iter();
+        let mut vals = self.vals.into_iter().zip(self.vals_offs.windows(2));
+        for (key, offs) in self.keys.into_iter().zip(self.keys_offs.windows(2)) {
+            for (val, upd_offs) in (&mut vals).take((offs[1] - offs[0]) as usize) {
+                for (time, diff) in (&mut updates).take((upd_offs[1] - upd_offs[0]) as usize) {
+                    builder.push(((key.clone(), val.clone()), time, diff));
+                }
+            }
+        }
+        builder.done(antichain(self.lower), antichain(self.upper), antichain(self.since))
+    }
+
+    /// Serializes the archive into bytes that `access` can read in place.
+    pub fn to_bytes(&self) -> rkyv::AlignedVec
+    where
+        Self: Serialize<AllocSerializer<SCRATCH>>,
+    {
+        let archive = rkyv::to_bytes::<_, SCRATCH>(self).expect("serializing to memory cannot fail");
+        let mut bytes = rkyv::AlignedVec::with_capacity(HEADER_LEN + archive.len());
+        bytes.extend_from_slice(&Header { format: Format::Archive, version: VERSION }.encode());
+        bytes.extend_from_slice(&archive);
+        bytes
+    }
+
+    /// Validates `bytes` as an archive, and presents its contents in place.
+    ///
+    /// Besides the layout of the bytes, the method validates the offsets of the archive: they must start
+    /// at zero, not decrease, and end at the number of values or updates they delimit, so that reading
+    /// the values and updates of any key or value stays in bounds. The bytes must be aligned as they were
+    /// when produced by `to_bytes`.
+    pub fn access<'a>(bytes: &'a [u8]) -> Result<&'a ArchivedBatchArchive<K, V, T, R>, String>
+    where
+        K: Archive,
+        V: Archive,
+        T: Archive,
+        R: Archive,
+        ArchivedBatchArchive<K, V, T, R>: CheckBytes<DefaultValidator<'a>>,
+    {
+        match Header::decode_version(Format::Archive, bytes).map_err(|error| error.to_string())? {
+            (VERSION, payload) => {
+                let archived = rkyv::check_archived_root::<Self>(payload).map_err(|error| error.to_string())?;
+                check_offsets("keys_offs", &archived.keys_offs, archived.keys.len(), archived.vals.len())?;
+                check_offsets("vals_offs", &archived.vals_offs, archived.vals.len(), archived.updates.len())?;
+                Ok(archived)
+            },
+            (version, _) => Err(unsupported_version(Format::Archive, version).to_string()),
+        }
+    }
+
+    /// Deserializes an archive read in place into an owned archive.
+    pub fn deserialize_from(archived: &ArchivedBatchArchive<K, V, T, R>) -> Self
+    where
+        K: Archive,
+        V: Archive,
+        T: Archive,
+        R: Archive,
+        ArchivedBatchArchive<K, V, T, R>: Deserialize<Self, rkyv::Infallible>,
+    {
+        match archived.deserialize(&mut rkyv::Infallible) {
+            Ok(archive) => archive,
+            Err(infallible) => match infallible { },
+        }
+    }
+}
+
+/// Checks that `offsets` delimit the ranges of `targets` items that belong to each of `items` items.
+fn check_offsets(name: &str, offsets: &[u64], items: usize, targets: usize) -> Result<(), String> {
+    if offsets.len() != items + 1 {
+        return Err(format!("{} has {} offsets for {} items", name, offsets.len(), items));
+    }
+    if offsets[0] != 0 || offsets.windows(2).any(|offs| offs[0] > offs[1]) {
+        return Err(format!("{} does not increase from zero", name));
+    }
+    if offsets[items] != targets as u64 {
+        return Err(format!("{} ends at {} rather than {}", name, offsets[items], targets));
+    }
+    Ok(())
+}
+
+/// Forms an antichain from its elements.
+fn antichain<T: Timestamp>(elements: Vec<T>) -> Antichain<T> {
+    let mut antichain = Antichain::new();
+    for element in elements {
+        antichain.insert(element);
+    }
+    antichain
+}
+
+impl<K: Archive, V: Archive, T: Archive, R: Archive> ArchivedBatchArchive<K, V, T, R> {
+    /// The positions in `vals` of the values of the key at position `key` in `keys`.
+    pub fn vals_range(&self, key: usize) -> Range<usize> {
+        (self.keys_offs[key] as usize) .. (self.keys_offs[key+1] as usize)
+    }
+    /// The positions in `updates` of the updates of the value at position `val` in `vals`.
+    pub fn updates_range(&self, val: usize) -> Range<usize> {
+        (self.vals_offs[val] as usize) .. (self.vals_offs[val+1] as usize)
+    }
+    /// The values of the key at position `key` in `keys`.
+    pub fn vals_of(&self, key: usize) -> &[Archived<V>] {
+        &self.vals[self.vals_range(key)]
+    }
+    /// The updates of the value at position `val` in `vals`.
+    pub fn updates_of(&self, val: usize) -> &[Archived<(T, R)>] {
+        &self.updates[self.updates_range(val)]
+    }
+}
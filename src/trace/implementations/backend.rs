@@ -0,0 +1,380 @@
+//! Traces whose batches are recorded in external storage.
+//!
+//! A `Spine` holds its batches in memory, and a dataflow that restarts must rebuild its arrangements
+//! from their inputs. The `TraceBackend` trait describes storage for batches, each an interval of
+//! times with the updates of that interval, that can be written, read, removed, and listed. The
+//! `Backed` trace wraps another trace, and writes the batches inserted into it to a backend. A trace
+//! can be restored from the backend with `Backed::restore`, which inserts the stored batches.
+//!
+//! In `WriteMode::Through`, each batch is written as it is inserted. In `WriteMode::Back`, inserted
+//! batches are retained and written only by `Backed::flush` or `Backed::checkpoint`, which allows the
+//! application to choose when to pay for writes, at the cost of losing batches not yet written.
+//!
+//! The wrapped trace merges and compacts its batches in memory, and the backend retains the batches
+//! as they were inserted until `Backed::checkpoint` replaces them with the current batches.
+//!
+//! The `MemoryBackend` stores batches in memory, and is mostly useful for testing. The `rocksdb`
+//! module implements a backend over a RocksDB database, with the `rocksdb-backend` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use timely::dataflow::operators::generic::OperatorInfo;
+//! use differential_dataflow::trace::{Trace, TraceReader};
+//! use differential_dataflow::trace::implementations::ValSpine;
+//! use differential_dataflow::trace::implementations::backend::{Backed, MemoryBackend};
+//! use differential_dataflow::trace::testing;
+//!
+//! type Spine = ValSpine<u64, u64, u64, i64>;
+//! type Builder = <Spine as Trace>::Builder;
+//!
+//! let info = || OperatorInfo::new(0, 0, &[]);
+//! let mut trace = Backed::<Spine, MemoryBackend<u64, u64, u64, i64>>::new(info(), None, None);
+//! for batch in testing::random_batches::<Builder>(0, 4, 100, 10, 10) {
+//!     trace.insert(batch);
+//! }
+//! assert_eq!(trace.stored(), 4);
+//!
+//! // Rebuild the trace from its stored batches.
+//! let backend = trace.into_backend();
+//! let restored = Backed::restore(Spine::new(info(), None, None), backend).unwrap();
+//! assert_eq!(restored.stored(), 4);
+//! ```
+
+use std::collections::BTreeMap;
+use std::io;
+
+use timely::dataflow::operators::generic::OperatorInfo;
+use timely::progress::{Antichain, Timestamp, frontier::AntichainRef};
+use timely::PartialOrder;
+
+use crate::consolidation::consolidate_updates;
+use crate::logging::Logger;
+use crate::trace::{Batch, BatchReader, Builder, Cursor, Description, ExertionLogic, MemoryPressureLogic, Trace, TraceReader};
+
+/// Storage for batches of updates, each identified by a number.
+pub trait TraceBackend {
+    /// Owned key type.
+    type Key;
+    /// Owned val type.
+    type Val;
+    /// Timestamp for changes.
+    type Time;
+    /// Associated update.
+    type Diff;
+
+    /// Stores a batch with the interval `description` and the updates `updates`, and returns its identifier.
+    fn write_batch(&mut self, description: &Description<Self::Time>, updates: Vec<((Self::Key, Self::Val), Self::Time, Self::Diff)>) -> io::Result<u64>;
+    /// Reads the updates of the batch `id`.
+    fn read_batch(&self, id: u64) -> io::Result<Vec<((Self::Key, Self::Val), Self::Time, Self::Diff)>>;
+    /// Removes the batch `id`.
+    fn remove_batch(&mut self, id: u64) -> io::Result<()>;
+    /// Identifies and describes the stored batches whose intervals intersect `[lower, upper)`.
+    ///
+    /// An empty `upper` indicates no upper bound, and so all batches past `lower`.
+    fn list(&self, lower: AntichainRef<Self::Time>, upper: AntichainRef<Self::Time>) -> io::Result<Vec<(u64, Description<Self::Time>)>>;
+}
+
+/// Whether the interval `[lower, upper)` intersects the interval described by `description`.
+///
+/// Intervals are compared by their frontiers, which is exact for totally ordered times and otherwise
+/// may report intersections where there are none.
+pub fn intersects<T: PartialOrder>(description: &Description<T>, lower: AntichainRef<T>, upper: AntichainRef<T>) -> bool {
+    let below_upper = upper.is_empty() || !PartialOrder::less_equal(&upper, &description.lower().borrow());
+    let above_lower = !PartialOrder::less_equal(&description.upper().borrow(), &lower);
+    below_upper && above_lower
+}
+
+/// A backend that stores batches in memory.
+pub struct MemoryBackend<K, V, T, R> {
+    /// The description and updates of each batch.
+    batches: BTreeMap<u64, (Description<T>, Vec<((K, V), T, R)>)>,
+    /// The identifier of the next batch.
+    next: u64,
+}
+
+impl<K, V, T, R> Default for MemoryBackend<K, V, T, R> {
+    fn default() -> Self {
+        Self { batches: BTreeMap::new(), next: 0 }
+    }
+}
+
+impl<K: Clone, V: Clone, T: PartialOrder+Clone, R: Clone> TraceBackend for MemoryBackend<K, V, T, R> {
+    type Key = K;
+    type Val = V;
+    type Time = T;
+    type Diff = R;
+
+    fn write_batch(&mut self, description: &Description<T>, updates: Vec<((K, V), T, R)>) -> io::Result<u64> {
+        let id = self.next;
+        self.next += 1;
+        self.batches.insert(id, (description.clone(), updates));
+        Ok(id)
+    }
+    fn read_batch(&self, id: u64) -> io::Result<Vec<((K, V), T, R)>> {
+        self.batches
+            .get(&id)
+            .map(|(_, updates)| updates.clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no batch {}", id)))
+    }
+    fn remove_batch(&mut self, id: u64) -> io::Result<()> {
+        self.batches.remove(&id);
+        Ok(())
+    }
+    fn list(&self, lower: AntichainRef<T>, upper: AntichainRef<T>) -> io::Result<Vec<(u64, Description<T>)>> {
+        Ok(self.batches
+            .iter()
+            .filter(|(_, (description, _))| intersects(description, lower, upper))
+            .map(|(id, (description, _))| (*id, description.clone()))
+            .collect())
+    }
+}
+
+/// When a `Backed` trace writes the batches inserted into it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WriteMode {
+    /// Each batch is written as it is inserted.
+    Through,
+    /// Batches are written by `Backed::flush` and `Backed::checkpoint`.
+    Back,
+}
+
+/// A trace that records the batches inserted into it in a `TraceBackend`.
+///
+/// The trace reads from and compacts the wrapped trace, and so behaves exactly as it does.
+pub struct Backed<Tr: TraceReader, S> {
+    /// The wrapped trace.
+    trace: Tr,
+    /// Storage for the batches of the trace.
+    backend: S,
+    /// When inserted batches are written.
+    mode: WriteMode,
+    /// Identifiers of the batches that make up the trace, as they are stored in `backend`.
+    stored: Vec<u64>,
+    /// Inserted batches not yet written, in the order of their insertion.
+    unwritten: Vec<Tr::Batch>,
+    /// The error of the most recent failed write made by `insert`, if it has not been taken.
+    error: Option<io::Error>,
+}
+
+impl<Tr: TraceReader, S> Backed<Tr, S> {
+    /// Wraps `trace`, and records the batches subsequently inserted into it in `backend` as they are inserted.
+    pub fn with_backend(trace: Tr, backend: S) -> Self {
+        Self::with_mode(trace, backend, WriteMode::Through)
+    }
+    /// Wraps `trace`, and records the batches subsequently inserted into it in `backend` as `mode` directs.
+    pub fn with_mode(trace: Tr, backend: S, mode: WriteMode) -> Self {
+        Self { trace, backend, mode, stored: Vec::new(), unwritten: Vec::new(), error: None }
+    }
+    /// The number of batches recorded in the backend.
+    pub fn stored(&self) -> usize {
+        self.stored.len()
+    }
+    /// The number of inserted batches not yet recorded in the backend.
+    pub fn unwritten(&self) -> usize {
+        self.unwritten.len()
+    }
+    /// Takes the error of the most recent failed write made by `insert`, if any.
+    ///
+    /// `Trace::insert` cannot report errors, and a batch whose write fails is retained, and written
+    /// by the next insert in `WriteMode::Through`, or by the next flush or checkpoint.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+    /// The backend recording the batches.
+    pub fn backend(&self) -> &S {
+        &self.backend
+    }
+    /// Unwraps the backend, for example to restore another trace from it.
+    ///
+    /// Batches not yet written are discarded, and should first be written with `flush`.
+    pub fn into_backend(self) -> S {
+        self.backend
+    }
+}
+
+impl<Tr, S> Backed<Tr, S>
+where
+    Tr: Trace + for<'a> TraceReader<Key<'a> = &'a S::Key, Val<'a> = &'a S::Val>,
+    Tr::Batch: Batch,
+    Tr::Builder: Builder<Input = ((S::Key, S::Val), Tr::Time, Tr::Diff)>,
+    S: TraceBackend<Time = Tr::Time, Diff = Tr::Diff>,
+    S::Key: Ord+Clone,
+    S::Val: Ord+Clone,
+    Tr::Diff: Clone,
+{
+    /// Restores the batches stored in `backend` into `trace`, and records further batches in `backend`.
+    ///
+    /// The stored batches must form a sequence of adjacent intervals from the minimum time, as do the
+    /// batches written by a `Backed` trace. The batches are inserted in order of their intervals.
+    pub fn restore(mut trace: Tr, backend: S) -> io::Result<Self> {
+        let empty = Antichain::new();
+        let mut listed = backend.list(Antichain::from_elem(Tr::Time::minimum()).borrow(), empty.borrow())?;
+        let mut stored = Vec::with_capacity(listed.len());
+        let mut lower = Antichain::from_elem(Tr::Time::minimum());
+        while let Some(position) = listed.iter().position(|(_, description)| description.lower() == &lower) {
+            let (id, description) = listed.swap_remove(position);
+            let mut updates = backend.read_batch(id)?;
+            consolidate_updates(&mut updates);
+            let mut builder = Tr::Builder::with_capacity(0, 0, updates.len());
+            for update in updates {
+                builder.push(update);
+            }
+            lower = description.upper().clone();
+            trace.insert(builder.done(description.lower().clone(), description.upper().clone(), description.since().clone()));
+            stored.push(id);
+        }
+        if !listed.is_empty() {
+            let message = format!("{} stored batches do not follow the batches from the minimum time", listed.len());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        Ok(Self { trace, backend, mode: WriteMode::Through, stored, unwritten: Vec::new(), error: None })
+    }
+
+    /// Replaces the recorded batches with the current batches of the trace.
+    ///
+    /// The trace merges and compacts its batches, and a checkpoint allows the backend to do the same.
+    /// The current batches are written before the recorded batches are removed.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        let mut batches = Vec::new();
+        self.trace.map_batches(|batch| batches.push(batch.clone()));
+        let mut stored = Vec::with_capacity(batches.len());
+        for batch in batches.iter() {
+            stored.push(self.backend.write_batch(batch.description(), updates(batch))?);
+        }
+        // The current batches hold the updates of the batches not yet written.
+        self.unwritten.clear();
+        for id in std::mem::replace(&mut self.stored, stored) {
+            self.backend.remove_batch(id)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Tr, S> Backed<Tr, S>
+where
+    Tr: for<'a> TraceReader<Key<'a> = &'a S::Key, Val<'a> = &'a S::Val>,
+    S: TraceBackend<Time = Tr::Time, Diff = Tr::Diff>,
+    S::Key: Clone,
+    S::Val: Clone,
+    Tr::Diff: Clone,
+{
+    /// Writes the inserted batches not yet written, in the order of their insertion.
+    ///
+    /// A batch whose write fails is retained, as are the batches inserted after it.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let mut written = 0;
+        let mut result = Ok(());
+        for batch in self.unwritten.iter() {
+            match self.backend.write_batch(batch.description(), updates(batch)) {
+                Ok(id) => { self.stored.push(id); written += 1; },
+                Err(error) => { result = Err(error); break; },
+            }
+        }
+        self.unwritten.drain(.. written);
+        result
+    }
+}
+
+/// The updates of `batch`, in the order of its cursor.
+fn updates<B, K, V>(batch: &B) -> Vec<((K, V), B::Time, B::Diff)>
+where
+    B: for<'a> BatchReader<Key<'a> = &'a K, Val<'a> = &'a V>,
+    K: Clone,
+    V: Clone,
+    B::Diff: Clone,
+{
+    let mut result = Vec::with_capacity(batch.len());
+    let mut cursor = batch.cursor();
+    while cursor.key_valid(batch) {
+        while cursor.val_valid(batch) {
+            let key = cursor.key(batch);
+            let val = cursor.val(batch);
+            cursor.map_times(batch, |time, diff| result.push(((key.clone(), val.clone()), time.clone(), diff.clone())));
+            cursor.step_val(batch);
+        }
+        cursor.step_key(batch);
+    }
+    result
+}
+
+impl<Tr: TraceReader, S> TraceReader for Backed<Tr, S> {
+    type Key<'a> = Tr::Key<'a>;
+    type KeyOwned = Tr::KeyOwned;
+    type Val<'a> = Tr::Val<'a>;
+    type Time = Tr::Time;
+    type Diff = Tr::Diff;
+
+    type Batch = Tr::Batch;
+    type Storage = Tr::Storage;
+    type Cursor = Tr::Cursor;
+
+    fn cursor_through(&mut self, upper: AntichainRef<Self::Time>) -> Option<(Self::Cursor, Self::Storage)> {
+        self.trace.cursor_through(upper)
+    }
+    fn set_logical_compaction(&mut self, frontier: AntichainRef<Self::Time>) {
+        self.trace.set_logical_compaction(frontier)
+    }
+    fn get_logical_compaction(&mut self) -> AntichainRef<Self::Time> {
+        self.trace.get_logical_compaction()
+    }
+    fn set_physical_compaction(&mut self, frontier: AntichainRef<Self::Time>) {
+        self.trace.set_physical_compaction(frontier)
+    }
+    fn get_physical_compaction(&mut self) -> AntichainRef<Self::Time> {
+        self.trace.get_physical_compaction()
+    }
+    fn map_batches<F: FnMut(&Self::Batch)>(&self, f: F) {
+        self.trace.map_batches(f)
+    }
+}
+
+impl<Tr, S> Trace for Backed<Tr, S>
+where
+    Tr: Trace + for<'a> TraceReader<Key<'a> = &'a S::Key, Val<'a> = &'a S::Val>,
+    Tr::Batch: Batch,
+    S: TraceBackend<Time = Tr::Time, Diff = Tr::Diff> + Default,
+    S::Key: Clone,
+    S::Val: Clone,
+    Tr::Diff: Clone,
+{
+    type Batcher = Tr::Batcher;
+    type Builder = Tr::Builder;
+
+    /// Wraps a new trace, recording its batches in a default backend.
+    fn new(
+        info: OperatorInfo,
+        logging: Option<Logger>,
+        activator: Option<timely::scheduling::activate::Activator>,
+    ) -> Self {
+        Self::with_backend(Tr::new(info, logging, activator), S::default())
+    }
+    fn exert(&mut self) {
+        self.trace.exert()
+    }
+    fn exert_with(&mut self, effort: usize) {
+        self.trace.exert_with(effort)
+    }
+    fn set_exert_logic(&mut self, logic: ExertionLogic) {
+        self.trace.set_exert_logic(logic)
+    }
+    fn set_memory_pressure_logic(&mut self, logic: MemoryPressureLogic) {
+        self.trace.set_memory_pressure_logic(logic)
+    }
+    /// Inserts `batch` into the wrapped trace, and records it in the backend as the write mode directs.
+    ///
+    /// If the backend fails to record the batch, the error is available from `take_error`.
+    fn insert(&mut self, batch: Self::Batch) {
+        self.unwritten.push(batch.clone());
+        if self.mode == WriteMode::Through {
+            if let Err(error) = self.flush() {
+                self.error = Some(error);
+            }
+        }
+        self.trace.insert(batch)
+    }
+    fn close(&mut self) {
+        self.trace.close()
+    }
+}
+
@@ -0,0 +1,122 @@
+//! A slice container that stores large items out of line.
+//!
+//! Batches lay out their keys and values contiguously, and cursors benefit from that locality as
+//! they seek and step through them. A few large items, for example wide JSON documents, spread the
+//! small items around them across many more cache lines and pages. The `BlobContainer` keeps items
+//! of at most `THRESHOLD` elements in its main sorted layer, and moves larger items into a separate
+//! arena, leaving only a handle in their place.
+
+use crate::trace::implementations::{BatchContainer, OffsetList};
+
+/// A container of slices `[B]`, which stores slices longer than `THRESHOLD` in a separate arena.
+///
+/// The container can serve as the key or value container of a `Layout` for `Vec<B>` data, for example
+/// `Vec<u8>` encodings of wide records. Items read back out of the container are slices, wherever they
+/// are stored, and compare as slices.
+pub struct BlobContainer<B, const THRESHOLD: usize = 64> {
+    /// Concatenated items of at most `THRESHOLD` elements.
+    inline: Vec<B>,
+    /// Offsets that bound each item in `inline`.
+    ///
+    /// The length will be one greater than the number of contained items. Items stored in the arena
+    /// have empty ranges.
+    offsets: OffsetList,
+    /// The indexes of items stored in the arena, in increasing order.
+    spilled: Vec<usize>,
+    /// Concatenated items of more than `THRESHOLD` elements.
+    arena: Vec<B>,
+    /// Offsets that bound each item in `arena`, one greater than the length of `spilled`.
+    arena_offsets: OffsetList,
+}
+
+impl<B, const THRESHOLD: usize> BlobContainer<B, THRESHOLD> {
+    /// The number of items stored in the arena.
+    pub fn spilled(&self) -> usize {
+        self.spilled.len()
+    }
+}
+
+impl<B, const THRESHOLD: usize> BatchContainer for BlobContainer<B, THRESHOLD>
+where
+    B: Ord + Clone + Sized + 'static,
+{
+    type PushItem = Vec<B>;
+    type ReadItem<'a> = &'a [B];
+
+    fn copy(&mut self, item: &[B]) {
+        if item.len() > THRESHOLD {
+            self.spilled.push(self.len());
+            self.arena.extend_from_slice(item);
+            self.arena_offsets.push(self.arena.len());
+        }
+        else {
+            self.inline.extend_from_slice(item);
+        }
+        self.offsets.push(self.inline.len());
+    }
+    fn with_capacity(size: usize) -> Self {
+        let mut offsets = OffsetList::with_capacity(size + 1);
+        offsets.push(0);
+        let mut arena_offsets = OffsetList::with_capacity(1);
+        arena_offsets.push(0);
+        Self {
+            inline: Vec::with_capacity(size),
+            offsets,
+            spilled: Vec::new(),
+            arena: Vec::new(),
+            arena_offsets,
+        }
+    }
+    fn merge_capacity(cont1: &Self, cont2: &Self) -> Self {
+        let mut offsets = OffsetList::with_capacity(cont1.len() + cont2.len() + 1);
+        offsets.push(0);
+        let mut arena_offsets = OffsetList::with_capacity(cont1.spilled() + cont2.spilled() + 1);
+        arena_offsets.push(0);
+        Self {
+            inline: Vec::with_capacity(cont1.inline.len() + cont2.inline.len()),
+            offsets,
+            spilled: Vec::with_capacity(cont1.spilled() + cont2.spilled()),
+            arena: Vec::with_capacity(cont1.arena.len() + cont2.arena.len()),
+            arena_offsets,
+        }
+    }
+    fn index(&self, index: usize) -> Self::ReadItem<'_> {
+        let lower = self.offsets.index(index);
+        let upper = self.offsets.index(index+1);
+        if lower == upper {
+            // Only items with empty inline ranges may be in the arena.
+            if let Ok(position) = self.spilled.binary_search(&index) {
+                let lower = self.arena_offsets.index(position);
+                let upper = self.arena_offsets.index(position+1);
+                return &self.arena[lower .. upper];
+            }
+        }
+        &self.inline[lower .. upper]
+    }
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+    fn byte_size(&self) -> usize {
+        (self.inline.len() + self.arena.len()) * std::mem::size_of::<B>()
+            + self.offsets.byte_size()
+            + self.arena_offsets.byte_size()
+            + self.spilled.len() * std::mem::size_of::<usize>()
+    }
+}
+
+/// Default implementation introduces a first offset.
+impl<B, const THRESHOLD: usize> Default for BlobContainer<B, THRESHOLD> {
+    fn default() -> Self {
+        let mut offsets = OffsetList::with_capacity(1);
+        offsets.push(0);
+        let mut arena_offsets = OffsetList::with_capacity(1);
+        arena_offsets.push(0);
+        Self {
+            inline: Vec::new(),
+            offsets,
+            spilled: Vec::new(),
+            arena: Vec::new(),
+            arena_offsets,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! A container that dictionary encodes its contents.
+//!
+//! Most batches contain updates at very few distinct times, each of which the ordered batches store
+//! once for every update. For wide timestamps, for example the `Product<Product<_, _>, _>` times of
+//! nested iterative scopes, the times dominate the size of the batch. A `DictionaryContainer` stores
+//! each distinct item once, and records for each item a code identifying it, as narrow as the number of
+//! distinct items allows.
+//!
+//! Updates are read as references to `(time, diff)` pairs, and so the container encodes distinct pairs
+//! rather than distinct times alone. Diffs vary little in most batches, and the distinct pairs are few
+//! whenever the distinct times are.
+
+use std::collections::BTreeMap;
+
+use crate::trace::implementations::{BatchContainer, UpdateContainer};
+
+/// The codes of items, as narrow as the number of distinct items allows.
+#[derive(Debug)]
+enum Codes {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Codes {
+    fn get(&self, index: usize) -> usize {
+        match self {
+            Codes::U8(codes) => codes[index] as usize,
+            Codes::U16(codes) => codes[index] as usize,
+            Codes::U32(codes) => codes[index] as usize,
+        }
+    }
+    fn len(&self) -> usize {
+        match self {
+            Codes::U8(codes) => codes.len(),
+            Codes::U16(codes) => codes.len(),
+            Codes::U32(codes) => codes.len(),
+        }
+    }
+    /// Appends `code`, first widening the codes if it does not fit.
+    fn push(&mut self, code: usize) {
+        if let Codes::U8(codes) = self {
+            if code > u8::MAX as usize {
+                *self = Codes::U16(codes.iter().map(|c| *c as u16).collect());
+            }
+        }
+        if let Codes::U16(codes) = self {
+            if code > u16::MAX as usize {
+                *self = Codes::U32(codes.iter().map(|c| *c as u32).collect());
+            }
+        }
+        match self {
+            Codes::U8(codes) => codes.push(code as u8),
+            Codes::U16(codes) => codes.push(code as u16),
+            Codes::U32(codes) => codes.push(code.try_into().expect("more than u32::MAX distinct items")),
+        }
+    }
+    fn byte_size(&self) -> usize {
+        match self {
+            Codes::U8(codes) => codes.len(),
+            Codes::U16(codes) => codes.len() * 2,
+            Codes::U32(codes) => codes.len() * 4,
+        }
+    }
+}
+
+/// A container that stores each distinct item once, and a code for each item.
+#[derive(Debug)]
+pub struct DictionaryContainer<T> {
+    /// The distinct items, in order of their first appearance.
+    dictionary: Vec<T>,
+    /// For each item, the index of its distinct item in `dictionary`.
+    codes: Codes,
+    /// The codes of distinct items, used while the container is built and released once it is sealed.
+    lookup: BTreeMap<T, usize>,
+}
+
+impl<T> DictionaryContainer<T> {
+    /// The number of distinct items.
+    pub fn distinct(&self) -> usize {
+        self.dictionary.len()
+    }
+}
+
+impl<T: Ord + Clone + 'static> BatchContainer for DictionaryContainer<T> {
+    type PushItem = T;
+    type ReadItem<'a> = &'a T;
+
+    fn copy_push(&mut self, item: &T) {
+        self.copy(item);
+    }
+    fn copy(&mut self, item: &T) {
+        let code = match self.lookup.get(item) {
+            Some(code) => *code,
+            None => {
+                let code = self.dictionary.len();
+                self.dictionary.push(item.clone());
+                self.lookup.insert(item.clone(), code);
+                code
+            }
+        };
+        self.codes.push(code);
+    }
+    fn with_capacity(size: usize) -> Self {
+        Self {
+            dictionary: Vec::new(),
+            codes: Codes::U8(Vec::with_capacity(size)),
+            lookup: BTreeMap::new(),
+        }
+    }
+    fn merge_capacity(cont1: &Self, cont2: &Self) -> Self {
+        Self::with_capacity(cont1.len() + cont2.len())
+    }
+    fn index(&self, index: usize) -> Self::ReadItem<'_> {
+        &self.dictionary[self.codes.get(index)]
+    }
+    fn len(&self) -> usize {
+        self.codes.len()
+    }
+    fn byte_size(&self) -> usize {
+        // Until the container is sealed, the lookup holds a second copy of each distinct item.
+        (self.dictionary.len() + self.lookup.len()) * std::mem::size_of::<T>() + self.codes.byte_size()
+    }
+    fn seal(&mut self) {
+        self.lookup = BTreeMap::new();
+    }
+}
+
+impl<T: Ord + Clone + 'static, R: Ord + Clone + 'static> UpdateContainer<T, R> for DictionaryContainer<(T, R)> {
+    fn index_update(&self, index: usize) -> (&T, &R) {
+        let (time, diff) = self.index(index);
+        (time, diff)
+    }
+}
@@ -0,0 +1,124 @@
+//! Versioned headers for persisted batch encodings.
+//!
+//! Batches written by the `rocksdb` backend and the `archive` and `shared` formats may outlive the
+//! version of the crate that wrote them. Each encoding is preceded by a `Header`, which identifies the
+//! format and the version of its layout, so that readers can decode layouts written by earlier versions
+//! of the crate, and report layouts written by later versions as unsupported rather than misreading them.
+//!
+//! The header occupies `HEADER_LEN` bytes: the magic bytes `MAGIC`, the format and the version as
+//! little-endian `u16`s, and four reserved zero bytes. Its length is a multiple of sixteen, so that
+//! encodings that are read in place keep the alignment of the buffer they are read from.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::trace::implementations::encoding::{Format, Header, HEADER_LEN};
+//!
+//! let header = Header { format: Format::Bincode, version: 1 };
+//! let mut bytes = header.encode().to_vec();
+//! bytes.extend_from_slice(b"payload");
+//!
+//! let (decoded, payload) = Header::decode(&bytes).unwrap();
+//! assert_eq!(decoded, header);
+//! assert_eq!(payload, b"payload");
+//! assert_eq!(bytes.len(), HEADER_LEN + payload.len());
+//! ```
+
+use std::io;
+
+/// The bytes that begin every header.
+pub const MAGIC: [u8; 8] = *b"ddbatch\0";
+
+/// The length of an encoded header.
+pub const HEADER_LEN: usize = 16;
+
+/// The format of a persisted batch encoding.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// Batch descriptions and updates serialized with `bincode`, as by the `rocksdb` backend.
+    Bincode,
+    /// A `BatchArchive` serialized with `rkyv`.
+    Archive,
+    /// A `SharedArchive` serialized with `rkyv`.
+    SharedArchive,
+}
+
+impl Format {
+    /// The code that identifies the format in headers.
+    fn code(self) -> u16 {
+        match self {
+            Format::Bincode => 1,
+            Format::Archive => 2,
+            Format::SharedArchive => 3,
+        }
+    }
+    /// The format identified by `code`, if any.
+    fn from_code(code: u16) -> Option<Self> {
+        match code {
+            1 => Some(Format::Bincode),
+            2 => Some(Format::Archive),
+            3 => Some(Format::SharedArchive),
+            _ => None,
+        }
+    }
+}
+
+/// The header that precedes a persisted batch encoding.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Header {
+    /// The format of the encoding.
+    pub format: Format,
+    /// The version of the layout of the format.
+    pub version: u16,
+}
+
+impl Header {
+    /// The bytes of the header.
+    pub fn encode(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..8].copy_from_slice(&MAGIC);
+        bytes[8..10].copy_from_slice(&self.format.code().to_le_bytes());
+        bytes[10..12].copy_from_slice(&self.version.to_le_bytes());
+        bytes
+    }
+
+    /// Reads the header at the start of `bytes`, and returns it with the bytes that follow it.
+    ///
+    /// The method returns an error if `bytes` do not begin with a valid header.
+    pub fn decode(bytes: &[u8]) -> io::Result<(Header, &[u8])> {
+        if !bytes.starts_with(&MAGIC) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "missing batch encoding header"));
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated batch encoding header"));
+        }
+        let code = u16::from_le_bytes([bytes[8], bytes[9]]);
+        let format = Format::from_code(code).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("unknown batch encoding format {}", code))
+        })?;
+        let version = u16::from_le_bytes([bytes[10], bytes[11]]);
+        Ok((Header { format, version }, &bytes[HEADER_LEN..]))
+    }
+
+    /// Reads the version of an encoding of `format`, and returns it with the bytes of the encoding.
+    ///
+    /// The method returns an error if the header is missing or invalid, or identifies another format.
+    pub fn decode_version(format: Format, bytes: &[u8]) -> io::Result<(u16, &[u8])> {
+        let (header, payload) = Header::decode(bytes)?;
+        if header.format != format {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected a {:?} batch encoding, found {:?}", format, header.format),
+            ));
+        }
+        Ok((header.version, payload))
+    }
+}
+
+/// The error for an encoding of `format` whose layout `version` is not supported.
+pub fn unsupported_version(format: Format, version: u16) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unsupported {:?} batch encoding version {}", format, version),
+    )
+}
@@ -0,0 +1,72 @@
+//! Arrangements of graph edges, as adjacency lists.
+//!
+//! Graph computations arrange collections of `(u32, u32)` edges by their source, and read the
+//! arrangements as adjacency lists. The ordered batches of `ord_neu` already lay out their updates in
+//! the form of compressed sparse rows: distinct sources, offsets into the concatenated lists of their
+//! destinations, and offsets into the updates of each edge. The `Adjacency` layout specializes them to
+//! edges, with each column a flat list of `u32`s while offsets fit, and with the diffs of updates
+//! run-length encoded.
+//!
+//! Edges with a single update equal to that of the edge before them store no update at all, and the
+//! diffs of the remaining updates are stored once per run. For a snapshot of a graph, where every edge has the
+//! same time and a diff of one, an edge costs its destination and one offset, eight bytes in all,
+//! where generic ordered tuples would also store its time and diff.
+//!
+//! # Examples
+//!
+//! ```
+//! use differential_dataflow::input::Input;
+//! use differential_dataflow::operators::arrange::Arrange;
+//! use differential_dataflow::operators::JoinCore;
+//! use differential_dataflow::trace::implementations::graph::GraphSpine;
+//!
+//! ::timely::example(|scope| {
+//!
+//!     let edges = scope.new_collection_from(vec![(0u32, 1u32), (0, 2), (1, 2)]).1;
+//!     let graph = edges.arrange::<GraphSpine<_,_>>();
+//!
+//!     // the two-hop paths of the graph.
+//!     edges.map(|(src, dst)| (dst, src))
+//!          .join_core(&graph, |_mid, &src, &dst| Some((src, dst)))
+//!          .assert_eq(&scope.new_collection_from(vec![(0, 2)]).1);
+//! });
+//! ```
+
+use std::rc::Rc;
+
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::implementations::spine_fueled::Spine;
+use crate::trace::implementations::merge_batcher::{MergeBatcher, VecMerger};
+use crate::trace::implementations::ord_neu::{OrdValBatch, OrdValBuilder};
+use crate::trace::implementations::rle_container::RunLengthContainer;
+use crate::trace::rc_blanket_impls::RcBuilder;
+
+use super::{Layout, OffsetList};
+
+/// A layout for `(u32, u32)` edges, with times `T` and diffs `R`.
+pub struct Adjacency<T, R> {
+    phantom: std::marker::PhantomData<(T, R)>,
+}
+
+impl<T, R> Layout for Adjacency<T, R>
+where
+    T: Ord+Lattice+timely::progress::Timestamp+Clone,
+    R: Semigroup+Clone,
+{
+    type Target = ((u32, u32), T, R);
+    type KeyContainer = Vec<u32>;
+    type ValContainer = Vec<u32>;
+    type UpdContainer = RunLengthContainer<T, R>;
+    type OffsetContainer = OffsetList;
+}
+
+/// A batch of edges, as adjacency lists.
+pub type GraphBatch<T, R> = OrdValBatch<Adjacency<T, R>>;
+
+/// A trace of edges, as adjacency lists.
+pub type GraphSpine<T, R> = Spine<
+    Rc<GraphBatch<T, R>>,
+    MergeBatcher<VecMerger<((u32, u32), T, R)>, T>,
+    RcBuilder<OrdValBuilder<Adjacency<T, R>>>,
+>;
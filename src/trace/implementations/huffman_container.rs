@@ -136,6 +136,13 @@ where
     fn len(&self) -> usize {
         self.offsets.len() - 1
     }
+    fn byte_size(&self) -> usize {
+        let inner = match &self.inner {
+            Ok((_huffman, bytes)) => bytes.len(),
+            Err(raw) => raw.len() * std::mem::size_of::<B>(),
+        };
+        inner + self.offsets.byte_size()
+    }
 }
 /// Default implementation introduces a first offset.
 impl<B: Ord+Clone> Default for HuffmanContainer<B> {
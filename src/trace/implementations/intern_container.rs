@@ -0,0 +1,101 @@
+//! A string container that interns repeated strings.
+//!
+//! Collections keyed or valued by low-cardinality strings, for example country names or status codes,
+//! repeat the same few strings across many updates. The `InternContainer` stores each distinct string
+//! once, and records a `u32` index into those strings for each item.
+//!
+//! The container implements `BatchContainer`, and serves as the key or value container of a `Layout`
+//! whose keys or values are `String`.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use crate::trace::implementations::{BatchContainer, OffsetList};
+
+/// A container of strings, which stores each distinct string once.
+pub struct InternContainer {
+    /// Concatenated distinct strings.
+    text: String,
+    /// Offsets that bound each distinct string in `text`.
+    ///
+    /// The length will be one greater than the number of distinct strings.
+    bounds: OffsetList,
+    /// For each item, the index of its distinct string.
+    indices: Vec<u32>,
+    /// The indexes of distinct strings, by the hash of their contents.
+    lookup: HashMap<u64, Vec<u32>>,
+}
+
+impl InternContainer {
+    /// The number of distinct strings.
+    pub fn distinct(&self) -> usize {
+        self.bounds.len() - 1
+    }
+    /// The distinct string at `index`.
+    fn string(&self, index: u32) -> &str {
+        let lower = self.bounds.index(index as usize);
+        let upper = self.bounds.index(index as usize + 1);
+        &self.text[lower .. upper]
+    }
+    /// The index of `item` among the distinct strings, which introduces it if it is new.
+    fn intern(&mut self, item: &str) -> u32 {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let hash = hasher.finish();
+        if let Some(candidates) = self.lookup.get(&hash) {
+            if let Some(index) = candidates.iter().find(|index| self.string(**index) == item) {
+                return *index;
+            }
+        }
+        let index: u32 = self.distinct().try_into().expect("more than u32::MAX distinct strings");
+        self.text.push_str(item);
+        self.bounds.push(self.text.len());
+        self.lookup.entry(hash).or_insert_with(Vec::new).push(index);
+        index
+    }
+}
+
+impl BatchContainer for InternContainer {
+    type PushItem = String;
+    type ReadItem<'a> = &'a str;
+
+    fn copy(&mut self, item: &str) {
+        let index = self.intern(item);
+        self.indices.push(index);
+    }
+    fn with_capacity(size: usize) -> Self {
+        let mut container = Self::default();
+        container.indices.reserve(size);
+        container
+    }
+    fn merge_capacity(cont1: &Self, cont2: &Self) -> Self {
+        let mut container = Self::default();
+        container.indices.reserve(cont1.len() + cont2.len());
+        container.text.reserve(cont1.text.len().max(cont2.text.len()));
+        container
+    }
+    fn index(&self, index: usize) -> Self::ReadItem<'_> {
+        self.string(self.indices[index])
+    }
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+    fn byte_size(&self) -> usize {
+        self.text.len() + self.bounds.byte_size() + self.indices.len() * std::mem::size_of::<u32>()
+    }
+}
+
+/// Default implementation introduces a first offset.
+impl Default for InternContainer {
+    fn default() -> Self {
+        let mut bounds = OffsetList::with_capacity(1);
+        bounds.push(0);
+        Self {
+            text: String::new(),
+            bounds,
+            indices: Vec::new(),
+            lookup: HashMap::new(),
+        }
+    }
+}
@@ -71,6 +71,83 @@ where
     // which we call `lower`, by assumption that after sealing a batcher we receive no more
     // updates with times not greater or equal to `upper`.
     fn seal<B: Builder<Input = Self::Output, Time = Self::Time>>(&mut self, upper: Antichain<T>) -> B::Output {
+        let mut readied = self.extract_readied(upper.borrow());
+        self.stash.clear();
+
+        let seal = M::seal::<B>(&mut readied, self.lower.borrow(), upper.borrow(), Antichain::from_elem(T::minimum()).borrow());
+        self.lower = upper;
+        seal
+    }
+
+    // As `seal`, but the readied updates are cut into runs of times holding at most `limit` updates,
+    // each of which is built into its own batch. We visit times in their `Ord` order, which we expect
+    // to be compatible with their partial order, and the upper bound of each run is formed from the
+    // times of all subsequent runs (and `upper`).
+    fn seal_limited<B: Builder<Input = Self::Output, Time = Self::Time>>(&mut self, upper: Antichain<T>, limit: usize) -> Vec<B::Output> {
+        let mut readied = self.extract_readied(upper.borrow());
+
+        // Count the updates at each distinct time.
+        let mut times = Vec::new();
+        M::times(&readied, &mut times);
+        times.sort();
+        let mut counts: Vec<(T, usize)> = Vec::new();
+        for time in times.drain(..) {
+            if counts.last().map(|(prev, _)| prev == &time).unwrap_or(false) {
+                counts.last_mut().unwrap().1 += 1;
+            }
+            else {
+                counts.push((time, 1));
+            }
+        }
+
+        // Determine the bounds at which new batches start. We only cut where the bound is a single time,
+        // which is always the case for totally ordered times, so that each batch after the first can use
+        // its lower bound as a capability hint.
+        let since = Antichain::from_elem(T::minimum());
+        let mut batches = Vec::new();
+        let mut total = 0;
+        for index in 0 .. counts.len() {
+            if total > 0 && total + counts[index].1 > limit {
+                let mut bound = upper.clone();
+                for (time, _count) in counts[index..].iter() {
+                    bound.insert(time.clone());
+                }
+                if bound.elements().len() == 1 {
+                    let mut part = Vec::new();
+                    let mut rest = Vec::new();
+                    let mut frontier = Antichain::new();
+                    self.merger.extract(readied, bound.borrow(), &mut frontier, &mut part, &mut rest, &mut self.stash);
+                    readied = rest;
+                    batches.push(M::seal::<B>(&mut part, self.lower.borrow(), bound.borrow(), since.borrow()));
+                    self.lower = bound;
+                    total = 0;
+                }
+            }
+            total += counts[index].1;
+        }
+        self.stash.clear();
+
+        batches.push(M::seal::<B>(&mut readied, self.lower.borrow(), upper.borrow(), since.borrow()));
+        self.lower = upper;
+        batches
+    }
+
+    /// The frontier of elements remaining after the most recent call to `self.seal`.
+    #[inline]
+    fn frontier(&mut self) -> AntichainRef<T> {
+        self.frontier.borrow()
+    }
+}
+
+impl<M, T> MergeBatcher<M, T>
+where
+    M: Merger<Time = T>,
+    T: Timestamp,
+{
+    /// Merges all chains, and returns the updates not greater or equal to an element of `upper`.
+    ///
+    /// The remaining updates are retained, and `self.frontier` is updated to their lower envelope.
+    fn extract_readied(&mut self, upper: AntichainRef<T>) -> Vec<M::Chunk> {
         // Finish
         let chain = self.merger.finish(&mut self.stash);
         if !chain.is_empty() {
@@ -91,23 +168,13 @@ where
         let mut readied = Vec::new();
         self.frontier.clear();
 
-        self.merger.extract(merged, upper.borrow(), &mut self.frontier, &mut readied, &mut kept, &mut self.stash);
+        self.merger.extract(merged, upper, &mut self.frontier, &mut readied, &mut kept, &mut self.stash);
 
         if !kept.is_empty() {
             self.chain_push(kept);
         }
 
-        self.stash.clear();
-
-        let seal = M::seal::<B>(&mut readied, self.lower.borrow(), upper.borrow(), Antichain::from_elem(T::minimum()).borrow());
-        self.lower = upper;
-        seal
-    }
-
-    /// The frontier of elements remaining after the most recent call to `self.seal`.
-    #[inline]
-    fn frontier(&mut self) -> AntichainRef<T> {
-        self.frontier.borrow()
+        readied
     }
 }
 
@@ -225,6 +292,12 @@ pub trait Merger: Default {
 
     /// Account size and allocation changes. Returns a tuple of (records, size, capacity, allocations).
     fn account(chunk: &Self::Chunk) -> (usize, usize, usize, usize);
+
+    /// Reports the time of each update in `chain` into `times`.
+    ///
+    /// This is used to cut a chain into batches by time. The default implementation reports no times,
+    /// which results in a single batch.
+    fn times(_chain: &[Self::Chunk], _times: &mut Vec<Self::Time>) { }
 }
 
 /// A merger that knows how to accept and maintain chains of vectors.
@@ -507,4 +580,10 @@ where
     fn account(chunk: &Self::Chunk) -> (usize, usize, usize, usize) {
         (chunk.len(), 0, 0, 0)
     }
+
+    fn times(chain: &[Self::Chunk], times: &mut Vec<Self::Time>) {
+        for buffer in chain.iter() {
+            times.extend(buffer.iter().map(|(_, time, _)| time.clone()));
+        }
+    }
 }
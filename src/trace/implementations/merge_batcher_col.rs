@@ -310,6 +310,12 @@ where
         chunk.heap_size(cb);
         (chunk.len(), size, capacity, allocations)
     }
+
+    fn times(chain: &[Self::Chunk], times: &mut Vec<Self::Time>) {
+        for buffer in chain.iter() {
+            times.extend(buffer.iter().map(|(_, time, _)| time.clone()));
+        }
+    }
 }
 
 struct TimelyStackQueue<T: Columnation> {
@@ -42,12 +42,33 @@ pub mod spine_fueled;
 
 pub mod merge_batcher;
 pub mod merge_batcher_col;
+pub mod presorted_batcher;
 pub mod ord_neu;
 pub mod rhh;
 pub mod huffman_container;
 pub mod option_container;
+pub mod blob_container;
+pub mod intern_container;
+pub mod rle_container;
+pub mod dictionary_container;
+pub mod graph;
+pub mod sorted_runs;
+pub mod backend;
+pub mod encoding;
+#[cfg(feature = "rocksdb-backend")]
+pub mod rocksdb;
+#[cfg(feature = "rkyv")]
+pub mod archive;
+#[cfg(feature = "shared-memory")]
+pub mod shared;
 
 // Opinionated takes on default spines.
+//
+// The `KeySpine` batches store no column of values, and are what `arrange_by_self`, `distinct`,
+// `count`, and `consolidate` use to arrange collections of keys. Arrangements of types that own heap
+// memory can opt in to `StackValSpine` and `StackKeySpine`, which store keys and values in columnar
+// regions and avoid an allocation per key or value. The default spines themselves are fixed, as a
+// feature that changed them would change the bounds of every arrangement in the dependency graph.
 pub use self::ord_neu::OrdValSpine as ValSpine;
 pub use self::ord_neu::OrdKeySpine as KeySpine;
 
@@ -95,7 +116,7 @@ pub trait Layout {
         BatchContainer<PushItem=<Self::Target as Update>::Val>;
     /// Container for update vals.
     type UpdContainer:
-        for<'a> BatchContainer<PushItem=(<Self::Target as Update>::Time, <Self::Target as Update>::Diff), ReadItem<'a> = &'a (<Self::Target as Update>::Time, <Self::Target as Update>::Diff)>;
+        UpdateContainer<<Self::Target as Update>::Time, <Self::Target as Update>::Diff>;
     /// Container for offsets.
     type OffsetContainer: BatchContainer<PushItem=usize>;
 }
@@ -136,6 +157,26 @@ where
     type OffsetContainer = OffsetList;
 }
 
+/// A layout that stores keys and values in timely stacks, and times and diffs in vectors.
+///
+/// Unlike `TStack`, the layout places no requirements on times and diffs, which are rarely
+/// the types that own heap memory.
+pub struct DataStack<U: Update> {
+    phantom: std::marker::PhantomData<U>,
+}
+
+impl<U: Update> Layout for DataStack<U>
+where
+    U::Key: Columnation + 'static,
+    U::Val: Columnation + 'static,
+{
+    type Target = U;
+    type KeyContainer = TimelyStack<U::Key>;
+    type ValContainer = TimelyStack<U::Val>;
+    type UpdContainer = Vec<(U::Time, U::Diff)>;
+    type OffsetContainer = OffsetList;
+}
+
 /// A type with a preferred container.
 ///
 /// Examples include types that implement `Clone` who prefer 
@@ -189,6 +230,116 @@ where
     type OffsetContainer = OffsetList;
 }
 
+/// A layout that stores the data of `L`, with offsets held in `O`.
+///
+/// The layouts of this module hold offsets in an `OffsetList`, which uses `u32` offsets until a batch
+/// outgrows them and promotes later offsets to `u64`. Other choices may suit some batches better: a
+/// `Vec<usize>` avoids the checks on each access, at the cost of pointer-width offsets throughout.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+/// use differential_dataflow::trace::implementations::{Vector, WithOffsets};
+/// use differential_dataflow::trace::implementations::ord_neu::{OrdValBatch, OrdValBuilder};
+/// use differential_dataflow::trace::implementations::merge_batcher::{MergeBatcher, VecMerger};
+/// use differential_dataflow::trace::implementations::spine_fueled::Spine;
+/// use differential_dataflow::trace::rc_blanket_impls::RcBuilder;
+///
+/// // A spine whose batches use pointer-width offsets.
+/// type Layout = WithOffsets<Vector<((u64, u64), u64, isize)>, Vec<usize>>;
+/// type WideSpine = Spine<
+///     Rc<OrdValBatch<Layout>>,
+///     MergeBatcher<VecMerger<((u64, u64), u64, isize)>, u64>,
+///     RcBuilder<OrdValBuilder<Layout>>,
+/// >;
+/// ```
+pub struct WithOffsets<L: Layout, O> {
+    phantom: std::marker::PhantomData<(L, O)>,
+}
+
+impl<L: Layout, O: BatchContainer<PushItem=usize>> Layout for WithOffsets<L, O> {
+    type Target = L::Target;
+    type KeyContainer = L::KeyContainer;
+    type ValContainer = L::ValContainer;
+    type UpdContainer = L::UpdContainer;
+    type OffsetContainer = O;
+}
+
+/// A layout that stores the keys, values, and offsets of `L`, with the diffs of updates run-length encoded.
+///
+/// Runs of equal consecutive diffs are stored once, which suits batches whose updates mostly have the
+/// same diff, as for count-like collections. Cursors and mergers read the updates of a value in sequence,
+/// but reading an update on its own costs a binary search among the runs.
+///
+/// # Examples
+///
+/// ```
+/// use differential_dataflow::trace::implementations::{Vector, RunLength};
+/// use differential_dataflow::trace::implementations::ord_neu::{OrdKeyBatch, OrdKeyBuilder};
+/// use differential_dataflow::trace::implementations::merge_batcher::{MergeBatcher, VecMerger};
+/// use differential_dataflow::trace::implementations::spine_fueled::Spine;
+/// use differential_dataflow::trace::rc_blanket_impls::RcBuilder;
+/// use std::rc::Rc;
+///
+/// // A spine whose batches run-length encode their updates.
+/// type Layout = RunLength<Vector<((u64, ()), u64, isize)>>;
+/// type CountSpine = Spine<
+///     Rc<OrdKeyBatch<Layout>>,
+///     MergeBatcher<VecMerger<((u64, ()), u64, isize)>, u64>,
+///     RcBuilder<OrdKeyBuilder<Layout>>,
+/// >;
+/// ```
+pub struct RunLength<L: Layout> {
+    phantom: std::marker::PhantomData<L>,
+}
+
+impl<L: Layout> Layout for RunLength<L> {
+    type Target = L::Target;
+    type KeyContainer = L::KeyContainer;
+    type ValContainer = L::ValContainer;
+    type UpdContainer = rle_container::RunLengthContainer<<L::Target as Update>::Time, <L::Target as Update>::Diff>;
+    type OffsetContainer = L::OffsetContainer;
+}
+
+/// A layout that stores the keys, values, and offsets of `L`, with updates dictionary encoded.
+///
+/// Each distinct `(time, diff)` update is stored once, and each update as a code of one, two, or four
+/// bytes, depending on the number of distinct updates. This suits batches of updates at few times, in
+/// particular when times are wide, as in nested iterative scopes.
+///
+/// # Examples
+///
+/// ```
+/// use differential_dataflow::trace::implementations::{Vector, Dictionary};
+/// use differential_dataflow::trace::implementations::ord_neu::{OrdValBatch, OrdValBuilder};
+/// use differential_dataflow::trace::implementations::merge_batcher::{MergeBatcher, VecMerger};
+/// use differential_dataflow::trace::implementations::spine_fueled::Spine;
+/// use differential_dataflow::trace::rc_blanket_impls::RcBuilder;
+/// use timely::order::Product;
+/// use std::rc::Rc;
+///
+/// // A spine for nested scopes, whose batches store each distinct update once.
+/// type Time = Product<Product<u64, u32>, u32>;
+/// type Layout = Dictionary<Vector<((u64, u64), Time, isize)>>;
+/// type NestedSpine = Spine<
+///     Rc<OrdValBatch<Layout>>,
+///     MergeBatcher<VecMerger<((u64, u64), Time, isize)>, Time>,
+///     RcBuilder<OrdValBuilder<Layout>>,
+/// >;
+/// ```
+pub struct Dictionary<L: Layout> {
+    phantom: std::marker::PhantomData<L>,
+}
+
+impl<L: Layout> Layout for Dictionary<L> {
+    type Target = L::Target;
+    type KeyContainer = L::KeyContainer;
+    type ValContainer = L::ValContainer;
+    type UpdContainer = dictionary_container::DictionaryContainer<(<L::Target as Update>::Time, <L::Target as Update>::Diff)>;
+    type OffsetContainer = L::OffsetContainer;
+}
+
 use std::convert::TryInto;
 use std::ops::Deref;
 use abomonation_derive::Abomonation;
@@ -318,9 +469,13 @@ impl BatchContainer for OffsetList {
     fn len(&self) -> usize {
         self.len()
     }
+
+    fn byte_size(&self) -> usize {
+        self.smol.len() * std::mem::size_of::<u32>() + self.chonk.len() * std::mem::size_of::<u64>()
+    }
 }
 
-pub use self::containers::{BatchContainer, SliceContainer, SliceContainer2};
+pub use self::containers::{BatchContainer, UpdateContainer, SliceContainer, SliceContainer2};
 
 /// Containers for data that resemble `Vec<T>`, with leaner implementations.
 pub mod containers {
@@ -374,6 +529,16 @@ pub mod containers {
         }
         /// Indicates if the length is zero.
         fn is_empty(&self) -> bool { self.len() == 0 }
+        /// An estimate of the bytes the contained items occupy.
+        ///
+        /// The default counts the size of `PushItem` for each item, which does not include memory the items
+        /// own. Containers that store items in some other form should report the size of that form.
+        fn byte_size(&self) -> usize { self.len() * std::mem::size_of::<Self::PushItem>() }
+        /// Releases state used only while items are pushed, once the container is complete.
+        ///
+        /// Builders and mergers call this as they complete a batch, after which the container is read
+        /// but not extended. The default does nothing.
+        fn seal(&mut self) { }
 
         /// Reports the number of elements satisfing the predicate.
         ///
@@ -420,6 +585,50 @@ pub mod containers {
         }
     }
 
+    /// A container of `(time, diff)` updates.
+    ///
+    /// Updates are read as references to their time and diff, rather than to a `(time, diff)` pair, so
+    /// that containers may store times and diffs apart.
+    pub trait UpdateContainer<T, R>: BatchContainer<PushItem = (T, R)> {
+        /// References to the time and diff of the update at this position.
+        fn index_update(&self, index: usize) -> (&T, &R);
+        /// References to the time and diff of the last update, if the container is non-empty.
+        fn last_update(&self) -> Option<(&T, &R)> {
+            if self.len() > 0 {
+                Some(self.index_update(self.len()-1))
+            }
+            else {
+                None
+            }
+        }
+        /// Calls `logic` on the time and diff of each update in `lower .. upper`, in order.
+        ///
+        /// Containers whose updates are costly to read at arbitrary positions should read a range in sequence.
+        fn map_updates<F: FnMut(&T, &R)>(&self, lower: usize, upper: usize, mut logic: F) {
+            for index in lower .. upper {
+                let (time, diff) = self.index_update(index);
+                logic(time, diff);
+            }
+        }
+    }
+
+    impl<T: Ord + Clone + 'static, R: Ord + Clone + 'static> UpdateContainer<T, R> for Vec<(T, R)> {
+        fn index_update(&self, index: usize) -> (&T, &R) {
+            let (time, diff) = &self[index];
+            (time, diff)
+        }
+    }
+
+    impl<T: Ord + Columnation + 'static, R: Ord + Columnation + 'static> UpdateContainer<T, R> for TimelyStack<(T, R)>
+    where
+        (T, R): ToOwned<Owned = (T, R)>,
+    {
+        fn index_update(&self, index: usize) -> (&T, &R) {
+            let (time, diff) = &self[index];
+            (time, diff)
+        }
+    }
+
     // All `T: Clone` also implement `ToOwned<Owned = T>`, but without the constraint Rust
     // struggles to understand why the owned type must be `T` (i.e. the one blanket impl).
     impl<T: Ord + Clone + 'static> BatchContainer for Vec<T> {
@@ -548,6 +757,9 @@ pub mod containers {
         fn len(&self) -> usize {
             self.offsets.len() - 1
         }
+        fn byte_size(&self) -> usize {
+            self.offsets.len() * std::mem::size_of::<usize>() + self.inner.len() * std::mem::size_of::<B>()
+        }
     }
 
     /// Default implementation introduces a first offset.
@@ -675,6 +887,9 @@ pub mod containers {
         fn len(&self) -> usize {
             self.offsets.len() - 1
         }
+        fn byte_size(&self) -> usize {
+            self.offsets.len() * std::mem::size_of::<usize>() + self.inner.len() * std::mem::size_of::<B>()
+        }
     }
 
     /// Default implementation introduces a first offset.
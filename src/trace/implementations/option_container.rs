@@ -66,6 +66,9 @@ where
     fn len(&self) -> usize {
         self.container.len() + self.defaults
     }
+    fn byte_size(&self) -> usize {
+        self.container.byte_size()
+    }
 }
 
 /// A read wrapper capable of cheaply representing a default value.
@@ -13,9 +13,10 @@ use std::rc::Rc;
 use crate::trace::implementations::spine_fueled::Spine;
 use crate::trace::implementations::merge_batcher::{MergeBatcher, VecMerger};
 use crate::trace::implementations::merge_batcher_col::ColumnationMerger;
+use crate::trace::implementations::presorted_batcher::PresortedBatcher;
 use crate::trace::rc_blanket_impls::RcBuilder;
 
-use super::{Update, Layout, Vector, TStack, Preferred};
+use super::{Update, Layout, Vector, TStack, DataStack, Preferred, RunLength};
 
 pub use self::val_batch::{OrdValBatch, OrdValBuilder};
 pub use self::key_batch::{OrdKeyBatch, OrdKeyBuilder};
@@ -29,6 +30,13 @@ pub type OrdValSpine<K, V, T, R> = Spine<
 // /// A trace implementation for empty values using a spine of ordered lists.
 // pub type OrdKeySpine<K, T, R> = Spine<Rc<OrdKeyBatch<Vector<((K,()),T,R)>>>>;
 
+/// A trace implementation using a spine of ordered lists, for input that arrives sorted.
+pub type PresortedValSpine<K, V, T, R> = Spine<
+    Rc<OrdValBatch<Vector<((K,V),T,R)>>>,
+    PresortedBatcher<(K, V), T, R>,
+    RcBuilder<OrdValBuilder<Vector<((K,V),T,R)>>>,
+>;
+
 /// A trace implementation backed by columnar storage.
 pub type ColValSpine<K, V, T, R> = Spine<
     Rc<OrdValBatch<TStack<((K,V),T,R)>>>,
@@ -36,6 +44,15 @@ pub type ColValSpine<K, V, T, R> = Spine<
     RcBuilder<OrdValBuilder<TStack<((K,V),T,R)>>>,
 >;
 
+/// A trace implementation that stores keys and values in columnar regions.
+///
+/// Unlike `ColValSpine`, the spine does not require times and diffs to implement `Columnation`.
+pub type StackValSpine<K, V, T, R> = Spine<
+    Rc<OrdValBatch<DataStack<((K,V),T,R)>>>,
+    MergeBatcher<VecMerger<((K, V), T, R)>, T>,
+    RcBuilder<OrdValBuilder<DataStack<((K,V),T,R)>>>,
+>;
+
 /// A trace implementation using a spine of ordered lists.
 pub type OrdKeySpine<K, T, R> = Spine<
     Rc<OrdKeyBatch<Vector<((K,()),T,R)>>>,
@@ -52,6 +69,29 @@ pub type ColKeySpine<K, T, R> = Spine<
     RcBuilder<OrdKeyBuilder<TStack<((K,()),T,R)>>>,
 >;
 
+/// A trace implementation that stores keys in columnar regions.
+///
+/// Unlike `ColKeySpine`, the spine does not require times and diffs to implement `Columnation`.
+pub type StackKeySpine<K, T, R> = Spine<
+    Rc<OrdKeyBatch<DataStack<((K,()),T,R)>>>,
+    MergeBatcher<VecMerger<((K, ()), T, R)>, T>,
+    RcBuilder<OrdKeyBuilder<DataStack<((K,()),T,R)>>>,
+>;
+
+/// A trace implementation using a spine of ordered lists, with run-length encoded updates.
+pub type RleValSpine<K, V, T, R> = Spine<
+    Rc<OrdValBatch<RunLength<Vector<((K,V),T,R)>>>>,
+    MergeBatcher<VecMerger<((K, V), T, R)>, T>,
+    RcBuilder<OrdValBuilder<RunLength<Vector<((K,V),T,R)>>>>,
+>;
+
+/// A trace implementation using a spine of ordered lists, with run-length encoded updates.
+pub type RleKeySpine<K, T, R> = Spine<
+    Rc<OrdKeyBatch<RunLength<Vector<((K,()),T,R)>>>>,
+    MergeBatcher<VecMerger<((K, ()), T, R)>, T>,
+    RcBuilder<OrdKeyBuilder<RunLength<Vector<((K,()),T,R)>>>>,
+>;
+
 /// A trace implementation backed by columnar storage.
 pub type PreferredSpine<K, V, T, R> = Spine<
     Rc<OrdValBatch<Preferred<K,V,T,R>>>,
@@ -59,6 +99,14 @@ pub type PreferredSpine<K, V, T, R> = Spine<
     RcBuilder<OrdValBuilder<Preferred<K,V,T,R>>>,
 >;
 
+/// A trace implementation for keys without values, based on preferred containers.
+///
+/// Unlike `PreferredSpine<K, (), T, R>`, the batches of the spine store no column of values.
+pub type PreferredKeySpine<K, T, R> = Spine<
+    Rc<OrdKeyBatch<Preferred<K,(),T,R>>>,
+    MergeBatcher<ColumnationMerger<((<K as ToOwned>::Owned,()),T,R)>,T>,
+    RcBuilder<OrdKeyBuilder<Preferred<K,(),T,R>>>,
+>;
 
 // /// A trace implementation backed by columnar storage.
 // pub type ColKeySpine<K, T, R> = Spine<Rc<OrdKeyBatch<TStack<((K,()),T,R)>>>>;
@@ -69,8 +117,8 @@ mod val_batch {
     use abomonation_derive::Abomonation;
     use timely::progress::{Antichain, frontier::AntichainRef};
 
-    use crate::trace::{Batch, BatchReader, Builder, Cursor, Description, Merger};
-    use crate::trace::implementations::BatchContainer;
+    use crate::trace::{Batch, BatchReader, BatchStats, Builder, Cursor, Description, Merger};
+    use crate::trace::implementations::{BatchContainer, UpdateContainer};
     use crate::trace::cursor::MyTrait;
 
     use super::{Layout, Update};
@@ -116,6 +164,14 @@ mod val_batch {
             }
             (lower, upper)
         }
+        /// Seals the containers, once the storage is complete.
+        fn seal(&mut self) {
+            self.keys.seal();
+            self.keys_offs.seal();
+            self.vals.seal();
+            self.vals_offs.seal();
+            self.updates.seal();
+        }
     }
 
     /// An immutable collection of update tuples, from a contiguous interval of logical times.
@@ -156,6 +212,18 @@ mod val_batch {
             // Perhaps we should count such exceptions to the side, to provide a correct accounting.
             self.updates
         }
+        fn stats(&self) -> BatchStats {
+            let storage = &self.storage;
+            BatchStats {
+                records: self.updates,
+                bytes: storage.keys.byte_size() + storage.keys_offs.byte_size() + storage.vals.byte_size() + storage.vals_offs.byte_size() + storage.updates.byte_size(),
+            }
+        }
+        fn may_contain_keys(&self, lower: Self::Key<'_>, upper: Self::Key<'_>) -> bool {
+            // Keys are sorted, and so the first and last keys bound those of the batch.
+            let keys = &self.storage.keys;
+            !keys.is_empty() && keys.index(0) <= upper && lower <= keys.index(keys.len() - 1)
+        }
         fn description(&self) -> &Description<<L::Target as Update>::Time> { &self.description }
     }
 
@@ -226,7 +294,8 @@ mod val_batch {
                 singletons: 0,
             }
         }
-        fn done(self) -> OrdValBatch<L> {
+        fn done(mut self) -> OrdValBatch<L> {
+            self.result.seal();
             OrdValBatch {
                 updates: self.result.updates.len() + self.singletons,
                 storage: self.result,
@@ -396,14 +465,29 @@ mod val_batch {
         /// Transfer updates for an indexed value in `source` into `self`, with compaction applied.
         fn stash_updates_for_val(&mut self, source: &OrdValStorage<L>, index: usize) {
             let (lower, upper) = source.updates_for_value(index);
-            for i in lower .. upper {
-                // NB: Here is where we would need to look back if `lower == upper`.
-                let (time, diff) = &source.updates.index(i).into_owned();
-                use crate::lattice::Lattice;
-                let mut new_time = time.clone();
-                new_time.advance_by(self.description.since().borrow());
-                self.update_stash.push((new_time, diff.clone()));
+            use crate::lattice::Lattice;
+            use timely::order::PartialOrder;
+            if <<L::Target as Update>::Time as Lattice>::TOTAL {
+                // A totally ordered `since` has at most one element, and advances exactly the times less than it.
+                let since = self.description.since().elements().first();
+                let update_stash = &mut self.update_stash;
+                source.updates.map_updates(lower, upper, |time, diff| {
+                    let new_time = match since {
+                        Some(since) if time.less_than(since) => since.clone(),
+                        _ => time.clone(),
+                    };
+                    update_stash.push((new_time, diff.clone()));
+                });
+                return;
             }
+            let since = self.description.since().borrow();
+            let update_stash = &mut self.update_stash;
+            // NB: Here is where we would need to look back if `lower == upper`.
+            source.updates.map_updates(lower, upper, |time, diff| {
+                let mut new_time = time.clone();
+                new_time.advance_by(since);
+                update_stash.push((new_time, diff.clone()));
+            });
         }
 
         /// Consolidates `self.updates_stash` and produces the offset to record, if any.
@@ -413,7 +497,7 @@ mod val_batch {
             if !self.update_stash.is_empty() {
                 // If there is a single element, equal to a just-prior recorded update,
                 // we push nothing and report an unincremented offset to encode this case.
-                if self.update_stash.len() == 1 && self.result.updates.last().map(|ud| self.update_stash.last().unwrap().equals(ud)).unwrap_or(false) {
+                if self.update_stash.len() == 1 && self.result.updates.last().map(|ud| ud.equals(self.update_stash.last().unwrap())).unwrap_or(false) {
                         // Just clear out update_stash, as we won't drain it here.
                     self.update_stash.clear();
                     self.singletons += 1;
@@ -453,12 +537,9 @@ mod val_batch {
 
         fn key<'a>(&self, storage: &'a OrdValBatch<L>) -> Self::Key<'a> { storage.storage.keys.index(self.key_cursor) }
         fn val<'a>(&self, storage: &'a OrdValBatch<L>) -> Self::Val<'a> { storage.storage.vals.index(self.val_cursor) }
-        fn map_times<L2: FnMut(&Self::Time, &Self::Diff)>(&mut self, storage: &OrdValBatch<L>, mut logic: L2) {
+        fn map_times<L2: FnMut(&Self::Time, &Self::Diff)>(&mut self, storage: &OrdValBatch<L>, logic: L2) {
             let (lower, upper) = storage.storage.updates_for_value(self.val_cursor);
-            for index in lower .. upper {
-                let (time, diff) = &storage.storage.updates.index(index);
-                logic(time, diff);
-            }
+            storage.storage.updates.map_updates(lower, upper, logic);
         }
         fn key_valid(&self, storage: &OrdValBatch<L>) -> bool { self.key_cursor < storage.storage.keys.len() }
         fn val_valid(&self, storage: &OrdValBatch<L>) -> bool { self.val_cursor < storage.storage.values_for_key(self.key_cursor).1 }
@@ -522,7 +603,7 @@ mod val_batch {
         /// to recover the singleton to push it into `updates` to join the second update.
         fn push_update(&mut self, time: <L::Target as Update>::Time, diff: <L::Target as Update>::Diff) {
             // If a just-pushed update exactly equals `(time, diff)` we can avoid pushing it.
-            if self.result.updates.last().map(|(t, d)| t == &time && d == &diff) == Some(true) {
+            if self.result.updates.last_update().map(|(t, d)| t == &time && d == &diff) == Some(true) {
                 assert!(self.singleton.is_none());
                 self.singleton = Some((time, diff));
             }
@@ -620,6 +701,7 @@ mod val_batch {
             // Remove any pending singleton, and if it was set increment our count.
             if self.singleton.take().is_some() { self.singletons += 1; }
             self.result.keys_offs.push(self.result.vals.len());
+            self.result.seal();
             OrdValBatch {
                 updates: self.result.updates.len() + self.singletons,
                 storage: self.result,
@@ -636,8 +718,8 @@ mod key_batch {
     use abomonation_derive::Abomonation;
     use timely::progress::{Antichain, frontier::AntichainRef};
 
-    use crate::trace::{Batch, BatchReader, Builder, Cursor, Description, Merger};
-    use crate::trace::implementations::BatchContainer;
+    use crate::trace::{Batch, BatchReader, BatchStats, Builder, Cursor, Description, Merger};
+    use crate::trace::implementations::{BatchContainer, UpdateContainer};
     use crate::trace::cursor::MyTrait;
 
     use super::{Layout, Update};
@@ -673,6 +755,12 @@ mod key_batch {
             }
             (lower, upper)
         }
+        /// Seals the containers, once the storage is complete.
+        fn seal(&mut self) {
+            self.keys.seal();
+            self.keys_offs.seal();
+            self.updates.seal();
+        }
     }
 
     /// An immutable collection of update tuples, from a contiguous interval of logical times.
@@ -714,6 +802,18 @@ mod key_batch {
             // Perhaps we should count such exceptions to the side, to provide a correct accounting.
             self.updates
         }
+        fn stats(&self) -> BatchStats {
+            let storage = &self.storage;
+            BatchStats {
+                records: self.updates,
+                bytes: storage.keys.byte_size() + storage.keys_offs.byte_size() + storage.updates.byte_size(),
+            }
+        }
+        fn may_contain_keys(&self, lower: Self::Key<'_>, upper: Self::Key<'_>) -> bool {
+            // Keys are sorted, and so the first and last keys bound those of the batch.
+            let keys = &self.storage.keys;
+            !keys.is_empty() && keys.index(0) <= upper && lower <= keys.index(keys.len() - 1)
+        }
         fn description(&self) -> &Description<<L::Target as Update>::Time> { &self.description }
     }
 
@@ -779,7 +879,8 @@ mod key_batch {
                 singletons: 0,
             }
         }
-        fn done(self) -> OrdKeyBatch<L> {
+        fn done(mut self) -> OrdKeyBatch<L> {
+            self.result.seal();
             OrdKeyBatch {
                 updates: self.result.updates.len() + self.singletons,
                 storage: self.result,
@@ -865,14 +966,29 @@ mod key_batch {
         /// Transfer updates for an indexed value in `source` into `self`, with compaction applied.
         fn stash_updates_for_key(&mut self, source: &OrdKeyStorage<L>, index: usize) {
             let (lower, upper) = source.updates_for_key(index);
-            for i in lower .. upper {
-                // NB: Here is where we would need to look back if `lower == upper`.
-                let (time, diff) = &source.updates.index(i);
-                use crate::lattice::Lattice;
-                let mut new_time = time.clone();
-                new_time.advance_by(self.description.since().borrow());
-                self.update_stash.push((new_time, diff.clone()));
+            use crate::lattice::Lattice;
+            use timely::order::PartialOrder;
+            if <<L::Target as Update>::Time as Lattice>::TOTAL {
+                // A totally ordered `since` has at most one element, and advances exactly the times less than it.
+                let since = self.description.since().elements().first();
+                let update_stash = &mut self.update_stash;
+                source.updates.map_updates(lower, upper, |time, diff| {
+                    let new_time = match since {
+                        Some(since) if time.less_than(since) => since.clone(),
+                        _ => time.clone(),
+                    };
+                    update_stash.push((new_time, diff.clone()));
+                });
+                return;
             }
+            let since = self.description.since().borrow();
+            let update_stash = &mut self.update_stash;
+            // NB: Here is where we would need to look back if `lower == upper`.
+            source.updates.map_updates(lower, upper, |time, diff| {
+                let mut new_time = time.clone();
+                new_time.advance_by(since);
+                update_stash.push((new_time, diff.clone()));
+            });
         }
 
         /// Consolidates `self.updates_stash` and produces the offset to record, if any.
@@ -882,7 +998,7 @@ mod key_batch {
             if !self.update_stash.is_empty() {
                 // If there is a single element, equal to a just-prior recorded update,
                 // we push nothing and report an unincremented offset to encode this case.
-                if self.update_stash.len() == 1 && self.update_stash.last() == self.result.updates.last() {
+                if self.update_stash.len() == 1 && self.result.updates.last().map(|ud| ud.equals(self.update_stash.last().unwrap())).unwrap_or(false) {
                     // Just clear out update_stash, as we won't drain it here.
                     self.update_stash.clear();
                     self.singletons += 1;
@@ -922,12 +1038,9 @@ mod key_batch {
 
         fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { storage.storage.keys.index(self.key_cursor) }
         fn val<'a>(&self, _storage: &'a Self::Storage) -> &'a () { &() }
-        fn map_times<L2: FnMut(&Self::Time, &Self::Diff)>(&mut self, storage: &Self::Storage, mut logic: L2) {
+        fn map_times<L2: FnMut(&Self::Time, &Self::Diff)>(&mut self, storage: &Self::Storage, logic: L2) {
             let (lower, upper) = storage.storage.updates_for_key(self.key_cursor);
-            for index in lower .. upper {
-                let (time, diff) = &storage.storage.updates.index(index);
-                logic(time, diff);
-            }
+            storage.storage.updates.map_updates(lower, upper, logic);
         }
         fn key_valid(&self, storage: &Self::Storage) -> bool { self.key_cursor < storage.storage.keys.len() }
         fn val_valid(&self, _storage: &Self::Storage) -> bool { !self.val_stepped }
@@ -986,7 +1099,7 @@ mod key_batch {
         /// to recover the singleton to push it into `updates` to join the second update.
         fn push_update(&mut self, time: <L::Target as Update>::Time, diff: <L::Target as Update>::Diff) {
             // If a just-pushed update exactly equals `(time, diff)` we can avoid pushing it.
-            if self.result.updates.last().map(|(t, d)| t == &time && d == &diff) == Some(true) {
+            if self.result.updates.last_update().map(|(t, d)| t == &time && d == &diff) == Some(true) {
                 assert!(self.singleton.is_none());
                 self.singleton = Some((time, diff));
             }
@@ -1057,6 +1170,7 @@ mod key_batch {
             self.result.keys_offs.push(self.result.updates.len());
             // Remove any pending singleton, and if it was set increment our count.
             if self.singleton.take().is_some() { self.singletons += 1; }
+            self.result.seal();
             OrdKeyBatch {
                 updates: self.result.updates.len() + self.singletons,
                 storage: self.result,
@@ -0,0 +1,130 @@
+//! A `Batcher` implementation for input that arrives sorted.
+//!
+//! Inputs that are read from sorted storage, for example bulk backfills, arrive in containers whose
+//! updates are already sorted by `(data, time)` and consolidated. The `PresortedBatcher` retains such
+//! containers as they are, as runs to be merged, and so spends no effort sorting them. Each container
+//! is checked as it arrives, and any that is not sorted and consolidated is sorted and consolidated,
+//! so that the batcher produces correct batches whatever its input, only more slowly.
+//!
+//! Exchanging data among workers cuts and concatenates the containers each worker sends. A container
+//! received from one worker remains sorted if that worker's updates at each time were sent in sorted
+//! order, across all of its containers; this is the case when each worker reads a sorted source.
+
+use timely::communication::message::RefOrMut;
+use timely::logging::WorkerIdentifier;
+use timely::logging_core::Logger;
+use timely::progress::frontier::AntichainRef;
+use timely::progress::{frontier::Antichain, Timestamp};
+
+use crate::consolidation::consolidate_updates;
+use crate::difference::Semigroup;
+use crate::logging::{BatcherEvent, DifferentialEvent};
+use crate::trace::{Batcher, Builder};
+use crate::trace::implementations::sorted_runs::{build_from_sorted_runs, merge_sorted_runs};
+
+/// Creates batches from containers of sorted and consolidated updates.
+pub struct PresortedBatcher<D, T, R> {
+    /// Sorted and consolidated runs, of geometrically decreasing lengths.
+    runs: Vec<Vec<(D, T, R)>>,
+    /// Logger for size accounting.
+    logger: Option<Logger<DifferentialEvent, WorkerIdentifier>>,
+    /// Timely operator ID.
+    operator_id: usize,
+    /// Current lower frontier, we sealed up to here.
+    lower: Antichain<T>,
+    /// The lower-bound frontier of the data, after the last call to seal.
+    frontier: Antichain<T>,
+}
+
+impl<D, T, R> Batcher for PresortedBatcher<D, T, R>
+where
+    D: Ord+Clone+Send,
+    T: Timestamp+Send,
+    R: Semigroup+Send,
+{
+    type Input = Vec<(D, T, R)>;
+    type Output = (D, T, R);
+    type Time = T;
+
+    fn new(logger: Option<Logger<DifferentialEvent, WorkerIdentifier>>, operator_id: usize) -> Self {
+        Self {
+            runs: Vec::new(),
+            logger,
+            operator_id,
+            lower: Antichain::from_elem(T::minimum()),
+            frontier: Antichain::new(),
+        }
+    }
+
+    fn push_container(&mut self, container: RefOrMut<Self::Input>) {
+        let mut run = match container {
+            RefOrMut::Ref(reference) => reference.clone(),
+            RefOrMut::Mut(reference) => std::mem::take(reference),
+        };
+        let sorted = run.windows(2).all(|pair| (&pair[0].0, &pair[0].1) < (&pair[1].0, &pair[1].1));
+        if !sorted || run.iter().any(|(_, _, diff)| diff.is_zero()) {
+            consolidate_updates(&mut run);
+        }
+        if !run.is_empty() {
+            self.account(run.len(), 1);
+            self.runs.push(run);
+            // Merge runs of similar lengths, to keep their number logarithmic in the number of updates.
+            while self.runs.len() > 1 && self.runs[self.runs.len() - 2].len() < 2 * self.runs[self.runs.len() - 1].len() {
+                let run2 = self.runs.pop().unwrap();
+                let run1 = self.runs.pop().unwrap();
+                let length = run1.len() + run2.len();
+                let merged = merge_sorted_runs(vec![run1, run2], 1);
+                self.account(length - merged.len(), -1);
+                self.runs.push(merged);
+            }
+        }
+    }
+
+    fn seal<B: Builder<Input = Self::Output, Time = Self::Time>>(&mut self, upper: Antichain<T>) -> B::Output {
+        let mut readied = Vec::with_capacity(self.runs.len());
+        self.frontier.clear();
+        for run in self.runs.iter_mut() {
+            let (ready, kept): (Vec<_>, Vec<_>) = std::mem::take(run).into_iter().partition(|(_, time, _)| !upper.less_equal(time));
+            for (_, time, _) in kept.iter() {
+                self.frontier.insert(time.clone());
+            }
+            self.account(ready.len(), -1);
+            readied.push(ready);
+            *run = kept;
+        }
+        self.runs.retain(|run| !run.is_empty());
+
+        let since = Antichain::from_elem(T::minimum());
+        let lower = std::mem::replace(&mut self.lower, upper.clone());
+        build_from_sorted_runs::<B, _, _, _>(readied, 1, lower, upper, since)
+    }
+
+    #[inline]
+    fn frontier(&mut self) -> AntichainRef<T> {
+        self.frontier.borrow()
+    }
+}
+
+impl<D, T, R> PresortedBatcher<D, T, R> {
+    /// Account changes in the number of retained updates. Only performs work if a logger exists.
+    #[inline]
+    fn account(&self, records: usize, diff: isize) {
+        if let Some(logger) = &self.logger {
+            logger.log(BatcherEvent {
+                operator: self.operator_id,
+                records_diff: (records as isize) * diff,
+                size_diff: 0,
+                capacity_diff: 0,
+                allocations_diff: 0,
+            })
+        }
+    }
+}
+
+impl<D, T, R> Drop for PresortedBatcher<D, T, R> {
+    fn drop(&mut self) {
+        // Retract accounting information for retained updates.
+        let records = self.runs.iter().map(|run| run.len()).sum();
+        self.account(records, -1);
+    }
+}
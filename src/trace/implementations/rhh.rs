@@ -83,8 +83,8 @@ mod val_batch {
 
     use crate::hashable::Hashable;
 
-    use crate::trace::{Batch, BatchReader, Builder, Cursor, Description, Merger};
-    use crate::trace::implementations::BatchContainer;
+    use crate::trace::{Batch, BatchReader, BatchStats, Builder, Cursor, Description, Merger};
+    use crate::trace::implementations::{BatchContainer, UpdateContainer};
     use crate::trace::cursor::MyTrait;
 
     use super::{Layout, Update, HashOrdered};
@@ -282,6 +282,13 @@ mod val_batch {
             // Perhaps we should count such exceptions to the side, to provide a correct accounting.
             self.updates
         }
+        fn stats(&self) -> BatchStats {
+            let storage = &self.storage;
+            BatchStats {
+                records: self.updates,
+                bytes: storage.keys.byte_size() + storage.keys_offs.byte_size() + storage.vals.byte_size() + storage.vals_offs.byte_size() + storage.updates.byte_size(),
+            }
+        }
         fn description(&self) -> &Description<<L::Target as Update>::Time> { &self.description }
     }
 
@@ -552,14 +559,15 @@ mod val_batch {
         /// Transfer updates for an indexed value in `source` into `self`, with compaction applied.
         fn stash_updates_for_val(&mut self, source: &RhhValStorage<L>, index: usize) {
             let (lower, upper) = source.updates_for_value(index);
-            for i in lower .. upper {
-                // NB: Here is where we would need to look back if `lower == upper`.
-                let (time, diff) = &source.updates.index(i);
+            let since = self.description.since().borrow();
+            let update_stash = &mut self.update_stash;
+            // NB: Here is where we would need to look back if `lower == upper`.
+            source.updates.map_updates(lower, upper, |time, diff| {
                 let mut new_time = time.clone();
                 use crate::lattice::Lattice;
-                new_time.advance_by(self.description.since().borrow());
-                self.update_stash.push((new_time, diff.clone()));
-            }
+                new_time.advance_by(since);
+                update_stash.push((new_time, diff.clone()));
+            });
         }
 
         /// Consolidates `self.updates_stash` and produces the offset to record, if any.
@@ -624,12 +632,9 @@ mod val_batch {
             storage.storage.keys.index(self.key_cursor) 
         }
         fn val<'a>(&self, storage: &'a RhhValBatch<L>) -> Self::Val<'a> { storage.storage.vals.index(self.val_cursor) }
-        fn map_times<L2: FnMut(&Self::Time, &Self::Diff)>(&mut self, storage: &RhhValBatch<L>, mut logic: L2) {
+        fn map_times<L2: FnMut(&Self::Time, &Self::Diff)>(&mut self, storage: &RhhValBatch<L>, logic: L2) {
             let (lower, upper) = storage.storage.updates_for_value(self.val_cursor);
-            for index in lower .. upper {
-                let (time, diff) = &storage.storage.updates.index(index);
-                logic(time, diff);
-            }
+            storage.storage.updates.map_updates(lower, upper, logic);
         }
         fn key_valid(&self, storage: &RhhValBatch<L>) -> bool { self.key_cursor < storage.storage.keys.len() }
         fn val_valid(&self, storage: &RhhValBatch<L>) -> bool { self.val_cursor < storage.storage.values_for_key(self.key_cursor).1 }
@@ -718,7 +723,7 @@ mod val_batch {
         /// to recover the singleton to push it into `updates` to join the second update.
         fn push_update(&mut self, time: <L::Target as Update>::Time, diff: <L::Target as Update>::Diff) {
             // If a just-pushed update exactly equals `(time, diff)` we can avoid pushing it.
-            if self.result.updates.last().map(|(t, d)| t == &time && d == &diff) == Some(true) {
+            if self.result.updates.last_update().map(|(t, d)| t == &time && d == &diff) == Some(true) {
                 assert!(self.singleton.is_none());
                 self.singleton = Some((time, diff));
             }
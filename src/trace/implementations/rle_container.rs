@@ -0,0 +1,181 @@
+//! A container that run-length encodes the diffs of its updates.
+//!
+//! The updates of a batch are frequently repetitive: count-like collections have a diff of one for
+//! nearly every record, and a snapshot's updates nearly all have the same diff. The ordered batches
+//! already avoid storing the update of a value whose only update repeats the one before it, but values
+//! and keys with several updates still store a diff for every update. A `RunLengthContainer` stores the
+//! time of each update, and each run of equal consecutive diffs once, with the index at which the run
+//! ends, and so stores the diffs in space proportional to the number of runs.
+//!
+//! Reading an update at an arbitrary index is a binary search among the ends of runs, but reading the
+//! updates of a range, as cursors and mergers do, searches once and then walks the runs in order.
+
+use abomonation_derive::Abomonation;
+
+use crate::trace::implementations::{BatchContainer, UpdateContainer};
+
+pub use self::wrapper::RunLengthRef;
+
+/// A container of `(time, diff)` updates that stores runs of equal consecutive diffs once each.
+#[derive(Abomonation, Debug)]
+pub struct RunLengthContainer<T, R> {
+    /// The time of each update.
+    times: Vec<T>,
+    /// The diff of each run.
+    diffs: Vec<R>,
+    /// The index one past the last update of each run.
+    ends: Vec<usize>,
+}
+
+impl<T, R> RunLengthContainer<T, R> {
+    /// The number of runs, which is the number of diffs the container stores.
+    pub fn runs(&self) -> usize {
+        self.diffs.len()
+    }
+    /// The run containing the update at `index`.
+    fn run(&self, index: usize) -> usize {
+        // The first run whose end exceeds `index`.
+        self.ends.partition_point(|end| *end <= index)
+    }
+}
+
+impl<T: Ord + Clone + 'static, R: Ord + Clone + 'static> BatchContainer for RunLengthContainer<T, R> {
+    type PushItem = (T, R);
+    type ReadItem<'a> = RunLengthRef<'a, T, R>;
+
+    fn push(&mut self, (time, diff): (T, R)) {
+        if self.diffs.last() == Some(&diff) {
+            // Extend the last run by one update.
+            *self.ends.last_mut().unwrap() += 1;
+        }
+        else {
+            self.diffs.push(diff);
+            self.ends.push(self.times.len() + 1);
+        }
+        self.times.push(time);
+    }
+    fn copy_push(&mut self, (time, diff): &(T, R)) {
+        self.copy(RunLengthRef::new(time, diff));
+    }
+    fn copy(&mut self, item: Self::ReadItem<'_>) {
+        let (time, diff) = item.into_parts();
+        if self.diffs.last() == Some(diff) {
+            // Extend the last run by one update.
+            *self.ends.last_mut().unwrap() += 1;
+        }
+        else {
+            self.diffs.push(diff.clone());
+            self.ends.push(self.times.len() + 1);
+        }
+        self.times.push(time.clone());
+    }
+    fn copy_range(&mut self, other: &Self, start: usize, end: usize) {
+        other.map_updates(start, end, |time, diff| self.copy(RunLengthRef::new(time, diff)));
+    }
+    fn with_capacity(size: usize) -> Self {
+        // The number of runs is not known, and is typically far smaller than `size`.
+        Self {
+            times: Vec::with_capacity(size),
+            diffs: Vec::new(),
+            ends: Vec::new(),
+        }
+    }
+    fn merge_capacity(cont1: &Self, cont2: &Self) -> Self {
+        let runs = cont1.runs() + cont2.runs();
+        Self {
+            times: Vec::with_capacity(cont1.len() + cont2.len()),
+            diffs: Vec::with_capacity(runs),
+            ends: Vec::with_capacity(runs),
+        }
+    }
+    fn index(&self, index: usize) -> Self::ReadItem<'_> {
+        RunLengthRef::new(&self.times[index], &self.diffs[self.run(index)])
+    }
+    fn len(&self) -> usize {
+        self.times.len()
+    }
+    fn last(&self) -> Option<Self::ReadItem<'_>> {
+        self.times.last().map(|time| RunLengthRef::new(time, self.diffs.last().unwrap()))
+    }
+    fn byte_size(&self) -> usize {
+        self.times.len() * std::mem::size_of::<T>() + self.diffs.len() * (std::mem::size_of::<R>() + std::mem::size_of::<usize>())
+    }
+}
+
+impl<T: Ord + Clone + 'static, R: Ord + Clone + 'static> UpdateContainer<T, R> for RunLengthContainer<T, R> {
+    fn index_update(&self, index: usize) -> (&T, &R) {
+        self.index(index).into_parts()
+    }
+    fn last_update(&self) -> Option<(&T, &R)> {
+        self.last().map(|item| item.into_parts())
+    }
+    // Finds the run of `lower` once, and then walks the runs in order.
+    fn map_updates<F: FnMut(&T, &R)>(&self, lower: usize, upper: usize, mut logic: F) {
+        if lower < upper {
+            let mut run = self.run(lower);
+            for index in lower .. upper {
+                if self.ends[run] <= index { run += 1; }
+                logic(&self.times[index], &self.diffs[run]);
+            }
+        }
+    }
+}
+
+mod wrapper {
+
+    use std::cmp::Ordering;
+
+    use crate::trace::MyTrait;
+
+    /// A reference to an update of a `RunLengthContainer`: its time, and the diff of its run.
+    #[derive(Debug)]
+    pub struct RunLengthRef<'a, T, R> {
+        time: &'a T,
+        diff: &'a R,
+    }
+
+    impl<'a, T, R> RunLengthRef<'a, T, R> {
+        /// A reference to the update with time `time` and diff `diff`.
+        pub fn new(time: &'a T, diff: &'a R) -> Self { Self { time, diff } }
+        /// References to the time and diff of the update.
+        pub fn into_parts(self) -> (&'a T, &'a R) { (self.time, self.diff) }
+    }
+
+    impl<'a, T, R> Copy for RunLengthRef<'a, T, R> { }
+    impl<'a, T, R> Clone for RunLengthRef<'a, T, R> {
+        fn clone(&self) -> Self { *self }
+    }
+
+    impl<'a, 'b, T: Ord, R: Ord> PartialEq<RunLengthRef<'a, T, R>> for RunLengthRef<'b, T, R> {
+        fn eq(&self, other: &RunLengthRef<'a, T, R>) -> bool {
+            self.time == other.time && self.diff == other.diff
+        }
+    }
+    impl<'a, T: Ord, R: Ord> Eq for RunLengthRef<'a, T, R> { }
+    impl<'a, 'b, T: Ord, R: Ord> PartialOrd<RunLengthRef<'a, T, R>> for RunLengthRef<'b, T, R> {
+        fn partial_cmp(&self, other: &RunLengthRef<'a, T, R>) -> Option<Ordering> {
+            Some((self.time, self.diff).cmp(&(other.time, other.diff)))
+        }
+    }
+    impl<'a, T: Ord, R: Ord> Ord for RunLengthRef<'a, T, R> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.partial_cmp(other).unwrap()
+        }
+    }
+    impl<'a, T: Ord + Clone, R: Ord + Clone> MyTrait<'a> for RunLengthRef<'a, T, R> {
+        type Owned = (T, R);
+        fn into_owned(self) -> Self::Owned {
+            (self.time.clone(), self.diff.clone())
+        }
+        fn clone_onto(&self, other: &mut Self::Owned) {
+            other.0.clone_from(self.time);
+            other.1.clone_from(self.diff);
+        }
+        fn compare(&self, other: &Self::Owned) -> Ordering {
+            (self.time, self.diff).cmp(&(&other.0, &other.1))
+        }
+        fn borrow_as(other: &'a Self::Owned) -> Self {
+            Self::new(&other.0, &other.1)
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! A `TraceBackend` that stores batches in a RocksDB database.
+//!
+//! Each batch is stored under two keys: its description under `d` followed by its identifier, and its
+//! updates under `u` followed by its identifier, both serialized with `bincode` after a versioned
+//! `encoding::Header`. Identifiers are written big-endian, so that the descriptions are listed in order
+//! of identifier.
+//!
+//! This module is available with the `rocksdb-backend` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use timely::dataflow::operators::generic::OperatorInfo;
+//! use differential_dataflow::trace::Trace;
+//! use differential_dataflow::trace::implementations::ValSpine;
+//! use differential_dataflow::trace::implementations::backend::Backed;
+//! use differential_dataflow::trace::implementations::rocksdb::RocksBackend;
+//! use differential_dataflow::trace::testing;
+//!
+//! type Spine = ValSpine<u64, u64, u64, i64>;
+//! type Builder = <Spine as Trace>::Builder;
+//!
+//! let path = std::env::temp_dir().join(format!("rocksdb-backend-{}", std::process::id()));
+//! let info = || OperatorInfo::new(0, 0, &[]);
+//!
+//! let backend = RocksBackend::open(&path).unwrap();
+//! let mut trace = Backed::with_backend(Spine::new(info(), None, None), backend);
+//! for batch in testing::random_batches::<Builder>(0, 4, 100, 10, 10) {
+//!     trace.insert(batch);
+//! }
+//! drop(trace);
+//!
+//! // Possibly after a restart.
+//! let backend = RocksBackend::open(&path).unwrap();
+//! let restored = Backed::restore(Spine::new(info(), None, None), backend).unwrap();
+//! assert_eq!(restored.stored(), 4);
+//! # drop(restored);
+//! # std::fs::remove_dir_all(&path).unwrap();
+//! ```
+
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use ::rocksdb::{DB, Direction, IteratorMode, WriteBatch};
+use serde::{Serialize, de::DeserializeOwned};
+use timely::progress::frontier::AntichainRef;
+use timely::PartialOrder;
+
+use crate::trace::Description;
+use super::backend::{TraceBackend, intersects};
+use super::encoding::{Format, Header, unsupported_version};
+
+/// The prefix of keys that hold batch descriptions.
+const DESCRIPTION: u8 = b'd';
+/// The prefix of keys that hold batch updates.
+const UPDATES: u8 = b'u';
+/// The version of the layout of stored values.
+const VERSION: u16 = 1;
+
+/// A backend that stores batches in a RocksDB database.
+pub struct RocksBackend<K, V, T, R> {
+    /// The database.
+    db: DB,
+    /// The identifier of the next batch.
+    next: u64,
+    phantom: PhantomData<((K, V), T, R)>,
+}
+
+impl<K, V, T, R> RocksBackend<K, V, T, R> {
+    /// Opens or creates the database at `path`.
+    ///
+    /// New batches are identified after the batches already in the database.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let db = DB::open_default(path).map_err(to_io)?;
+        let next = {
+            let mut entries = db.iterator(IteratorMode::From(&[DESCRIPTION + 1], Direction::Reverse));
+            match entries.next() {
+                Some(entry) => {
+                    let (key, _) = entry.map_err(to_io)?;
+                    if key.first() == Some(&DESCRIPTION) { decode_id(&key)? + 1 } else { 0 }
+                }
+                None => 0,
+            }
+        };
+        Ok(Self { db, next, phantom: PhantomData })
+    }
+}
+
+impl<K, V, T, R> TraceBackend for RocksBackend<K, V, T, R>
+where
+    K: Serialize+DeserializeOwned,
+    V: Serialize+DeserializeOwned,
+    T: Serialize+DeserializeOwned+PartialOrder,
+    R: Serialize+DeserializeOwned,
+{
+    type Key = K;
+    type Val = V;
+    type Time = T;
+    type Diff = R;
+
+    fn write_batch(&mut self, description: &Description<T>, updates: Vec<((K, V), T, R)>) -> io::Result<u64> {
+        let id = self.next;
+        let mut batch = WriteBatch::default();
+        batch.put(encode_id(DESCRIPTION, id), encode(description)?);
+        batch.put(encode_id(UPDATES, id), encode(&updates)?);
+        self.db.write(batch).map_err(to_io)?;
+        self.next += 1;
+        Ok(id)
+    }
+    fn read_batch(&self, id: u64) -> io::Result<Vec<((K, V), T, R)>> {
+        let bytes = self.db
+            .get(encode_id(UPDATES, id))
+            .map_err(to_io)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no batch {}", id)))?;
+        decode(&bytes)
+    }
+    fn remove_batch(&mut self, id: u64) -> io::Result<()> {
+        let mut batch = WriteBatch::default();
+        batch.delete(encode_id(DESCRIPTION, id));
+        batch.delete(encode_id(UPDATES, id));
+        self.db.write(batch).map_err(to_io)
+    }
+    fn list(&self, lower: AntichainRef<T>, upper: AntichainRef<T>) -> io::Result<Vec<(u64, Description<T>)>> {
+        let mut result = Vec::new();
+        for entry in self.db.iterator(IteratorMode::From(&[DESCRIPTION], Direction::Forward)) {
+            let (key, value) = entry.map_err(to_io)?;
+            if key.first() != Some(&DESCRIPTION) { break; }
+            let description: Description<T> = decode(&value)?;
+            if intersects(&description, lower, upper) {
+                result.push((decode_id(&key)?, description));
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Serializes `value` after a header.
+fn encode<D: Serialize+?Sized>(value: &D) -> io::Result<Vec<u8>> {
+    let mut bytes = Header { format: Format::Bincode, version: VERSION }.encode().to_vec();
+    bincode::serialize_into(&mut bytes, value).map_err(to_io)?;
+    Ok(bytes)
+}
+
+/// Deserializes a value written by `encode`.
+fn decode<D: DeserializeOwned>(bytes: &[u8]) -> io::Result<D> {
+    match Header::decode_version(Format::Bincode, bytes)? {
+        (VERSION, payload) => bincode::deserialize(payload).map_err(to_io),
+        (version, _) => Err(unsupported_version(Format::Bincode, version)),
+    }
+}
+
+/// The database key for `prefix` and the batch `id`.
+fn encode_id(prefix: u8, id: u64) -> [u8; 9] {
+    let mut key = [prefix; 9];
+    key[1..].copy_from_slice(&id.to_be_bytes());
+    key
+}
+
+/// The batch identifier of a database key.
+fn decode_id(key: &[u8]) -> io::Result<u64> {
+    let bytes = key.get(1..9).and_then(|bytes| bytes.try_into().ok());
+    bytes
+        .map(u64::from_be_bytes)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed batch key"))
+}
+
+/// Reports a database or serialization error as an IO error.
+fn to_io<E: std::error::Error+Send+Sync+'static>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
@@ -0,0 +1,222 @@
+//! Experimental export of arrangements through shared memory.
+//!
+//! An exporting process writes the batches of a trace into a single file, laid out as the `rkyv`
+//! archive of a `SharedArchive`. Other processes map the file into memory read-only, and navigate
+//! its batches in place with `ArchiveCursor`s, without copying or deserializing them. Placing the file
+//! in a memory-backed file system (for example `/dev/shm`) makes the region shared memory, so that a
+//! sidecar process can serve reads of an arrangement without holding a second copy of it.
+//!
+//! The file begins with a versioned `encoding::Header`, so that later versions of the crate can read
+//! files written by this one.
+//!
+//! Exports are snapshots: `export_trace` writes a new file and renames it over the previous one, so
+//! that readers never observe a partially written file, and readers that opened the previous file
+//! continue to read it until they re-open the path.
+//!
+//! This module is available with the `shared-memory` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use timely::dataflow::operators::generic::OperatorInfo;
+//! use differential_dataflow::trace::{Cursor, Trace, TraceReader};
+//! use differential_dataflow::trace::implementations::ValSpine;
+//! use differential_dataflow::trace::implementations::shared::{export_trace, SharedArrangement, ArchiveCursor};
+//! use differential_dataflow::trace::testing;
+//!
+//! type Spine = ValSpine<u64, u64, u64, i64>;
+//! type Builder = <Spine as Trace>::Builder;
+//!
+//! let mut trace = Spine::new(OperatorInfo::new(0, 0, &[]), None, None);
+//! for batch in testing::random_batches::<Builder>(0, 4, 100, 10, 10) {
+//!     trace.insert(batch);
+//! }
+//!
+//! let path = std::env::temp_dir().join(format!("shared-arrangement-{}", std::process::id()));
+//! export_trace(&mut trace, &path, |v| *v).unwrap();
+//!
+//! // Possibly in another process.
+//! let shared = SharedArrangement::<u64, u64, u64, i64>::open(&path).unwrap();
+//! let mut count = 0;
+//! for batch in shared.archive().batches.iter() {
+//!     let mut cursor = ArchiveCursor::new(batch);
+//!     cursor.seek_key_by(|key| key.cmp(&5));
+//!     while cursor.key_valid() && *cursor.key() == 5 {
+//!         while cursor.val_valid() {
+//!             count += cursor.updates().len();
+//!             cursor.step_val();
+//!         }
+//!         cursor.step_key();
+//!     }
+//! }
+//!
+//! // The export holds the same updates of the key as the trace.
+//! let mut expected = 0;
+//! let (mut cursor, storage) = trace.cursor();
+//! cursor.seek_key(&storage, &5);
+//! if cursor.get_key(&storage) == Some(&5) {
+//!     while cursor.val_valid(&storage) {
+//!         cursor.map_times(&storage, |_, _| expected += 1);
+//!         cursor.step_val(&storage);
+//!     }
+//! }
+//! assert!(count > 0);
+//! assert_eq!(count, expected);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use memmap2::Mmap;
+use rkyv::{Archive, Archived, Deserialize, Serialize};
+use rkyv::ser::serializers::AllocSerializer;
+use rkyv::validation::validators::DefaultValidator;
+use rkyv::bytecheck::CheckBytes;
+
+use crate::trace::TraceReader;
+use crate::trace::implementations::archive::{ArchivedBatchArchive, BatchArchive};
+use crate::trace::implementations::encoding::{Format, Header, unsupported_version};
+
+/// The scratch space to reserve when serializing an archive.
+const SCRATCH: usize = 4096;
+/// The version of the layout of exported files.
+const VERSION: u16 = 1;
+
+/// The batches of a trace, laid out for zero-copy archiving.
+#[derive(Archive, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[archive(check_bytes)]
+pub struct SharedArchive<K, V, T, R> {
+    /// The batches of the trace, in the order of their descriptions.
+    pub batches: Vec<BatchArchive<K, V, T, R>>,
+}
+
+/// Writes the batches of `trace` to `path`, converting values to owned values with `val`.
+///
+/// The archive is written to a temporary file alongside `path`, which then replaces `path`.
+pub fn export_trace<Tr, K, V, F>(trace: &mut Tr, path: &Path, val: F) -> io::Result<()>
+where
+    Tr: TraceReader<KeyOwned=K>,
+    F: Fn(Tr::Val<'_>) -> V,
+    SharedArchive<K, V, Tr::Time, Tr::Diff>: Serialize<AllocSerializer<SCRATCH>>,
+{
+    let mut archive = SharedArchive { batches: Vec::new() };
+    trace.map_batches(|batch| archive.batches.push(BatchArchive::from_batch(batch, |v| val(v))));
+    let bytes = rkyv::to_bytes::<_, SCRATCH>(&archive).expect("serializing to memory cannot fail");
+
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let mut file = File::create(&temporary)?;
+    file.write_all(&Header { format: Format::SharedArchive, version: VERSION }.encode())?;
+    file.write_all(&bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&temporary, path)
+}
+
+/// A read-only mapping of an exported arrangement.
+pub struct SharedArrangement<K, V, T, R> {
+    mmap: Mmap,
+    /// The position of the archive in the mapping, after any header.
+    offset: usize,
+    phantom: PhantomData<(K, V, T, R)>,
+}
+
+impl<K, V, T, R> SharedArrangement<K, V, T, R>
+where
+    K: Archive,
+    V: Archive,
+    T: Archive,
+    R: Archive,
+    ArchivedSharedArchive<K, V, T, R>: for<'a> CheckBytes<DefaultValidator<'a>>,
+{
+    /// Maps the exported arrangement at `path`, and validates its contents.
+    ///
+    /// The file must not be modified while it is mapped, which `export_trace` ensures by replacing
+    /// rather than overwriting files.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: `export_trace` never modifies files once written, but only replaces them.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let offset = match Header::decode_version(Format::SharedArchive, &mmap[..])? {
+            (VERSION, payload) => mmap.len() - payload.len(),
+            (version, _) => return Err(unsupported_version(Format::SharedArchive, version)),
+        };
+        rkyv::check_archived_root::<SharedArchive<K, V, T, R>>(&mmap[offset..])
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        Ok(SharedArrangement { mmap, offset, phantom: PhantomData })
+    }
+
+    /// The archived batches, read in place.
+    pub fn archive(&self) -> &ArchivedSharedArchive<K, V, T, R> {
+        // Safety: the bytes were validated in `open`, and are not modified while mapped.
+        unsafe { rkyv::archived_root::<SharedArchive<K, V, T, R>>(&self.mmap[self.offset..]) }
+    }
+}
+
+/// A cursor over the keys and values of an archived batch.
+///
+/// The cursor mirrors the navigation of `Cursor`, but presents archived keys and values, and the
+/// archived `(time, diff)` updates of each value as a slice.
+pub struct ArchiveCursor<'a, K: Archive, V: Archive, T: Archive, R: Archive> {
+    batch: &'a ArchivedBatchArchive<K, V, T, R>,
+    key: usize,
+    val: usize,
+}
+
+impl<'a, K: Archive, V: Archive, T: Archive, R: Archive> ArchiveCursor<'a, K, V, T, R> {
+    /// Creates a cursor positioned at the first key and value of `batch`.
+    pub fn new(batch: &'a ArchivedBatchArchive<K, V, T, R>) -> Self {
+        ArchiveCursor { batch, key: 0, val: 0 }
+    }
+
+    /// Indicates if the current key is valid.
+    pub fn key_valid(&self) -> bool { self.key < self.batch.keys.len() }
+    /// Indicates if the current value is valid.
+    pub fn val_valid(&self) -> bool { self.key_valid() && self.val < self.batch.vals_range(self.key).end }
+
+    /// The current key. Panics if invalid.
+    pub fn key(&self) -> &'a Archived<K> { &self.batch.keys[self.key] }
+    /// The current value. Panics if invalid.
+    pub fn val(&self) -> &'a Archived<V> { &self.batch.vals[self.val] }
+    /// The updates of the current value. Panics if invalid.
+    pub fn updates(&self) -> &'a [Archived<(T, R)>] { self.batch.updates_of(self.val) }
+
+    /// Advances the cursor to the next key.
+    pub fn step_key(&mut self) {
+        self.key += 1;
+        self.rewind_vals();
+    }
+    /// Advances the cursor to the first key not less than the sought key.
+    ///
+    /// The `order` function compares keys to the sought key.
+    pub fn seek_key_by<F: Fn(&Archived<K>)->Ordering>(&mut self, order: F) {
+        let keys = &self.batch.keys[self.key ..];
+        self.key += keys.partition_point(|key| order(key) == Ordering::Less);
+        self.rewind_vals();
+    }
+    /// Advances the cursor to the next value.
+    pub fn step_val(&mut self) { self.val += 1; }
+    /// Advances the cursor to the first value not less than the sought value.
+    ///
+    /// The `order` function compares values to the sought value.
+    pub fn seek_val_by<F: Fn(&Archived<V>)->Ordering>(&mut self, order: F) {
+        if self.key_valid() {
+            let vals = &self.batch.vals[self.val .. self.batch.vals_range(self.key).end];
+            self.val += vals.partition_point(|val| order(val) == Ordering::Less);
+        }
+    }
+    /// Rewinds the cursor to the first key.
+    pub fn rewind_keys(&mut self) {
+        self.key = 0;
+        self.rewind_vals();
+    }
+    /// Rewinds the cursor to the first value of the current key.
+    pub fn rewind_vals(&mut self) {
+        if self.key_valid() {
+            self.val = self.batch.vals_range(self.key).start;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Forming batches from runs of updates that are already sorted.
+//!
+//! Batchers sort and consolidate updates one worker thread at a time, which is the right thing for
+//! streams of moderately sized batches. For a large initial snapshot that has been partitioned and
+//! sorted elsewhere, perhaps in parallel, the remaining work is a multi-way merge of the sorted runs
+//! and the formation of the batch. The merge proceeds as a tree of pairwise merges, whose subtrees are
+//! merged on separate threads when parallelism is requested.
+
+use crate::difference::Semigroup;
+use crate::trace::Builder;
+use timely::progress::Antichain;
+
+/// Merges sorted and consolidated runs of updates into one sorted and consolidated run.
+///
+/// Each run must be sorted by `(data, time)`, and contain each `(data, time)` at most once. Updates
+/// with equal data and time from different runs are accumulated, and those that accumulate to zero are
+/// discarded. The runs are merged by up to `threads` threads, including the calling thread.
+pub fn merge_sorted_runs<D, T, R>(mut runs: Vec<Vec<(D, T, R)>>, threads: usize) -> Vec<(D, T, R)>
+where
+    D: Ord+Send,
+    T: Ord+Send,
+    R: Semigroup+Send,
+{
+    match runs.len() {
+        0 => Vec::new(),
+        1 => runs.pop().unwrap(),
+        _ => {
+            let right = runs.split_off(runs.len() / 2);
+            let left = runs;
+            let (left, right) = if threads > 1 {
+                std::thread::scope(|scope| {
+                    let handle = scope.spawn(|| merge_sorted_runs(right, threads / 2));
+                    let left = merge_sorted_runs(left, threads - threads / 2);
+                    (left, handle.join().expect("merging thread panicked"))
+                })
+            }
+            else {
+                (merge_sorted_runs(left, 1), merge_sorted_runs(right, 1))
+            };
+            merge_two(left, right)
+        }
+    }
+}
+
+/// Merges two sorted and consolidated runs.
+fn merge_two<D: Ord, T: Ord, R: Semigroup>(run1: Vec<(D, T, R)>, run2: Vec<(D, T, R)>) -> Vec<(D, T, R)> {
+    let mut result = Vec::with_capacity(run1.len() + run2.len());
+    let mut iter1 = run1.into_iter().peekable();
+    let mut iter2 = run2.into_iter().peekable();
+    loop {
+        let order = match (iter1.peek(), iter2.peek()) {
+            (Some((d1, t1, _)), Some((d2, t2, _))) => (d1, t1).cmp(&(d2, t2)),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => break,
+        };
+        match order {
+            std::cmp::Ordering::Less => result.push(iter1.next().unwrap()),
+            std::cmp::Ordering::Greater => result.push(iter2.next().unwrap()),
+            std::cmp::Ordering::Equal => {
+                let (data, time, mut diff) = iter1.next().unwrap();
+                let (_, _, diff2) = iter2.next().unwrap();
+                diff.plus_equals(&diff2);
+                if !diff.is_zero() {
+                    result.push((data, time, diff));
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Builds a batch from sorted and consolidated runs of updates.
+///
+/// The runs are merged by `merge_sorted_runs` using up to `threads` threads, and the result is pushed
+/// into a builder sized for it. All update times must lie between `lower` and `upper`.
+pub fn build_from_sorted_runs<Bu, D, T, R>(
+    runs: Vec<Vec<(D, T, R)>>,
+    threads: usize,
+    lower: Antichain<T>,
+    upper: Antichain<T>,
+    since: Antichain<T>,
+) -> Bu::Output
+where
+    Bu: Builder<Input=(D, T, R), Time=T>,
+    D: Ord+Send,
+    T: Ord+Send,
+    R: Semigroup+Send,
+{
+    let updates = merge_sorted_runs(runs, threads);
+    let distinct = updates.windows(2).filter(|pair| pair[0].0 != pair[1].0).count() + usize::from(!updates.is_empty());
+    let mut builder = Bu::with_capacity(distinct, distinct, updates.len());
+    for update in updates {
+        builder.push(update);
+    }
+    builder.done(lower, upper, since)
+}
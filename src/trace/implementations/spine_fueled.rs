@@ -70,14 +70,34 @@
 
 
 use crate::logging::Logger;
-use crate::trace::{Batch, Batcher, Builder, BatchReader, Trace, TraceReader, ExertionLogic};
+use crate::trace::{Batch, Batcher, Builder, BatchReader, Trace, TraceReader, ExertionLogic, MemoryPressure, MemoryPressureLogic};
 use crate::trace::cursor::CursorList;
-use crate::trace::Merger;
+use crate::trace::{Merger, MergeProgress};
 
 use ::timely::dataflow::operators::generic::OperatorInfo;
 use ::timely::progress::{Antichain, frontier::AntichainRef};
 use ::timely::order::PartialOrder;
 
+use crate::lattice::Lattice;
+
+/// Compares frontiers as `PartialOrder::less_equal` does.
+///
+/// Frontiers of totally ordered times have at most one element, and are compared by their elements
+/// rather than by the general antichain comparison.
+#[inline]
+fn frontier_less_equal<T: Lattice>(lower: AntichainRef<T>, upper: AntichainRef<T>) -> bool {
+    if T::TOTAL {
+        match (lower.first(), upper.first()) {
+            (Some(lower), Some(upper)) => lower.less_equal(upper),
+            (None, upper) => upper.is_none(),
+            (Some(_), None) => true,
+        }
+    }
+    else {
+        PartialOrder::less_equal(&lower, &upper)
+    }
+}
+
 /// An append-only collection of update tuples.
 ///
 /// A spine maintains a small number of immutable collections of update tuples, merging the collections when
@@ -102,6 +122,8 @@ where
     exert_logic_param: Vec<(usize, usize, usize)>,
     /// Logic to indicate whether and how many records we should introduce in the absence of actual updates.
     exert_logic: Option<ExertionLogic>,
+    /// Logic to report memory pressure, which makes merging more or less eager.
+    memory_pressure_logic: Option<MemoryPressureLogic>,
     phantom: std::marker::PhantomData<(BA, BU)>,
 }
 
@@ -146,7 +168,7 @@ where
         // Check that `upper` is greater or equal to `self.physical_frontier`.
         // Otherwise, the cut could be in `self.merging` and it is user error anyhow.
         // assert!(upper.iter().all(|t1| self.physical_frontier.iter().any(|t2| t2.less_equal(t1))));
-        assert!(PartialOrder::less_equal(&self.physical_frontier.borrow(), &upper));
+        assert!(frontier_less_equal(self.physical_frontier.borrow(), upper));
 
         let mut cursors = Vec::new();
         let mut storage = Vec::new();
@@ -197,8 +219,8 @@ where
                 // TODO: It is not clear if this is the 100% correct logic, due
                 // to the possible non-total-orderedness of the frontiers.
 
-                let include_lower = PartialOrder::less_equal(&batch.lower().borrow(), &upper);
-                let include_upper = PartialOrder::less_equal(&batch.upper().borrow(), &upper);
+                let include_lower = frontier_less_equal(batch.lower().borrow(), upper);
+                let include_upper = frontier_less_equal(batch.upper().borrow(), upper);
 
                 if include_lower != include_upper && upper != batch.lower().borrow() {
                     panic!("`cursor_through`: `upper` straddles batch");
@@ -214,6 +236,23 @@ where
 
         Some((CursorList::new(cursors, &storage), storage))
     }
+    fn cursor_for_keys(&mut self, lower: Self::Key<'_>, upper: Self::Key<'_>) -> (Self::Cursor, Self::Storage) {
+
+        // We can't grab a cursor into a closed trace, whose batches may have been dropped.
+        if self.logical_frontier.borrow().is_empty() {
+            panic!("unable to acquire complete cursor for trace; is it closed?");
+        }
+
+        // Include the batches `cursor()` would, other than those without keys in the range.
+        let mut storage = Vec::new();
+        self.map_batches(|batch| {
+            if !batch.is_empty() && batch.may_contain_keys(lower, upper) {
+                storage.push(batch.clone());
+            }
+        });
+        let cursors = storage.iter().map(|batch| batch.cursor()).collect();
+        (CursorList::new(cursors, &storage), storage)
+    }
     #[inline]
     fn set_logical_compaction(&mut self, frontier: AntichainRef<B::Time>) {
         self.logical_frontier.clear();
@@ -246,6 +285,24 @@ where
             f(batch);
         }
     }
+
+    fn read_merged_upper(&mut self, target: &mut Antichain<Self::Time>) {
+        // Batches are visited from oldest to newest; the first merge in progress ends the prefix,
+        // as do pending batches, which have yet to enter the spine.
+        target.clear();
+        target.insert(<Self::Time as timely::progress::Timestamp>::minimum());
+        for batch in self.merging.iter().rev() {
+            match batch {
+                MergeState::Double(MergeVariant::InProgress(..)) => { return; },
+                MergeState::Double(MergeVariant::Complete(Some((batch, _)))) => { target.clone_from(batch.upper()); },
+                MergeState::Single(Some(batch)) => { target.clone_from(batch.upper()); },
+                _ => { },
+            }
+        }
+        if self.pending.is_empty() {
+            target.clone_from(&self.upper);
+        }
+    }
 }
 
 // A trace implementation for any key type that can be borrowed from or converted into `Key`.
@@ -275,19 +332,13 @@ where
     fn exert(&mut self) {
         // If there is work to be done, ...
         self.tidy_layers();
+        // If memory is tight, release the inputs of merges in progress.
+        if self.memory_pressure() == MemoryPressure::Tight {
+            self.complete_merges();
+        }
         // Determine whether we should apply effort independent of updates.
         if let Some(effort) = self.exert_effort() {
-
-            // If any merges exist, we can directly call `apply_fuel`.
-            if self.merging.iter().any(|b| b.is_double()) {
-                self.apply_fuel(&mut (effort as isize));
-            }
-            // Otherwise, we'll need to introduce fake updates to move merges along.
-            else {
-                // Introduce an empty batch with roughly *effort number of virtual updates.
-                let level = effort.next_power_of_two().trailing_zeros() as usize;
-                self.introduce_batch(None, level);
-            }
+            self.apply_effort(effort);
             // We were not in reduced form, so let's check again in the future.
             if let Some(activator) = &self.activator {
                 activator.activate();
@@ -295,10 +346,23 @@ where
         }
     }
 
+    fn exert_with(&mut self, effort: usize) {
+        self.tidy_layers();
+        // Effort is only useful if merges are in progress, or if more than one batch remains.
+        let batches = self.merging.iter().filter(|b| b.len() > 0).count();
+        if self.merging.iter().any(|b| b.is_double()) || batches > 1 {
+            self.apply_effort(effort);
+        }
+    }
+
     fn set_exert_logic(&mut self, logic: ExertionLogic) {
         self.exert_logic = Some(logic);
     }
 
+    fn set_memory_pressure_logic(&mut self, logic: MemoryPressureLogic) {
+        self.memory_pressure_logic = Some(logic);
+    }
+
     // Ideally, this method acts as insertion of `batch`, even if we are not yet able to begin
     // merging the batch. This means it is a good time to perform amortized work proportional
     // to the size of batch.
@@ -384,7 +448,12 @@ impl<B: Batch, BA, BU> Spine<B, BA, BU> {
     ///
     /// This method prepares an iterator over batches, including the level, count, and length of each layer.
     /// It supplies this to `self.exert_logic`, who produces the response of the amount of exertion to apply.
+    ///
+    /// When memory is plentiful, no effort is requested, deferring maintenance until updates arrive.
     fn exert_effort(&mut self) -> Option<usize> {
+        if self.memory_pressure() == MemoryPressure::Plentiful {
+            return None;
+        }
         self.exert_logic.as_ref().and_then(|exert_logic| {
             self.exert_logic_param.clear();
             self.exert_logic_param.extend(self.merging.iter().enumerate().rev().map(|(index, batch)| {
@@ -399,6 +468,43 @@ impl<B: Batch, BA, BU> Spine<B, BA, BU> {
         })
     }
 
+    /// Applies `effort` to merges in progress, or initiates merges if there are none.
+    fn apply_effort(&mut self, effort: usize) {
+        // If any merges exist, we can directly call `apply_fuel`.
+        if self.merging.iter().any(|b| b.is_double()) {
+            self.apply_fuel(&mut (effort as isize));
+        }
+        // Otherwise, we'll need to introduce fake updates to move merges along,
+        // unless a shared merge budget granted no effort.
+        else if effort > 0 {
+            // Introduce an empty batch with roughly *effort number of virtual updates.
+            let level = effort.next_power_of_two().trailing_zeros() as usize;
+            self.introduce_batch(None, level);
+        }
+    }
+
+    /// The memory pressure reported by `self.memory_pressure_logic`, or `Normal` if there is none.
+    fn memory_pressure(&self) -> MemoryPressure {
+        self.memory_pressure_logic
+            .as_ref()
+            .map(|logic| logic())
+            .unwrap_or(MemoryPressure::Normal)
+    }
+
+    /// Completes all merges in progress, releasing the batches they merge.
+    ///
+    /// Layers are completed from the largest down, so that each merged batch is inserted into a layer
+    /// that is not merging. Its insertion may initiate a new merge there, which acquires memory only as
+    /// it is fueled.
+    fn complete_merges(&mut self) {
+        for index in (0 .. self.merging.len()).rev() {
+            if self.merging[index].is_double() {
+                let merged = self.complete_at(index);
+                self.insert_at(merged, index + 1);
+            }
+        }
+    }
+
     /// Describes the merge progress of layers in the trace.
     ///
     /// Intended for diagnostics rather than public consumption.
@@ -414,6 +520,77 @@ impl<B: Batch, BA, BU> Spine<B, BA, BU> {
             .collect()
     }
 
+    /// Checks the documented invariants of the layers, and panics if any is violated.
+    ///
+    /// The batches of the spine, from the largest layer through the pending batches, must form a chain
+    /// in which the upper frontier of each batch is the lower frontier of the next, and the last ends at
+    /// the upper frontier of the spine. Each batch at layer `i` must contain at most `2^i` updates, and
+    /// each merge in progress at layer `k` must have a deficit: fewer than `2^k` records at the layers
+    /// below it, accounting each batch at layer `i` as `2^i` records, so that no merge can be invaded
+    /// before it completes.
+    ///
+    /// The checks examine the batches but not their contents, and are intended for tests of changes to
+    /// the merging and fueling logic. The method is available in debug builds, and in release builds
+    /// with the `spine-invariants` feature.
+    #[cfg(any(debug_assertions, feature = "spine-invariants"))]
+    pub fn assert_invariants(&self) {
+
+        // The accounted capacity of a layer, saturating for absurdly deep spines.
+        let capacity = |index: usize| 1usize.checked_shl(index as u32).unwrap_or(usize::MAX);
+
+        // Check that the batches form a chain.
+        let mut upper: Option<Antichain<B::Time>> = None;
+        let mut check_link = |batch: &B| {
+            if let Some(upper) = &upper {
+                assert_eq!(upper, batch.lower(), "batch bounds do not form a chain");
+            }
+            upper = Some(batch.upper().clone());
+        };
+        for merge_state in self.merging.iter().rev() {
+            match merge_state {
+                MergeState::Double(MergeVariant::InProgress(batch1, batch2, _)) => { check_link(batch1); check_link(batch2); },
+                MergeState::Double(MergeVariant::Complete(Some((batch, _)))) => { check_link(batch) },
+                MergeState::Single(Some(batch)) => { check_link(batch) },
+                _ => { },
+            }
+        }
+        for batch in self.pending.iter() {
+            check_link(batch);
+        }
+        if let Some(upper) = upper {
+            assert_eq!(upper, self.upper, "final batch does not end at the spine's upper frontier");
+        }
+
+        // Check the sizes of layers, and the deficits of merges in progress.
+        let mut accounted = 0usize;
+        for (index, merge_state) in self.merging.iter().enumerate() {
+            match merge_state {
+                MergeState::Vacant => { },
+                MergeState::Single(batch) => {
+                    if let Some(batch) = batch {
+                        assert!(batch.len() <= capacity(index), "layer {} holds a batch of {} updates", index, batch.len());
+                    }
+                    accounted = accounted.saturating_add(capacity(index));
+                },
+                MergeState::Double(variant) => {
+                    match variant {
+                        MergeVariant::InProgress(batch1, batch2, _) => {
+                            assert!(batch1.len() <= capacity(index), "layer {} holds a batch of {} updates", index, batch1.len());
+                            assert!(batch2.len() <= capacity(index), "layer {} holds a batch of {} updates", index, batch2.len());
+                            assert!(accounted < capacity(index), "merge at layer {} has {} records below it", index, accounted);
+                        },
+                        MergeVariant::Complete(batch) => {
+                            if let Some((batch, _)) = batch {
+                                assert!(batch.len() <= capacity(index+1), "layer {} holds a merged batch of {} updates", index, batch.len());
+                            }
+                        },
+                    }
+                    accounted = accounted.saturating_add(capacity(index+1));
+                },
+            }
+        }
+    }
+
     /// Allocates a fueled `Spine` with a specified effort multiplier.
     ///
     /// This trace will merge batches progressively, with each inserted batch applying a multiple
@@ -441,6 +618,7 @@ impl<B: Batch, BA, BU> Spine<B, BA, BU> {
             activator,
             exert_logic_param: Vec::default(),
             exert_logic: None,
+            memory_pressure_logic: None,
             phantom: std::marker::PhantomData,
         }
     }
@@ -454,7 +632,7 @@ impl<B: Batch, BA, BU> Spine<B, BA, BU> {
 
         // TODO: Consider merging pending batches before introducing them.
         // TODO: We could use a `VecDeque` here to draw from the front and append to the back.
-        while !self.pending.is_empty() && PartialOrder::less_equal(self.pending[0].upper(), &self.physical_frontier)
+        while !self.pending.is_empty() && frontier_less_equal(self.pending[0].upper().borrow(), self.physical_frontier.borrow())
             //   self.physical_frontier.iter().all(|t1| self.pending[0].upper().iter().any(|t2| t2.less_equal(t1)))
         {
             // Batch can be taken in optimized insertion.
@@ -481,8 +659,14 @@ impl<B: Batch, BA, BU> Spine<B, BA, BU> {
             }
         }
 
+        // If memory is tight, release the inputs of merges in progress.
+        if self.memory_pressure() == MemoryPressure::Tight {
+            self.complete_merges();
+        }
+
         // Having performed all of our work, if we should perform more work reschedule ourselves.
-        if self.exert_effort().is_some() {
+        // The effort is exerted once rescheduled, and so is not taken from any shared budget now.
+        if crate::trace::scheduler::peek(|| self.exert_effort()).is_some() {
             if let Some(activator) = &self.activator {
                 activator.activate();
             }
@@ -891,22 +1075,30 @@ impl<B: Batch> MergeVariant<B> {
     ///
     /// The result is either `None`, for structurally empty batches,
     /// or a batch and optionally input batches from which it derived.
-    fn complete(mut self) -> Option<(B, Option<(B, B)>)> {
-        let mut fuel = isize::max_value();
-        self.work(&mut fuel);
-        if let MergeVariant::Complete(batch) = self { batch }
-        else { panic!("Failed to complete a merge!"); }
+    fn complete(self) -> Option<(B, Option<(B, B)>)> {
+        match self {
+            MergeVariant::InProgress(b1, b2, mut merge) => {
+                // `work` completes the merge, even if it must wait for its inputs.
+                let mut fuel = isize::max_value();
+                merge.work(&b1, &b2, &mut fuel);
+                if fuel > 0 { Some((merge.done(), Some((b1, b2)))) }
+                else { panic!("Failed to complete a merge!"); }
+            }
+            MergeVariant::Complete(batch) => batch,
+        }
     }
 
     /// Applies some amount of work, potentially completing the merge.
     ///
     /// In case the work completes, the source batches are returned.
     /// This allows the caller to manage the released resources.
+    ///
+    /// A merge that waits for its inputs is left in progress, for its
+    /// merger to re-schedule the trace once it can proceed.
     fn work(&mut self, fuel: &mut isize) {
         let variant = std::mem::replace(self, MergeVariant::Complete(None));
         if let MergeVariant::InProgress(b1,b2,mut merge) = variant {
-            merge.work(&b1,&b2,fuel);
-            if *fuel > 0 {
+            if merge.poll_work(&b1,&b2,fuel) == MergeProgress::Complete {
                 *self = MergeVariant::Complete(Some((merge.done(), Some((b1,b2)))));
             }
             else {
@@ -11,6 +11,9 @@ pub mod cursor;
 pub mod description;
 pub mod implementations;
 pub mod wrappers;
+pub mod scheduler;
+pub mod clock;
+pub mod testing;
 
 use timely::communication::message::RefOrMut;
 use timely::logging::WorkerIdentifier;
@@ -19,16 +22,42 @@ use timely::progress::{Antichain, frontier::AntichainRef};
 use timely::progress::Timestamp;
 
 use crate::logging::DifferentialEvent;
-use crate::trace::cursor::MyTrait;
+use crate::trace::cursor::{MyTrait, CursorList};
 use crate::difference::Semigroup;
 use crate::lattice::Lattice;
 // use ::difference::Semigroup;
-pub use self::cursor::Cursor;
+pub use self::cursor::{Cursor, Keys};
 pub use self::description::Description;
 
+/// Summary statistics of a batch, which can be read without navigating its contents.
+///
+/// Batches compute their statistics from their own contents, so that the statistics of a merged batch
+/// describe the merged updates, including any consolidated by logical compaction.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct BatchStats {
+    /// The number of updates in the batch.
+    pub records: usize,
+    /// An estimate of the bytes the batch occupies, or zero if the batch does not report one.
+    pub bytes: usize,
+}
+
 /// A type used to express how much effort a trace should exert even in the absence of updates.
 pub type ExertionLogic = std::sync::Arc<dyn for<'a> Fn(&'a [(usize, usize, usize)])->Option<usize>+Send+Sync>;
 
+/// A type used to report the availability of memory to a trace.
+pub type MemoryPressureLogic = std::sync::Arc<dyn Fn()->MemoryPressure+Send+Sync>;
+
+/// The availability of memory, as reported to traces by a `MemoryPressureLogic`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MemoryPressure {
+    /// Memory is plentiful, and traces may defer maintenance that is not yet required.
+    Plentiful,
+    /// Traces should perform maintenance as usual.
+    Normal,
+    /// Memory is tight, and traces should complete merges to release the batches they merge.
+    Tight,
+}
+
 //     The traces and batch and cursors want the flexibility to appear as if they manage certain types of keys and
 //     values and such, while perhaps using other representations, I'm thinking mostly of wrappers around the keys
 //     and vals that change the `Ord` implementation, or stash hash codes, or the like.
@@ -81,6 +110,16 @@ pub trait TraceReader {
         }
     }
 
+    /// Provides a cursor over updates contained in the trace, for reading keys from `lower` through `upper`.
+    ///
+    /// The cursor presents at least the keys from `lower` through `upper`, inclusive, and may omit batches
+    /// that contain no such keys. Its contents outside of this range are unspecified. Point lookups, which
+    /// read few keys, can use this method to avoid seeking in batches that cannot contain them.
+    fn cursor_for_keys(&mut self, lower: Self::Key<'_>, upper: Self::Key<'_>) -> (Self::Cursor, Self::Storage) {
+        let _ = (lower, upper);
+        self.cursor()
+    }
+
     /// Acquires a cursor to the restriction of the collection's contents to updates at times not greater or
     /// equal to an element of `upper`.
     ///
@@ -88,6 +127,9 @@ pub trait TraceReader {
     /// the trace, and (ii) the trace has not been advanced beyond `upper`. Practically, the implementation should
     /// be expected to look for a "clean cut" using `upper`, and if it finds such a cut can return a cursor. This
     /// should allow `upper` such as `&[]` as used by `self.cursor()`, though it is difficult to imagine other uses.
+    ///
+    /// Shared trace handles return `None` once their trace has been released. Operators that read from shared
+    /// traces should treat this as the end of their input.
     fn cursor_through(&mut self, upper: AntichainRef<Self::Time>) -> Option<(Self::Cursor, Self::Storage)>;
 
     /// Advances the frontier that constrains logical compaction.
@@ -170,6 +212,49 @@ pub trait TraceReader {
     /// cursor methods, as they (by default) just move through batches accumulating cursors into a cursor list.
     fn map_batches<F: FnMut(&Self::Batch)>(&self, f: F);
 
+    /// Applies `logic` to each distinct key of the trace, in order.
+    ///
+    /// The keys are visited without their values or times, which suits logic that only needs to know
+    /// which keys are present. A key is reported if the trace holds updates for it, even if they
+    /// accumulate to zero.
+    fn map_keys<F: FnMut(Self::Key<'_>)>(&mut self, mut logic: F) {
+        let (cursor, storage) = self.cursor();
+        for key in cursor.into_keys(&storage) {
+            logic(key);
+        }
+    }
+
+    /// Acquires a cursor to the updates in the trace at times greater or equal to an element of `lower`.
+    ///
+    /// This serves readers that have already observed the updates at times not greater or equal to `lower`,
+    /// for example through an earlier cursor, and want to read only the updates since. As for `cursor_through`,
+    /// `lower` should be a bound observed in batches from the trace, and the physical compaction frontier must
+    /// not be beyond `lower`, which prevents the trace from merging batches on either side of it. The method
+    /// returns `None` if either of these does not hold.
+    fn updates_since(&mut self, lower: AntichainRef<Self::Time>) -> Option<(CursorList<<Self::Batch as BatchReader>::Cursor>, Vec<Self::Batch>)> {
+
+        if !timely::PartialOrder::less_equal(&self.get_physical_compaction(), &lower) {
+            return None;
+        }
+
+        let mut straddled = false;
+        let mut cursors = Vec::new();
+        let mut storage = Vec::new();
+        self.map_batches(|batch| {
+            if timely::PartialOrder::less_equal(&lower, &batch.lower().borrow()) {
+                if !batch.is_empty() {
+                    cursors.push(batch.cursor());
+                    storage.push(batch.clone());
+                }
+            }
+            else if !timely::PartialOrder::less_equal(&batch.upper().borrow(), &lower) && !batch.is_empty() {
+                straddled = true;
+            }
+        });
+
+        if straddled { None } else { Some((CursorList::new(cursors, &storage), storage)) }
+    }
+
     /// Reads the upper frontier of committed times.
     ///
     ///
@@ -182,6 +267,17 @@ pub trait TraceReader {
         });
     }
 
+    /// Reads the upper frontier of the times whose batches are fully merged.
+    ///
+    /// Traces that merge their batches may hold batches that have been inserted but not yet merged,
+    /// or whose merges are in progress. Updates at times not greater or equal to an element of this
+    /// frontier are in batches that are not waiting for merge work, and whose compaction was applied
+    /// as they merged. The frontier is never beyond `read_upper`, and the default implementation, for
+    /// traces that do not merge their batches, reports that frontier.
+    fn read_merged_upper(&mut self, target: &mut Antichain<Self::Time>) {
+        self.read_upper(target);
+    }
+
     /// Advances `upper` by any empty batches.
     ///
     /// An empty batch whose `batch.lower` bound equals the current
@@ -223,6 +319,14 @@ where <Self as TraceReader>::Batch: Batch {
     /// Exert merge effort, even without updates.
     fn exert(&mut self);
 
+    /// Exerts `effort` merge effort, regardless of the exertion logic.
+    ///
+    /// Traces with no merges to advance may ignore the effort. The default implementation calls `exert`.
+    fn exert_with(&mut self, effort: usize) {
+        let _ = effort;
+        self.exert();
+    }
+
     /// Sets the logic for exertion in the absence of updates.
     ///
     /// The function receives an iterator over batch levels, from large to small, as triples `(level, count, length)`,
@@ -230,6 +334,12 @@ where <Self as TraceReader>::Batch: Batch {
     /// updates to perform, or `None` if no work is required.
     fn set_exert_logic(&mut self, logic: ExertionLogic);
 
+    /// Sets the logic that reports memory pressure.
+    ///
+    /// The function is consulted as the trace performs maintenance, which it may adjust in response.
+    /// The default implementation ignores memory pressure.
+    fn set_memory_pressure_logic(&mut self, _logic: MemoryPressureLogic) { }
+
     /// Introduces a batch of updates to the trace.
     ///
     /// Batches describe the time intervals they contain, and they should be added to the trace in contiguous
@@ -272,10 +382,25 @@ where
     type Cursor: for<'a> Cursor<Storage=Self, Key<'a> = Self::Key<'a>, KeyOwned = Self::KeyOwned, Val<'a> = Self::Val<'a>, Time = Self::Time, Diff = Self::Diff>;
     /// Acquires a cursor to the batch's contents.
     fn cursor(&self) -> Self::Cursor;
+    /// An iterator over the distinct keys of the batch, which does not visit their values or times.
+    fn keys(&self) -> Keys<'_, Self::Cursor> where Self: Sized {
+        self.cursor().into_keys(self)
+    }
     /// The number of updates in the batch.
     fn len(&self) -> usize;
     /// True if the batch is empty.
     fn is_empty(&self) -> bool { self.len() == 0 }
+    /// Summary statistics of the batch.
+    ///
+    /// The default reports the number of updates, and no estimate of bytes.
+    fn stats(&self) -> BatchStats {
+        BatchStats { records: self.len(), bytes: 0 }
+    }
+    /// False only if the batch contains no keys from `lower` through `upper`, inclusive.
+    ///
+    /// The default is always true. Batches that know the range of their keys, or summarize them in
+    /// other ways, can report that they contain none of a range, so that lookups can skip them.
+    fn may_contain_keys(&self, _lower: Self::Key<'_>, _upper: Self::Key<'_>) -> bool { true }
     /// Describes the times of the updates in the batch.
     fn description(&self) -> &Description<Self::Time>;
 
@@ -314,6 +439,15 @@ pub trait Batcher {
     fn push_container(&mut self, batch: RefOrMut<Self::Input>);
     /// Returns all updates not greater or equal to an element of `upper`.
     fn seal<B: Builder<Input=Self::Output, Time=Self::Time>>(&mut self, upper: Antichain<Self::Time>) -> B::Output;
+    /// Returns all updates not greater or equal to an element of `upper`, as a sequence of batches.
+    ///
+    /// The batches have chained descriptions, the first starting where the previous seal ended and the
+    /// last ending at `upper`. Implementations should aim for batches of at most `limit` updates, but
+    /// are not required to split updates at the same time across batches. The default implementation
+    /// produces a single batch.
+    fn seal_limited<B: Builder<Input=Self::Output, Time=Self::Time>>(&mut self, upper: Antichain<Self::Time>, _limit: usize) -> Vec<B::Output> {
+        vec![self.seal::<B>(upper)]
+    }
     /// Returns the lower envelope of contained update times.
     fn frontier(&mut self) -> timely::progress::frontier::AntichainRef<Self::Time>;
 }
@@ -365,6 +499,31 @@ pub trait Merger<Output: Batch> {
     /// has not brought `fuel` to zero. Otherwise, the merge is still in
     /// progress.
     fn done(self) -> Output;
+    /// Performs some amount of work, decrementing `fuel`, unless the merge must wait for its inputs.
+    ///
+    /// Mergers whose inputs are not in memory, for example batches on disk or at other processes, may
+    /// return `MergeProgress::Waiting` rather than block the worker while reads complete. Such mergers
+    /// should not consume fuel while waiting, and are responsible for re-scheduling the operator that
+    /// maintains the trace, for example through an `Activator`, once they can make progress.
+    ///
+    /// The `work` method must still complete the merge when supplied with enough fuel, blocking if it
+    /// must, as traces call it when they cannot proceed without the merged batch. The default
+    /// implementation calls `work`, and never waits.
+    fn poll_work(&mut self, source1: &Output, source2: &Output, fuel: &mut isize) -> MergeProgress {
+        self.work(source1, source2, fuel);
+        if *fuel > 0 { MergeProgress::Complete } else { MergeProgress::Incomplete }
+    }
+}
+
+/// The outcome of a call to `Merger::poll_work`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MergeProgress {
+    /// The merge is complete, and `done` produces the merged batch.
+    Complete,
+    /// The merge exhausted its fuel, and requires more work.
+    Incomplete,
+    /// The merge awaits its inputs, and the merger will re-schedule the trace when it can proceed.
+    Waiting,
 }
 
 
@@ -374,7 +533,7 @@ pub mod rc_blanket_impls {
     use std::rc::Rc;
 
     use timely::progress::{Antichain, frontier::AntichainRef};
-    use super::{Batch, BatchReader, Builder, Merger, Cursor, Description};
+    use super::{Batch, BatchReader, BatchStats, Builder, Merger, MergeProgress, Cursor, Description};
 
     impl<B: BatchReader> BatchReader for Rc<B> {
         type Key<'a> = B::Key<'a>;
@@ -392,6 +551,10 @@ pub mod rc_blanket_impls {
 
         /// The number of updates in the batch.
         fn len(&self) -> usize { (**self).len() }
+        /// Summary statistics of the batch.
+        fn stats(&self) -> BatchStats { (**self).stats() }
+        /// False only if the batch contains no keys in the range.
+        fn may_contain_keys(&self, lower: Self::Key<'_>, upper: Self::Key<'_>) -> bool { (**self).may_contain_keys(lower, upper) }
         /// Describes the times of the updates in the batch.
         fn description(&self) -> &Description<Self::Time> { (**self).description() }
     }
@@ -467,6 +630,7 @@ pub mod rc_blanket_impls {
         fn new(source1: &Rc<B>, source2: &Rc<B>, compaction_frontier: AntichainRef<B::Time>) -> Self { RcMerger { merger: B::begin_merge(source1, source2, compaction_frontier) } }
         fn work(&mut self, source1: &Rc<B>, source2: &Rc<B>, fuel: &mut isize) { self.merger.work(source1, source2, fuel) }
         fn done(self) -> Rc<B> { Rc::new(self.merger.done()) }
+        fn poll_work(&mut self, source1: &Rc<B>, source2: &Rc<B>, fuel: &mut isize) -> MergeProgress { self.merger.poll_work(source1, source2, fuel) }
     }
 }
 
@@ -477,7 +641,7 @@ pub mod abomonated_blanket_impls {
     use abomonation::abomonated::Abomonated;
     use timely::progress::{Antichain, frontier::AntichainRef};
 
-    use super::{Batch, BatchReader, Builder, Merger, Cursor, Description};
+    use super::{Batch, BatchReader, BatchStats, Builder, Merger, MergeProgress, Cursor, Description};
 
     impl<B: BatchReader+Abomonation> BatchReader for Abomonated<B, Vec<u8>> {
 
@@ -496,6 +660,8 @@ pub mod abomonated_blanket_impls {
 
         /// The number of updates in the batch.
         fn len(&self) -> usize { (**self).len() }
+        /// Summary statistics of the batch.
+        fn stats(&self) -> BatchStats { (**self).stats() }
         /// Describes the times of the updates in the batch.
         fn description(&self) -> &Description<Self::Time> { (**self).description() }
     }
@@ -582,6 +748,9 @@ pub mod abomonated_blanket_impls {
         fn work(&mut self, source1: &Abomonated<B,Vec<u8>>, source2: &Abomonated<B,Vec<u8>>, fuel: &mut isize) {
             self.merger.work(source1, source2, fuel)
         }
+        fn poll_work(&mut self, source1: &Abomonated<B,Vec<u8>>, source2: &Abomonated<B,Vec<u8>>, fuel: &mut isize) -> MergeProgress {
+            self.merger.poll_work(source1, source2, fuel)
+        }
         fn done(self) -> Abomonated<B, Vec<u8>> {
             let batch = self.merger.done();
             let mut bytes = Vec::with_capacity(measure(&batch));
@@ -0,0 +1,158 @@
+//! A budget of idle merge effort shared by the traces of a worker.
+//!
+//! Traces with exertion logic merge their batches while idle, each exerting the effort its logic
+//! requests every time its operator is scheduled. When many arrangements want to merge at the same
+//! moment, for example after a burst of updates to several of them, the worker may spend long
+//! stretches merging before it attends to other operators. A `MergeScheduler` limits the idle effort
+//! of all traces of a worker to a budget each round, where rounds are intervals of wall-clock time.
+//!
+//! The budget of a round is distributed among the requests for effort: each request is granted at
+//! most an equal share, determined by the number of requests in the previous round. Traces that are
+//! granted no effort request none, and so are not rescheduled until their operator asks the scheduler
+//! when to retry, with `retry_after`.
+//!
+//! The scheduler accounts for each worker thread separately, in thread-local state, and so a single
+//! scheduler can be installed in the configuration shared by all workers. Effort traces exert as they
+//! insert batches is not affected; only the idle effort requested by exertion logic is.
+//!
+//! Rounds are measured by a `Clock`, the system clock unless the scheduler is created `with_clock`.
+//!
+//! Traces also consult their exertion logic to decide whether to reschedule themselves, without
+//! exerting the effort. They do so within `peek`, where requests report the effort they would be
+//! granted but take nothing from the budget.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use super::ExertionLogic;
+use super::clock::{Clock, SystemClock};
+
+/// Distinguishes the state of schedulers within each worker thread.
+static SCHEDULERS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The current round of each scheduler used by this worker thread, by scheduler identifier.
+    static ROUNDS: RefCell<HashMap<usize, Round>> = RefCell::new(HashMap::new());
+    /// Whether this worker thread is within `peek`.
+    static PEEKING: Cell<bool> = Cell::new(false);
+}
+
+/// Evaluates `logic`, in which requests to any scheduler report the effort they would be granted without taking it.
+pub fn peek<T>(logic: impl FnOnce() -> T) -> T {
+    let peeking = PEEKING.with(|flag| flag.replace(true));
+    let result = logic();
+    PEEKING.with(|flag| flag.set(peeking));
+    result
+}
+
+/// The state of a round of a scheduler, for one worker.
+#[derive(Debug)]
+struct Round {
+    /// The time the round started.
+    start: Duration,
+    /// What remains of the budget of the round.
+    remaining: usize,
+    /// The most effort granted to each request in the round.
+    share: usize,
+    /// The number of requests in the round.
+    requests: usize,
+    /// Whether a request in the round was granted less than it requested.
+    denied: bool,
+}
+
+/// Distributes a budget of idle merge effort among the traces of each worker, each round.
+#[derive(Debug)]
+pub struct MergeScheduler {
+    id: usize,
+    budget: usize,
+    period: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl MergeScheduler {
+    /// A scheduler granting each worker `budget` effort in each round of length `period`.
+    pub fn new(budget: usize, period: Duration) -> Self {
+        Self::with_clock(budget, period, Arc::new(SystemClock))
+    }
+
+    /// A scheduler as `new`, whose rounds are measured by `clock`.
+    pub fn with_clock(budget: usize, period: Duration, clock: Arc<dyn Clock>) -> Self {
+        MergeScheduler {
+            id: SCHEDULERS.fetch_add(1, Ordering::Relaxed),
+            budget,
+            period,
+            clock,
+        }
+    }
+
+    /// Applies `logic` to the current round of the calling worker, starting a new round if it is due.
+    fn with_round<T>(&self, logic: impl FnOnce(&mut Round) -> T) -> T {
+        let now = self.clock.now();
+        ROUNDS.with(|rounds| {
+            let mut rounds = rounds.borrow_mut();
+            let round = rounds.entry(self.id).or_insert(Round {
+                start: now,
+                remaining: self.budget,
+                share: self.budget,
+                requests: 0,
+                denied: false,
+            });
+            if now.saturating_sub(round.start) >= self.period {
+                round.share = self.budget / std::cmp::max(round.requests, 1);
+                round.start = now;
+                round.remaining = self.budget;
+                round.requests = 0;
+                round.denied = false;
+            }
+            logic(round)
+        })
+    }
+
+    /// Grants up to `effort` from the budget of the calling worker, and returns the amount granted.
+    ///
+    /// The amount granted is at most the share of each request in the current round.
+    pub fn grant(&self, effort: usize) -> usize {
+        self.with_round(|round| {
+            round.requests += 1;
+            let granted = std::cmp::min(effort, std::cmp::min(round.share, round.remaining));
+            round.remaining -= granted;
+            if granted < effort {
+                round.denied = true;
+            }
+            granted
+        })
+    }
+
+    /// The amount `grant(effort)` would grant, without taking it from the budget or recording the request.
+    pub fn available(&self, effort: usize) -> usize {
+        self.with_round(|round| std::cmp::min(effort, std::cmp::min(round.share, round.remaining)))
+    }
+
+    /// The time until the next round of the calling worker, if a request in this round was denied effort.
+    ///
+    /// Operators whose traces were granted no effort should reschedule themselves after this time.
+    pub fn retry_after(&self) -> Option<Duration> {
+        let now = self.clock.now();
+        self.with_round(|round| {
+            if round.denied {
+                Some(self.period.saturating_sub(now.saturating_sub(round.start)))
+            }
+            else { None }
+        })
+    }
+
+    /// Exertion logic that requests the effort `logic` requests, granted from the budget.
+    ///
+    /// Traces granted no effort request none, and wait to be rescheduled as `retry_after` directs.
+    /// Within `peek`, the logic requests the effort that would be granted, and takes none.
+    pub fn exertion_logic(self: Arc<Self>, logic: ExertionLogic) -> ExertionLogic {
+        Arc::new(move |batches| {
+            logic(batches)
+                .map(|effort| if PEEKING.with(|flag| flag.get()) { self.available(effort) } else { self.grant(effort) })
+                .filter(|granted| *granted > 0)
+        })
+    }
+}
@@ -0,0 +1,249 @@
+//! Utilities for validating implementations of the batch traits.
+//!
+//! The functions in this module generate updates, build batches from them, and check that the
+//! results honor the contracts of `BatchReader`, `Builder`, and `Merger`. They are meant for the
+//! tests of batch implementations, which can apply them to many randomly generated inputs.
+//!
+//! The checks report the first violation they find as an `Err` describing it, rather than panicking,
+//! so that they can be used from within property-testing frameworks.
+//!
+//! # Examples
+//!
+//! ```
+//! use timely::progress::Antichain;
+//! use differential_dataflow::trace::Trace;
+//! use differential_dataflow::trace::implementations::ValSpine;
+//! use differential_dataflow::trace::testing;
+//!
+//! type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+//!
+//! for seed in 0 .. 10 {
+//!     let batches = testing::random_batches::<Builder>(seed, 2, 100, 10, 10);
+//!     testing::check_batch(&batches[0]).unwrap();
+//!     testing::check_merge(&batches[0], &batches[1], Antichain::from_elem(1).borrow(), 10, |v| *v).unwrap();
+//! }
+//! ```
+
+use timely::progress::{Antichain, frontier::AntichainRef};
+use timely::PartialOrder;
+
+use crate::consolidation::{consolidate, consolidate_updates};
+use crate::difference::Semigroup;
+use crate::lattice::Lattice;
+use crate::trace::{Batch, BatchReader, Builder, Cursor, Merger};
+use crate::trace::cursor::MyTrait;
+
+/// A deterministic source of pseudo-random numbers, for generating test inputs.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    /// Creates a generator from a seed; equal seeds produce equal sequences.
+    pub fn new(seed: u64) -> Self {
+        // xorshift must not be seeded with zero.
+        Generator { state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+    /// The next number in the sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+    /// A number in `[0, bound)`, or zero if `bound` is zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 { 0 } else { self.next_u64() % bound }
+    }
+    /// Random updates with keys in `[0, keys)`, values in `[0, vals)`, times in `[lower, upper)`,
+    /// and non-zero differences in `[-2, 2]`.
+    pub fn updates(&mut self, count: usize, keys: u64, vals: u64, lower: u64, upper: u64) -> Vec<((u64, u64), u64, i64)> {
+        (0 .. count)
+            .map(|_| {
+                let key = self.below(keys);
+                let val = self.below(vals);
+                let time = lower + self.below(upper - lower);
+                let diff = match self.below(4) { 0 => -2, 1 => -1, 2 => 1, _ => 2 };
+                ((key, val), time, diff)
+            })
+            .collect()
+    }
+}
+
+/// Builds a batch from unsorted updates, which are consolidated first.
+///
+/// The batch is described by `lower` and `upper` and has a minimal `since`; all update times must
+/// lie between `lower` and `upper`.
+pub fn build_batch<Bu, D, T, R>(mut updates: Vec<(D, T, R)>, lower: Antichain<T>, upper: Antichain<T>) -> Bu::Output
+where
+    Bu: Builder<Input=(D, T, R), Time=T>,
+    D: Ord,
+    T: Ord+Lattice+timely::progress::Timestamp,
+    R: Semigroup,
+{
+    consolidate_updates(&mut updates);
+    let mut builder = Bu::with_capacity(updates.len(), updates.len(), updates.len());
+    for update in updates {
+        builder.push(update);
+    }
+    builder.done(lower, upper, Antichain::from_elem(T::minimum()))
+}
+
+/// A chain of `count` random batches, the `i`th of which contains updates at time `i`.
+///
+/// Each batch holds at most `updates` updates, with keys in `[0, keys)` and values in `[0, vals)`.
+pub fn random_batches<Bu>(seed: u64, count: usize, updates: usize, keys: u64, vals: u64) -> Vec<Bu::Output>
+where
+    Bu: Builder<Input=((u64, u64), u64, i64), Time=u64>,
+{
+    let mut generator = Generator::new(seed);
+    (0 .. count as u64)
+        .map(|time| {
+            let updates = generator.updates(updates, keys, vals, time, time + 1);
+            build_batch::<Bu, _, _, _>(updates, Antichain::from_elem(time), Antichain::from_elem(time + 1))
+        })
+        .collect()
+}
+
+/// The updates of a batch, in the order its cursor presents them.
+///
+/// The `val` function converts the batch's values to an owned representation.
+pub fn contents<B, V, F>(batch: &B, val: F) -> Vec<((B::KeyOwned, V), B::Time, B::Diff)>
+where
+    B: BatchReader,
+    V: Clone,
+    F: Fn(B::Val<'_>) -> V,
+{
+    let mut result = Vec::new();
+    let mut cursor = batch.cursor();
+    while let Some(key) = cursor.get_key(batch) {
+        while let Some(v) = cursor.get_val(batch) {
+            let v = val(v);
+            cursor.map_times(batch, |time, diff| {
+                result.push(((key.into_owned(), v.clone()), time.clone(), diff.clone()));
+            });
+            cursor.step_val(batch);
+        }
+        cursor.step_key(batch);
+    }
+    result
+}
+
+/// Checks that a batch honors the contracts of `BatchReader`.
+///
+/// Keys must be presented in strictly increasing order, each with at least one value, and values in
+/// strictly increasing order within each key. Each update time must lie between the batch's `lower`
+/// and `upper` frontiers, unless the batch is compacted and the time is already advanced by `since`. Differences
+/// must be non-zero, and `len` must report the number of updates.
+pub fn check_batch<B: BatchReader>(batch: &B) -> Result<(), String> {
+    let description = batch.description();
+    if !PartialOrder::less_equal(description.lower(), description.upper()) {
+        return Err(format!("lower {:?} is not less or equal to upper {:?}", description.lower(), description.upper()));
+    }
+
+    let mut count = 0;
+    let mut cursor = batch.cursor();
+    let mut prev_key = None;
+    while let Some(key) = cursor.get_key(batch) {
+        if prev_key.map(|prev| prev >= key).unwrap_or(false) {
+            return Err(format!("keys out of order at key #{}", count));
+        }
+        prev_key = Some(key);
+
+        let mut prev_val = None;
+        if cursor.get_val(batch).is_none() {
+            return Err("key without values".to_string());
+        }
+        while let Some(v) = cursor.get_val(batch) {
+            if prev_val.map(|prev| prev >= v).unwrap_or(false) {
+                return Err("values out of order".to_string());
+            }
+            prev_val = Some(v);
+
+            let mut error = None;
+            cursor.map_times(batch, |time, diff| {
+                count += 1;
+                let in_bounds = description.lower().less_equal(time) && !description.upper().less_equal(time);
+                // Compaction may advance times beyond the bounds, but only to times `since` would not advance.
+                let compacted = !PartialOrder::less_equal(description.since(), description.lower()) && {
+                    let mut advanced = time.clone();
+                    advanced.advance_by(description.since().borrow());
+                    &advanced == time
+                };
+                if error.is_none() && !in_bounds && !compacted {
+                    error = Some(format!("time {:?} outside of [{:?}, {:?})", time, description.lower(), description.upper()));
+                }
+                if error.is_none() && diff.is_zero() {
+                    error = Some(format!("zero difference at time {:?}", time));
+                }
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+            cursor.step_val(batch);
+        }
+        cursor.step_key(batch);
+    }
+
+    if count != batch.len() {
+        return Err(format!("len() reports {} updates, but the cursor presents {}", batch.len(), count));
+    }
+    Ok(())
+}
+
+/// Checks that merging two batches produces the correct result.
+///
+/// The batches are merged with `compaction` as the compaction frontier, providing `fuel` to each call
+/// to `work`. The merged batch must pass `check_batch`, span the descriptions of both batches, and hold
+/// the same accumulated updates as a reference model that advances the updates of both inputs by
+/// `compaction` and consolidates them.
+pub fn check_merge<B, V, F>(batch1: &B, batch2: &B, compaction: AntichainRef<B::Time>, fuel: isize, val: F) -> Result<(), String>
+where
+    B: Batch,
+    V: Ord+Clone,
+    F: Fn(B::Val<'_>) -> V,
+{
+    check_batch(batch1)?;
+    check_batch(batch2)?;
+    if batch1.upper() != batch2.lower() {
+        return Err(format!("batches are not consecutive: {:?} and {:?}", batch1.upper(), batch2.lower()));
+    }
+
+    let mut merger = batch1.begin_merge(batch2, compaction);
+    loop {
+        let mut remaining = fuel;
+        merger.work(batch1, batch2, &mut remaining);
+        if remaining > 0 { break; }
+    }
+    let merged = merger.done();
+
+    check_batch(&merged)?;
+    if merged.lower() != batch1.lower() || merged.upper() != batch2.upper() {
+        return Err(format!(
+            "merged batch spans [{:?}, {:?}), expected [{:?}, {:?})",
+            merged.lower(), merged.upper(), batch1.lower(), batch2.upper(),
+        ));
+    }
+
+    let mut expected = contents(batch1, &val);
+    expected.extend(contents(batch2, &val));
+    for (_, time, _) in expected.iter_mut() {
+        time.advance_by(compaction);
+    }
+    let mut actual = contents(&merged, &val);
+    for (_, time, _) in actual.iter_mut() {
+        time.advance_by(compaction);
+    }
+    if normalize(expected) != normalize(actual) {
+        return Err("merged contents differ from the reference model".to_string());
+    }
+    Ok(())
+}
+
+/// Consolidates updates into a canonical form for comparison.
+fn normalize<D: Ord, T: Ord, R: Semigroup>(updates: Vec<(D, T, R)>) -> Vec<((D, T), R)> {
+    let mut updates = updates.into_iter().map(|(data, time, diff)| ((data, time), diff)).collect();
+    consolidate(&mut updates);
+    updates
+}
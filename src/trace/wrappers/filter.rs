@@ -1,9 +1,13 @@
 //! Wrapper for filtered trace.
+//!
+//! The filter is applied as cursors navigate the trace: cursors skip values that fail the predicate,
+//! and keys without values that pass it, so that a filtered view of a shared arrangement can be used
+//! (and imported into other dataflows) without forming a second arrangement of the filtered data.
 
 use timely::progress::frontier::AntichainRef;
 
 use crate::trace::{TraceReader, BatchReader, Description};
-use crate::trace::cursor::Cursor;
+use crate::trace::cursor::{Cursor, FilterCursor};
 
 /// Wrapper to provide trace to nested scope.
 pub struct TraceFilter<Tr, F> {
@@ -53,7 +57,7 @@ where
     fn get_physical_compaction(&mut self) -> AntichainRef<Tr::Time> { self.trace.get_physical_compaction() }
 
     fn cursor_through(&mut self, upper: AntichainRef<Tr::Time>) -> Option<(Self::Cursor, Self::Storage)> {
-        self.trace.cursor_through(upper).map(|(x,y)| (CursorFilter::new(x, self.logic.clone()), y))
+        self.trace.cursor_through(upper).map(|(x,y)| (FilterCursor::new(x, self.logic.clone(), &y), y))
     }
 }
 
@@ -92,7 +96,7 @@ where
     type Cursor = BatchCursorFilter<B::Cursor, F>;
 
     fn cursor(&self) -> Self::Cursor {
-        BatchCursorFilter::new(self.batch.cursor(), self.logic.clone())
+        BatchCursorFilter::new(self.batch.cursor(), self.logic.clone(), &self.batch)
     }
     fn len(&self) -> usize { self.batch.len() }
     fn description(&self) -> &Description<B::Time> { self.batch.description() }
@@ -112,71 +116,21 @@ where
 }
 
 /// Wrapper to provide cursor to nested scope.
-pub struct CursorFilter<C, F> {
-    cursor: C,
-    logic: F,
-}
+pub type CursorFilter<C, F> = FilterCursor<C, F>;
 
-impl<C, F> CursorFilter<C, F> {
-    fn new(cursor: C, logic: F) -> Self {
-        CursorFilter {
-            cursor,
-            logic,
-        }
-    }
+/// Wrapper to provide cursor to nested scope.
+pub struct BatchCursorFilter<C, F> {
+    cursor: FilterCursor<C, F>,
 }
 
-impl<C, F> Cursor for CursorFilter<C, F>
+impl<C, F> BatchCursorFilter<C, F>
 where
     C: Cursor,
-    F: FnMut(C::Key<'_>, C::Val<'_>)->bool+'static
+    F: FnMut(C::Key<'_>, C::Val<'_>)->bool+'static,
 {
-    type Key<'a> = C::Key<'a>;
-    type KeyOwned = C::KeyOwned;
-    type Val<'a> = C::Val<'a>;
-    type Time = C::Time;
-    type Diff = C::Diff;
-
-    type Storage = C::Storage;
-
-    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(storage) }
-    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(storage) }
-
-    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { self.cursor.key(storage) }
-    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { self.cursor.val(storage) }
-
-    #[inline]
-    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, logic: L) {
-        let key = self.key(storage);
-        let val = self.val(storage);
-        if (self.logic)(key, val) {
-            self.cursor.map_times(storage, logic)
-        }
-    }
-
-    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
-    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) { self.cursor.seek_key(storage, key) }
-
-    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(storage) }
-    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) { self.cursor.seek_val(storage, val) }
-
-    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(storage) }
-    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(storage) }
-}
-
-
-
-/// Wrapper to provide cursor to nested scope.
-pub struct BatchCursorFilter<C, F> {
-    cursor: C,
-    logic: F,
-}
-
-impl<C, F> BatchCursorFilter<C, F> {
-    fn new(cursor: C, logic: F) -> Self {
+    fn new(cursor: C, logic: F, storage: &C::Storage) -> Self {
         BatchCursorFilter {
-            cursor,
-            logic,
+            cursor: FilterCursor::new(cursor, logic, storage),
         }
     }
 }
@@ -201,11 +155,7 @@ where
 
     #[inline]
     fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, logic: L) {
-        let key = self.key(storage);
-        let val = self.val(storage);
-        if (self.logic)(key, val) {
-            self.cursor.map_times(&storage.batch, logic)
-        }
+        self.cursor.map_times(&storage.batch, logic)
     }
 
     #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(&storage.batch) }
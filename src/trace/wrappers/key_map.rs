@@ -0,0 +1,315 @@
+//! Wrapper presenting a trace under a different, but equivalent, key type.
+//!
+//! The wrapper is provided with a pair of functions that convert between the keys of the wrapped trace
+//! and owned presented keys. The functions must be inverses of each other, and must preserve the order
+//! of keys, as cursors rely on keys being presented in order. These conditions hold for example for
+//! newtypes around the key, or for reordering the fields of a tuple whose first field is unique.
+//!
+//! Cursors present the keys of the wrapped trace as `MappedKey`s, which convert them only when they are
+//! compared with or converted to presented keys, and otherwise compare as the wrapped keys do.
+
+use std::cmp::Ordering;
+
+use timely::progress::frontier::AntichainRef;
+
+use crate::trace::{TraceReader, BatchReader, Description};
+use crate::trace::cursor::{Cursor, MyTrait};
+
+/// A key of a wrapped trace, presented as a key of type `K`.
+pub enum MappedKey<'a, K0, K, F> {
+    /// A key of the wrapped trace, and the function converting it to a presented key.
+    Inner(K0, &'a F),
+    /// A presented key.
+    Mapped(&'a K),
+}
+
+impl<'a, K0: Copy, K, F> Clone for MappedKey<'a, K0, K, F> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, K0: Copy, K, F> Copy for MappedKey<'a, K0, K, F> { }
+
+impl<'a, K0, K, F> MappedKey<'a, K0, K, F>
+where
+    K0: Copy+Ord,
+    K: Ord+Clone,
+    F: Fn(K0)->K,
+{
+    /// Applies `logic` to the presented key.
+    fn with<T>(&self, logic: impl FnOnce(&K)->T) -> T {
+        match self {
+            MappedKey::Inner(key, forward) => logic(&forward(*key)),
+            MappedKey::Mapped(key) => logic(key),
+        }
+    }
+}
+
+impl<'a, K0: Copy+Ord, K: Ord+Clone, F: Fn(K0)->K> PartialEq for MappedKey<'a, K0, K, F> {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl<'a, K0: Copy+Ord, K: Ord+Clone, F: Fn(K0)->K> Eq for MappedKey<'a, K0, K, F> { }
+
+impl<'a, K0: Copy+Ord, K: Ord+Clone, F: Fn(K0)->K> PartialOrd for MappedKey<'a, K0, K, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<'a, K0: Copy+Ord, K: Ord+Clone, F: Fn(K0)->K> Ord for MappedKey<'a, K0, K, F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            // the conversion preserves order, and so wrapped keys need not be converted.
+            (MappedKey::Inner(key1, _), MappedKey::Inner(key2, _)) => key1.cmp(key2),
+            _ => self.with(|key1| other.with(|key2| key1.cmp(key2))),
+        }
+    }
+}
+
+impl<'a, K0, K, F> MyTrait<'a> for MappedKey<'a, K0, K, F>
+where
+    K0: Copy+Ord,
+    K: Ord+Clone,
+    F: Fn(K0)->K,
+{
+    type Owned = K;
+    fn into_owned(self) -> K { self.with(|key| key.clone()) }
+    fn clone_onto(&self, other: &mut K) { *other = self.into_owned() }
+    fn compare(&self, other: &K) -> Ordering { self.with(|key| key.cmp(other)) }
+    fn borrow_as(other: &'a K) -> Self { MappedKey::Mapped(other) }
+}
+
+/// Wrapper to present a trace under a different key type.
+pub struct TraceKeyMap<Tr, F, B> {
+    trace: Tr,
+    forward: F,
+    backward: B,
+}
+
+impl<Tr, F, B> Clone for TraceKeyMap<Tr, F, B>
+where
+    Tr: TraceReader+Clone,
+    F: Clone,
+    B: Clone,
+{
+    fn clone(&self) -> Self {
+        TraceKeyMap {
+            trace: self.trace.clone(),
+            forward: self.forward.clone(),
+            backward: self.backward.clone(),
+        }
+    }
+}
+
+impl<Tr, K, F, B> TraceReader for TraceKeyMap<Tr, F, B>
+where
+    Tr: TraceReader,
+    Tr::Batch: Clone,
+    K: Ord+Clone+'static,
+    F: for<'a> Fn(Tr::Key<'a>)->K+Clone+'static,
+    B: Fn(&K)->Tr::KeyOwned+Clone+'static,
+{
+    type Key<'a> = MappedKey<'a, Tr::Key<'a>, K, F>;
+    type KeyOwned = K;
+    type Val<'a> = Tr::Val<'a>;
+    type Time = Tr::Time;
+    type Diff = Tr::Diff;
+
+    type Batch = BatchKeyMap<Tr::Batch, F, B>;
+    type Storage = (Tr::Storage, F);
+    type Cursor = CursorKeyMap<Tr::Cursor, F, B>;
+
+    fn map_batches<F2: FnMut(&Self::Batch)>(&self, mut f: F2) {
+        let forward = self.forward.clone();
+        let backward = self.backward.clone();
+        self.trace
+            .map_batches(|batch| f(&Self::Batch::make_from(batch.clone(), forward.clone(), backward.clone())))
+    }
+
+    fn set_logical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) { self.trace.set_logical_compaction(frontier) }
+    fn get_logical_compaction(&mut self) -> AntichainRef<Tr::Time> { self.trace.get_logical_compaction() }
+
+    fn set_physical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) { self.trace.set_physical_compaction(frontier) }
+    fn get_physical_compaction(&mut self) -> AntichainRef<Tr::Time> { self.trace.get_physical_compaction() }
+
+    fn cursor_through(&mut self, upper: AntichainRef<Tr::Time>) -> Option<(Self::Cursor, Self::Storage)> {
+        let forward = self.forward.clone();
+        let backward = self.backward.clone();
+        self.trace.cursor_through(upper).map(|(x,y)| (CursorKeyMap::new(x, backward), (y, forward)))
+    }
+}
+
+impl<Tr, F, B> TraceKeyMap<Tr, F, B>
+where
+    Tr: TraceReader,
+{
+    /// Makes a new trace wrapper
+    pub fn make_from(trace: Tr, forward: F, backward: B) -> Self {
+        TraceKeyMap {
+            trace,
+            forward,
+            backward,
+        }
+    }
+}
+
+
+/// Wrapper to present a batch under a different key type.
+#[derive(Clone)]
+pub struct BatchKeyMap<Ba, F, B> {
+    batch: Ba,
+    forward: F,
+    backward: B,
+}
+
+impl<Ba, K, F, B> BatchReader for BatchKeyMap<Ba, F, B>
+where
+    Ba: BatchReader,
+    K: Ord+Clone+'static,
+    F: for<'a> Fn(Ba::Key<'a>)->K+Clone+'static,
+    B: Fn(&K)->Ba::KeyOwned+Clone+'static,
+{
+    type Key<'a> = MappedKey<'a, Ba::Key<'a>, K, F>;
+    type KeyOwned = K;
+    type Val<'a> = Ba::Val<'a>;
+    type Time = Ba::Time;
+    type Diff = Ba::Diff;
+
+    type Cursor = BatchCursorKeyMap<Ba::Cursor, F, B>;
+
+    fn cursor(&self) -> Self::Cursor {
+        BatchCursorKeyMap::new(self.batch.cursor())
+    }
+    fn len(&self) -> usize { self.batch.len() }
+    fn description(&self) -> &Description<Ba::Time> { self.batch.description() }
+}
+
+impl<Ba, F, B> BatchKeyMap<Ba, F, B>
+where
+    Ba: BatchReader,
+{
+    /// Makes a new batch wrapper
+    pub fn make_from(batch: Ba, forward: F, backward: B) -> Self {
+        BatchKeyMap {
+            batch,
+            forward,
+            backward,
+        }
+    }
+}
+
+/// Wrapper to present a trace cursor under a different key type.
+///
+/// The storage of the cursor is that of the wrapped cursor, with the function converting its keys.
+pub struct CursorKeyMap<C, F, B> {
+    cursor: C,
+    backward: B,
+    phantom: std::marker::PhantomData<F>,
+}
+
+impl<C, F, B> CursorKeyMap<C, F, B> {
+    fn new(cursor: C, backward: B) -> Self {
+        CursorKeyMap {
+            cursor,
+            backward,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C, K, F, B> Cursor for CursorKeyMap<C, F, B>
+where
+    C: Cursor,
+    K: Ord+Clone+'static,
+    F: for<'a> Fn(C::Key<'a>)->K+'static,
+    B: Fn(&K)->C::KeyOwned+'static,
+{
+    type Key<'a> = MappedKey<'a, C::Key<'a>, K, F>;
+    type KeyOwned = K;
+    type Val<'a> = C::Val<'a>;
+    type Time = C::Time;
+    type Diff = C::Diff;
+
+    type Storage = (C::Storage, F);
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(&storage.0) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(&storage.0) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { MappedKey::Inner(self.cursor.key(&storage.0), &storage.1) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { self.cursor.val(&storage.0) }
+
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, logic: L) {
+        self.cursor.map_times(&storage.0, logic)
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(&storage.0) }
+    #[inline]
+    fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) {
+        match key {
+            MappedKey::Inner(key, _) => self.cursor.seek_key(&storage.0, key),
+            MappedKey::Mapped(key) => self.cursor.seek_key_owned(&storage.0, &(self.backward)(key)),
+        }
+    }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(&storage.0) }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) { self.cursor.seek_val(&storage.0, val) }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(&storage.0) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(&storage.0) }
+}
+
+
+/// Wrapper to present a batch cursor under a different key type.
+pub struct BatchCursorKeyMap<C, F, B> {
+    cursor: C,
+    phantom: std::marker::PhantomData<(F, B)>,
+}
+
+impl<C, F, B> BatchCursorKeyMap<C, F, B> {
+    fn new(cursor: C) -> Self {
+        BatchCursorKeyMap {
+            cursor,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: Cursor, K, F, B> Cursor for BatchCursorKeyMap<C, F, B>
+where
+    K: Ord+Clone+'static,
+    F: for<'a> Fn(C::Key<'a>)->K+'static,
+    B: Fn(&K)->C::KeyOwned+'static,
+{
+    type Key<'a> = MappedKey<'a, C::Key<'a>, K, F>;
+    type KeyOwned = K;
+    type Val<'a> = C::Val<'a>;
+    type Time = C::Time;
+    type Diff = C::Diff;
+
+    type Storage = BatchKeyMap<C::Storage, F, B>;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(&storage.batch) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(&storage.batch) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { MappedKey::Inner(self.cursor.key(&storage.batch), &storage.forward) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { self.cursor.val(&storage.batch) }
+
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, logic: L) {
+        self.cursor.map_times(&storage.batch, logic)
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(&storage.batch) }
+    #[inline]
+    fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) {
+        match key {
+            MappedKey::Inner(key, _) => self.cursor.seek_key(&storage.batch, key),
+            MappedKey::Mapped(key) => self.cursor.seek_key_owned(&storage.batch, &(storage.backward)(key)),
+        }
+    }
+
+    #[inline] fn step_val(&mut self, storage: &Self::Storage) { self.cursor.step_val(&storage.batch) }
+    #[inline] fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) { self.cursor.seek_val(&storage.batch, val) }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(&storage.batch) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(&storage.batch) }
+}
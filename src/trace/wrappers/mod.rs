@@ -6,4 +6,6 @@ pub mod frontier;
 pub mod rc;
 
 pub mod filter;
+pub mod key_map;
+pub mod project;
 pub mod freeze;
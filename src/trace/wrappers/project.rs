@@ -0,0 +1,286 @@
+//! Wrapper presenting a projection of the values of a trace.
+//!
+//! The wrapper is provided with a function from references to the values of the wrapped trace to
+//! references to the projected values, for example to a field of each value. The projection must
+//! preserve order, though it need not be injective: it must map ordered values to ordered projected
+//! values, as is the case for the first field of a tuple, but may map distinct values to the same
+//! projected value. The cursors present each distinct projected value once, with the consolidated
+//! updates of all the values that project to it.
+
+use timely::progress::frontier::AntichainRef;
+
+use crate::trace::{TraceReader, BatchReader, Description};
+use crate::trace::cursor::Cursor;
+use crate::consolidation::consolidate;
+
+/// Wrapper to present a projection of the values of a trace.
+pub struct TraceProject<Tr, F> {
+    trace: Tr,
+    logic: F,
+}
+
+impl<Tr, F> Clone for TraceProject<Tr, F>
+where
+    Tr: TraceReader+Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        TraceProject {
+            trace: self.trace.clone(),
+            logic: self.logic.clone(),
+        }
+    }
+}
+
+impl<Tr, P, F> TraceReader for TraceProject<Tr, F>
+where
+    Tr: TraceReader,
+    Tr::Batch: Clone,
+    P: Ord+Clone+'static,
+    F: for<'a> Fn(Tr::Val<'a>)->&'a P+Clone+'static,
+{
+    type Key<'a> = Tr::Key<'a>;
+    type KeyOwned = Tr::KeyOwned;
+    type Val<'a> = &'a P;
+    type Time = Tr::Time;
+    type Diff = Tr::Diff;
+
+    type Batch = BatchProject<Tr::Batch, F>;
+    type Storage = Tr::Storage;
+    type Cursor = CursorProject<Tr::Cursor, F>;
+
+    fn map_batches<F2: FnMut(&Self::Batch)>(&self, mut f: F2) {
+        let logic = self.logic.clone();
+        self.trace
+            .map_batches(|batch| f(&Self::Batch::make_from(batch.clone(), logic.clone())))
+    }
+
+    fn set_logical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) { self.trace.set_logical_compaction(frontier) }
+    fn get_logical_compaction(&mut self) -> AntichainRef<Tr::Time> { self.trace.get_logical_compaction() }
+
+    fn set_physical_compaction(&mut self, frontier: AntichainRef<Tr::Time>) { self.trace.set_physical_compaction(frontier) }
+    fn get_physical_compaction(&mut self) -> AntichainRef<Tr::Time> { self.trace.get_physical_compaction() }
+
+    fn cursor_through(&mut self, upper: AntichainRef<Tr::Time>) -> Option<(Self::Cursor, Self::Storage)> {
+        self.trace.cursor_through(upper).map(|(x,y)| (CursorProject::new(x, self.logic.clone()), y))
+    }
+}
+
+impl<Tr, F> TraceProject<Tr, F>
+where
+    Tr: TraceReader,
+{
+    /// Makes a new trace wrapper
+    pub fn make_from(trace: Tr, logic: F) -> Self {
+        TraceProject {
+            trace,
+            logic,
+        }
+    }
+}
+
+
+/// Wrapper to present a projection of the values of a batch.
+#[derive(Clone)]
+pub struct BatchProject<B, F> {
+    batch: B,
+    logic: F,
+}
+
+impl<B, P, F> BatchReader for BatchProject<B, F>
+where
+    B: BatchReader,
+    P: Ord+Clone+'static,
+    F: for<'a> Fn(B::Val<'a>)->&'a P+Clone+'static,
+{
+    type Key<'a> = B::Key<'a>;
+    type KeyOwned = B::KeyOwned;
+    type Val<'a> = &'a P;
+    type Time = B::Time;
+    type Diff = B::Diff;
+
+    type Cursor = BatchCursorProject<B::Cursor, F>;
+
+    fn cursor(&self) -> Self::Cursor {
+        BatchCursorProject::new(self.batch.cursor(), self.logic.clone())
+    }
+    // The updates of the wrapped batch, some of which may consolidate under their projected values.
+    fn len(&self) -> usize { self.batch.len() }
+    fn description(&self) -> &Description<B::Time> { self.batch.description() }
+}
+
+impl<B, F> BatchProject<B, F>
+where
+    B: BatchReader,
+{
+    /// Makes a new batch wrapper
+    pub fn make_from(batch: B, logic: F) -> Self {
+        BatchProject {
+            batch,
+            logic,
+        }
+    }
+}
+
+/// Wrapper to present a projection of the values of a trace cursor.
+pub struct CursorProject<C: Cursor, F> {
+    cursor: C,
+    logic: F,
+    /// The updates of the values with the current projection, consolidated.
+    updates: Vec<(C::Time, C::Diff)>,
+}
+
+impl<C: Cursor, F> CursorProject<C, F> {
+    fn new(cursor: C, logic: F) -> Self {
+        CursorProject {
+            cursor,
+            logic,
+            updates: Vec::new(),
+        }
+    }
+}
+
+impl<C, P, F> Cursor for CursorProject<C, F>
+where
+    C: Cursor,
+    P: Ord+Clone+'static,
+    F: for<'a> Fn(C::Val<'a>)->&'a P+'static,
+{
+    type Key<'a> = C::Key<'a>;
+    type KeyOwned = C::KeyOwned;
+    type Val<'a> = &'a P;
+    type Time = C::Time;
+    type Diff = C::Diff;
+
+    type Storage = C::Storage;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(storage) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(storage) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { self.cursor.key(storage) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { (self.logic)(self.cursor.val(storage)) }
+
+    // Collects the updates of all values with the current projection, consolidates them once, and
+    // returns to the first of the values.
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        let first = self.cursor.val(storage);
+        let projected = (self.logic)(first);
+        self.updates.clear();
+        while self.cursor.get_val(storage).map(|val| (self.logic)(val) == projected).unwrap_or(false) {
+            let updates = &mut self.updates;
+            self.cursor.map_times(storage, |time, diff| updates.push((time.clone(), diff.clone())));
+            self.cursor.step_val(storage);
+        }
+        consolidate(&mut self.updates);
+        for (time, diff) in self.updates.iter() {
+            logic(time, diff);
+        }
+        self.cursor.rewind_vals(storage);
+        self.cursor.seek_val(storage, first);
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(storage) }
+    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) { self.cursor.seek_key(storage, key) }
+
+    #[inline]
+    fn step_val(&mut self, storage: &Self::Storage) {
+        let projected = (self.logic)(self.cursor.val(storage));
+        while self.cursor.get_val(storage).map(|val| (self.logic)(val) == projected).unwrap_or(false) {
+            self.cursor.step_val(storage);
+        }
+    }
+    // Values are sought by scanning, as projected values cannot be converted to values.
+    #[inline]
+    fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) {
+        while self.cursor.get_val(storage).map(|v| (self.logic)(v) < val).unwrap_or(false) {
+            self.cursor.step_val(storage);
+        }
+    }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(storage) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(storage) }
+}
+
+
+/// Wrapper to present a projection of the values of a batch cursor.
+pub struct BatchCursorProject<C: Cursor, F> {
+    cursor: C,
+    logic: F,
+    /// The updates of the values with the current projection, consolidated.
+    updates: Vec<(C::Time, C::Diff)>,
+}
+
+impl<C: Cursor, F> BatchCursorProject<C, F> {
+    fn new(cursor: C, logic: F) -> Self {
+        BatchCursorProject {
+            cursor,
+            logic,
+            updates: Vec::new(),
+        }
+    }
+}
+
+impl<C: Cursor, P, F> Cursor for BatchCursorProject<C, F>
+where
+    P: Ord+Clone+'static,
+    F: for<'a> Fn(C::Val<'a>)->&'a P+'static,
+{
+    type Key<'a> = C::Key<'a>;
+    type KeyOwned = C::KeyOwned;
+    type Val<'a> = &'a P;
+    type Time = C::Time;
+    type Diff = C::Diff;
+
+    type Storage = BatchProject<C::Storage, F>;
+
+    #[inline] fn key_valid(&self, storage: &Self::Storage) -> bool { self.cursor.key_valid(&storage.batch) }
+    #[inline] fn val_valid(&self, storage: &Self::Storage) -> bool { self.cursor.val_valid(&storage.batch) }
+
+    #[inline] fn key<'a>(&self, storage: &'a Self::Storage) -> Self::Key<'a> { self.cursor.key(&storage.batch) }
+    #[inline] fn val<'a>(&self, storage: &'a Self::Storage) -> Self::Val<'a> { (self.logic)(self.cursor.val(&storage.batch)) }
+
+    // Collects the updates of all values with the current projection, consolidates them once, and
+    // returns to the first of the values.
+    #[inline]
+    fn map_times<L: FnMut(&Self::Time,&Self::Diff)>(&mut self, storage: &Self::Storage, mut logic: L) {
+        let batch = &storage.batch;
+        let first = self.cursor.val(batch);
+        let projected = (self.logic)(first);
+        self.updates.clear();
+        while self.cursor.get_val(batch).map(|val| (self.logic)(val) == projected).unwrap_or(false) {
+            let updates = &mut self.updates;
+            self.cursor.map_times(batch, |time, diff| updates.push((time.clone(), diff.clone())));
+            self.cursor.step_val(batch);
+        }
+        consolidate(&mut self.updates);
+        for (time, diff) in self.updates.iter() {
+            logic(time, diff);
+        }
+        self.cursor.rewind_vals(batch);
+        self.cursor.seek_val(batch, first);
+    }
+
+    #[inline] fn step_key(&mut self, storage: &Self::Storage) { self.cursor.step_key(&storage.batch) }
+    #[inline] fn seek_key(&mut self, storage: &Self::Storage, key: Self::Key<'_>) { self.cursor.seek_key(&storage.batch, key) }
+
+    #[inline]
+    fn step_val(&mut self, storage: &Self::Storage) {
+        let batch = &storage.batch;
+        let projected = (self.logic)(self.cursor.val(batch));
+        while self.cursor.get_val(batch).map(|val| (self.logic)(val) == projected).unwrap_or(false) {
+            self.cursor.step_val(batch);
+        }
+    }
+    // Values are sought by scanning, as projected values cannot be converted to values.
+    #[inline]
+    fn seek_val(&mut self, storage: &Self::Storage, val: Self::Val<'_>) {
+        let batch = &storage.batch;
+        while self.cursor.get_val(batch).map(|v| (self.logic)(v) < val).unwrap_or(false) {
+            self.cursor.step_val(batch);
+        }
+    }
+
+    #[inline] fn rewind_keys(&mut self, storage: &Self::Storage) { self.cursor.rewind_keys(&storage.batch) }
+    #[inline] fn rewind_vals(&mut self, storage: &Self::Storage) { self.cursor.rewind_vals(&storage.batch) }
+}
@@ -30,6 +30,8 @@ pub struct TraceBox<Tr: TraceReader> {
     pub physical_compaction: MutableAntichain<Tr::Time>,
     /// The wrapped trace.
     pub trace: Tr,
+    /// Indicates that the trace has been released, and accepts no further batches.
+    pub released: bool,
 }
 
 impl<Tr: TraceReader> TraceBox<Tr> {
@@ -48,6 +50,7 @@ impl<Tr: TraceReader> TraceBox<Tr> {
             logical_compaction,
             physical_compaction,
             trace,
+            released: false,
         }
     }
     /// Replaces elements of `lower` with those of `upper`.
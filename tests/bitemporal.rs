@@ -0,0 +1,35 @@
+use timely::dataflow::operators::Probe;
+use timely::progress::frontier::AntichainRef;
+
+use differential_dataflow::input::InputSession;
+use differential_dataflow::bitemporal::{Bitemporal, read_as_of};
+use differential_dataflow::trace::TraceReader;
+
+#[test]
+fn bitemporal_compaction() {
+
+    timely::execute_directly(|worker| {
+
+        let mut input = InputSession::<Bitemporal<u64, u64>, (String, i64), isize>::new();
+        let (mut trace, probe) = worker.dataflow(|scope| {
+            let arranged = input.to_collection(scope).arrange_bitemporal();
+            (arranged.trace, arranged.stream.probe())
+        });
+
+        input.update_at(("alice".to_string(), 100), Bitemporal::new(0, 0), 1);
+        input.update_at(("alice".to_string(), 90), Bitemporal::new(2, 5), 1);
+        input.advance_to(Bitemporal::new(3, 7));
+        input.flush();
+        while probe.less_than(input.time()) { worker.step(); }
+
+        // advanced to the input frontier, the handle compacts only along system time.
+        let frontier = probe.with_frontier(|frontier| frontier.to_owned());
+        trace.set_logical_compaction(frontier.borrow());
+        assert_eq!(trace.get_logical_compaction(), AntichainRef::new(&[Bitemporal::new(3, 0)]));
+
+        // the history of valid times remains, as of the current system time.
+        assert_eq!(read_as_of(&mut trace, 3, 0).unwrap(), vec![(("alice".to_string(), 100), 1)]);
+        assert_eq!(read_as_of(&mut trace, 3, 5).unwrap(), vec![(("alice".to_string(), 90), 1), (("alice".to_string(), 100), 1)]);
+        assert_eq!(read_as_of(&mut trace, 2, 5), None);
+    });
+}
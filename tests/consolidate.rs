@@ -0,0 +1,65 @@
+use timely::dataflow::operators::{ToStream, Capture};
+use timely::dataflow::operators::capture::Extract;
+use differential_dataflow::AsCollection;
+
+#[test]
+fn suppress_updates_until_closed() {
+
+    let data = timely::example(|scope| {
+
+        // all updates are sent with the capability of time zero, at later times.
+        let col1 = vec![(1u32, 5u64, 1isize), (2, 5, 1), (1, 3, 1)].to_stream(scope).as_collection();
+        let col2 = vec![(1u32, 5u64, -1isize)].to_stream(scope).as_collection();
+
+        col1.concat(&col2)
+            .suppress_updates_until_closed()
+            .inner
+            .capture()
+    });
+
+    // updates are released at their own times, consolidated across messages.
+    let extracted = data.extract();
+    assert_eq!(extracted, vec![
+        (3, vec![(1, 3, 1)]),
+        (5, vec![(2, 5, 1)]),
+    ]);
+}
+
+#[test]
+fn align() {
+
+    let data = timely::example(|scope| {
+
+        // updates are sent with the capability of time zero, at later times.
+        let col1 = vec![(1u32, 5u64, 1isize), (2, 3, 1)].to_stream(scope).as_collection();
+        let col2 = vec![(3u32, 4u64, 1isize)].to_stream(scope).as_collection();
+
+        let aligned = differential_dataflow::collection::align(&[col1, col2]);
+        (aligned[0].inner.capture(), aligned[1].inner.capture())
+    });
+
+    // each update is released at its own time, once all inputs have passed it.
+    assert_eq!(data.0.extract(), vec![(3, vec![(2, 3, 1)]), (5, vec![(1, 5, 1)])]);
+    assert_eq!(data.1.extract(), vec![(4, vec![(3, 4, 1)])]);
+}
+
+#[test]
+fn append_only_panic_across_messages() {
+    use differential_dataflow::operators::AppendOnly;
+    use differential_dataflow::operators::append_only::OnRetraction;
+
+    let data = timely::example(|scope| {
+
+        // the retraction of 1 is outweighed by insertions in another message.
+        let col1 = vec![(1u32, 5u64, 1isize), (1, 5, 1), (2, 5, 1)].to_stream(scope).as_collection();
+        let col2 = vec![(1u32, 5u64, -1isize)].to_stream(scope).as_collection();
+
+        col2.concat(&col1)
+            .append_only(OnRetraction::Panic)
+            .inner
+            .capture()
+    });
+
+    // the accumulation at time five has no negative differences.
+    assert_eq!(data.extract(), vec![(5, vec![(1, 5, 1), (2, 5, 1)])]);
+}
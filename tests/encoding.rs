@@ -0,0 +1,103 @@
+//! Compatibility tests for persisted batch encodings.
+//!
+//! The bytes of headers are fixed: changing them makes persisted batches unreadable, and should only
+//! happen alongside a new format or version that can still decode the old ones.
+
+use differential_dataflow::trace::implementations::encoding::{Format, Header, HEADER_LEN, MAGIC};
+
+#[test]
+fn header_layout() {
+    let cases = [
+        (Format::Bincode, 1u16, [1u8, 0, 1, 0]),
+        (Format::Archive, 1, [2, 0, 1, 0]),
+        (Format::SharedArchive, 1, [3, 0, 1, 0]),
+        (Format::Archive, 258, [2, 0, 2, 1]),
+    ];
+    for (format, version, tail) in cases {
+        let mut expected = b"ddbatch\0".to_vec();
+        expected.extend_from_slice(&tail);
+        expected.extend_from_slice(&[0, 0, 0, 0]);
+        let header = Header { format, version };
+        assert_eq!(&header.encode()[..], &expected[..]);
+        assert_eq!(Header::decode(&expected).unwrap(), (header, &[][..]));
+    }
+}
+
+#[test]
+fn header_absent() {
+    // Encodings without a header are rejected.
+    let bytes = [7u8, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3];
+    assert!(Header::decode(&bytes).is_err());
+    assert!(Header::decode_version(Format::Bincode, &bytes).is_err());
+    assert!(Header::decode(&[]).is_err());
+}
+
+#[test]
+fn header_invalid() {
+    // A header cut short.
+    assert!(Header::decode(&MAGIC).is_err());
+    // An unknown format.
+    let mut bytes = Header { format: Format::Bincode, version: 1 }.encode();
+    bytes[8] = 200;
+    assert!(Header::decode(&bytes).is_err());
+    // A different format than expected.
+    let bytes = Header { format: Format::Archive, version: 1 }.encode();
+    assert!(Header::decode_version(Format::Bincode, &bytes).is_err());
+    assert_eq!(bytes.len(), HEADER_LEN);
+}
+
+#[cfg(feature = "rkyv")]
+mod archive {
+
+    use differential_dataflow::trace::Trace;
+    use differential_dataflow::trace::implementations::ValSpine;
+    use differential_dataflow::trace::implementations::archive::BatchArchive;
+    use differential_dataflow::trace::implementations::encoding::{Format, Header};
+    use differential_dataflow::trace::testing;
+
+    type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+
+    fn archive() -> BatchArchive<u64, u64, u64, i64> {
+        let batch = testing::random_batches::<Builder>(0, 1, 100, 10, 10).pop().unwrap();
+        BatchArchive::from_batch(&batch, |v| *v)
+    }
+
+    #[test]
+    fn current_version() {
+        let archive = archive();
+        let bytes = archive.to_bytes();
+        assert_eq!(Header::decode(&bytes).unwrap().0, Header { format: Format::Archive, version: 1 });
+        let archived = BatchArchive::<u64, u64, u64, i64>::access(&bytes).unwrap();
+        assert_eq!(BatchArchive::deserialize_from(archived), archive);
+    }
+
+    #[test]
+    fn without_header() {
+        let archive = archive();
+        let bytes = rkyv::to_bytes::<_, 4096>(&archive).unwrap();
+        assert!(BatchArchive::<u64, u64, u64, i64>::access(&bytes).is_err());
+    }
+
+    #[test]
+    fn corrupt_offsets() {
+        // offsets past the end of the values they delimit.
+        let mut past_end = archive();
+        *past_end.keys_offs.last_mut().unwrap() += 1;
+        assert!(BatchArchive::<u64, u64, u64, i64>::access(&past_end.to_bytes()).is_err());
+
+        // offsets that decrease.
+        let mut decreasing = archive();
+        decreasing.vals_offs[1] = decreasing.vals_offs[2] + 1;
+        assert!(BatchArchive::<u64, u64, u64, i64>::access(&decreasing.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn later_version() {
+        let archive = archive();
+        let payload = rkyv::to_bytes::<_, 4096>(&archive).unwrap();
+        let mut bytes = rkyv::AlignedVec::new();
+        bytes.extend_from_slice(&Header { format: Format::Archive, version: 2 }.encode());
+        bytes.extend_from_slice(&payload);
+        assert!(BatchArchive::<u64, u64, u64, i64>::access(&bytes).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use timely::dataflow::operators::{ToStream, Capture};
+use timely::dataflow::operators::capture::Extract;
+use differential_dataflow::AsCollection;
+use differential_dataflow::operators::ExpireAfter;
+
+#[test]
+fn expire() {
+
+    let data = timely::example(|scope| {
+        vec![((1u32, 'a'), 0u64, 1isize), ((2, 'b'), 5, 1)]
+            .to_stream(scope)
+            .as_collection()
+            .expire_after(10)
+            .inner
+            .capture()
+    });
+
+    // each key is retracted ten ticks after its update.
+    assert_eq!(data.extract(), vec![
+        (0, vec![((1, 'a'), 0, 1), ((2, 'b'), 5, 1)]),
+        (10, vec![((1, 'a'), 10, -1)]),
+        (15, vec![((2, 'b'), 15, -1)]),
+    ]);
+}
+
+#[test]
+fn expire_refresh() {
+
+    let data = timely::example(|scope| {
+        vec![((1u32, 'a'), 0u64, 1isize), ((1, 'b'), 5, 1), ((1, 'a'), 8, -1), ((2, 'c'), 8, 1), ((2, 'c'), 9, -1)]
+            .to_stream(scope)
+            .as_collection()
+            .expire_after(10)
+            .inner
+            .capture()
+    });
+
+    // an update to any value of a key refreshes the whole key, and keys without values do not expire.
+    assert_eq!(data.extract(), vec![
+        (0, vec![((1, 'a'), 0, 1), ((1, 'a'), 8, -1), ((1, 'b'), 5, 1), ((2, 'c'), 8, 1), ((2, 'c'), 9, -1)]),
+        (18, vec![((1, 'b'), 18, -1)]),
+    ]);
+}
+
+#[test]
+fn expire_future_times() {
+
+    let data = timely::example(|scope| {
+        // the update at time twenty is received before the key expires at time ten.
+        vec![((1u32, 'a'), 0u64, 1isize), ((1, 'a'), 20, 1)]
+            .to_stream(scope)
+            .as_collection()
+            .arrange_expiring(10)
+            .expired
+            .inner
+            .capture()
+    });
+
+    // the key expires at time ten, and the later update starts a new accumulation.
+    assert_eq!(data.extract(), vec![
+        (10, vec![(1, 10, 1)]),
+        (30, vec![(1, 30, 1)]),
+    ]);
+}
@@ -243,3 +243,124 @@ fn import_skewed() {
         (4, vec![((0, 1), 1)]),
     ]);
 }
+
+#[test]
+fn test_release() {
+    use differential_dataflow::trace::BatchReader;
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, mut trace) = worker.dataflow(|scope| {
+            let (input, edges) = scope.new_input();
+            let arranged = edges.as_collection()
+                                .arrange_by_key();
+            (input, arranged.trace.clone())
+        });
+        let probe = worker.dataflow(|scope| trace.import(scope).stream.probe());
+
+        input.send(((1u64, 2u64), 0usize, 1i64));
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(&1));
+
+        let mut records = 0;
+        trace.map_batches(|batch| records += batch.len());
+        assert_eq!(records, 1);
+
+        // the released trace is empty and complete, though its input remains open.
+        trace.release();
+        let mut records = 0;
+        trace.map_batches(|batch| records += batch.len());
+        assert_eq!(records, 0);
+        worker.step_while(|| !probe.done());
+
+        input.send(((3, 4), 1, 1));
+        input.advance_to(2);
+        worker.step();
+        let mut upper = timely::progress::Antichain::new();
+        trace.read_upper(&mut upper);
+        assert!(upper.is_empty());
+        let mut records = 0;
+        trace.map_batches(|batch| records += batch.len());
+        assert_eq!(records, 0);
+    });
+}
+
+#[test]
+fn test_release_downstream() {
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use timely::dataflow::ProbeHandle;
+    use differential_dataflow::operators::JoinCore;
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, mut trace) = worker.dataflow(|scope| {
+            let (input, edges) = scope.new_input();
+            let arranged = edges.as_collection()
+                                .arrange_by_key();
+            (input, arranged.trace.clone())
+        });
+
+        let joined = Rc::new(RefCell::new(Vec::new()));
+        let reduced = Rc::new(RefCell::new(Vec::new()));
+        let mut other = InputSession::<usize, (u64, u64), i64>::new();
+        let mut probe = ProbeHandle::new();
+        worker.dataflow(|scope| {
+            let imported = trace.import(scope);
+            let other = other.to_collection(scope).arrange_by_key();
+            let joined = joined.clone();
+            imported.join_core(&other, |key, val1, val2| Some((*key, *val1, *val2)))
+                    .inspect(move |x| joined.borrow_mut().push(x.clone()))
+                    .probe_with(&mut probe);
+            let reduced = reduced.clone();
+            imported.reduce(|_key, input, output| output.push((*input[0].0, 1isize)))
+                    .inspect(move |x| reduced.borrow_mut().push(x.clone()))
+                    .probe_with(&mut probe);
+        });
+
+        input.send(((1u64, 2u64), 0usize, 1i64));
+        input.advance_to(1);
+        other.advance_to(1);
+        other.flush();
+        worker.step_while(|| probe.less_than(&1));
+        assert_eq!(*reduced.borrow(), vec![((1, 2), 0, 1)]);
+
+        // a released trace provides no cursors.
+        trace.release();
+        assert!(trace.cursor_through(AntichainRef::new(&[])).is_none());
+
+        // operators reading the released trace shut down, rather than panic, and produce no further output.
+        other.update((1, 3), 1);
+        other.advance_to(2);
+        other.flush();
+        for _ in 0 .. 10 {
+            worker.step();
+        }
+
+        drop(input);
+        other.close();
+        worker.step_while(|| !probe.done());
+        assert!(joined.borrow().is_empty());
+        assert_eq!(*reduced.borrow(), vec![((1, 2), 0, 1)]);
+    });
+}
+
+#[test]
+fn compaction_driver_completes() {
+    use std::time::Duration;
+    use differential_dataflow::operators::arrange::compaction::{CompactionDriver, Immediate};
+
+    timely::execute_directly(|worker| {
+        let mut input = InputSession::<usize, u64, isize>::new();
+        let trace = worker.dataflow(|scope| input.to_collection(scope).arrange_by_self().trace);
+        let driver = worker.dataflow(|scope| CompactionDriver::new(scope, Duration::from_millis(1)));
+        driver.register(trace, Immediate);
+
+        input.insert(1);
+        input.close();
+
+        // the dataflows complete once the registered trace is complete, though the driver is held.
+        while worker.step_or_park(None) { }
+        drop(driver);
+    });
+}
@@ -73,6 +73,108 @@ fn antijoin() {
     assert_eq!(extracted[0].1, vec![((1,2), Default::default(),1)]);
 }
 
+#[test]
+fn join_builder() {
+    use differential_dataflow::operators::join_builder::JoinBuilder;
+
+    let data = timely::example(|scope| {
+        let col1 = vec![((0,0), Default::default(),1),((1,2), Default::default(),1)].into_iter().to_stream(scope).as_collection();
+        let col2 = vec![((0,'a'), Default::default(),1),((1,'B'), Default::default(),1),((2,'c'), Default::default(),1)].into_iter().to_stream(scope).as_collection();
+
+        let builder = JoinBuilder::new(&col1).join(&col2).join(&col1);
+        // `col1` is arranged once, and shared with its second join.
+        assert_eq!(builder.arrangements(), 3);
+        builder.join(&col2).build().inner.capture()
+    });
+
+    let extracted = data.extract();
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].1, vec![((0,(((0,'a'),0),'a')), Default::default(), 1), ((1,(((2,'B'),2),'B')), Default::default(), 1)]);
+}
+
+#[test]
+fn join_skewed() {
+
+    use differential_dataflow::input::Input;
+
+    // Keys become heavy and light again as records arrive and depart; the results must match `join`.
+    timely::execute(timely::Config::process(3), |worker| {
+
+        let (mut input1, mut input2) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input1, data1) = scope.new_collection::<(u32, u32), isize>();
+            let (input2, data2) = scope.new_collection::<(u32, char), isize>();
+            data1.join_skewed(&data2, 5)
+                 .assert_eq(&data1.join(&data2));
+            (input1, input2)
+        });
+
+        if worker.index() == 0 {
+            for key in 0 .. 4 { input2.insert((key, 'a')); input2.insert((key, 'b')); }
+            for round in 0 .. 10u32 {
+                // key zero gains records each round; key one loses them after round five.
+                for val in 0 .. 3 { input1.insert((0, round * 3 + val)); }
+                if round < 5 { input1.insert((1, round)); } else { input1.remove((1, round - 5)); }
+                input1.insert((2 + round % 2, round));
+                input1.advance_to(round + 1);
+                input2.advance_to(round + 1);
+            }
+        }
+        // the dataflow runs to completion as the worker shuts down.
+        input1.close();
+        input2.close();
+
+    }).unwrap();
+}
+
+#[test]
+fn join_time_sliced() {
+
+    use std::sync::{Arc, Mutex};
+    use differential_dataflow::input::Input;
+
+    // A join that yields after each unit of work produces the same results.
+    let mut config = timely::Config::thread();
+    let options = differential_dataflow::Config::default()
+        .join_fuel(Some(10))
+        .join_time_slice(Some(std::time::Duration::from_secs(0)));
+    differential_dataflow::configure(&mut config.worker, &options);
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    timely::execute(config, move |worker| {
+
+        let results = results2.clone();
+        let (mut input1, mut input2, probe) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input1, data1) = scope.new_collection::<(u64, u64), isize>();
+            let (input2, data2) = scope.new_collection::<(u64, u64), isize>();
+            let probe = data1.join(&data2)
+                             .map(|(key, (val1, val2))| (key, val1 + val2))
+                             .consolidate()
+                             .inspect(move |x| results.lock().unwrap().push(x.clone()))
+                             .probe();
+            (input1, input2, probe)
+        });
+
+        for i in 0 .. 100 { input1.insert((i % 10, i)); input2.insert((i % 10, 1000 * i)); }
+        input1.advance_to(1); input2.advance_to(1);
+        input1.flush(); input2.flush();
+        while probe.less_than(input1.time()) { worker.step(); }
+
+    }).unwrap();
+
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    let mut expected = Vec::new();
+    for i in 0 .. 100u64 {
+        for j in 0 .. 100u64 {
+            if i % 10 == j % 10 { expected.push(((i % 10, i + 1000 * j), 0, 1)); }
+        }
+    }
+    expected.sort();
+    assert_eq!(results, expected);
+}
+
 #[test] fn join_scale_1() { join_scaling(1); }
 #[test] fn join_scale_10() { join_scaling(10); }
 #[test] fn join_scale_100() { join_scaling(100); }
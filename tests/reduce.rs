@@ -39,4 +39,276 @@ fn reduce_scaling() {
 
     let extracted = data.extract();
     assert_eq!(extracted.len(), 1);
-}
\ No newline at end of file
+}
+
+#[test]
+fn reduce_fueled() {
+    reduce_yielding(differential_dataflow::Config::default().reduce_fuel(Some(10)));
+}
+
+#[test]
+fn reduce_time_sliced() {
+    // A slice of zero yields after each key.
+    reduce_yielding(differential_dataflow::Config::default().reduce_time_slice(Some(std::time::Duration::from_secs(0))));
+}
+
+/// Checks that a `reduce` configured with `options` yields without changing its results.
+fn reduce_yielding(options: differential_dataflow::Config) {
+
+    use std::sync::{Arc, Mutex};
+    use differential_dataflow::input::Input;
+
+    let mut config = timely::Config::thread();
+    differential_dataflow::configure(&mut config.worker, &options);
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    timely::execute(config, move |worker| {
+
+        let results = results2.clone();
+        let (mut input, probe) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, data) = scope.new_collection();
+            let probe = data.map(|x: u64| (x % 100, x))
+                            .reduce(|_key, input, output| output.push((input.len() as isize, 1)))
+                            .inspect(move |x| results.lock().unwrap().push(x.clone()))
+                            .probe();
+            (input, probe)
+        });
+
+        for i in 0 .. 1000 { input.insert(i); }
+        input.advance_to(1); input.flush();
+        while probe.less_than(input.time()) { worker.step(); }
+
+        for i in 0 .. 500 { input.remove(i); }
+        input.advance_to(2); input.flush();
+        while probe.less_than(input.time()) { worker.step(); }
+
+    }).unwrap();
+
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    let mut expected = Vec::new();
+    for key in 0 .. 100 {
+        expected.push(((key, 10), 0, 1));
+        expected.push(((key, 10), 1, -1));
+        expected.push(((key, 5), 1, 1));
+    }
+    expected.sort();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn arrange_fueled() {
+
+    use std::sync::{Arc, Mutex};
+    use differential_dataflow::input::Input;
+
+    // Arrangements that receive a few records at a time produce the same results.
+    let mut config = timely::Config::thread();
+    let options = differential_dataflow::Config::default()
+        .arrange_fuel(Some(1))
+        .arrange_time_slice(Some(std::time::Duration::from_secs(0)));
+    differential_dataflow::configure(&mut config.worker, &options);
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    timely::execute(config, move |worker| {
+
+        let results = results2.clone();
+        let (mut input, probe) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, data) = scope.new_collection();
+            let probe = data.map(|x: u64| x % 10)
+                            .count()
+                            .inspect(move |x| results.lock().unwrap().push(x.clone()))
+                            .probe();
+            (input, probe)
+        });
+
+        for round in 0 .. 10 {
+            for i in 0 .. 100 { input.insert(i); }
+            input.advance_to(round + 1); input.flush();
+        }
+        while probe.less_than(input.time()) { worker.step(); }
+
+    }).unwrap();
+
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    let mut expected = Vec::new();
+    for key in 0 .. 10 {
+        expected.push(((key, 10), 0, 1));
+        for round in 1 .. 10 {
+            expected.push(((key, 10 * round as isize), round, -1));
+            expected.push(((key, 10 * (round as isize + 1)), round, 1));
+        }
+    }
+    expected.sort();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn reduce_monotonic() {
+
+    use std::sync::{Arc, Mutex};
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::ReduceMonotonic;
+
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let results2 = results.clone();
+
+    timely::execute(timely::Config::thread(), move |worker| {
+
+        let results = results2.clone();
+        let (mut input, probe) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, data) = scope.new_collection();
+            let probe = data.map(|x: u64| (x % 2, x))
+                            .reduce_monotonic(|_key, max: Option<&u64>, val, _diff: &isize| {
+                                max.map(|max| std::cmp::max(*max, *val)).unwrap_or(*val)
+                            })
+                            .inspect(move |x| results.lock().unwrap().push(x.clone()))
+                            .probe();
+            (input, probe)
+        });
+
+        for i in 0 .. 10 { input.insert(i); }
+        input.advance_to(1); input.flush();
+        while probe.less_than(input.time()) { worker.step(); }
+
+        // Only the odd key's maximum improves.
+        input.insert(4); input.insert(11);
+        input.advance_to(2); input.flush();
+        while probe.less_than(input.time()) { worker.step(); }
+
+    }).unwrap();
+
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    let mut expected = vec![((0, 8), 0, 1), ((1, 9), 0, 1), ((1, 9), 1, -1), ((1, 11), 1, 1)];
+    expected.sort();
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn reduce_fallible() {
+
+    use differential_dataflow::input::Input;
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, values, errors) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, data) = scope.new_collection::<(u32, i64), isize>();
+            let (values, errors) = data.reduce_fallible(|_key, input, output| {
+                let sum: i64 = input.iter().map(|(val, diff)| **val * (*diff as i64)).sum();
+                if sum < 0 { return Err(sum); }
+                output.push((sum, 1));
+                Ok(())
+            });
+            (input, values.inner.capture(), errors.inner.capture())
+        });
+
+        input.insert((0, 5));
+        input.insert((1, -3));
+        input.advance_to(1);
+        // the error of key 1 is retracted once its sum is again non-negative.
+        input.insert((1, 4));
+        input.close();
+        while worker.step() { }
+
+        let mut values = values.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec![((0, 5), 0, 1), ((1, 1), 1, 1)]);
+        let mut errors = errors.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        errors.sort();
+        assert_eq!(errors, vec![((1, -3), 0, 1), ((1, -3), 1, -1)]);
+    });
+}
+
+#[test]
+fn distinct_values() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::{DistinctValues, DistinctValuesTotal};
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, general, total) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, data) = scope.new_collection::<(u32, u32), isize>();
+            (input, data.distinct_values().inner.capture(), data.distinct_values_total().inner.capture())
+        });
+
+        input.insert((0, 0));
+        input.insert((0, 0));
+        input.insert((0, 1));
+        input.insert((1, 0));
+        input.advance_to(1);
+        // (0, 0) remains present, while (0, 1) is removed.
+        input.remove((0, 0));
+        input.remove((0, 1));
+        input.insert((1, 1));
+        input.close();
+        while worker.step() { }
+
+        let expected = vec![((0, 0), 0, 1), ((0, 1), 0, 1), ((0, 1), 1, -1), ((1, 0), 0, 1), ((1, 1), 1, 1)];
+        for captured in [general, total] {
+            let mut results = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+            results.sort();
+            assert_eq!(results, expected);
+        }
+    });
+}
+
+#[test]
+fn aggregate_total() {
+
+    use differential_dataflow::input::Input;
+    use differential_dataflow::operators::AggregateTotal;
+
+    timely::execute_directly(|worker| {
+
+        let (mut input, captured) = worker.dataflow::<u32,_,_>(|scope| {
+            let (input, data) = scope.new_collection::<(u32, i64), isize>();
+            let sums = data.aggregate_total(|_key, input| input.iter().map(|(val, diff)| **val * (*diff as i64)).sum::<i64>());
+            (input, sums.inner.capture())
+        });
+
+        input.insert((0, 5));
+        input.insert((0, 5));
+        input.insert((1, 3));
+        input.advance_to(1);
+        input.remove((0, 5));
+        input.insert((1, -3));
+        input.advance_to(2);
+        input.remove((0, 5));
+        input.close();
+        while worker.step() { }
+
+        let mut results = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+        results.sort();
+        assert_eq!(results, vec![(0, 0, 10), (0, 1, -5), (0, 2, -5), (1, 0, 3), (1, 1, -3)]);
+    });
+}
+
+#[test]
+fn count_local_then_global() {
+
+    let data = timely::example(|scope| {
+
+        // updates are sent with the capability of time zero, at later times, split across messages.
+        let col1 = vec![(1u32, 5u64, 1isize), (2, 3, 1), (1, 3, 1)].to_stream(scope).as_collection();
+        let col2 = vec![(1u32, 5u64, 1isize), (2, 5, -1)].to_stream(scope).as_collection();
+
+        col1.concat(&col2)
+            .count_local_then_global()
+            .inner
+            .capture()
+    });
+
+    // counts are reported at the times of the updates.
+    let extracted = data.extract();
+    assert_eq!(extracted, vec![
+        (3, vec![((1, 1), 3, 1), ((2, 1), 3, 1)]),
+        (5, vec![((1, 1), 5, -1), ((1, 3), 5, 1), ((2, 1), 5, -1)]),
+    ]);
+}
@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::operators::Probe;
+use timely::dataflow::operators::capture::{Capture, Extract};
+
+use differential_dataflow::input::Input;
+use differential_dataflow::capture::harness::replay;
+use differential_dataflow::operators::arrange::ArrangeByKey;
+use differential_dataflow::operators::arrange::reshard::{handoff, Handoff};
+
+#[test]
+fn test_handoff_to_more_workers() {
+
+    let data: Vec<(u64, u64)> = (0 .. 100).map(|x| (x % 17, x)).collect();
+
+    // Arrange the data with two workers, and hand off the trace of each.
+    let handoffs = Arc::new(Mutex::new(Vec::new()));
+    let handoffs2 = handoffs.clone();
+    let data2 = data.clone();
+    timely::execute(timely::Config::process(2), move |worker| {
+        let index = worker.index();
+        let peers = worker.peers();
+        let (mut input, mut trace, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, collection) = scope.new_collection::<(u64, u64), isize>();
+            let arranged = collection.arrange_by_key();
+            (input, arranged.trace.clone(), arranged.stream.probe())
+        });
+        for (position, datum) in data2.iter().enumerate() {
+            if position % peers == index {
+                input.insert(*datum);
+            }
+        }
+        input.advance_to(1);
+        input.flush();
+        worker.step_while(|| probe.less_than(input.time()));
+        handoffs2.lock().unwrap().push(handoff(&mut trace, &[5, 10], |v| *v));
+    }).unwrap().join().into_iter().for_each(|result| result.unwrap());
+
+    let handoffs = std::mem::take(&mut *handoffs.lock().unwrap());
+    let merged = Handoff::merge(handoffs);
+    assert_eq!(merged.upper, vec![1]);
+    assert!(merged.shards[0].iter().all(|((key, _), _, _)| *key < 5));
+    assert!(merged.shards[1].iter().all(|((key, _), _, _)| 5 <= *key && *key < 10));
+    assert!(merged.shards[2].iter().all(|((key, _), _, _)| 10 <= *key));
+
+    // Replay one shard on each of three workers, and re-arrange the replayed collection.
+    let messages = Arc::new(merged.into_messages());
+    let captured = timely::execute(timely::Config::process(3), move |worker| {
+        let messages = messages[worker.index()].clone();
+        let (token, probe, captured) = worker.dataflow::<u64,_,_>(|scope| {
+            let (token, collection) = replay(scope, messages, None);
+            let contents = collection.arrange_by_key().as_collection(|k, v| (*k, *v));
+            (token, contents.probe(), contents.inner.capture())
+        });
+        worker.step_while(|| probe.less_than(&1));
+        drop(token);
+        captured
+    }).unwrap().join();
+
+    let mut results = captured
+        .into_iter()
+        .flat_map(|result| result.unwrap().extract())
+        .flat_map(|(_, updates)| updates)
+        .collect::<Vec<_>>();
+    results.sort();
+
+    let mut expected = data.into_iter().map(|datum| (datum, 0, 1)).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(results, expected);
+}
@@ -57,3 +57,605 @@ fn test_trace() {
     let vec_4 = cursor4.to_vec(|v| v.clone(), &storage4);
     assert_eq!(vec_4, vec_3);
 }
+
+#[test]
+fn test_cursor_for_keys() {
+    use differential_dataflow::trace::BatchReader;
+
+    let mut trace = get_trace();
+
+    // batches with keys in the range are read in full, and others may be omitted.
+    let (mut cursor, storage) = trace.cursor_for_keys(&2, &2);
+    let contents = cursor.to_vec(|v| v.clone(), &storage);
+    assert!(contents.contains(&((2, 3), vec![(1, 1), (2, -1)])));
+    assert!(storage.iter().all(|batch| batch.may_contain_keys(&2, &2)));
+
+    let (_cursor, storage) = trace.cursor_for_keys(&5, &9);
+    assert!(storage.is_empty());
+}
+
+#[test]
+fn test_seal_limited() {
+    use differential_dataflow::trace::BatchReader;
+
+    let mut batcher = <IntegerTrace as Trace>::Batcher::new(None, 0);
+
+    use timely::communication::message::RefOrMut;
+    batcher.push_container(RefOrMut::Mut(&mut vec![
+        ((1, 2), 0, 1),
+        ((2, 3), 0, 1),
+        ((3, 4), 1, 1),
+        ((4, 5), 2, 1),
+        ((5, 6), 2, 1),
+        ((6, 7), 2, 1),
+    ]));
+
+    let batches = batcher.seal_limited::<IntegerBuilder>(Antichain::from_elem(3), 2);
+    let lowers = batches.iter().map(|b| b.lower().elements().to_vec()).collect::<Vec<_>>();
+    let uppers = batches.iter().map(|b| b.upper().elements().to_vec()).collect::<Vec<_>>();
+    let lengths = batches.iter().map(|b| b.len()).collect::<Vec<_>>();
+
+    // Updates at time 2 exceed the limit, but are not split.
+    assert_eq!(lowers, vec![vec![0], vec![1], vec![2]]);
+    assert_eq!(uppers, vec![vec![1], vec![2], vec![3]]);
+    assert_eq!(lengths, vec![2, 1, 3]);
+}
+
+#[test]
+fn test_batch_contracts() {
+    use differential_dataflow::trace::testing;
+
+    type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+
+    for seed in 0 .. 20 {
+        let batches = testing::random_batches::<Builder>(seed, 2, 200, 20, 5);
+        for batch in batches.iter() {
+            testing::check_batch(batch).unwrap();
+        }
+        for frontier in [0u64, 1, 2] {
+            let compaction = Antichain::from_elem(frontier);
+            testing::check_merge(&batches[0], &batches[1], compaction.borrow(), 7, |v| *v).unwrap();
+        }
+    }
+}
+
+#[test]
+#[cfg(any(debug_assertions, feature = "spine-invariants"))]
+fn test_spine_invariants() {
+    use differential_dataflow::trace::BatchReader;
+    use differential_dataflow::trace::testing;
+
+    type Spine = ValSpine<u64, u64, u64, i64>;
+    type Builder = <Spine as Trace>::Builder;
+
+    let mut trace = Spine::new(OperatorInfo::new(0, 0, &[]), None, None);
+    for (index, batch) in testing::random_batches::<Builder>(0, 500, 20, 100, 10).into_iter().enumerate() {
+        let upper = batch.upper().clone();
+        trace.insert(batch);
+        trace.set_physical_compaction(upper.borrow());
+        if index % 3 == 0 {
+            trace.set_logical_compaction(upper.borrow());
+        }
+        trace.assert_invariants();
+    }
+}
+
+#[test]
+fn test_cursor_combinators() {
+    use differential_dataflow::trace::BatchReader;
+    use differential_dataflow::trace::cursor::{ChainCursor, FilterCursor, MapCursor};
+    use differential_dataflow::trace::testing;
+
+    type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+
+    let batches = testing::random_batches::<Builder>(0, 2, 100, 10, 5);
+    let flatten = |contents: Vec<((u64, u64), Vec<(u64, i64)>)>| {
+        contents
+            .into_iter()
+            .flat_map(|(data, updates)| updates.into_iter().map(move |(time, diff)| (data, time, diff)))
+            .collect::<Vec<_>>()
+    };
+
+    // Chaining the batches presents the updates of both, in order.
+    let storage = (batches[0].clone(), batches[1].clone());
+    let mut chain = ChainCursor::new(batches[0].cursor(), batches[1].cursor());
+    let mut expected = testing::contents(&batches[0], |v| *v);
+    expected.extend(testing::contents(&batches[1], |v| *v));
+    expected.sort();
+    assert_eq!(flatten(chain.to_vec(|v| *v, &storage)), expected);
+
+    // Filtering presents only the matching keys.
+    let mut filter = FilterCursor::new(batches[0].cursor(), |k: &u64, _v: &u64| k % 2 == 0, &batches[0]);
+    let expected = testing::contents(&batches[0], |v| *v)
+        .into_iter()
+        .filter(|((k, _), _, _)| k % 2 == 0)
+        .collect::<Vec<_>>();
+    assert_eq!(flatten(filter.to_vec(|v| *v, &batches[0])), expected);
+
+    // Mapping transforms each time and difference.
+    let mut map = MapCursor::new(batches[1].cursor(), |t: &u64, r: &i64| (t + 1, 2 * r));
+    let expected = testing::contents(&batches[1], |v| *v)
+        .into_iter()
+        .map(|(data, t, r)| (data, t + 1, 2 * r))
+        .collect::<Vec<_>>();
+    assert_eq!(flatten(map.to_vec(|v| *v, &batches[1])), expected);
+}
+
+#[test]
+fn test_key_iteration() {
+    use differential_dataflow::trace::BatchReader;
+    use differential_dataflow::trace::testing;
+
+    type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+
+    let batches = testing::random_batches::<Builder>(0, 1, 200, 30, 5);
+    let mut expected = testing::contents(&batches[0], |v| *v)
+        .into_iter()
+        .map(|((k, _), _, _)| k)
+        .collect::<Vec<_>>();
+    expected.dedup();
+    assert_eq!(batches[0].keys().cloned().collect::<Vec<_>>(), expected);
+
+    // The trace reports the keys of all its batches once each.
+    let mut trace = get_trace();
+    let mut keys = Vec::new();
+    trace.map_keys(|key| keys.push(*key));
+    assert_eq!(keys, vec![1, 2]);
+}
+
+#[test]
+fn test_key_map() {
+    use differential_dataflow::trace::BatchReader;
+    use differential_dataflow::trace::cursor::MyTrait;
+    use differential_dataflow::trace::wrappers::key_map::TraceKeyMap;
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Id(u64);
+
+    let mut trace = TraceKeyMap::make_from(get_trace(), |key: &u64| Id(*key), |key: &Id| key.0);
+
+    // The trace presents its keys as owned keys of the mapped type.
+    let (mut cursor, storage) = trace.cursor();
+    assert_eq!(cursor.to_vec(|v| *v, &storage), vec![
+        ((Id(1), 2), vec![(0, 1)]),
+        ((Id(2), 3), vec![(1, 1), (2, -1)]),
+    ]);
+
+    // Keys can be sought by presented keys, and compare as presented keys.
+    cursor.rewind_keys(&storage);
+    let first = cursor.key(&storage);
+    cursor.seek_key_owned(&storage, &Id(2));
+    assert!(cursor.key(&storage).equals(&Id(2)));
+    assert!(first < cursor.key(&storage));
+    assert!(first.less_than(&Id(2)));
+    cursor.seek_key_owned(&storage, &Id(3));
+    assert!(!cursor.key_valid(&storage));
+
+    // Batches present their keys in the same way.
+    let mut keys = Vec::new();
+    trace.map_batches(|batch| keys.extend(batch.keys().map(|key| key.into_owned())));
+    keys.sort();
+    keys.dedup();
+    assert_eq!(keys, vec![Id(1), Id(2)]);
+}
+
+#[test]
+fn test_merged_upper() {
+    use timely::PartialOrder;
+
+    let mut trace = get_trace();
+    let mut upper = Antichain::new();
+    let mut merged = Antichain::new();
+    trace.read_upper(&mut upper);
+    trace.read_merged_upper(&mut merged);
+    assert!(PartialOrder::less_equal(&merged, &upper));
+
+    // Batches held back by the physical compaction frontier are not merged.
+    assert_eq!(merged, Antichain::from_elem(0));
+
+    // Once batches are released and merges complete, all inserted batches are merged.
+    trace.set_physical_compaction(upper.borrow());
+    trace.apply_fuel(&mut 1_000_000);
+    trace.read_merged_upper(&mut merged);
+    assert_eq!(merged, upper);
+}
+
+#[test]
+fn test_exert_with() {
+    use differential_dataflow::trace::BatchReader;
+
+    let mut trace = get_trace();
+    let mut upper = Antichain::new();
+    trace.read_upper(&mut upper);
+    trace.set_physical_compaction(upper.borrow());
+
+    let (mut cursor, storage) = trace.cursor();
+    let before = cursor.to_vec(|v| v.clone(), &storage);
+
+    // Effort applied without updates merges the trace down to a single batch.
+    for _ in 0 .. 4 {
+        trace.exert_with(1_000_000);
+    }
+    let mut batches = 0;
+    trace.map_batches(|batch| if !batch.is_empty() { batches += 1; });
+    assert_eq!(batches, 1);
+
+    let mut merged = Antichain::new();
+    trace.read_merged_upper(&mut merged);
+    assert_eq!(merged, upper);
+
+    let (mut cursor, storage) = trace.cursor();
+    assert_eq!(cursor.to_vec(|v| v.clone(), &storage), before);
+}
+
+#[test]
+fn test_simulated_clock() {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use differential_dataflow::trace::clock::SimulatedClock;
+    use differential_dataflow::trace::scheduler::MergeScheduler;
+
+    // Exertion logic granted from a budget measured by a simulated clock behaves the same in each run.
+    let clock = Arc::new(SimulatedClock::new());
+    let scheduler = Arc::new(MergeScheduler::with_clock(100, Duration::from_millis(10), clock.clone()));
+    let logic = scheduler.exertion_logic(Arc::new(|_batches: &[(usize, usize, usize)]| Some(40)));
+    let granted = (0 .. 4).map(|_| logic(&[])).collect::<Vec<_>>();
+    assert_eq!(granted, vec![Some(40), Some(40), Some(20), None]);
+    assert_eq!(scheduler.retry_after(), Some(Duration::from_millis(10)));
+
+    clock.advance(Duration::from_millis(9));
+    assert_eq!(logic(&[]), None);
+    assert_eq!(scheduler.retry_after(), Some(Duration::from_millis(1)));
+    clock.advance(Duration::from_millis(1));
+    // the five requests of the last round each have a share of a fifth of the budget.
+    assert_eq!(logic(&[]), Some(20));
+    assert_eq!(scheduler.retry_after(), Some(Duration::from_millis(10)));
+    assert_eq!(clock.elapsed(), Duration::from_millis(10));
+}
+
+#[test]
+fn test_insert_leaves_merge_budget() {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use differential_dataflow::trace::clock::SimulatedClock;
+    use differential_dataflow::trace::scheduler::MergeScheduler;
+
+    // Traces consult their exertion logic as they insert batches, but only exerting takes from the budget.
+    let clock = Arc::new(SimulatedClock::new());
+    let scheduler = Arc::new(MergeScheduler::with_clock(100, Duration::from_millis(10), clock.clone()));
+    let logic = scheduler.clone().exertion_logic(Arc::new(|_batches: &[(usize, usize, usize)]| Some(40)));
+
+    let mut trace = IntegerTrace::new(OperatorInfo::new(0, 0, &[]), None, None);
+    trace.set_exert_logic(logic.clone());
+    let source = get_trace();
+    source.map_batches(|batch| trace.insert(batch.clone()));
+    assert_eq!(scheduler.retry_after(), None);
+
+    let granted = (0 .. 4).map(|_| logic(&[])).collect::<Vec<_>>();
+    assert_eq!(granted, vec![Some(40), Some(40), Some(20), None]);
+}
+
+#[test]
+fn test_offset_widths() {
+    use differential_dataflow::trace::implementations::{OffsetList, Vector, WithOffsets};
+    use differential_dataflow::trace::implementations::ord_neu::OrdValBuilder;
+    use differential_dataflow::trace::rc_blanket_impls::RcBuilder;
+    use differential_dataflow::trace::testing;
+
+    // Offsets are held as `u32` until one does not fit, and as `u64` from then on.
+    let mut offsets = OffsetList::with_capacity(4);
+    for offset in [0, 0, 7, 1 << 33, 3] {
+        offsets.push(offset);
+    }
+    assert_eq!((offsets.zero_prefix, offsets.smol.len(), offsets.chonk.len()), (2, 1, 2));
+    let read = (0 .. offsets.len()).map(|index| offsets.index(index)).collect::<Vec<_>>();
+    assert_eq!(read, vec![0, 0, 7, 1 << 33, 3]);
+
+    // Batches with pointer-width offsets present the same contents.
+    type Update = ((u64, u64), u64, i64);
+    type Narrow = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+    type Wide = RcBuilder<OrdValBuilder<WithOffsets<Vector<Update>, Vec<usize>>>>;
+    let narrow = testing::random_batches::<Narrow>(0, 3, 100, 10, 5);
+    let wide = testing::random_batches::<Wide>(0, 3, 100, 10, 5);
+    for (narrow, wide) in narrow.iter().zip(wide.iter()) {
+        testing::check_batch(wide).unwrap();
+        assert_eq!(testing::contents(narrow, |v| *v), testing::contents(wide, |v| *v));
+    }
+    let compaction = Antichain::from_elem(1);
+    testing::check_merge(&wide[0], &wide[1], compaction.borrow(), 7, |v| *v).unwrap();
+}
+
+#[test]
+fn test_blob_container() {
+    use differential_dataflow::trace::implementations::BatchContainer;
+    use differential_dataflow::trace::implementations::blob_container::BlobContainer;
+
+    let items = (0 .. 20usize).map(|len| vec![len as u8; (len * 7) % 23]).collect::<Vec<_>>();
+    let mut container = BlobContainer::<u8, 8>::with_capacity(items.len());
+    for item in items.iter() {
+        container.copy_push(item);
+    }
+    assert_eq!(container.spilled(), items.iter().filter(|item| item.len() > 8).count());
+    for (index, item) in items.iter().enumerate() {
+        assert_eq!(container.index(index), &item[..]);
+    }
+
+    // Merging preserves items, wherever they are stored.
+    let mut merged = BlobContainer::merge_capacity(&container, &container);
+    merged.copy_range(&container, 0, 10);
+    merged.copy_range(&container, 10, 20);
+    assert_eq!(merged.spilled(), container.spilled());
+    assert!((0 .. 20).all(|index| merged.index(index) == container.index(index)));
+}
+
+#[test]
+fn test_intern_container() {
+    use differential_dataflow::trace::implementations::BatchContainer;
+    use differential_dataflow::trace::implementations::intern_container::InternContainer;
+
+    let names = ["active", "pending", "closed"];
+    let items = (0 .. 100).map(|index| names[(index * index) % 3].to_string()).collect::<Vec<_>>();
+    let mut container = InternContainer::with_capacity(items.len());
+    for item in items.iter() {
+        container.copy_push(item);
+    }
+    assert_eq!(container.distinct(), 3);
+    assert!(items.iter().enumerate().all(|(index, item)| container.index(index) == item.as_str()));
+
+    // Merged containers intern strings afresh.
+    let mut merged = InternContainer::merge_capacity(&container, &container);
+    merged.copy_range(&container, 50, 100);
+    assert_eq!(merged.len(), 50);
+    assert!(merged.distinct() <= 3);
+}
+
+#[test]
+fn test_run_length_container() {
+    use differential_dataflow::trace::implementations::{BatchContainer, UpdateContainer};
+    use differential_dataflow::trace::implementations::rle_container::RunLengthContainer;
+
+    // Runs are of equal diffs, whatever the times.
+    let items = [(0u64, 1i64), (1, 1), (0, 1), (1, 1), (1, -1), (2, -1), (0, 1)];
+    let mut container = RunLengthContainer::with_capacity(items.len());
+    for item in items.iter() {
+        container.copy_push(item);
+    }
+    assert_eq!((container.len(), container.runs()), (7, 3));
+    assert!(items.iter().enumerate().all(|(index, (time, diff))| container.index_update(index) == (time, diff)));
+    assert_eq!(container.last_update(), Some((&0, &1)));
+
+    // Reading a range in sequence agrees with reading each update.
+    let mut read = Vec::new();
+    container.map_updates(2, 6, |time, diff| read.push((*time, *diff)));
+    assert_eq!(read, items[2 .. 6].to_vec());
+}
+
+#[test]
+fn test_run_length_batches() {
+    use differential_dataflow::trace::implementations::{RunLength, Vector};
+    use differential_dataflow::trace::implementations::ord_neu::OrdValBuilder;
+    use differential_dataflow::trace::rc_blanket_impls::RcBuilder;
+    use differential_dataflow::trace::testing;
+
+    // Batches with run-length encoded updates present the same contents.
+    type Update = ((u64, u64), u64, i64);
+    type Plain = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+    type Encoded = RcBuilder<OrdValBuilder<RunLength<Vector<Update>>>>;
+    let plain = testing::random_batches::<Plain>(0, 3, 100, 10, 5);
+    let encoded = testing::random_batches::<Encoded>(0, 3, 100, 10, 5);
+    for (plain, encoded) in plain.iter().zip(encoded.iter()) {
+        testing::check_batch(encoded).unwrap();
+        assert_eq!(testing::contents(plain, |v| *v), testing::contents(encoded, |v| *v));
+    }
+    let compaction = Antichain::from_elem(1);
+    testing::check_merge(&encoded[0], &encoded[1], compaction.borrow(), 7, |v| *v).unwrap();
+}
+
+#[test]
+fn test_dictionary_container() {
+    use differential_dataflow::trace::implementations::BatchContainer;
+    use differential_dataflow::trace::implementations::dictionary_container::DictionaryContainer;
+
+    // Codes widen as the number of distinct items grows.
+    let items = (0 .. 1000u64).map(|index| ((index * 7) % 300, 1i64)).collect::<Vec<_>>();
+    let mut container = DictionaryContainer::with_capacity(items.len());
+    for item in items.iter() {
+        container.copy_push(item);
+    }
+    assert_eq!((container.len(), container.distinct()), (1000, 300));
+    assert!(items.iter().enumerate().all(|(index, item)| container.index(index) == item));
+}
+
+#[test]
+fn test_dictionary_batches() {
+    use differential_dataflow::trace::implementations::{Dictionary, Vector};
+    use differential_dataflow::trace::implementations::ord_neu::OrdValBuilder;
+    use differential_dataflow::trace::rc_blanket_impls::RcBuilder;
+    use differential_dataflow::trace::testing;
+
+    // Batches with dictionary encoded updates present the same contents.
+    type Update = ((u64, u64), u64, i64);
+    type Plain = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+    type Encoded = RcBuilder<OrdValBuilder<Dictionary<Vector<Update>>>>;
+    let plain = testing::random_batches::<Plain>(0, 3, 100, 10, 5);
+    let encoded = testing::random_batches::<Encoded>(0, 3, 100, 10, 5);
+    for (plain, encoded) in plain.iter().zip(encoded.iter()) {
+        testing::check_batch(encoded).unwrap();
+        assert_eq!(testing::contents(plain, |v| *v), testing::contents(encoded, |v| *v));
+    }
+    let compaction = Antichain::from_elem(1);
+    testing::check_merge(&encoded[0], &encoded[1], compaction.borrow(), 7, |v| *v).unwrap();
+}
+
+#[test]
+fn test_graph_batches() {
+    use differential_dataflow::trace::implementations::graph::GraphSpine;
+    use differential_dataflow::trace::testing::{self, Generator};
+
+    // Batches of edges present the same contents as generic ordered batches.
+    type Plain = <ValSpine<u32, u32, u64, i64> as Trace>::Builder;
+    type Graph = <GraphSpine<u64, i64> as Trace>::Builder;
+    let mut generator = Generator::new(0);
+    let mut plain = Vec::new();
+    let mut graph = Vec::new();
+    for time in 0 .. 3 {
+        let edges = generator.updates(100, 10, 10, time, time + 1)
+            .into_iter()
+            .map(|((src, dst), time, diff)| ((src as u32, dst as u32), time, diff))
+            .collect::<Vec<_>>();
+        let (lower, upper) = (Antichain::from_elem(time), Antichain::from_elem(time + 1));
+        plain.push(testing::build_batch::<Plain, _, _, _>(edges.clone(), lower.clone(), upper.clone()));
+        graph.push(testing::build_batch::<Graph, _, _, _>(edges, lower, upper));
+    }
+    for (plain, graph) in plain.iter().zip(graph.iter()) {
+        testing::check_batch(graph).unwrap();
+        assert_eq!(testing::contents(plain, |v| *v), testing::contents(graph, |v| *v));
+    }
+    let compaction = Antichain::from_elem(1);
+    testing::check_merge(&graph[0], &graph[1], compaction.borrow(), 7, |v| *v).unwrap();
+}
+
+#[test]
+fn test_build_from_sorted_runs() {
+    use differential_dataflow::consolidation::consolidate_updates;
+    use differential_dataflow::trace::implementations::sorted_runs::build_from_sorted_runs;
+    use differential_dataflow::trace::testing::{self, Generator};
+
+    type Builder = <ValSpine<u64, u64, u64, i64> as Trace>::Builder;
+
+    let mut generator = Generator::new(0);
+    let runs = (0 .. 7).map(|_| {
+        let mut run = generator.updates(100, 20, 5, 0, 3);
+        consolidate_updates(&mut run);
+        run
+    }).collect::<Vec<_>>();
+
+    let all = runs.concat();
+    let expected = testing::build_batch::<Builder, _, _, _>(all, Antichain::from_elem(0), Antichain::from_elem(3));
+    for threads in [1, 4] {
+        let batch = build_from_sorted_runs::<Builder, _, _, _>(runs.clone(), threads, Antichain::from_elem(0), Antichain::from_elem(3), Antichain::from_elem(0));
+        testing::check_batch(&batch).unwrap();
+        assert_eq!(testing::contents(&batch, |v| *v), testing::contents(&expected, |v| *v));
+    }
+}
+
+#[test]
+fn test_presorted_batcher() {
+    use differential_dataflow::trace::testing::{self, Generator};
+    use differential_dataflow::trace::implementations::ord_neu::PresortedValSpine;
+    use differential_dataflow::consolidation::consolidate_updates;
+    use timely::communication::message::RefOrMut;
+
+    type Spine = PresortedValSpine<u64, u64, u64, i64>;
+    type Builder = <Spine as Trace>::Builder;
+
+    let mut generator = Generator::new(0);
+    let mut batcher = <Spine as Trace>::Batcher::new(None, 0);
+    let mut all = Vec::new();
+    for index in 0 .. 10 {
+        let mut updates = generator.updates(50, 20, 5, 0, 4);
+        all.extend(updates.iter().cloned());
+        // Leave every third container unsorted.
+        if index % 3 != 0 { consolidate_updates(&mut updates); }
+        batcher.push_container(RefOrMut::Mut(&mut updates));
+    }
+
+    for upper in 1 .. 5 {
+        let batch = batcher.seal::<Builder>(Antichain::from_elem(upper));
+        testing::check_batch(&batch).unwrap();
+        let expected = all.iter().filter(|(_, time, _)| *time == upper - 1).cloned().collect::<Vec<_>>();
+        let expected = testing::build_batch::<Builder, _, _, _>(expected, Antichain::from_elem(upper - 1), Antichain::from_elem(upper));
+        assert_eq!(testing::contents(&batch, |v| *v), testing::contents(&expected, |v| *v));
+    }
+}
+
+#[test]
+fn test_validated_compaction() {
+    use differential_dataflow::operators::arrange::TraceAgent;
+
+    let op_info = OperatorInfo::new(0, 0, &[]);
+    let (mut agent, _writer) = TraceAgent::new(get_trace(), op_info, None);
+    assert_eq!(agent.logical_compaction(), Antichain::from_elem(0));
+
+    assert!(agent.try_set_logical_compaction(AntichainRef::new(&[2])).is_ok());
+    assert_eq!(agent.logical_compaction(), Antichain::from_elem(2));
+    // frontiers may not regress, and must be antichains.
+    assert!(agent.try_set_logical_compaction(AntichainRef::new(&[1])).is_err());
+    assert!(agent.try_set_physical_compaction(AntichainRef::new(&[2, 3])).is_err());
+    assert_eq!(agent.logical_compaction(), Antichain::from_elem(2));
+
+    assert!(agent.try_set_physical_compaction(AntichainRef::new(&[])).is_ok());
+    assert!(agent.physical_compaction().is_empty());
+}
+
+#[test]
+fn test_updates_since() {
+    let mut trace = get_trace();
+
+    let (mut cursor, storage) = trace.updates_since(AntichainRef::new(&[1])).unwrap();
+    assert_eq!(cursor.to_vec(|v| v.clone(), &storage), vec![((2, 3), vec![(1, 1), (2, -1)])]);
+
+    let (mut cursor, storage) = trace.updates_since(AntichainRef::new(&[3])).unwrap();
+    assert!(cursor.to_vec(|v| v.clone(), &storage).is_empty());
+
+    // once batches may merge past `lower`, the updates since cannot be distinguished.
+    trace.set_physical_compaction(AntichainRef::new(&[2]));
+    assert!(trace.updates_since(AntichainRef::new(&[1])).is_none());
+}
+
+#[test]
+fn test_batch_stats() {
+    use differential_dataflow::trace::BatchReader;
+
+    let trace = get_trace();
+    let mut records = 0;
+    trace.map_batches(|batch| {
+        let stats = batch.stats();
+        assert_eq!(stats.records, batch.len());
+        assert_eq!(stats.bytes > 0, !batch.is_empty());
+        records += stats.records;
+    });
+    assert_eq!(records, 3);
+}
+
+#[test]
+fn test_backed_restore() {
+    use differential_dataflow::trace::implementations::backend::{Backed, MemoryBackend};
+
+    let mut batches = Vec::new();
+    get_trace().map_batches(|batch| batches.push(batch.clone()));
+
+    let mut backed = Backed::<IntegerTrace, MemoryBackend<u64, u64, usize, i64>>::new(OperatorInfo::new(0, 0, &[]), None, None);
+    for batch in batches {
+        backed.insert(batch);
+    }
+    let (mut cursor, storage) = backed.cursor();
+    let expected = cursor.to_vec(|v| v.clone(), &storage);
+
+    backed.checkpoint().unwrap();
+    let mut restored = Backed::restore(IntegerTrace::new(OperatorInfo::new(0, 0, &[]), None, None), backed.into_backend()).unwrap();
+    let (mut cursor, storage) = restored.cursor();
+    assert_eq!(cursor.to_vec(|v| v.clone(), &storage), expected);
+}
+
+#[test]
+fn test_backed_write_back() {
+    use differential_dataflow::trace::implementations::backend::{Backed, MemoryBackend, WriteMode};
+
+    let mut batches = Vec::new();
+    get_trace().map_batches(|batch| batches.push(batch.clone()));
+    let count = batches.len();
+
+    let trace = IntegerTrace::new(OperatorInfo::new(0, 0, &[]), None, None);
+    let mut backed = Backed::with_mode(trace, MemoryBackend::<u64, u64, usize, i64>::default(), WriteMode::Back);
+    for batch in batches {
+        backed.insert(batch);
+    }
+    // batches are written only once flushed.
+    assert_eq!(backed.stored(), 0);
+    assert_eq!(backed.unwritten(), count);
+    backed.flush().unwrap();
+    assert_eq!(backed.stored(), count);
+    assert_eq!(backed.unwritten(), 0);
+    assert!(backed.take_error().is_none());
+}